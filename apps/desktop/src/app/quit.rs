@@ -15,6 +15,7 @@ use crate::connection::{
     ConnectionId, ConnectionState, LiveConnection, close_any_connection, live_connection_count,
     live_connections,
 };
+use crate::editor::open_transactions::{open_transaction_count, rollback_warning};
 use crate::widgets::{engine_icon, engine_label_inline};
 use crate::workspace::Workspace;
 use crate::workspace::WorkspaceRef;
@@ -173,6 +174,7 @@ fn show_quit_dialog(
         window.has_active_dialog(cx)
     ));
     let registry_for_ok = registry.clone();
+    let open_transactions = open_transaction_count(cx);
 
     window.open_alert_dialog(cx, move |alert, _window, cx| {
         trace("show_quit_dialog: open_alert_dialog builder invoked");
@@ -185,10 +187,18 @@ fn show_quit_dialog(
             .border_color(theme.red)
             .text_color(theme.primary_foreground);
         let connection_list = render_live_connection_list(&live, cx);
+        let transaction_note = (open_transactions > 0).then(|| {
+            div()
+                .mt_2()
+                .text_sm()
+                .text_color(cx.theme().warning)
+                .child(rollback_warning(open_transactions))
+        });
         alert
             .title("Quit with active connections?")
             .description("Quitting will disconnect these connections:")
             .child(connection_list)
+            .children(transaction_note)
             .footer(
                 DialogFooter::new()
                     .child(
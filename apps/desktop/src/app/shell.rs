@@ -26,7 +26,7 @@ use crate::bindings::{
     CloseAllTabs, CloseCleanTabs, CloseOtherTabs, CloseTab, CycleAppearance, GoBackTab,
     GoForwardTab, NewQuery, OpenHome, OpenOnboarding, SplitPaneBottom, SplitPaneLeft,
     SplitPaneRight, SplitPaneTop, ToggleCommandPalette, ToggleHistoryPane, ToggleInspectorPane,
-    ToggleSavedPane, ToggleSidebarRail,
+    ToggleProblemsPane, ToggleSavedPane, ToggleSidebarRail,
 };
 use crate::project::{
     prompt_open_project_in_new_window, prompt_open_project_in_window,
@@ -174,6 +174,7 @@ fn view_menu_items() -> Vec<MenuItem> {
         MenuItem::action("Inspector", ToggleInspectorPane),
         MenuItem::action("History", ToggleHistoryPane),
         MenuItem::action("Saved Queries", ToggleSavedPane),
+        MenuItem::action("Problems", ToggleProblemsPane),
         MenuItem::separator(),
         MenuItem::action("Cycle Appearance", CycleAppearance),
         MenuItem::action("Back", GoBackTab),
@@ -27,6 +27,7 @@ gpui::actions!([
     ToggleInspectorPane,
     ToggleHistoryPane,
     ToggleSavedPane,
+    ToggleProblemsPane,
 ]);
 
 pub fn init(cx: &mut App) {
@@ -58,5 +59,7 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("ctrl-alt-h", ToggleHistoryPane, None),
         KeyBinding::new("cmd-alt-s", ToggleSavedPane, None),
         KeyBinding::new("ctrl-alt-s", ToggleSavedPane, None),
+        KeyBinding::new("cmd-alt-p", ToggleProblemsPane, None),
+        KeyBinding::new("ctrl-alt-p", ToggleProblemsPane, None),
     ]);
 }
@@ -141,6 +141,29 @@ fn push_workspace_commands(results: &mut Vec<PaletteResult>, q: &str, cx: &App)
             ));
         }
     }
+    if cx.try_global::<ProjectRoot>().is_some()
+        && (wants_project_commands(q) || q.contains("migrat"))
+    {
+        results.push(blank_command(
+            WorkspacePaletteAction::MigrateProject,
+            "Migrate Project Files",
+            "project",
+        ));
+    }
+    if cx.try_global::<ProjectRoot>().is_some()
+        && (wants_project_commands(q) || q.contains("secret") || q.contains("env"))
+    {
+        results.push(blank_command(
+            WorkspacePaletteAction::FillMissingSecrets,
+            "Fill Missing Secrets",
+            "project",
+        ));
+        results.push(blank_command(
+            WorkspacePaletteAction::SyncEnvExample,
+            "Update .env.example",
+            "project",
+        ));
+    }
     if manual_update_checks_enabled() && (q.is_empty() || q.contains("update")) {
         results.push(blank_command(
             WorkspacePaletteAction::CheckForUpdates,
@@ -223,6 +246,7 @@ fn push_history(
                     init: QueryEditorInit::MongoPipeline {
                         pipeline: Some(entry.query.clone()),
                         collection: None,
+                        query_path: None,
                     },
                 },
                 _ => TabSpec::QueryEditor {
@@ -230,6 +254,7 @@ fn push_history(
                     init: QueryEditorInit::Sql {
                         sql: Some(entry.query.clone()),
                         auto_run: false,
+                        query_path: None,
                     },
                 },
             };
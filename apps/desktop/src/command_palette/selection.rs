@@ -101,6 +101,7 @@ mod tests {
                 init: QueryEditorInit::Sql {
                     sql: Some("SELECT 1".into()),
                     auto_run: false,
+                    query_path: None,
                 },
             },
             project_query_path: None,
@@ -26,6 +26,9 @@ pub enum WorkspacePaletteAction {
     OpenProject,
     OpenProjectInNewWindow,
     CloseProject,
+    MigrateProject,
+    FillMissingSecrets,
+    SyncEnvExample,
 }
 
 /// A search result the palette can return.
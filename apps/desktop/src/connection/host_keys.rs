@@ -0,0 +1,148 @@
+//! Trust-on-first-use for SSH host keys: tunnels opened on the Tokio runtime ask
+//! here, and the user confirms the fingerprint in a dialog on the active window.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use based_ssh::{HostKeyCallback, HostKeyPolicy, UnknownHostKey};
+use futures::channel::{mpsc, oneshot};
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    v_flex,
+};
+
+use crate::project::personal::personal_root;
+
+struct HostKeyPrompt {
+    key: UnknownHostKey,
+    reply: oneshot::Sender<bool>,
+}
+
+/// Sends unknown host keys from tunnel tasks to the UI.
+pub struct HostKeyPrompts {
+    tx: mpsc::UnboundedSender<HostKeyPrompt>,
+}
+
+impl Global for HostKeyPrompts {}
+
+pub fn init(cx: &mut App) {
+    let (tx, mut rx) = mpsc::unbounded();
+    cx.set_global(HostKeyPrompts { tx });
+    cx.spawn(async move |cx| {
+        while let Some(prompt) = rx.next().await {
+            cx.update(|app| show_prompt(prompt, app));
+        }
+    })
+    .detach();
+}
+
+/// Keys the user trusts in Based are saved here, not in `~/.ssh/known_hosts`.
+pub fn managed_known_hosts_path() -> PathBuf {
+    personal_root().join("known_hosts")
+}
+
+/// Host key checks for tunnels opened from the app: `~/.ssh/known_hosts`, then Based's
+/// own file, then a confirmation dialog.
+pub fn host_key_policy(cx: &App) -> HostKeyPolicy {
+    let confirm = cx
+        .try_global::<HostKeyPrompts>()
+        .map(|prompts| -> HostKeyCallback {
+            let tx = prompts.tx.clone();
+            Arc::new(move |key: UnknownHostKey| ask(tx.clone(), key))
+        });
+    HostKeyPolicy {
+        known_hosts: None,
+        managed_known_hosts: Some(managed_known_hosts_path()),
+        confirm,
+    }
+}
+
+fn ask(tx: mpsc::UnboundedSender<HostKeyPrompt>, key: UnknownHostKey) -> BoxFuture<'static, bool> {
+    async move {
+        let (reply, answer) = oneshot::channel();
+        if tx.unbounded_send(HostKeyPrompt { key, reply }).is_err() {
+            return false;
+        }
+        // A dialog closed without an answer drops the sender: not trusted.
+        answer.await.unwrap_or(false)
+    }
+    .boxed()
+}
+
+fn show_prompt(prompt: HostKeyPrompt, cx: &mut App) {
+    let Some(handle) = cx.active_window() else {
+        log::warn!(
+            "no window to confirm SSH host key for {}:{}",
+            prompt.key.host,
+            prompt.key.port
+        );
+        return;
+    };
+    let HostKeyPrompt { key, reply } = prompt;
+    let reply = Rc::new(RefCell::new(Some(reply)));
+    let _ = handle.update(cx, |_, window, cx| {
+        window.open_dialog(cx, move |dialog, _, cx| {
+            let trust_btn = Button::new("host-key-trust").label("Trust").primary();
+            let (ok_reply, cancel_reply) = (reply.clone(), reply.clone());
+            dialog
+                .title("Trust this SSH host?")
+                .child(prompt_body(&key, cx))
+                .footer(
+                    DialogFooter::new()
+                        .child(
+                            DialogClose::new()
+                                .child(Button::new("host-key-cancel").outline().label("Cancel")),
+                        )
+                        .child(DialogAction::new().child(trust_btn)),
+                )
+                .on_ok(move |_, _, _| {
+                    answer(&ok_reply, true);
+                    true
+                })
+                .on_cancel(move |_, _, _| {
+                    answer(&cancel_reply, false);
+                    true
+                })
+        });
+    });
+}
+
+fn answer(reply: &RefCell<Option<oneshot::Sender<bool>>>, trusted: bool) {
+    if let Some(reply) = reply.borrow_mut().take() {
+        let _ = reply.send(trusted);
+    }
+}
+
+fn prompt_body(key: &UnknownHostKey, cx: &App) -> impl IntoElement {
+    let theme = cx.theme();
+    let saved_to = format!(
+        "Trusted keys are saved to {}.",
+        managed_known_hosts_path().display()
+    );
+    v_flex()
+        .gap_2()
+        .child(format!(
+            "Based has not connected to {}:{} before. Check that this fingerprint \
+             matches the server's before trusting it.",
+            key.host, key.port
+        ))
+        .child(
+            div()
+                .font_family(theme.mono_font_family.clone())
+                .text_sm()
+                .child(format!("{} {}", key.algorithm, key.fingerprint)),
+        )
+        .child(
+            div()
+                .text_xs()
+                .text_color(theme.muted_foreground)
+                .child(saved_to),
+        )
+}
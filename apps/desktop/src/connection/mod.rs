@@ -5,6 +5,7 @@
 // APIs directly; nothing from this module leaks DB-querying concerns.
 
 pub mod descriptor;
+pub mod host_keys;
 pub mod lifecycle;
 pub mod open;
 pub mod registry;
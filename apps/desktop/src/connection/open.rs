@@ -45,7 +45,10 @@ pub fn open_connection(
 pub fn opened_into_any(opened: OpenedConnection, cx: &mut App) -> AnyConnection {
     match opened {
         OpenedConnection::Sqlite(conn) => AnyConnection::SQLite(cx.new(|_| conn)),
-        OpenedConnection::Postgres(conn) => AnyConnection::Postgres(cx.new(|_| conn)),
+        OpenedConnection::Postgres(conn) => AnyConnection::Postgres(cx.new(|cx| {
+            conn.watch_tunnel(cx);
+            conn
+        })),
         OpenedConnection::MongoDB(conn) => AnyConnection::MongoDB(cx.new(|_| conn)),
     }
 }
@@ -2,7 +2,7 @@
 
 use anyhow::{Result, bail};
 use based_core::SshTunnelConfig;
-use based_ssh::{SshTunnel, open_tunnel};
+use based_ssh::{HostKeyPolicy, SshTunnel, open_tunnel_with};
 
 use crate::postgres::SslMode;
 
@@ -20,12 +20,15 @@ pub async fn open_optional_tunnel(
     remote_host: &str,
     remote_port: u16,
     ssl_mode: SslMode,
+    host_keys: HostKeyPolicy,
 ) -> Result<Option<SshTunnel>> {
     let Some(ssh) = ssh else {
         return Ok(None);
     };
     ssh_hostname_verify_supported(ssl_mode)?;
-    Ok(Some(open_tunnel(ssh, remote_host, remote_port).await?))
+    Ok(Some(
+        open_tunnel_with(ssh, remote_host, remote_port, host_keys).await?,
+    ))
 }
 
 pub fn rewrite_tcp_endpoint(host: &mut String, port: &mut u16, tunnel: &SshTunnel) {
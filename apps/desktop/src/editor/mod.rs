@@ -1,10 +1,17 @@
-//! Editor services — per-tab context, variable scoping, and schema caching.
+//! Editor services — per-tab context, variable scoping, parameter binding and the
+//! run-time parameter form, connection policies, schema caching, and script runs.
 //!
 //! Engine-agnostic: all SQL editor panels (Postgres, SQLite) and future
 //! LSP/autocomplete features share this layer.
 
 pub mod context;
+pub mod open_transactions;
+pub mod param_form;
+pub mod policy;
+pub mod query_file;
+pub mod query_params;
 pub mod schema_cache;
+pub mod script_run;
 pub mod sql_completion;
 pub mod sqlite_schema;
 pub mod variable_scope;
@@ -0,0 +1,62 @@
+//! Query tabs holding an open manual transaction.
+//!
+//! Editors register here on `BEGIN` and unregister on commit, rollback, or release, so
+//! the status bar can show an indicator and tab close / quit can warn before the
+//! transaction is rolled back.
+
+use std::collections::HashMap;
+
+use gpui::{App, EntityId, Global};
+
+use crate::connection::ConnectionId;
+use crate::workspace::WorkspaceRef;
+
+#[derive(Default)]
+pub struct OpenTransactions {
+    /// Query editor panel → connection it pinned.
+    tabs: HashMap<EntityId, ConnectionId>,
+}
+
+impl Global for OpenTransactions {}
+
+pub fn mark_transaction_open(panel: EntityId, conn_id: ConnectionId, cx: &mut App) {
+    cx.default_global::<OpenTransactions>()
+        .tabs
+        .insert(panel, conn_id);
+    refresh_workspace(cx);
+}
+
+pub fn mark_transaction_closed(panel: EntityId, cx: &mut App) {
+    let removed = cx
+        .try_global::<OpenTransactions>()
+        .is_some_and(|open| open.tabs.contains_key(&panel));
+    if removed {
+        cx.global_mut::<OpenTransactions>().tabs.remove(&panel);
+        refresh_workspace(cx);
+    }
+}
+
+pub fn has_open_transaction(panel: EntityId, cx: &App) -> bool {
+    cx.try_global::<OpenTransactions>()
+        .is_some_and(|open| open.tabs.contains_key(&panel))
+}
+
+pub fn open_transaction_count(cx: &App) -> usize {
+    cx.try_global::<OpenTransactions>()
+        .map_or(0, |open| open.tabs.len())
+}
+
+/// Warning line for close / quit dialogs, e.g. "2 open transactions will be rolled back."
+pub fn rollback_warning(count: usize) -> String {
+    if count == 1 {
+        "1 open transaction will be rolled back.".to_string()
+    } else {
+        format!("{count} open transactions will be rolled back.")
+    }
+}
+
+fn refresh_workspace(cx: &mut App) {
+    if let Some(ws) = cx.try_global::<WorkspaceRef>().map(|w| w.0.clone()) {
+        ws.update(cx, |_, cx| cx.notify());
+    }
+}
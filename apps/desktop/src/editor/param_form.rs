@@ -0,0 +1,139 @@
+//! Run-time parameter form for `{{name}}` placeholders that no variable scope defines.
+//!
+//! Values entered here are remembered per query in `.based/state/query_params.toml` and
+//! reach the run as session-scope variables, so saved reports with a date range run
+//! without editing their SQL. A field hints at the type its `[params]` entry declares;
+//! undeclared ones bind as text the server reads like a literal, so they only ask for a
+//! value.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use based_project::{load_query_params, persist_query_params};
+use based_query::{VariableContext, find_missing_variables};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    input::{Input, InputState},
+    v_flex,
+};
+
+use crate::connection::ConnectionId;
+use crate::editor::VariableScope;
+use crate::editor::variable_scope::VarType;
+use crate::project::ProjectRoot;
+use crate::widgets::labeled_field;
+
+/// Key remembered values are stored under: the saved query path, else the connection.
+pub fn param_key(query_path: Option<&str>, conn_id: &ConnectionId) -> String {
+    query_path.map_or_else(|| format!("connection:{}", conn_id.0), str::to_string)
+}
+
+/// Return `ctx` when every placeholder of `text` resolves. Otherwise open the parameter
+/// form, prefilled with the values last used for `key`, and return `None`; submitting it
+/// fills `ctx.session` and calls `run`. Values are coerced at bind time, so a malformed
+/// one fails the run like any other bad variable.
+pub fn prompt_missing_params<T: 'static>(
+    text: &str,
+    ctx: VariableContext,
+    scope: &VariableScope,
+    key: String,
+    window: &mut Window,
+    cx: &mut Context<T>,
+    run: impl Fn(&mut T, VariableContext, &mut Window, &mut Context<T>) + 'static,
+) -> Option<VariableContext> {
+    let names = find_missing_variables(text, &ctx);
+    if names.is_empty() {
+        return Some(ctx);
+    }
+    let root = cx.try_global::<ProjectRoot>().map(|p| p.0.clone());
+    let remembered = root
+        .as_deref()
+        .map(|root| load_query_params(root, &key))
+        .transpose()
+        .unwrap_or_else(|e| {
+            log::warn!("remembered query parameters unavailable: {e:#}");
+            None
+        })
+        .unwrap_or_default();
+    let fields: Rc<Vec<(String, Entity<InputState>)>> = Rc::new(
+        names
+            .into_iter()
+            .map(|name| {
+                let placeholder = scope.declared_type(&name).map_or("value", VarType::hint);
+                let value = remembered.get(&name).cloned().unwrap_or_default();
+                let input = cx.new(|cx| {
+                    InputState::new(window, cx)
+                        .placeholder(placeholder)
+                        .default_value(value)
+                });
+                (name, input)
+            })
+            .collect(),
+    );
+    let panel = cx.entity().downgrade();
+    let run = Rc::new(run);
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let muted = cx.theme().muted_foreground;
+        let fields = fields.clone();
+        let panel = panel.clone();
+        let run = run.clone();
+        let ctx = ctx.clone();
+        let key = key.clone();
+        let root = root.clone();
+        dialog
+            .title("Query parameters")
+            .child(
+                v_flex().gap_3().children(
+                    fields.iter().map(|(name, input)| {
+                        labeled_field(name, muted, Input::new(input).w_full())
+                    }),
+                ),
+            )
+            .footer(
+                DialogFooter::new()
+                    .child(
+                        DialogClose::new()
+                            .child(Button::new("query-params-cancel").outline().label("Cancel")),
+                    )
+                    .child(
+                        DialogAction::new()
+                            .child(Button::new("query-params-run").primary().label("Run")),
+                    ),
+            )
+            .on_ok(move |_, window, cx| {
+                let values: HashMap<String, String> = fields
+                    .iter()
+                    .map(|(name, input)| (name.clone(), input.read(cx).value().to_string()))
+                    .collect();
+                if let Some(root) = &root
+                    && let Err(e) = persist_query_params(root, &key, &values)
+                {
+                    log::warn!("could not remember query parameters: {e:#}");
+                }
+                let mut ctx = ctx.clone();
+                ctx.session.extend(values);
+                let run = run.clone();
+                let _ = panel.update(cx, |panel, cx| run(panel, ctx, window, cx));
+                true
+            })
+    });
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_queries_key_by_path() {
+        let conn = ConnectionId::from_key("local/northwind");
+        assert_eq!(
+            param_key(Some("local/northwind/monthly-sales"), &conn),
+            "local/northwind/monthly-sales"
+        );
+        assert_eq!(param_key(None, &conn), "connection:local/northwind");
+    }
+}
@@ -0,0 +1,360 @@
+//! Enforce a connection's `[policy]` before SQL runs: each statement is classified with
+//! the same classifier as script runs, then blocked, or confirmed in a dialog, or run.
+//! Data viewers only read, so they check the allow-list and `max_rows`.
+
+use std::iter;
+use std::rc::Rc;
+
+use based_project::ConnectionPolicy;
+use based_query::{
+    Dialect, StatementClass, StatementKind, classify_statement, nested_writes, statements_in_script,
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    v_flex,
+};
+
+use crate::connection::ConnectionId;
+use crate::project::ProjectContext;
+use crate::project::settings::max_result_rows;
+use crate::workspace::notify::push_error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PolicyVerdict {
+    Run,
+    /// `confirm_writes`: the message names the write statements.
+    Confirm(String),
+    Block(String),
+}
+
+/// Check every statement of `sql` against `policy`, including the writes a statement
+/// hides under another kind (data-modifying CTEs, `EXPLAIN ANALYZE`). Blocks win over
+/// confirmations.
+pub fn evaluate(policy: &ConnectionPolicy, sql: &str, dialect: Dialect) -> PolicyVerdict {
+    if policy.is_default() {
+        return PolicyVerdict::Run;
+    }
+    let mut writes = Vec::new();
+    for stmt in statements_in_script(sql, dialect) {
+        let text = stmt.text(sql);
+        let class = classify_statement(text, dialect);
+        let label = class.kind.label();
+        if class.kind == StatementKind::Empty {
+            continue;
+        }
+        for class in iter::once(class).chain(nested_writes(text, dialect)) {
+            if let Some(reason) = blocked(policy, class) {
+                return PolicyVerdict::Block(reason);
+            }
+        }
+        if class.is_write && !policy.allows_writes() {
+            return PolicyVerdict::Block(format!(
+                "{label} statements that write are not allowed on this connection"
+            ));
+        }
+        if class.is_write {
+            writes.push(label);
+        }
+    }
+    if policy.confirm_writes && !writes.is_empty() {
+        let noun = if writes.len() == 1 {
+            "statement"
+        } else {
+            "statements"
+        };
+        return PolicyVerdict::Confirm(format!(
+            "This runs {} write {noun} ({}).",
+            writes.len(),
+            writes.join(", ")
+        ));
+    }
+    PolicyVerdict::Run
+}
+
+/// Why `policy` refuses a statement of this class, if it does.
+fn blocked(policy: &ConnectionPolicy, class: StatementClass) -> Option<String> {
+    let label = class.kind.label();
+    if !policy.allows_kind(label) {
+        return Some(format!(
+            "{label} statements are not allowed on this connection"
+        ));
+    }
+    if policy.forbid_ddl && class.kind == StatementKind::Ddl {
+        return Some("DDL is forbidden on this connection".into());
+    }
+    if policy.require_where_for_update_delete
+        && matches!(class.kind, StatementKind::Update | StatementKind::Delete)
+        && !class.has_where
+    {
+        return Some(format!(
+            "{label} without WHERE is not allowed on this connection"
+        ));
+    }
+    None
+}
+
+/// `[policy]` of the open project's connection `id`; the default for personal and
+/// unsaved connections.
+pub fn connection_policy(id: &ConnectionId, cx: &App) -> ConnectionPolicy {
+    cx.try_global::<ProjectContext>()
+        .and_then(|ctx| ctx.snapshot.connections.iter().find(|c| c.id == id.0))
+        .map(|c| c.policy.clone())
+        .unwrap_or_default()
+}
+
+/// `rows`, lowered to the connection's `max_rows`.
+pub fn cap_rows(id: &ConnectionId, rows: u64, cx: &App) -> u64 {
+    connection_policy(id, cx)
+        .max_rows
+        .map_or(rows, |max| rows.min(max))
+}
+
+/// Row cap for a run on `id`: the project cap, lowered by the connection's `max_rows`.
+pub fn row_cap(id: &ConnectionId, cx: &App) -> u64 {
+    cap_rows(id, max_result_rows(cx), cx)
+}
+
+/// `cap` raised by one project cap for "Fetch more", never past the connection's
+/// `max_rows`.
+pub fn next_row_cap(id: &ConnectionId, cap: u64, cx: &App) -> u64 {
+    cap_rows(id, cap + max_result_rows(cx), cx)
+}
+
+/// Whether data viewers may read `id`: an allow-list must include `select`. A blocked
+/// read is reported.
+pub fn allows_browsing(id: &ConnectionId, cx: &mut App) -> bool {
+    if connection_policy(id, cx).allows_kind(StatementKind::Select.label()) {
+        return true;
+    }
+    push_error(
+        cx,
+        "Blocked by connection policy",
+        "SELECT statements are not allowed on this connection",
+    );
+    false
+}
+
+/// Short toolbar note for a non-default policy, e.g. "confirm writes · no DDL".
+pub fn policy_summary(policy: &ConnectionPolicy) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(allowed) = &policy.allowed_statements {
+        parts.push(format!("only {}", allowed.join(", ").to_ascii_uppercase()));
+    }
+    if policy.forbid_ddl {
+        parts.push("no DDL".to_string());
+    }
+    if policy.require_where_for_update_delete {
+        parts.push("WHERE required".to_string());
+    }
+    if policy.confirm_writes {
+        parts.push("confirm writes".to_string());
+    }
+    if let Some(max) = policy.max_rows {
+        parts.push(format!("max {max} rows"));
+    }
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+/// Return `true` when `sql` may run now. A blocked run reports why and returns `false`;
+/// one that needs confirmation opens a dialog whose Run button calls `run`, and returns
+/// `false`.
+pub fn check_connection_policy<T: 'static>(
+    sql: &str,
+    dialect: Dialect,
+    conn_id: &ConnectionId,
+    window: &mut Window,
+    cx: &mut Context<T>,
+    run: impl Fn(&mut T, &mut Window, &mut Context<T>) + 'static,
+) -> bool {
+    let policy = connection_policy(conn_id, cx);
+    let message = match evaluate(&policy, sql, dialect) {
+        PolicyVerdict::Run => return true,
+        PolicyVerdict::Block(reason) => {
+            push_error(cx, "Blocked by connection policy", reason);
+            return false;
+        }
+        PolicyVerdict::Confirm(message) => message,
+    };
+    let message: SharedString = message.into();
+    let panel = cx.entity().downgrade();
+    let run = Rc::new(run);
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let theme = cx.theme();
+        let muted = theme.muted_foreground;
+        let run_btn = Button::new("policy-run")
+            .label("Run")
+            .primary()
+            .bg(theme.red)
+            .border_color(theme.red)
+            .text_color(theme.primary_foreground);
+        let panel = panel.clone();
+        let run = run.clone();
+        dialog
+            .title("Run writes?")
+            .child(
+                v_flex().gap_2().child(message.clone()).child(
+                    div()
+                        .text_xs()
+                        .text_color(muted)
+                        .child("This connection's policy asks before changing data."),
+                ),
+            )
+            .footer(
+                DialogFooter::new()
+                    .child(
+                        DialogClose::new()
+                            .child(Button::new("policy-cancel").outline().label("Cancel")),
+                    )
+                    .child(DialogAction::new().child(run_btn)),
+            )
+            .on_ok(move |_, window, cx| {
+                let run = run.clone();
+                let _ = panel.update(cx, |panel, cx| run(panel, window, cx));
+                true
+            })
+    });
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use based_project::STATEMENT_KIND_NAMES;
+
+    fn policy(raw: &str) -> ConnectionPolicy {
+        toml::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn allow_list_names_match_classifier_labels() {
+        for kind in [
+            StatementKind::Select,
+            StatementKind::Insert,
+            StatementKind::Ddl,
+            StatementKind::Maintenance,
+            StatementKind::Other,
+        ] {
+            let name = kind.label().to_ascii_lowercase();
+            assert!(STATEMENT_KIND_NAMES.contains(&name.as_str()), "{name}");
+        }
+    }
+
+    #[test]
+    fn blocks_before_confirming() {
+        let p = policy(
+            "confirm_writes = true\nforbid_ddl = true\nrequire_where_for_update_delete = true\n",
+        );
+        assert_eq!(
+            evaluate(&p, "SELECT 1", Dialect::Postgres),
+            PolicyVerdict::Run
+        );
+        assert_eq!(
+            evaluate(&p, "UPDATE t SET a = 1 WHERE id = 2", Dialect::Postgres),
+            PolicyVerdict::Confirm("This runs 1 write statement (UPDATE).".into())
+        );
+        assert_eq!(
+            evaluate(
+                &p,
+                "INSERT INTO t VALUES (1); DROP TABLE t",
+                Dialect::Postgres
+            ),
+            PolicyVerdict::Block("DDL is forbidden on this connection".into())
+        );
+        assert_eq!(
+            evaluate(&p, "-- all\nDELETE FROM t", Dialect::Sqlite),
+            PolicyVerdict::Block("DELETE without WHERE is not allowed on this connection".into())
+        );
+    }
+
+    #[test]
+    fn allow_list_uses_statement_kinds() {
+        let p = policy("allowed_statements = [\"select\", \"explain\"]\n");
+        assert_eq!(
+            evaluate(
+                &p,
+                "WITH x AS (SELECT 1) SELECT * FROM x",
+                Dialect::Postgres
+            ),
+            PolicyVerdict::Run
+        );
+        assert_eq!(
+            evaluate(&p, "SELECT 1; COPY t FROM stdin", Dialect::Postgres),
+            PolicyVerdict::Block("COPY statements are not allowed on this connection".into())
+        );
+        assert_eq!(policy_summary(&p).as_deref(), Some("only SELECT, EXPLAIN"));
+    }
+
+    #[test]
+    fn writes_under_other_kinds_are_checked() {
+        let p = policy("allowed_statements = [\"select\", \"explain\"]\n");
+        assert_eq!(
+            evaluate(
+                &p,
+                "WITH g AS (DELETE FROM t RETURNING *) SELECT * FROM g",
+                Dialect::Postgres
+            ),
+            PolicyVerdict::Block("DELETE statements are not allowed on this connection".into())
+        );
+        assert_eq!(
+            evaluate(&p, "SELECT * INTO x FROM t", Dialect::Postgres),
+            PolicyVerdict::Block(
+                "SELECT statements that write are not allowed on this connection".into()
+            )
+        );
+        assert_eq!(
+            evaluate(&p, "EXPLAIN ANALYZE DELETE FROM t", Dialect::Postgres),
+            PolicyVerdict::Block("DELETE statements are not allowed on this connection".into())
+        );
+        assert_eq!(
+            evaluate(&p, "EXPLAIN DELETE FROM t", Dialect::Postgres),
+            PolicyVerdict::Run
+        );
+
+        let p = policy("require_where_for_update_delete = true\n");
+        assert_eq!(
+            evaluate(
+                &p,
+                "WITH g AS (DELETE FROM t RETURNING *) SELECT * FROM g",
+                Dialect::Postgres
+            ),
+            PolicyVerdict::Block("DELETE without WHERE is not allowed on this connection".into())
+        );
+        assert_eq!(
+            evaluate(
+                &p,
+                "EXPLAIN (ANALYZE) UPDATE t SET a = 1",
+                Dialect::Postgres
+            ),
+            PolicyVerdict::Block("UPDATE without WHERE is not allowed on this connection".into())
+        );
+        assert_eq!(
+            evaluate(
+                &p,
+                "WITH g AS (DELETE FROM t WHERE id = 1 RETURNING *) SELECT * FROM g",
+                Dialect::Postgres
+            ),
+            PolicyVerdict::Run
+        );
+    }
+
+    #[test]
+    fn maintenance_and_unknown_statements_are_writes() {
+        let p = policy("confirm_writes = true\nforbid_ddl = true\n");
+        assert_eq!(
+            evaluate(&p, "VACUUM FULL t; LOCK TABLE t", Dialect::Postgres),
+            PolicyVerdict::Confirm("This runs 2 write statements (MAINTENANCE, OTHER).".into())
+        );
+        assert_eq!(
+            evaluate(&p, "ATTACH 'other.db' AS other", Dialect::Sqlite),
+            PolicyVerdict::Block("DDL is forbidden on this connection".into())
+        );
+        let p = policy("allowed_statements = [\"select\", \"maintenance\"]\n");
+        assert_eq!(
+            evaluate(&p, "REFRESH MATERIALIZED VIEW mv", Dialect::Postgres),
+            PolicyVerdict::Run
+        );
+    }
+}
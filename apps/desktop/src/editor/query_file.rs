@@ -0,0 +1,197 @@
+//! Save editor text as a project query under `.based/queries/`.
+//!
+//! Tabs opened from a saved query write back to that file (or its external body file);
+//! other tabs ask for a name and path and create a new, reviewable query file.
+
+use std::rc::Rc;
+
+use based_core::EngineKind;
+use based_project::{
+    ProjectQuery, QueryBody, QueryTarget, TargetConnection, slug_from_label, write_query_file,
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    input::{Input, InputState},
+    v_flex,
+};
+
+use crate::connection::ConnectionId;
+use crate::editor::VariableScope;
+use crate::editor::variable_scope::VarType;
+use crate::project::{ProjectRoot, RegistryRef};
+use crate::query_store::QueryStore;
+use crate::widgets::labeled_field;
+use crate::workspace::notify::{push_error, push_info};
+use crate::workspace::project_query::include_in_project_target;
+
+fn saved_query(query_path: Option<&str>, cx: &App) -> Option<ProjectQuery> {
+    let path = query_path?;
+    cx.try_global::<QueryStore>()?
+        .project_queries()
+        .iter()
+        .find(|q| q.path == path)
+        .cloned()
+}
+
+/// `base` with the variable types the saved query at `query_path` declares in `[params]`.
+pub fn query_scope(base: &VariableScope, query_path: Option<&str>, cx: &App) -> VariableScope {
+    let mut scope = base.clone();
+    for (name, ty) in saved_query(query_path, cx)
+        .map(|q| q.params)
+        .unwrap_or_default()
+    {
+        if let Some(var_type) = VarType::from_name(&ty) {
+            scope.declare(name, var_type);
+        }
+    }
+    scope
+}
+
+/// Whether a project is open, so Save has a `.based/queries/` tree to write into.
+pub fn can_save_query(cx: &App) -> bool {
+    cx.has_global::<ProjectRoot>()
+}
+
+/// Write `body` to `query`, or to a new query file when `query.path` is not yet saved.
+/// Failures are reported as notifications. The project watcher picks the change up and
+/// reloads the query list.
+fn write_and_report(query: &ProjectQuery, cx: &mut App) -> bool {
+    let Some(root) = cx.try_global::<ProjectRoot>().map(|p| p.0.clone()) else {
+        return false;
+    };
+    match write_query_file(&root.join(".based"), query) {
+        Ok(path) => {
+            push_info(cx, format!("Saved {}", path.display()));
+            true
+        }
+        Err(e) => {
+            push_error(cx, "Save query", format!("{e:#}"));
+            false
+        }
+    }
+}
+
+/// Target for a query first saved from a tab on `conn_id`: that connection when it is
+/// committed to the project, else any connection of the same engine.
+fn new_query_target(conn_id: &ConnectionId, engine: EngineKind, cx: &App) -> QueryTarget {
+    let committed = cx
+        .try_global::<RegistryRef>()
+        .and_then(|r| r.0.read(cx).get(conn_id, cx))
+        .is_some_and(|entry| {
+            let entry = entry.read(cx);
+            include_in_project_target(entry.origin, &entry.id)
+        });
+    if committed {
+        QueryTarget {
+            connection: Some(TargetConnection::Exclusive(conn_id.0.clone())),
+            ..QueryTarget::default()
+        }
+    } else {
+        QueryTarget {
+            engine: Some(engine.as_str().to_string()),
+            ..QueryTarget::default()
+        }
+    }
+}
+
+/// Save the tab's text. A tab opened from a saved query rewrites that file in place;
+/// otherwise the "Save query" form asks for a name and path, and `saved` receives the
+/// new query path once the file is written. Returns whether an existing query was saved.
+pub fn save_query<T: 'static>(
+    query_path: Option<&str>,
+    body: QueryBody,
+    conn_id: &ConnectionId,
+    engine: EngineKind,
+    window: &mut Window,
+    cx: &mut Context<T>,
+    saved: impl Fn(&mut T, String, &mut Context<T>) + 'static,
+) -> bool {
+    if let Some(mut query) = saved_query(query_path, cx) {
+        query.body = body;
+        return write_and_report(&query, cx);
+    }
+    let target = new_query_target(conn_id, engine, cx);
+    let name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Monthly sales"));
+    let path_input = cx.new(|cx| {
+        InputState::new(window, cx).placeholder("Defaults to the name, e.g. reports/monthly-sales")
+    });
+    let panel = cx.entity().downgrade();
+    let saved = Rc::new(saved);
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let muted = cx.theme().muted_foreground;
+        let (name_input, path_input) = (name_input.clone(), path_input.clone());
+        let panel = panel.clone();
+        let saved = saved.clone();
+        let target = target.clone();
+        let body = body.clone();
+        dialog
+            .title("Save query")
+            .child(
+                v_flex()
+                    .gap_3()
+                    .child(labeled_field(
+                        "Name",
+                        muted,
+                        Input::new(&name_input).w_full(),
+                    ))
+                    .child(labeled_field(
+                        "Path in .based/queries",
+                        muted,
+                        Input::new(&path_input).w_full(),
+                    )),
+            )
+            .footer(
+                DialogFooter::new()
+                    .child(
+                        DialogClose::new()
+                            .child(Button::new("save-query-cancel").outline().label("Cancel")),
+                    )
+                    .child(
+                        DialogAction::new()
+                            .child(Button::new("save-query-ok").primary().label("Save")),
+                    ),
+            )
+            .on_ok(move |_, _, cx| {
+                let name = name_input.read(cx).value().trim().to_string();
+                if name.is_empty() {
+                    push_error(cx, "Save query", "Name is required");
+                    return false;
+                }
+                let path = path_input
+                    .read(cx)
+                    .value()
+                    .trim()
+                    .trim_matches('/')
+                    .to_string();
+                let path = if path.is_empty() {
+                    slug_from_label(&name)
+                } else {
+                    path
+                };
+                if saved_query(Some(&path), cx).is_some() {
+                    push_error(cx, "Save query", format!("Query {path} already exists"));
+                    return false;
+                }
+                let query = ProjectQuery {
+                    path: path.clone(),
+                    name,
+                    description: None,
+                    tags: vec![],
+                    target: target.clone(),
+                    params: Default::default(),
+                    body: body.clone(),
+                    body_file: None,
+                };
+                if !write_and_report(&query, cx) {
+                    return false;
+                }
+                let saved = saved.clone();
+                let _ = panel.update(cx, |panel, cx| saved(panel, path, cx));
+                true
+            })
+    });
+    false
+}
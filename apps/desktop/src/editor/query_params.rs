@@ -0,0 +1,84 @@
+//! Editor SQL to bind parameters.
+//!
+//! `$VAR` project variables are still spliced as text; `{{name}}` placeholders become
+//! engine-native parameters whose values are coerced by the [`VariableScope`] types, and
+//! `{{raw:name}}` stays text for identifiers.
+
+use based_core::Value;
+use based_query::{
+    Dialect, ResolveError, VariableContext, bind_sql, classify_statement, statements_in_script,
+};
+
+use crate::editor::VariableScope;
+use crate::project::{ProjectVars, substitute};
+
+/// A statement ready to run: SQL with `$n` / `?n` placeholders and their values.
+#[derive(Debug, Clone, Default)]
+pub struct BoundQuery {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+impl BoundQuery {
+    /// Whether running this again only reads. "Fetch more" re-runs the query with a
+    /// larger cap, which must not repeat a write such as `INSERT … RETURNING`.
+    pub fn can_rerun(&self, dialect: Dialect) -> bool {
+        statements_in_script(&self.sql, dialect)
+            .iter()
+            .all(|stmt| !classify_statement(stmt.text(&self.sql), dialect).is_write)
+    }
+}
+
+/// Variable context for editor runs: project variables fill the workspace and
+/// connection scopes, and the active project environment the environment scope.
+pub fn editor_variable_context(project_vars: &ProjectVars) -> VariableContext {
+    VariableContext {
+        workspace: project_vars.vars.clone(),
+        connection: project_vars.vars.clone(),
+        environment: project_vars.environment.clone(),
+        ..Default::default()
+    }
+}
+
+/// Substitute `$VAR`s from the workspace scope of `ctx`, then bind the `{{…}}`
+/// placeholders of `sql` for `dialect`.
+pub fn bind_editor_sql(
+    sql: &str,
+    dialect: Dialect,
+    ctx: &VariableContext,
+    scope: &VariableScope,
+) -> Result<BoundQuery, ResolveError> {
+    let sql = substitute(sql, &ctx.workspace);
+    let bound = bind_sql(&sql, ctx, dialect)?;
+    Ok(BoundQuery {
+        params: scope.bind_values(&bound.params)?,
+        sql: bound.sql,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(sql: &str) -> BoundQuery {
+        BoundQuery {
+            sql: sql.to_string(),
+            params: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn only_reads_can_be_rerun() {
+        assert!(query("SELECT * FROM t WHERE id > $1").can_rerun(Dialect::Postgres));
+        assert!(query("-- list\nVALUES (1); SHOW search_path").can_rerun(Dialect::Postgres));
+        for sql in [
+            "INSERT INTO t (a) VALUES (1) RETURNING id",
+            "UPDATE t SET a = 1 WHERE id = 2 RETURNING *",
+            "WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone",
+            "SELECT 1; DELETE FROM t RETURNING id",
+        ] {
+            assert!(!query(sql).can_rerun(Dialect::Postgres), "{sql}");
+        }
+        assert!(!query("DELETE FROM t RETURNING id").can_rerun(Dialect::Sqlite));
+    }
+}
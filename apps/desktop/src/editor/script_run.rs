@@ -0,0 +1,289 @@
+//! "Run script" state shared by the SQL editors.
+//!
+//! A [`ScriptRun`] holds one entry per statement of the script with its grid data or
+//! error, which statement the Results tab shows, and where a run that stopped on an
+//! error continues from.
+
+use based_core::{QueryErrorDetail, Value};
+use based_query::{
+    Dialect, ResolveError, StatementKind, VariableContext, classify_statement, statements_in_script,
+};
+use gpui::SharedString;
+use gpui_component::table::Column;
+
+use crate::editor::VariableScope;
+use crate::editor::query_params::{BoundQuery, bind_editor_sql};
+use crate::widgets::column_header::GridColumnMeta;
+use crate::widgets::result_tabs::{StatementTab, StatementTabState};
+
+/// Grid-ready result of one statement.
+#[derive(Clone, Default)]
+pub struct ResultGrid {
+    pub columns: Vec<Column>,
+    pub column_meta: Vec<GridColumnMeta>,
+    pub rows: Vec<Vec<Value>>,
+    pub rows_affected: u64,
+    pub truncated_at: Option<u64>,
+}
+
+pub enum StatementOutcome {
+    NotRun,
+    Done {
+        grid: ResultGrid,
+        elapsed_ms: u64,
+    },
+    Failed {
+        error: QueryErrorDetail,
+        elapsed_ms: u64,
+    },
+    Cancelled {
+        elapsed_ms: u64,
+    },
+}
+
+pub struct ScriptStatement {
+    pub query: BoundQuery,
+    pub kind: StatementKind,
+    pub outcome: StatementOutcome,
+}
+
+pub struct ScriptRun {
+    pub statements: Vec<ScriptStatement>,
+    /// Statement shown in the Results tab.
+    pub active: usize,
+    /// First statement left after a stop on error; "Continue" resumes here.
+    pub resume_at: Option<usize>,
+}
+
+impl ScriptRun {
+    /// Split `script` into statements and bind each one's placeholders; none has run yet.
+    pub fn new(
+        script: &str,
+        dialect: Dialect,
+        ctx: &VariableContext,
+        scope: &VariableScope,
+    ) -> Result<Self, ResolveError> {
+        let statements = statements_in_script(script, dialect)
+            .iter()
+            .map(|stmt| {
+                let query = bind_editor_sql(stmt.text(script), dialect, ctx, scope)?;
+                Ok(ScriptStatement {
+                    kind: classify_statement(&query.sql, dialect).kind,
+                    query,
+                    outcome: StatementOutcome::NotRun,
+                })
+            })
+            .collect::<Result<_, ResolveError>>()?;
+        Ok(Self {
+            statements,
+            active: 0,
+            resume_at: None,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// The bound statements as one script, for the connection policy. Each ends on its
+    /// own line, so a trailing `--` comment cannot swallow the separator.
+    pub fn bound_sql(&self) -> String {
+        self.statements
+            .iter()
+            .map(|s| s.query.sql.as_str())
+            .collect::<Vec<_>>()
+            .join("\n;\n")
+    }
+
+    /// The statements from `from` on, in order.
+    pub fn remaining(&self, from: usize) -> Vec<BoundQuery> {
+        self.statements[from..]
+            .iter()
+            .map(|s| s.query.clone())
+            .collect()
+    }
+
+    pub fn record(&mut self, index: usize, outcome: StatementOutcome) {
+        if let Some(stmt) = self.statements.get_mut(index) {
+            stmt.outcome = outcome;
+        }
+    }
+
+    /// Close a batch that started at `from` and ran `ran` statements: show the last one
+    /// and, when it failed with statements left, remember where to continue.
+    pub fn finish_batch(&mut self, from: usize, ran: usize) {
+        if ran == 0 {
+            return;
+        }
+        let last = from + ran - 1;
+        self.active = last;
+        let failed = matches!(
+            self.statements[last].outcome,
+            StatementOutcome::Failed { .. }
+        );
+        self.resume_at = (failed && last + 1 < self.statements.len()).then_some(last + 1);
+    }
+
+    pub fn active_grid(&self) -> Option<&ResultGrid> {
+        match &self.statements.get(self.active)?.outcome {
+            StatementOutcome::Done { grid, .. } => Some(grid),
+            _ => None,
+        }
+    }
+
+    /// Error of the active statement, prefixed with its position for the status bar.
+    pub fn active_error(&self) -> Option<QueryErrorDetail> {
+        match &self.statements.get(self.active)?.outcome {
+            StatementOutcome::Failed { error, .. } => Some(QueryErrorDetail {
+                category: error.category,
+                details: Some(format!(
+                    "Statement {}: {}",
+                    self.active + 1,
+                    error.details.as_deref().unwrap_or_default()
+                )),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn was_cancelled(&self) -> bool {
+        self.statements
+            .iter()
+            .any(|s| matches!(s.outcome, StatementOutcome::Cancelled { .. }))
+    }
+
+    pub fn total_affected(&self) -> u64 {
+        self.statements
+            .iter()
+            .filter_map(|s| match &s.outcome {
+                StatementOutcome::Done { grid, .. } => Some(grid.rows_affected),
+                _ => None,
+            })
+            .sum()
+    }
+
+    pub fn total_elapsed_ms(&self) -> u64 {
+        self.statements
+            .iter()
+            .map(|s| match s.outcome {
+                StatementOutcome::NotRun => 0,
+                StatementOutcome::Done { elapsed_ms, .. }
+                | StatementOutcome::Failed { elapsed_ms, .. }
+                | StatementOutcome::Cancelled { elapsed_ms } => elapsed_ms,
+            })
+            .sum()
+    }
+
+    pub fn tabs(&self) -> Vec<StatementTab> {
+        self.statements
+            .iter()
+            .enumerate()
+            .map(|(ix, stmt)| {
+                let label: SharedString = format!("{} · {}", ix + 1, stmt.kind.label()).into();
+                let (state, summary) = match &stmt.outcome {
+                    StatementOutcome::NotRun => (StatementTabState::NotRun, "Not run".to_string()),
+                    StatementOutcome::Done { grid, elapsed_ms } => {
+                        (StatementTabState::Ok, done_summary(grid, *elapsed_ms))
+                    }
+                    StatementOutcome::Failed { error, .. } => {
+                        (StatementTabState::Failed, error.display_message())
+                    }
+                    StatementOutcome::Cancelled { elapsed_ms } => (
+                        StatementTabState::Cancelled,
+                        format!("Cancelled after {elapsed_ms} ms"),
+                    ),
+                };
+                StatementTab {
+                    label,
+                    state,
+                    summary: summary.into(),
+                }
+            })
+            .collect()
+    }
+
+    /// Footer for the Messages tab when the run stopped with statements left.
+    pub fn stop_note(&self) -> Option<SharedString> {
+        let next = self.resume_at?;
+        let left = self.statements.len() - next;
+        Some(
+            format!(
+                "Stopped at statement {next} after an error. Continue runs the remaining {left}."
+            )
+            .into(),
+        )
+    }
+}
+
+fn done_summary(grid: &ResultGrid, elapsed_ms: u64) -> String {
+    if grid.columns.is_empty() {
+        return format!("{} affected · {elapsed_ms} ms", grid.rows_affected);
+    }
+    match grid.truncated_at {
+        Some(n) => format!(
+            "{} rows (truncated at {n} rows) · {elapsed_ms} ms",
+            grid.rows.len()
+        ),
+        None => format!("{} rows · {elapsed_ms} ms", grid.rows.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(sql: &str, dialect: Dialect) -> ScriptRun {
+        let mut ctx = VariableContext::default();
+        ctx.session.insert("id".into(), "1".into());
+        ScriptRun::new(sql, dialect, &ctx, &VariableScope::default()).unwrap()
+    }
+
+    fn failed() -> StatementOutcome {
+        StatementOutcome::Failed {
+            error: QueryErrorDetail::failed("relation \"nope\" does not exist"),
+            elapsed_ms: 2,
+        }
+    }
+
+    #[test]
+    fn splits_and_labels_statements() {
+        let run = script(
+            "CREATE TABLE t (id int);\nINSERT INTO t VALUES ({{id}});\n-- done\n",
+            Dialect::Postgres,
+        );
+        let labels: Vec<_> = run.tabs().iter().map(|t| t.label.to_string()).collect();
+        assert_eq!(labels, vec!["1 · DDL", "2 · INSERT"]);
+        let rest = run.remaining(1);
+        assert_eq!(rest[0].sql, "INSERT INTO t VALUES ($1)");
+        assert_eq!(rest[0].params, vec![Value::Text("1".into())]);
+        assert_eq!(
+            run.bound_sql(),
+            "CREATE TABLE t (id int)\n;\nINSERT INTO t VALUES ($1)"
+        );
+    }
+
+    #[test]
+    fn failure_with_statements_left_can_continue() {
+        let mut run = script("SELECT 1; SELECT nope; SELECT 3", Dialect::Sqlite);
+        run.record(
+            0,
+            StatementOutcome::Done {
+                grid: ResultGrid::default(),
+                elapsed_ms: 1,
+            },
+        );
+        run.record(1, failed());
+        run.finish_batch(0, 2);
+        assert_eq!(run.active, 1);
+        assert_eq!(run.resume_at, Some(2));
+        assert_eq!(
+            run.active_error().and_then(|e| e.details).as_deref(),
+            Some("Statement 2: relation \"nope\" does not exist")
+        );
+
+        // A failing last statement leaves nothing to continue.
+        run.record(2, failed());
+        run.finish_batch(2, 1);
+        assert_eq!(run.resume_at, None);
+    }
+}
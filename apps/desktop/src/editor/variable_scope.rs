@@ -1,8 +1,18 @@
 //! Typed variable scope for editor autocomplete and query substitution.
+//!
+//! A variable's [`VarType`] also decides how a `{{name}}` bind parameter is coerced
+//! before it reaches the driver. Saved queries declare types in `[params]`; values
+//! without one bind as text, so a zip code such as `02134` still matches a text column,
+//! and Postgres reads them as the type it infers for the parameter.
 
 use std::collections::HashMap;
 
-/// The data type of a variable, used to drive autocomplete hints.
+use based_core::Value;
+use based_query::{ResolveError, SqlParam};
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Month, OffsetDateTime};
+
+/// The data type of a variable, used to drive autocomplete hints and bind coercion.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VarType {
     String,
@@ -14,6 +24,85 @@ pub enum VarType {
     Json,
 }
 
+impl VarType {
+    /// Type named in a query file's `[params]` table.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "string" => Self::String,
+            "integer" => Self::Integer,
+            "float" => Self::Float,
+            "boolean" => Self::Boolean,
+            "date" => Self::Date,
+            "json" => Self::Json,
+            _ => return None,
+        })
+    }
+
+    /// Type of a builtin such as `{{$timestamp}}`, by its name without the `$`.
+    fn builtin(name: &str) -> Self {
+        if name == "timestamp" || name.starts_with("randomInt(") {
+            Self::Integer
+        } else if name == "isoTimestamp" {
+            Self::Date
+        } else {
+            Self::String
+        }
+    }
+
+    /// Input placeholder for the run-time parameter form.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::String => "text",
+            Self::Integer => "integer",
+            Self::Float => "number",
+            Self::Boolean => "true or false",
+            Self::Date => "YYYY-MM-DD",
+            Self::Json => "JSON",
+        }
+    }
+
+    /// Coerce `raw` to a bind value of this type.
+    pub fn coerce(&self, raw: &str) -> Result<Value, String> {
+        let trimmed = raw.trim();
+        match self {
+            Self::String => Ok(Value::Text(raw.to_string())),
+            Self::Integer => trimmed
+                .parse()
+                .map(Value::Int)
+                .map_err(|_| format!("expected an integer, got {raw:?}")),
+            Self::Float => trimmed
+                .parse()
+                .map(Value::Float)
+                .map_err(|_| format!("expected a number, got {raw:?}")),
+            Self::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(Value::Bool(true)),
+                "false" | "0" | "no" => Ok(Value::Bool(false)),
+                _ => Err(format!("expected true or false, got {raw:?}")),
+            },
+            Self::Date => OffsetDateTime::parse(trimmed, &Rfc3339)
+                .map(Value::TimestampTz)
+                .ok()
+                .or_else(|| parse_date(trimmed).map(Value::Date))
+                .ok_or_else(|| {
+                    format!("expected YYYY-MM-DD or an RFC 3339 timestamp, got {raw:?}")
+                }),
+            Self::Json => serde_json::from_str(trimmed)
+                .map(Value::Json)
+                .map_err(|e| format!("invalid JSON: {e}")),
+        }
+    }
+}
+
+fn parse_date(raw: &str) -> Option<Date> {
+    let mut parts = raw.splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let month = Month::try_from(month.parse::<u8>().ok()?).ok()?;
+    Date::from_calendar_date(year.parse().ok()?, month, day.parse().ok()?).ok()
+}
+
 /// A single named variable available in the editor.
 #[derive(Debug, Clone)]
 pub struct ScopedVar {
@@ -31,6 +120,8 @@ pub struct ScopedVar {
 #[derive(Debug, Clone, Default)]
 pub struct VariableScope {
     vars: HashMap<String, ScopedVar>,
+    /// Types the saved query declares, by variable name.
+    declared: HashMap<String, VarType>,
 }
 
 impl VariableScope {
@@ -54,7 +145,10 @@ impl VariableScope {
                 )
             })
             .collect();
-        Self { vars }
+        Self {
+            vars,
+            declared: HashMap::new(),
+        }
     }
 
     pub fn get(&self, name: &str) -> Option<&ScopedVar> {
@@ -75,4 +169,137 @@ impl VariableScope {
     pub fn is_empty(&self) -> bool {
         self.vars.is_empty()
     }
+
+    /// Declare the type `name` binds as, overriding the variable's own.
+    pub fn declare(&mut self, name: impl Into<String>, var_type: VarType) {
+        self.declared.insert(name.into(), var_type);
+    }
+
+    /// Type declared for `name` by the saved query, if any.
+    pub fn declared_type(&self, name: &str) -> Option<&VarType> {
+        self.declared.get(name)
+    }
+
+    /// Type `name` binds as: the builtin's type for `$name`, its declared type, else text.
+    pub fn var_type(&self, name: &str) -> VarType {
+        if let Some(builtin) = name.strip_prefix('$') {
+            return VarType::builtin(builtin);
+        }
+        self.declared_type(name)
+            .or_else(|| self.get(name).map(|var| &var.var_type))
+            .map_or(VarType::String, VarType::clone)
+    }
+
+    /// Coerce bound SQL parameters to driver values, in bind order.
+    pub fn bind_values(&self, params: &[SqlParam]) -> Result<Vec<Value>, ResolveError> {
+        params
+            .iter()
+            .map(|param| {
+                self.var_type(&param.name)
+                    .coerce(&param.value)
+                    .map_err(|reason| ResolveError::InvalidValue {
+                        name: param.name.clone(),
+                        reason,
+                    })
+            })
+            .collect()
+    }
+
+    /// Coerce a pipeline parameter to the JSON that becomes its BSON value; dates use
+    /// extended JSON (`{"$date": …}`) so they arrive as BSON datetimes.
+    pub fn bind_json(&self, param: &SqlParam) -> Result<serde_json::Value, ResolveError> {
+        let value = self
+            .var_type(&param.name)
+            .coerce(&param.value)
+            .map_err(|reason| ResolveError::InvalidValue {
+                name: param.name.clone(),
+                reason,
+            })?;
+        let instant = match value {
+            Value::Date(date) => date.midnight().assume_utc(),
+            Value::TimestampTz(ts) => ts,
+            other => return Ok(other.to_json()),
+        };
+        let iso = instant
+            .format(&Rfc3339)
+            .map_err(|e| ResolveError::InvalidValue {
+                name: param.name.clone(),
+                reason: e.to_string(),
+            })?;
+        Ok(serde_json::json!({ "$date": iso }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untyped_values_bind_as_text() {
+        let mut scope = VariableScope::from_string_map(&HashMap::from([
+            ("id".to_string(), "42".to_string()),
+            ("agent".to_string(), "007".to_string()),
+        ]));
+        scope.vars.insert(
+            "limit".into(),
+            ScopedVar {
+                name: "limit".into(),
+                value: "10".into(),
+                var_type: VarType::Integer,
+                description: None,
+            },
+        );
+        let params = ["id", "agent", "limit", "$timestamp"].map(|name| SqlParam {
+            name: name.into(),
+            value: "7".into(),
+        });
+        assert_eq!(
+            scope.bind_values(&params).unwrap(),
+            vec![
+                Value::Text("7".into()),
+                Value::Text("7".into()),
+                Value::Int(7),
+                Value::Int(7),
+            ]
+        );
+        assert_eq!(scope.var_type("missing"), VarType::String);
+        assert_eq!(scope.var_type("$isoTimestamp"), VarType::Date);
+    }
+
+    #[test]
+    fn declared_types_override_the_variable() {
+        let mut scope =
+            VariableScope::from_string_map(&HashMap::from([("id".to_string(), "42".to_string())]));
+        scope.declare("id", VarType::from_name("integer").unwrap());
+        scope.declare("from", VarType::Date);
+        let params = ["id", "from"].map(|name| SqlParam {
+            name: name.into(),
+            value: if name == "id" { "42" } else { "2024-01-01" }.into(),
+        });
+        let values = scope.bind_values(&params).unwrap();
+        assert_eq!(values[0], Value::Int(42));
+        assert!(matches!(values[1], Value::Date(_)));
+        assert_eq!(scope.declared_type("id"), Some(&VarType::Integer));
+        assert_eq!(VarType::from_name("numeric"), None);
+    }
+
+    #[test]
+    fn coercion_reports_bad_values() {
+        assert_eq!(VarType::Integer.coerce(" 7 "), Ok(Value::Int(7)));
+        assert_eq!(VarType::Boolean.coerce("no"), Ok(Value::Bool(false)));
+        assert!(VarType::Integer.coerce("seven").is_err());
+
+        let scope = VariableScope::from_string_map(&HashMap::from([(
+            "zip".to_string(),
+            "02134".to_string(),
+        )]));
+        let params = [SqlParam {
+            name: "zip".into(),
+            value: "02134".into(),
+        }];
+        assert_eq!(
+            scope.bind_values(&params).unwrap(),
+            vec![Value::Text("02134".into())]
+        );
+    }
 }
@@ -35,7 +35,7 @@ use mongodb::MongoEngine;
 use postgres::PostgresEngine;
 use project::{
     ProjectContext, ProjectVars, find_project_root, install_reload_watcher,
-    settings::apply_project_settings, variables::load_variables,
+    variables::{load_environment_vars, load_variables},
 };
 use sqlite::SqliteEngine;
 use workspace::{PopOutManager, SqlInject, TabOpenQueue, WorkspaceNavQueue};
@@ -78,14 +78,14 @@ fn main() {
             PopOutManager::init(cx);
             app::aux_windows::AuxWindows::init(cx);
             app::launch::AppLaunch::init(cx);
+            connection::host_keys::init(cx);
 
             let project_root = find_project_root();
             let project_context = project_root
                 .as_ref()
-                .and_then(|root| ProjectContext::load(root.clone()).ok());
+                .map(|root| ProjectContext::load(root.clone()));
             if let Some(ref ctx) = project_context {
                 cx.set_global(ctx.clone());
-                apply_project_settings(&ctx.snapshot.manifest, cx);
             }
             query_store::init(
                 project_root.clone(),
@@ -102,7 +102,8 @@ fn main() {
                     })
                 })
                 .unwrap_or_default();
-            cx.set_global(ProjectVars { vars });
+            let environment = project_root.as_deref().and_then(load_environment_vars);
+            cx.set_global(ProjectVars { vars, environment });
             if let Some(root) = project_root.clone() {
                 install_reload_watcher(root, cx);
             }
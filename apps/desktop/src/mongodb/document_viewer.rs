@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 
+use based_core::categorize_query_error;
 use gpui::{prelude::*, *};
 use gpui_component::{
     ActiveTheme, Sizable as _,
@@ -18,6 +19,8 @@ use mongodb::options::FindOptions;
 use gpui_component::table::TableEvent;
 
 use crate::db;
+use crate::mongodb::map_timeout_error;
+use crate::project::settings::query_timeout;
 use crate::widgets::cell_detail::{CellDetail, CellValue, interpret_cell_with_meta};
 use crate::widgets::data_table::{configure_row_table, render_row_table};
 use crate::widgets::export;
@@ -26,7 +29,7 @@ use crate::widgets::panel::data_viewer_toolbar;
 use crate::widgets::virtual_table::{
     RowDelegate, align_meta_to_columns, data_column, replace_table_data,
 };
-use crate::workspace::notify::push_export_success;
+use crate::workspace::notify::{push_error, push_export_success};
 
 fn mongo_filter_doc(expr: &FilterExpr) -> Document {
     let s = expr.to_mongo_filter();
@@ -106,10 +109,11 @@ impl DocumentViewerPanel {
             .current_expr(cx)
             .map(|e| mongo_filter_doc(&e))
             .unwrap_or_else(|| doc! {});
+        let timeout = query_timeout(cx);
 
         cx.spawn(async move |this, cx| {
             let docs = match db::run(cx, async move {
-                let opts = FindOptions::builder().limit(lim).build();
+                let opts = FindOptions::builder().limit(lim).max_time(timeout).build();
                 let mut cursor = coll.find(filter_doc, opts).await?;
                 let mut docs: Vec<Document> = Vec::new();
                 use futures::TryStreamExt;
@@ -121,8 +125,10 @@ impl DocumentViewerPanel {
             .await
             {
                 Ok(d) => d,
-                Err(_) => {
+                Err(e) => {
+                    let detail = categorize_query_error(&map_timeout_error(e, timeout));
                     let _ = cx.update(|cx| {
+                        push_error(cx, "Load documents", detail.display_message());
                         this.update(cx, |panel, cx| {
                             panel.loading = false;
                             cx.notify();
@@ -210,21 +216,7 @@ impl Render for DocumentViewerPanel {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let muted = cx.theme().muted_foreground;
 
-        let (export_headers, export_rows) = {
-            let st = self.table.read(cx);
-            let d = st.delegate();
-            let h = d
-                .columns
-                .iter()
-                .map(|c| c.key.to_string())
-                .collect::<Vec<_>>();
-            let r = d
-                .rows
-                .iter()
-                .map(|row| row.iter().map(|c| c.to_string()).collect())
-                .collect::<Vec<Vec<String>>>();
-            (h, r)
-        };
+        let (export_headers, export_rows) = self.table.read(cx).delegate().export_snapshot();
 
         v_flex()
             .relative()
@@ -11,7 +11,10 @@ pub mod pipeline_builder;
 pub mod tab_dispatch;
 pub mod tree;
 
-pub use based_mongo::{MongoConfig, document_from_json, mongo_uri, mongosh_command};
+pub use based_mongo::{
+    MongoCancelHandle, MongoConfig, document_from_json, is_interrupted, map_timeout_error,
+    mongo_uri, mongosh_command,
+};
 
 use mongodb::bson::doc;
 use mongodb::options::ClientOptions;
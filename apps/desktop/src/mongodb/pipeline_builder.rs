@@ -2,6 +2,9 @@
 
 use std::collections::HashMap;
 
+use based_core::categorize_query_error;
+use based_project::QueryBody;
+use based_query::{VariableContext, bind_json};
 use gpui::{prelude::*, *};
 use gpui_component::{
     Sizable as _,
@@ -15,9 +18,17 @@ use gpui_component::{
 };
 use mongodb::Collection;
 use mongodb::bson::{Document, to_document};
+use mongodb::options::AggregateOptions;
 
 use crate::connection::ConnectionId;
 use crate::db;
+use crate::editor::VariableScope;
+use crate::editor::param_form::{param_key, prompt_missing_params};
+use crate::editor::query_file::{can_save_query, query_scope, save_query};
+use crate::editor::query_params::editor_variable_context;
+use crate::mongodb::{MongoCancelHandle, is_interrupted, map_timeout_error};
+use crate::project::ProjectVars;
+use crate::project::settings::query_timeout;
 use crate::query_store::{HistoryEntry, QueryStore};
 use crate::widgets::data_table::{configure_row_table, render_row_table};
 use crate::widgets::export;
@@ -35,7 +46,11 @@ pub struct PipelineBuilderPanel {
     pipeline_input: Entity<EditorState>,
     result: Entity<TableState<RowDelegate>>,
     status: SharedString,
+    /// Set while an aggregate is running; its tag is used for `killOp`.
+    running: Option<MongoCancelHandle>,
     pub(crate) tab_label: SharedString,
+    /// Saved project query this tab was opened from; keys remembered parameter values.
+    pub(crate) query_path: Option<String>,
 }
 
 impl PipelineBuilderPanel {
@@ -68,7 +83,9 @@ impl PipelineBuilderPanel {
             pipeline_input,
             result,
             status: SharedString::from(""),
+            running: None,
             tab_label,
+            query_path: None,
         }
     }
 
@@ -80,16 +97,90 @@ impl PipelineBuilderPanel {
         self.collection.name()
     }
 
-    fn run(&mut self, cx: &mut Context<Self>) {
+    /// Stop the running aggregate: drop the cursor and `killOp` the server operation.
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        let Some(handle) = self.running.clone() else {
+            return;
+        };
+        let client = self.collection.client().clone();
+        self.status = "Cancelling…".into();
+        cx.spawn(async move |_, cx| {
+            if let Err(e) = db::run(cx, async move { handle.cancel(&client).await }).await {
+                log::warn!("killOp failed: {e:#}");
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    /// Write the pipeline to its saved query, or save it as a new project query.
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let body = QueryBody::Aggregate {
+            pipeline: text_from_input(&self.pipeline_input, cx),
+            collection: Some(self.collection.name().to_string()),
+        };
+        save_query(
+            self.query_path.as_deref(),
+            body,
+            &self.conn_id,
+            based_core::EngineKind::MongoDB,
+            window,
+            cx,
+            |panel, path, _| panel.query_path = Some(path),
+        );
+    }
+
+    fn run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.running.is_some() {
+            return;
+        }
+        let raw = text_from_input(&self.pipeline_input, cx);
+        let project_vars = cx
+            .try_global::<ProjectVars>()
+            .map(|pv| pv.vars.clone())
+            .unwrap_or_default();
+        let var_ctx = cx
+            .try_global::<ProjectVars>()
+            .map(editor_variable_context)
+            .unwrap_or_default();
+        let scope = query_scope(
+            &VariableScope::from_string_map(&project_vars),
+            self.query_path.as_deref(),
+            cx,
+        );
+        let key = param_key(self.query_path.as_deref(), &self.conn_id);
+        if let Some(var_ctx) =
+            prompt_missing_params(&raw, var_ctx, &scope, key, window, cx, Self::run_with)
+        {
+            self.run_with(var_ctx, window, cx);
+        }
+    }
+
+    fn run_with(&mut self, var_ctx: VariableContext, _: &mut Window, cx: &mut Context<Self>) {
         let coll = self.collection.clone();
         let raw = text_from_input(&self.pipeline_input, cx);
         let conn_id = self.conn_id.clone();
+        let handle = MongoCancelHandle::new();
+        let timeout = query_timeout(cx);
+        let project_vars = cx
+            .try_global::<ProjectVars>()
+            .map(|pv| pv.vars.clone())
+            .unwrap_or_default();
+        let scope = query_scope(
+            &VariableScope::from_string_map(&project_vars),
+            self.query_path.as_deref(),
+            cx,
+        );
+        self.running = Some(handle.clone());
+        self.status = "Running…".into();
+        cx.notify();
         cx.spawn(async move |this, cx| {
-            let vals: Vec<serde_json::Value> = match serde_json::from_str(&raw) {
+            let mut vals: Vec<serde_json::Value> = match serde_json::from_str(&raw) {
                 Ok(v) => v,
                 Err(e) => {
                     let _ = cx.update(|cx| {
                         this.update(cx, |p, cx| {
+                            p.running = None;
                             p.status = format!("invalid JSON: {e}").into();
                             cx.notify();
                         })
@@ -98,6 +189,20 @@ impl PipelineBuilderPanel {
                 }
             };
 
+            // `"{{name}}"` strings become typed BSON values rather than spliced text.
+            for stage in &mut vals {
+                if let Err(e) = bind_json(stage, &var_ctx, &mut |param| scope.bind_json(param)) {
+                    let _ = cx.update(|cx| {
+                        this.update(cx, |p, cx| {
+                            p.running = None;
+                            p.status = e.to_string().into();
+                            cx.notify();
+                        })
+                    });
+                    return;
+                }
+            }
+
             let mut stages = Vec::<Document>::new();
             for v in vals {
                 match to_document(&v) {
@@ -105,6 +210,7 @@ impl PipelineBuilderPanel {
                     Err(e) => {
                         let _ = cx.update(|cx| {
                             this.update(cx, |p, cx| {
+                                p.running = None;
                                 p.status = format!("BSON error: {e}").into();
                                 cx.notify();
                             })
@@ -116,25 +222,54 @@ impl PipelineBuilderPanel {
 
             let pipeline_for_history = raw.clone();
             let start = Instant::now();
+            let run_handle = handle.clone();
             let docs_result = db::run(cx, async move {
-                let mut cursor = coll.aggregate(stages, None).await?;
+                let opts = AggregateOptions::builder()
+                    .comment(run_handle.comment())
+                    .max_time(timeout)
+                    .build();
+                let mut cursor = coll.aggregate(stages, opts).await?;
                 let mut docs = Vec::<Document>::new();
                 use futures::TryStreamExt;
-                while let Some(d) = cursor.try_next().await.unwrap_or(None) {
+                while let Some(d) = cursor.try_next().await? {
+                    // Dropping the cursor early sends `killCursors`.
+                    if run_handle.is_cancelled() {
+                        break;
+                    }
                     docs.push(d);
                 }
                 Ok(docs)
             })
-            .await;
+            .await
+            .map_err(|e| map_timeout_error(e, timeout));
 
             let ms = start.elapsed().as_millis() as u64;
 
+            let cancelled =
+                handle.is_cancelled() || docs_result.as_ref().is_err_and(is_interrupted);
             let docs = match docs_result {
-                Ok(d) => d,
-                Err(e) => {
+                Ok(d) if !cancelled => d,
+                outcome => {
+                    let (status, run_status) = match outcome {
+                        Err(e) if !cancelled => (
+                            categorize_query_error(&e).display_message(),
+                            based_query::RunStatus::Error,
+                        ),
+                        _ => ("Cancelled".to_string(), based_query::RunStatus::Cancelled),
+                    };
                     let _ = cx.update(|cx| {
                         this.update(cx, |p, cx| {
-                            p.status = format!("aggregate error: {e}").into();
+                            p.running = None;
+                            p.status = status.into();
+                            cx.update_global(|store: &mut QueryStore, _| {
+                                store.push_history(HistoryEntry::new(
+                                    conn_id.clone(),
+                                    pipeline_for_history,
+                                    ms,
+                                    None,
+                                    run_status,
+                                ));
+                            });
                             cx.notify();
                         })
                     });
@@ -172,6 +307,7 @@ impl PipelineBuilderPanel {
 
             let _ = cx.update(|cx| {
                 this.update(cx, |panel, cx| {
+                    panel.running = None;
                     panel.status = format!("{} rows", rows.len()).into();
                     cx.update_global(|store: &mut QueryStore, _| {
                         store.push_history(HistoryEntry::new(
@@ -222,21 +358,8 @@ impl Panel for PipelineBuilderPanel {
 
 impl Render for PipelineBuilderPanel {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let (export_headers, export_rows) = {
-            let st = self.result.read(cx);
-            let d = st.delegate();
-            let h = d
-                .columns
-                .iter()
-                .map(|c| c.key.to_string())
-                .collect::<Vec<_>>();
-            let r = d
-                .rows
-                .iter()
-                .map(|row| row.iter().map(|c| c.to_string()).collect())
-                .collect::<Vec<Vec<String>>>();
-            (h, r)
-        };
+        let (export_headers, export_rows) = self.result.read(cx).delegate().export_snapshot();
+        let can_save = can_save_query(cx);
 
         v_flex()
             .size_full()
@@ -260,8 +383,26 @@ impl Render for PipelineBuilderPanel {
                         Button::new("mongo-run-pipe")
                             .primary()
                             .label("Run pipeline")
-                            .on_click(cx.listener(|p, _, _, cx| p.run(cx))),
+                            .on_click(cx.listener(|p, _, window, cx| p.run(window, cx))),
                     )
+                    .when(self.running.is_some(), |row| {
+                        row.child(
+                            Button::new("mongo-cancel-pipe")
+                                .ghost()
+                                .small()
+                                .label("Cancel")
+                                .on_click(cx.listener(|p, _, _, cx| p.cancel(cx))),
+                        )
+                    })
+                    .when(can_save, |row| {
+                        row.child(
+                            Button::new("mongo-save-pipe")
+                                .ghost()
+                                .small()
+                                .label("Save")
+                                .on_click(cx.listener(|p, _, window, cx| p.save(window, cx))),
+                        )
+                    })
                     .child(
                         Button::new("mongo-pipe-export-json")
                             .ghost()
@@ -32,7 +32,10 @@ pub fn build_panel(
             let collection: Collection<Document> = db.collection(object);
             let panel = cx
                 .new(|cx| super::document_viewer::DocumentViewerPanel::new(collection, window, cx));
-            panel.update(cx, |p, _| p.tab_label = label);
+            panel.update(cx, |p, _| {
+                p.tab_label = label;
+                p.query_path = query_path.clone();
+            });
             Some(Arc::new(panel))
         }
         TabSpec::QueryEditor {
@@ -40,6 +43,7 @@ pub fn build_panel(
                 QueryEditorInit::MongoPipeline {
                     pipeline,
                     collection,
+                    query_path,
                 },
             ..
         } => {
@@ -59,7 +63,10 @@ pub fn build_panel(
                     cx,
                 )
             });
-            panel.update(cx, |p, _| p.tab_label = label);
+            panel.update(cx, |p, _| {
+                p.tab_label = label;
+                p.query_path = query_path.clone();
+            });
             Some(Arc::new(panel))
         }
         TabSpec::QueryEditor {
@@ -1,6 +1,6 @@
 use std::time::Instant;
 
-use gpui::{App, Task};
+use gpui::{App, Context, Task};
 // postgres/ — GPUI panels + connection lifecycle; driver logic in `based-postgres`.
 
 pub mod data_viewer;
@@ -13,16 +13,21 @@ pub mod tree;
 pub mod wizard;
 
 pub use based_postgres::{
-    PostgresConfig, SslMode, execute_sql, pg_connect_options, postgres_uri, psql_command,
+    PgCancelHandle, PgManualTx, PgScriptSession, PgSettings, PostgresConfig, SslMode,
+    TargetSessionAttrs as PgTargetSessionAttrs, connect_pool, execute_sql_cancellable,
+    is_query_cancelled, postgres_uri, psql_command, resolve_libpq,
 };
 
 use sqlx::PgPool;
-use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::postgres::PgPoolOptions;
 
+use crate::connection::host_keys::host_key_policy;
 use crate::connection::lifecycle::{Connectable, TestReport};
 use crate::connection::tunnel::{open_optional_tunnel, rewrite_tcp_endpoint};
 use crate::db;
-use based_ssh::SshTunnel;
+use crate::workspace::WorkspaceRef;
+use crate::workspace::notify::push_error;
+use based_ssh::{HostKeyPolicy, SshTunnel, TunnelHealth};
 use gpui_tokio::Tokio;
 
 /// Live Postgres connection wrapping a sqlx pool.
@@ -33,41 +38,76 @@ pub struct PgConnection {
     pub pool: PgPool,
 }
 
-async fn connect_opts(
+impl PgConnection {
+    /// Health of the SSH tunnel in front of the pool, if there is one.
+    pub fn tunnel_health(&self) -> Option<TunnelHealth> {
+        self.tunnel.as_ref().map(SshTunnel::health)
+    }
+
+    /// Re-render the workspace as the tunnel reconnects, so the status bar follows it,
+    /// and report a tunnel that gave up.
+    pub fn watch_tunnel(&self, cx: &mut Context<Self>) {
+        let Some(mut health) = self.tunnel.as_ref().map(SshTunnel::watch_health) else {
+            return;
+        };
+        let label = self.config.label.clone();
+        cx.spawn(async move |_, cx| {
+            while health.changed().await.is_ok() {
+                let state = health.borrow_and_update().clone();
+                cx.update(|cx| {
+                    if let TunnelHealth::Failed(reason) = state {
+                        push_error(cx, format!("SSH tunnel lost: {label}"), reason);
+                    }
+                    if let Some(ws) = cx.try_global::<WorkspaceRef>().map(|w| w.0.clone()) {
+                        ws.update(cx, |_, cx| cx.notify());
+                    }
+                });
+            }
+        })
+        .detach();
+    }
+}
+
+/// Pool on the first acceptable host, after applying the libpq service and `.pgpass`.
+/// Through an SSH tunnel only the primary host is reachable, so failover hosts are
+/// skipped.
+async fn connect(
     config: &PostgresConfig,
-) -> anyhow::Result<(PgConnectOptions, Option<SshTunnel>, PostgresConfig)> {
-    let stored = config.clone();
+    pool: PgPoolOptions,
+    host_keys: HostKeyPolicy,
+) -> anyhow::Result<(PgPool, Option<SshTunnel>)> {
+    let mut connect = resolve_libpq(config)?;
     let tunnel = open_optional_tunnel(
-        config.ssh.as_ref(),
-        &config.host,
-        config.port,
-        config.ssl_mode,
+        connect.ssh.as_ref(),
+        &connect.host,
+        connect.port,
+        connect.ssl_mode,
+        host_keys,
     )
     .await?;
-    let mut connect = config.clone();
     if let Some(tunnel) = &tunnel {
         rewrite_tcp_endpoint(&mut connect.host, &mut connect.port, tunnel);
+        connect.settings.failover_hosts.clear();
     }
-    Ok((pg_connect_options(&connect), tunnel, stored))
+    let pool = connect_pool(&connect, pool).await?;
+    Ok((pool, tunnel))
 }
 
 impl Connectable for PgConnection {
     type Config = PostgresConfig;
 
     fn open(config: Self::Config, cx: &mut App) -> Task<anyhow::Result<Self>> {
+        let host_keys = host_key_policy(cx);
         Tokio::spawn_result(cx, async move {
-            let (opts, tunnel, stored) = connect_opts(&config).await?;
-            let pool = PgPoolOptions::new()
-                .max_connections(8)
-                .connect_with(opts)
-                .await?;
+            let pool = PgPoolOptions::new().max_connections(8);
+            let (pool, tunnel) = connect(&config, pool, host_keys).await?;
             let version: String = sqlx::query_scalar("SELECT version()")
                 .fetch_one(&pool)
                 .await?;
             let short = version.lines().next().unwrap_or(&version).to_string();
             Ok(Self {
                 tunnel,
-                config: stored,
+                config,
                 pool,
                 server_version: Some(short),
             })
@@ -76,13 +116,11 @@ impl Connectable for PgConnection {
 
     fn test(config: &Self::Config, cx: &mut App) -> Task<anyhow::Result<TestReport>> {
         let config = config.clone();
+        let host_keys = host_key_policy(cx);
         Tokio::spawn_result(cx, async move {
             let start = Instant::now();
-            let (opts, _tunnel, _) = connect_opts(&config).await?;
-            let pool = PgPoolOptions::new()
-                .max_connections(1)
-                .connect_with(opts)
-                .await?;
+            let pool = PgPoolOptions::new().max_connections(1);
+            let (pool, _tunnel) = connect(&config, pool, host_keys).await?;
             let version: String = sqlx::query_scalar("SELECT version()")
                 .fetch_one(&pool)
                 .await?;
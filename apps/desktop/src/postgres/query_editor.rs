@@ -1,7 +1,12 @@
 // postgres::query_editor — run ad-hoc SQL against a pool.
 
+use std::cell::Cell;
 use std::rc::Rc;
 
+use based_core::{QueryErrorDetail, categorize_query_error};
+use based_postgres::{BoundStatement, QueryResult, ScriptStep};
+use based_project::QueryBody;
+use based_query::{Dialect, VariableContext};
 use gpui::{App, prelude::*, *};
 use gpui_component::{
     ActiveTheme, IconName, Sizable as _,
@@ -21,12 +26,24 @@ use crate::connection::ConnectionId;
 use crate::db;
 use crate::editor::EditorContext;
 use crate::editor::VariableScope;
-use crate::postgres::execute_sql;
+use crate::editor::open_transactions::{mark_transaction_closed, mark_transaction_open};
+use crate::editor::param_form::{param_key, prompt_missing_params};
+use crate::editor::policy::{
+    check_connection_policy, connection_policy, next_row_cap, policy_summary, row_cap,
+};
+use crate::editor::query_file::{can_save_query, query_scope, save_query};
+use crate::editor::query_params::{BoundQuery, bind_editor_sql, editor_variable_context};
+use crate::editor::script_run::{ResultGrid, ScriptRun, StatementOutcome};
 use crate::postgres::explain_plan::{PlanNode, parse_pg_explain_json, render_plan_node};
-use crate::project::{ProjectRoot, ProjectVars, substitute};
+use crate::postgres::{
+    PgCancelHandle, PgManualTx, PgScriptSession, execute_sql_cancellable, is_query_cancelled,
+};
+use crate::project::settings::{max_result_rows, query_timeout};
+use crate::project::{ProjectRoot, ProjectVars};
 use crate::query_store::{HistoryEntry, QueryStore};
 use crate::widgets::data_table::{configure_row_table, render_row_table};
 use crate::widgets::export_popover::export_popover;
+use crate::widgets::metadata_pill::metadata_pill;
 use crate::widgets::panel::{
     panel_tab_content, tab_breadcrumb_footer, tab_breadcrumb_for_connection,
 };
@@ -34,14 +51,17 @@ use crate::widgets::query_panel_extras;
 use crate::widgets::query_status::{
     QueryStatusDisplay, query_error_card, query_status_indicator, tab_breadcrumb_query_trailing,
 };
-use crate::widgets::result_tabs::{BottomTab, result_tab_strip};
+use crate::widgets::result_tabs::{
+    BottomTab, result_tab_strip, statement_message_list, statement_tab_strip,
+};
 use crate::widgets::shortcut_run_kbd_in_primary_button;
 use crate::widgets::sql_editor::{self, new_sql_input, set_input_text, text_from_input};
 use crate::widgets::virtual_table::{
-    RowDelegate, data_column, meta_from_query_type, replace_table_data,
+    RowDelegate, data_column, meta_from_query_type, replace_table_values,
 };
+use crate::workspace::notify::push_error;
 use crate::workspace::pop_out::PopOutWindowTitle;
-use crate::workspace::{mark_query_tab_dirty, tabs::take_sql_inject};
+use crate::workspace::{mark_query_tab_clean, mark_query_tab_dirty, tabs::take_sql_inject};
 use gpui::Entity;
 
 use std::time::Instant;
@@ -53,8 +73,10 @@ pub enum QueryStatus {
         rows: usize,
         affected: u64,
         elapsed_ms: u64,
+        truncated_at: Option<u64>,
     },
-    Error(String),
+    Cancelled,
+    Error(QueryErrorDetail),
 }
 
 /// Result of an inline EXPLAIN (FORMAT JSON) run.
@@ -79,8 +101,25 @@ pub struct QueryEditorPanel {
     split_state: Entity<ResizableState>,
     bottom_tab: BottomTab,
     explain: ExplainView,
+    /// Bound query of the last run, re-executed by "Fetch more"; `None` when it wrote,
+    /// so a capped `… RETURNING` never runs twice.
+    last_query: Option<BoundQuery>,
+    /// Row cap of the last run; grows by one project cap per "Fetch more".
+    row_cap: u64,
+    /// Backend PID of the running statement, for `pg_cancel_backend`.
+    cancel: PgCancelHandle,
+    cancel_requested: bool,
+    /// Per-statement results of the last "Run script"; `None` after a plain run.
+    script: Option<ScriptRun>,
+    /// Connection of a script stopped on an error, kept so "Continue" runs the rest in
+    /// the same session.
+    script_session: Option<PgScriptSession>,
+    /// Manual transaction pinned to this tab; runs go through it until commit/rollback.
+    tx: Option<PgManualTx>,
     dirty: bool,
     pub(crate) tab_label: SharedString,
+    /// Saved project query this tab was opened from; keys remembered parameter values.
+    pub(crate) query_path: Option<String>,
     pub editor_ctx: Entity<EditorContext>,
 }
 
@@ -124,10 +163,21 @@ impl QueryEditorPanel {
             split_state,
             bottom_tab: BottomTab::Results,
             explain: ExplainView::Empty,
+            last_query: None,
+            row_cap: max_result_rows(cx),
+            cancel: PgCancelHandle::new(),
+            cancel_requested: false,
+            script: None,
+            script_session: None,
+            tx: None,
             dirty: false,
             tab_label: "Query".into(),
+            query_path: None,
             editor_ctx,
         };
+        let panel_id = cx.entity_id();
+        cx.on_release(move |_, cx| mark_transaction_closed(panel_id, cx))
+            .detach();
         let conn_for_dirty = panel.conn_id.clone();
         cx.observe(&sql_input, move |panel, _, cx| {
             if !panel.dirty {
@@ -137,19 +187,19 @@ impl QueryEditorPanel {
             cx.notify();
         })
         .detach();
-        cx.subscribe_in(&sql_input, window, |panel, _, event, _, cx| {
+        cx.subscribe_in(&sql_input, window, |panel, _, event, window, cx| {
             if let InputEvent::PressEnter {
                 secondary: true,
                 shift: false,
             } = event
             {
-                panel.run(cx);
+                panel.run(window, cx);
             }
         })
         .detach();
         if auto_run && !sql_text.trim().is_empty() {
-            cx.defer_in(window, |panel, _, cx| {
-                panel.run(cx);
+            cx.defer_in(window, |panel, window, cx| {
+                panel.run(window, cx);
             });
         }
         panel
@@ -168,6 +218,15 @@ impl QueryEditorPanel {
         text_from_input(&self.sql_input, cx)
     }
 
+    /// Editor variables with the types the saved query declares.
+    fn variable_scope(&self, cx: &App) -> VariableScope {
+        query_scope(
+            &self.editor_ctx.read(cx).variables,
+            self.query_path.as_deref(),
+            cx,
+        )
+    }
+
     /// Switch the bottom dock to the Explain tab and (re)run EXPLAIN inline.
     fn switch_to_explain(&mut self, cx: &mut Context<Self>) {
         let sql = self.current_sql(cx);
@@ -218,57 +277,296 @@ impl QueryEditorPanel {
         .detach();
     }
 
-    fn run(&mut self, cx: &mut Context<Self>) {
-        let sql_raw = self.current_sql(cx);
-        if sql_raw.trim().is_empty() {
-            return;
+    fn run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(var_ctx) = self.prompt_params(window, cx, Self::run_with) {
+            self.run_with(var_ctx, window, cx);
         }
-        let project_vars = cx.global::<ProjectVars>().vars.clone();
-        let sql = substitute(&sql_raw, &project_vars);
-        let var_ctx = based_query::VariableContext {
-            session: Default::default(),
-            query: Default::default(),
-            collection: Default::default(),
-            environment: None,
-            workspace: project_vars.clone(),
-            connection: project_vars,
+    }
+
+    /// Bind the editor text, then run it if the connection policy allows the bound SQL,
+    /// which is what reaches the server.
+    fn run_with(&mut self, var_ctx: VariableContext, window: &mut Window, cx: &mut Context<Self>) {
+        let sql = self.current_sql(cx);
+        let scope = self.variable_scope(cx);
+        let query = match bind_editor_sql(&sql, Dialect::Postgres, &var_ctx, &scope) {
+            Ok(query) => query,
+            Err(e) => {
+                self.show_resolve_error(e.to_string(), cx);
+                return;
+            }
         };
-        let sql = match based_query::resolve_query(&sql, &var_ctx) {
-            Ok(resolved) => resolved,
+        let confirmed = query.clone();
+        if check_connection_policy(
+            &query.sql,
+            Dialect::Postgres,
+            &self.conn_id,
+            window,
+            cx,
+            move |panel, _, cx| panel.execute(confirmed.clone(), row_cap(&panel.conn_id, cx), cx),
+        ) {
+            self.execute(query, row_cap(&self.conn_id, cx), cx);
+        }
+    }
+
+    /// Variable context for a run of the editor text, or `None` when the editor is empty
+    /// or the parameter form opened to ask for missing `{{name}}`s (it calls `run`).
+    fn prompt_params(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        run: fn(&mut Self, VariableContext, &mut Window, &mut Context<Self>),
+    ) -> Option<VariableContext> {
+        let sql = self.current_sql(cx);
+        if sql.trim().is_empty() {
+            return None;
+        }
+        let var_ctx = editor_variable_context(cx.global::<ProjectVars>());
+        let scope = self.variable_scope(cx);
+        let key = param_key(self.query_path.as_deref(), &self.conn_id);
+        prompt_missing_params(&sql, var_ctx, &scope, key, window, cx, run)
+    }
+
+    /// Run each statement of the editor on its own, one result tab per statement.
+    fn run_script(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(var_ctx) = self.prompt_params(window, cx, Self::run_script_with) {
+            self.run_script_with(var_ctx, window, cx);
+        }
+    }
+
+    /// Bind every statement, then start the script if the connection policy allows the
+    /// bound statements.
+    fn run_script_with(
+        &mut self,
+        var_ctx: VariableContext,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let sql = self.current_sql(cx);
+        let scope = self.variable_scope(cx);
+        let script = match ScriptRun::new(&sql, Dialect::Postgres, &var_ctx, &scope) {
+            Ok(script) if script.is_empty() => return,
+            Ok(script) => script,
             Err(e) => {
-                self.status = QueryStatus::Error(e.to_string());
-                self.bottom_tab = BottomTab::Messages;
-                cx.notify();
+                self.show_resolve_error(e.to_string(), cx);
                 return;
             }
         };
-        let sql_executed = sql.clone();
+        let bound_sql = script.bound_sql();
+        // The confirm dialog's Run starts the script; otherwise it starts right away.
+        let pending = Rc::new(Cell::new(Some(script)));
+        let confirmed = pending.clone();
+        let allowed = check_connection_policy(
+            &bound_sql,
+            Dialect::Postgres,
+            &self.conn_id,
+            window,
+            cx,
+            move |panel, _, cx| {
+                if let Some(script) = confirmed.take() {
+                    panel.start_script(script, cx);
+                }
+            },
+        );
+        if allowed && let Some(script) = pending.take() {
+            self.start_script(script, cx);
+        }
+    }
+
+    fn start_script(&mut self, script: ScriptRun, cx: &mut Context<Self>) {
+        self.finish_script_session(cx);
+        self.script = Some(script);
+        self.execute_script_from(0, cx);
+    }
+
+    /// Resume a script that stopped on an error with the statement after the failure.
+    fn continue_script(&mut self, cx: &mut Context<Self>) {
+        let Some(from) = self.script.as_ref().and_then(|s| s.resume_at) else {
+            return;
+        };
+        self.execute_script_from(from, cx);
+    }
+
+    /// Release the connection of a stopped script, rolling back what it left open.
+    fn finish_script_session(&mut self, cx: &mut Context<Self>) {
+        let Some(session) = self.script_session.take() else {
+            return;
+        };
+        cx.spawn(async move |_, cx| {
+            if let Err(e) = db::run(cx, async move { session.finish().await }).await {
+                log::warn!("finish script session failed: {e:#}");
+            }
+        })
+        .detach();
+    }
+
+    /// Write the editor text to its saved query, or save it as a new project query.
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let body = QueryBody::Sql {
+            query: self.current_sql(cx),
+        };
+        let saved = save_query(
+            self.query_path.as_deref(),
+            body,
+            &self.conn_id,
+            based_core::EngineKind::Postgres,
+            window,
+            cx,
+            |panel, path, cx| {
+                panel.query_path = Some(path);
+                panel.mark_saved(cx);
+            },
+        );
+        if saved {
+            self.mark_saved(cx);
+        }
+    }
+
+    fn mark_saved(&mut self, cx: &mut Context<Self>) {
+        self.dirty = false;
+        mark_query_tab_clean(&self.conn_id, cx);
+        cx.notify();
+    }
+
+    /// Report a variable that couldn't be resolved or coerced; nothing runs.
+    fn show_resolve_error(&mut self, message: String, cx: &mut Context<Self>) {
+        self.script = None;
+        self.status = QueryStatus::Error(QueryErrorDetail::failed(message));
+        self.bottom_tab = BottomTab::Messages;
+        cx.notify();
+    }
+
+    /// Re-run the last query with the row cap raised by another project cap, up to the
+    /// connection's `max_rows`.
+    fn fetch_more(&mut self, cx: &mut Context<Self>) {
+        let Some(query) = self.last_query.clone() else {
+            return;
+        };
+        let cap = next_row_cap(&self.conn_id, self.row_cap, cx);
+        if cap == self.row_cap {
+            return;
+        }
+        self.execute(query, cap, cx);
+    }
+
+    /// Cancel the running statement via `pg_cancel_backend` on a side connection.
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        if !matches!(self.status, QueryStatus::Running) {
+            return;
+        }
+        self.cancel_requested = true;
+        let handle = self.cancel.clone();
+        let pool = self.pool.clone();
+        cx.spawn(async move |_, cx| {
+            if let Err(e) = db::run(cx, async move { handle.cancel(&pool).await }).await {
+                log::warn!("pg_cancel_backend failed: {e:#}");
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    /// Pin a pooled connection to this tab and issue `BEGIN`.
+    fn begin_transaction(&mut self, cx: &mut Context<Self>) {
+        if self.tx.is_some() {
+            return;
+        }
+        self.finish_script_session(cx);
+        let pool = self.pool.clone();
+        cx.spawn(async move |this, cx| {
+            let outcome = db::run(cx, async move { PgManualTx::begin(&pool).await }).await;
+            let _ = this.update(cx, |panel, cx| {
+                match outcome {
+                    Ok(tx) => {
+                        panel.tx = Some(tx);
+                        mark_transaction_open(cx.entity_id(), panel.conn_id.clone(), cx);
+                    }
+                    Err(e) => push_error(cx, "Begin transaction", format!("{e:#}")),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Commit or roll back the open transaction and release its connection.
+    fn end_transaction(&mut self, commit: bool, cx: &mut Context<Self>) {
+        let Some(tx) = self.tx.take() else {
+            return;
+        };
+        mark_transaction_closed(cx.entity_id(), cx);
+        cx.spawn(async move |_, cx| {
+            let outcome = db::run(cx, async move {
+                if commit {
+                    tx.commit().await
+                } else {
+                    tx.rollback().await
+                }
+            })
+            .await;
+            if let Err(e) = outcome {
+                let title = if commit { "Commit" } else { "Rollback" };
+                let _ = cx.update(|cx| push_error(cx, title, format!("{e:#}")));
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn execute(&mut self, query: BoundQuery, row_cap: u64, cx: &mut Context<Self>) {
+        let sql_executed = query.sql.clone();
         let conn_id = self.conn_id.clone();
+        self.script = None;
+        self.finish_script_session(cx);
+        self.last_query = query.can_rerun(Dialect::Postgres).then(|| query.clone());
+        self.row_cap = row_cap;
         self.status = QueryStatus::Running;
         self.bottom_tab = BottomTab::Results;
+        self.cancel = PgCancelHandle::new();
+        self.cancel_requested = false;
         let pool = self.pool.clone();
+        let tx = self.tx.clone();
+        let handle = self.cancel.clone();
+        let timeout = query_timeout(cx);
         cx.spawn(async move |this, cx| {
             let start = Instant::now();
-            let outcome = db::run(cx, async move { execute_sql(&pool, &sql).await }).await;
+            let outcome = db::run(cx, async move {
+                let BoundQuery { sql, params } = query;
+                match tx {
+                    Some(tx) => {
+                        tx.execute(&sql, &params, Some(row_cap), Some(timeout), &handle)
+                            .await
+                    }
+                    None => {
+                        execute_sql_cancellable(
+                            &pool,
+                            &sql,
+                            &params,
+                            Some(row_cap),
+                            Some(timeout),
+                            &handle,
+                        )
+                        .await
+                    }
+                }
+            })
+            .await;
             let ms = start.elapsed().as_millis() as u64;
             let _ = this.update(cx, |panel, cx| {
                 panel.status = match outcome {
-                    Ok((cols, rows, aff)) => {
-                        let col_models: Vec<Column> = cols
+                    Ok(result) => {
+                        let col_models: Vec<Column> = result
+                            .columns
                             .iter()
                             .map(|c| data_column(c.name.clone(), c.name.clone()))
                             .collect();
-                        let column_meta: Vec<_> = cols
+                        let column_meta: Vec<_> = result
+                            .columns
                             .iter()
                             .map(|c| meta_from_query_type(&c.type_name))
                             .collect();
-                        let data: Vec<Vec<SharedString>> = rows
-                            .into_iter()
-                            .map(|r| r.into_iter().map(SharedString::from).collect())
-                            .collect();
-                        let row_count = data.len();
+                        let row_count = result.rows.len();
                         panel.result.update(cx, |state, cx| {
-                            replace_table_data(state, col_models, data, column_meta, cx);
+                            replace_table_values(state, col_models, result.rows, column_meta, cx);
                         });
                         cx.update_global(|store: &mut QueryStore, _| {
                             store.push_history(HistoryEntry::new(
@@ -281,10 +579,23 @@ impl QueryEditorPanel {
                         });
                         QueryStatus::Done {
                             rows: row_count,
-                            affected: aff,
+                            affected: result.rows_affected,
                             elapsed_ms: ms,
+                            truncated_at: result.truncated_at,
                         }
                     }
+                    Err(e) if panel.cancel_requested || is_query_cancelled(&e, &panel.cancel) => {
+                        cx.update_global(|store: &mut QueryStore, _| {
+                            store.push_history(HistoryEntry::new(
+                                conn_id.clone(),
+                                sql_executed,
+                                ms,
+                                None,
+                                based_query::RunStatus::Cancelled,
+                            ));
+                        });
+                        QueryStatus::Cancelled
+                    }
                     Err(e) => {
                         cx.update_global(|store: &mut QueryStore, _| {
                             store.push_history(HistoryEntry::new(
@@ -296,7 +607,7 @@ impl QueryEditorPanel {
                             ));
                         });
                         panel.bottom_tab = BottomTab::Messages;
-                        QueryStatus::Error(e.to_string())
+                        QueryStatus::Error(categorize_query_error(&e))
                     }
                 };
                 cx.notify();
@@ -304,6 +615,179 @@ impl QueryEditorPanel {
         })
         .detach();
     }
+
+    /// Run the script's statements from `from` on a single connection, stopping at the
+    /// first failure. A stopped script keeps its connection for "Continue".
+    fn execute_script_from(&mut self, from: usize, cx: &mut Context<Self>) {
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        let statements: Vec<BoundStatement> = script
+            .remaining(from)
+            .into_iter()
+            .map(|q| BoundStatement {
+                sql: q.sql,
+                params: q.params,
+            })
+            .collect();
+        script.resume_at = None;
+        self.last_query = None;
+        self.status = QueryStatus::Running;
+        self.bottom_tab = BottomTab::Results;
+        self.cancel = PgCancelHandle::new();
+        self.cancel_requested = false;
+        let pool = self.pool.clone();
+        let tx = self.tx.clone();
+        let handle = self.cancel.clone();
+        let timeout = query_timeout(cx);
+        let row_cap = row_cap(&self.conn_id, cx);
+        let session = self.script_session.take();
+        cx.spawn(async move |this, cx| {
+            let outcome = db::run(cx, {
+                let statements = statements.clone();
+                async move {
+                    if let Some(tx) = tx {
+                        let steps = tx
+                            .execute_script(&statements, Some(row_cap), Some(timeout), &handle)
+                            .await?;
+                        return Ok((steps, None));
+                    }
+                    let session = match session {
+                        Some(session) => session,
+                        None => PgScriptSession::start(&pool).await?,
+                    };
+                    let steps = session
+                        .execute(&statements, Some(row_cap), Some(timeout), &handle)
+                        .await?;
+                    if steps.len() < statements.len() {
+                        return Ok((steps, Some(session)));
+                    }
+                    session.finish().await?;
+                    Ok::<_, anyhow::Error>((steps, None))
+                }
+            })
+            .await;
+            let _ = this.update(cx, |panel, cx| {
+                let outcome = outcome.map(|(steps, session)| {
+                    panel.script_session = session;
+                    steps
+                });
+                panel.finish_script(from, statements, outcome, cx);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn finish_script(
+        &mut self,
+        from: usize,
+        statements: Vec<BoundStatement>,
+        outcome: anyhow::Result<Vec<ScriptStep>>,
+        cx: &mut Context<Self>,
+    ) {
+        let steps = match outcome {
+            Ok(steps) => steps,
+            Err(e) => {
+                self.bottom_tab = BottomTab::Messages;
+                self.status = QueryStatus::Error(categorize_query_error(&e));
+                return;
+            }
+        };
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        let ran = steps.len();
+        let mut history = Vec::with_capacity(ran);
+        for (offset, (step, stmt)) in steps.into_iter().zip(statements).enumerate() {
+            let elapsed_ms = step.elapsed.as_millis() as u64;
+            let (outcome, rows, status) = match step.result {
+                Ok(result) => {
+                    let rows = result.rows.len() as u64;
+                    let grid = result_grid(result);
+                    (
+                        StatementOutcome::Done { grid, elapsed_ms },
+                        Some(rows),
+                        based_query::RunStatus::Ok,
+                    )
+                }
+                Err(e) if self.cancel_requested || is_query_cancelled(&e, &self.cancel) => (
+                    StatementOutcome::Cancelled { elapsed_ms },
+                    None,
+                    based_query::RunStatus::Cancelled,
+                ),
+                Err(e) => (
+                    StatementOutcome::Failed {
+                        error: categorize_query_error(&e),
+                        elapsed_ms,
+                    },
+                    None,
+                    based_query::RunStatus::Error,
+                ),
+            };
+            script.record(from + offset, outcome);
+            history.push(HistoryEntry::new(
+                self.conn_id.clone(),
+                stmt.sql,
+                elapsed_ms,
+                rows,
+                status,
+            ));
+        }
+        script.finish_batch(from, ran);
+        cx.update_global(|store: &mut QueryStore, _| {
+            for entry in history {
+                store.push_history(entry);
+            }
+        });
+
+        self.status = if let Some(error) = script.active_error() {
+            self.bottom_tab = BottomTab::Messages;
+            QueryStatus::Error(error)
+        } else if script.was_cancelled() {
+            QueryStatus::Cancelled
+        } else {
+            QueryStatus::Done {
+                rows: script.active_grid().map_or(0, |g| g.rows.len()),
+                affected: script.total_affected(),
+                elapsed_ms: script.total_elapsed_ms(),
+                truncated_at: None,
+            }
+        };
+        let active = script.active;
+        self.show_script_statement(active, cx);
+    }
+
+    /// Load statement `ix` of the script run into the result grid.
+    fn show_script_statement(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        script.active = ix;
+        let grid = script.active_grid().cloned().unwrap_or_default();
+        self.result.update(cx, |state, cx| {
+            replace_table_values(state, grid.columns, grid.rows, grid.column_meta, cx);
+        });
+        cx.notify();
+    }
+}
+
+fn result_grid(result: QueryResult) -> ResultGrid {
+    ResultGrid {
+        columns: result
+            .columns
+            .iter()
+            .map(|c| data_column(c.name.clone(), c.name.clone()))
+            .collect(),
+        column_meta: result
+            .columns
+            .iter()
+            .map(|c| meta_from_query_type(&c.type_name))
+            .collect(),
+        rows: result.rows,
+        rows_affected: result.rows_affected,
+        truncated_at: result.truncated_at,
+    }
 }
 
 impl EventEmitter<PanelEvent> for QueryEditorPanel {}
@@ -345,12 +829,15 @@ fn query_status_display(status: &QueryStatus) -> QueryStatusDisplay {
             rows,
             affected,
             elapsed_ms,
+            truncated_at,
         } => QueryStatusDisplay::Done {
             rows: *rows,
             affected: Some(*affected),
             elapsed_ms: *elapsed_ms,
+            truncated_at: *truncated_at,
         },
-        QueryStatus::Error(e) => QueryStatusDisplay::Error(e.clone().into()),
+        QueryStatus::Cancelled => QueryStatusDisplay::Cancelled,
+        QueryStatus::Error(e) => QueryStatusDisplay::Error(e.clone()),
     }
 }
 
@@ -363,12 +850,46 @@ impl QueryEditorPanel {
     /// Render the body for the currently selected bottom tab.
     fn render_bottom_body(&self, cx: &mut Context<Self>) -> AnyElement {
         match self.bottom_tab {
-            BottomTab::Results => div()
-                .flex_1()
-                .min_h(px(0.0))
-                .child(render_row_table(&self.result, cx))
-                .into_any_element(),
-            BottomTab::Messages => self.render_messages(cx),
+            BottomTab::Results => {
+                let table = div()
+                    .flex_1()
+                    .min_h(px(0.0))
+                    .child(render_row_table(&self.result, cx));
+                match &self.script {
+                    Some(script) => {
+                        let entity = cx.entity();
+                        let on_select: Rc<dyn Fn(usize, &mut Window, &mut App)> =
+                            Rc::new(move |ix, _, cx| {
+                                entity.update(cx, |panel, cx| panel.show_script_statement(ix, cx));
+                            });
+                        v_flex()
+                            .flex_1()
+                            .min_h(px(0.0))
+                            .child(statement_tab_strip(
+                                "pg-stmt",
+                                &script.tabs(),
+                                script.active,
+                                on_select,
+                                cx,
+                            ))
+                            .child(table)
+                            .into_any_element()
+                    }
+                    None => table.into_any_element(),
+                }
+            }
+            BottomTab::Messages => match &self.script {
+                Some(script) if !matches!(self.status, QueryStatus::Running) => {
+                    statement_message_list(
+                        "pg-stmt-messages",
+                        &script.tabs(),
+                        script.stop_note(),
+                        cx,
+                    )
+                    .into_any_element()
+                }
+                _ => self.render_messages(cx),
+            },
             BottomTab::Explain => self.render_explain(cx),
         }
     }
@@ -383,7 +904,7 @@ impl QueryEditorPanel {
                 .p_3()
                 .child(query_error_card(
                     "pg-query-error-card",
-                    full.clone().into(),
+                    full.display_message().into(),
                     cx,
                 ))
                 .into_any_element(),
@@ -391,15 +912,21 @@ impl QueryEditorPanel {
                 rows,
                 affected,
                 elapsed_ms,
+                truncated_at,
             } => div()
                 .flex_1()
                 .min_h(px(0.0))
                 .p_3()
                 .text_xs()
                 .text_color(muted)
-                .child(format!(
-                    "Query OK · {rows} rows · {affected} affected · {elapsed_ms} ms"
-                ))
+                .child(match truncated_at {
+                    Some(n) => {
+                        format!("Query OK · {rows} rows (truncated at {n} rows) · {elapsed_ms} ms")
+                    }
+                    None => {
+                        format!("Query OK · {rows} rows · {affected} affected · {elapsed_ms} ms")
+                    }
+                })
                 .into_any_element(),
             QueryStatus::Running => div()
                 .flex_1()
@@ -409,6 +936,14 @@ impl QueryEditorPanel {
                 .text_color(muted)
                 .child("Running…")
                 .into_any_element(),
+            QueryStatus::Cancelled => div()
+                .flex_1()
+                .min_h(px(0.0))
+                .p_3()
+                .text_xs()
+                .text_color(muted)
+                .child("Query cancelled.")
+                .into_any_element(),
             QueryStatus::Idle => div()
                 .flex_1()
                 .min_h(px(0.0))
@@ -475,22 +1010,22 @@ impl Render for QueryEditorPanel {
         let var_map = cx.global::<ProjectVars>().vars.clone();
         let mono_font = cx.theme().mono_font_family.clone();
 
-        let (export_headers, export_rows) = {
-            let st = self.result.read(cx);
-            let d = st.delegate();
-            let h = d
-                .columns
-                .iter()
-                .map(|c| c.key.to_string())
-                .collect::<Vec<_>>();
-            let r = d
-                .rows
-                .iter()
-                .map(|row| row.iter().map(|c| c.to_string()).collect())
-                .collect::<Vec<Vec<String>>>();
-            (h, r)
-        };
+        let (export_headers, export_rows) = self.result.read(cx).delegate().export_snapshot();
         let export_popover = export_popover("pg-qe", export_headers, export_rows);
+        let running = matches!(self.status, QueryStatus::Running);
+        let cancel_requested = self.cancel_requested;
+        let can_continue = !running && self.script.as_ref().is_some_and(|s| s.resume_at.is_some());
+        let in_transaction = self.tx.is_some();
+        let can_save = can_save_query(cx);
+        let policy = policy_summary(&connection_policy(&self.conn_id, cx));
+        let can_fetch_more = self.last_query.is_some()
+            && matches!(
+                self.status,
+                QueryStatus::Done {
+                    truncated_at: Some(_),
+                    ..
+                }
+            );
 
         let toolbar = h_flex()
             .gap(px(6.0))
@@ -507,8 +1042,72 @@ impl Render for QueryEditorPanel {
                     .icon(IconName::Play)
                     .label("Run")
                     .child(shortcut_run_kbd_in_primary_button(cx))
-                    .on_click(cx.listener(|panel, _, _, cx| panel.run(cx))),
+                    .on_click(cx.listener(|panel, _, window, cx| panel.run(window, cx))),
+            )
+            .child(
+                Button::new("pg-run-script")
+                    .ghost()
+                    .small()
+                    .label("Run script")
+                    .disabled(running)
+                    .on_click(cx.listener(|panel, _, window, cx| panel.run_script(window, cx))),
             )
+            .when(can_continue, |toolbar| {
+                toolbar.child(
+                    Button::new("pg-continue-script")
+                        .ghost()
+                        .small()
+                        .icon(IconName::ChevronRight)
+                        .label("Continue")
+                        .on_click(cx.listener(|panel, _, _, cx| panel.continue_script(cx))),
+                )
+            })
+            .when(running, |toolbar| {
+                toolbar.child(
+                    Button::new("pg-cancel")
+                        .ghost()
+                        .small()
+                        .icon(IconName::CircleX)
+                        .label("Cancel")
+                        .disabled(cancel_requested)
+                        .on_click(cx.listener(|panel, _, _, cx| panel.cancel(cx))),
+                )
+            })
+            .when(!in_transaction, |toolbar| {
+                toolbar.child(
+                    Button::new("pg-begin")
+                        .ghost()
+                        .small()
+                        .label("Transaction")
+                        .disabled(running)
+                        .on_click(cx.listener(|panel, _, _, cx| panel.begin_transaction(cx))),
+                )
+            })
+            .when(in_transaction, |toolbar| {
+                toolbar
+                    .child(metadata_pill("transaction", "open", cx))
+                    .child(
+                        Button::new("pg-commit")
+                            .ghost()
+                            .small()
+                            .icon(IconName::Check)
+                            .label("Commit")
+                            .disabled(running)
+                            .on_click(
+                                cx.listener(|panel, _, _, cx| panel.end_transaction(true, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("pg-rollback")
+                            .ghost()
+                            .small()
+                            .label("Rollback")
+                            .disabled(running)
+                            .on_click(
+                                cx.listener(|panel, _, _, cx| panel.end_transaction(false, cx)),
+                            ),
+                    )
+            })
             .child(
                 Button::new("pg-explain")
                     .ghost()
@@ -516,6 +1115,18 @@ impl Render for QueryEditorPanel {
                     .label("Explain")
                     .on_click(cx.listener(|panel, _, _, cx| panel.switch_to_explain(cx))),
             )
+            .when(can_save, |toolbar| {
+                toolbar.child(
+                    Button::new("pg-save")
+                        .ghost()
+                        .small()
+                        .label("Save")
+                        .on_click(cx.listener(|panel, _, window, cx| panel.save(window, cx))),
+                )
+            })
+            .when_some(policy, |toolbar, summary| {
+                toolbar.child(metadata_pill("policy", summary, cx))
+            })
             .child(query_panel_extras::variables_popover(
                 "pg-vars-popover",
                 project_dir,
@@ -524,6 +1135,15 @@ impl Render for QueryEditorPanel {
                 cx,
             ))
             .child(export_popover)
+            .when(can_fetch_more, |toolbar| {
+                toolbar.child(
+                    Button::new("pg-fetch-more")
+                        .ghost()
+                        .small()
+                        .label("Fetch more")
+                        .on_click(cx.listener(|panel, _, _, cx| panel.fetch_more(cx))),
+                )
+            })
             .child(div().flex_1())
             .child(render_status_cluster(&self.status, cx));
 
@@ -1,7 +1,7 @@
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use based_project::ProjectSnapshot;
+use based_project::{Diagnostic, ProjectSnapshot, Severity, validate_project};
 use gpui::Global;
 
 /// Runtime project context for title bar and reload.
@@ -9,18 +9,23 @@ use gpui::Global;
 pub struct ProjectContext {
     pub root: PathBuf,
     pub snapshot: ProjectSnapshot,
+    /// Problems found while loading `.based/`, listed in the Problems pane.
+    pub diagnostics: Vec<Diagnostic>,
     pub git_branch: Option<String>,
 }
 
 impl ProjectContext {
-    pub fn load(root: PathBuf) -> anyhow::Result<Self> {
-        let snapshot = based_project::load_project(&root)?;
+    /// Load leniently: files that fail to parse are skipped and reported in `diagnostics`
+    /// rather than keeping the project from opening.
+    pub fn load(root: PathBuf) -> Self {
+        let validation = validate_project(&root);
         let git_branch = read_git_branch(&root);
-        Ok(Self {
+        Self {
             root,
-            snapshot,
+            snapshot: validation.snapshot,
+            diagnostics: validation.diagnostics,
             git_branch,
-        })
+        }
     }
 
     pub fn project_name(&self) -> &str {
@@ -30,6 +35,13 @@ impl ProjectContext {
     pub fn active_env(&self) -> &str {
         &self.snapshot.active_environment
     }
+
+    pub fn error_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count()
+    }
 }
 
 impl Global for ProjectContext {}
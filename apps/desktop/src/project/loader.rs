@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use based_core::SshTunnelConfig;
+use based_core::{SshJumpHost, SshTunnelConfig};
 use based_project::{
-    ConnectionSpec, PragmaSettings, ProjectConnection, SshSettings,
-    load_connections_from_based_dir, load_env_file,
+    ConnectionPolicy, ConnectionSpec, PostgresSettings, PostgresSslMode, PragmaSettings,
+    ProjectConnection, SshSettings, TargetSessionAttrs, active_environment_vars,
+    load_connections_lenient, load_env_file,
 };
 use based_sqlite::SqlitePragma;
 
 use crate::connection::{ConnectionConfig, ConnectionEntry, ConnectionId, ConnectionOrigin};
 use crate::mongodb::MongoConfig;
-use crate::postgres::{PostgresConfig, SslMode};
+use crate::postgres::{PgSettings, PgTargetSessionAttrs, PostgresConfig, SslMode};
 use crate::sqlite::SqliteConfig;
 
 pub fn entry_from_project(conn: &ProjectConnection) -> anyhow::Result<ConnectionEntry> {
@@ -27,6 +28,7 @@ pub fn entry_from_tree(
             label: conn.label.clone(),
             path: file.clone(),
             read_only: conn.read_only,
+            query_only: !conn.policy.allows_writes(),
             pragma: pragma.as_ref().map(map_pragma),
         }),
         ConnectionSpec::Postgres {
@@ -35,7 +37,8 @@ pub fn entry_from_tree(
             database,
             username,
             password,
-            ssl,
+            sslmode,
+            settings,
         } => ConnectionConfig::Postgres(PostgresConfig {
             label: conn.label.clone(),
             host: host.clone(),
@@ -43,11 +46,8 @@ pub fn entry_from_tree(
             database: database.clone(),
             username: username.clone(),
             password: password.resolve_with(file_vars)?,
-            ssl_mode: if *ssl {
-                SslMode::Require
-            } else {
-                SslMode::Disable
-            },
+            ssl_mode: map_ssl_mode(*sslmode),
+            settings: map_postgres_settings(settings, &conn.policy)?,
             ssh: conn
                 .ssh
                 .as_ref()
@@ -84,23 +84,41 @@ pub fn load_entries_from_based_dir(
     origin: ConnectionOrigin,
 ) -> Vec<ConnectionEntry> {
     let file_vars = load_env_file(&based_dir.join(".env")).unwrap_or_default();
-    let connections = match load_connections_from_based_dir(based_dir) {
-        Ok(c) => c,
-        Err(err) => {
-            log::warn!(
-                "load connections from {} failed: {err:#}",
-                based_dir.display()
-            );
-            return Vec::new();
-        }
+    // A bad file skips only itself; project problems are listed in the Problems pane.
+    let (connections, diagnostics) = load_connections_lenient(based_dir);
+    for diagnostic in diagnostics {
+        log::warn!("{diagnostic}");
+    }
+    // Committed connections may use `{{name}}` from the active project environment.
+    let env_vars = match origin {
+        ConnectionOrigin::Project => Some(
+            based_dir
+                .parent()
+                .and_then(|root| {
+                    active_environment_vars(root, &file_vars).unwrap_or_else(|err| {
+                        log::warn!("active environment unavailable: {err:#}");
+                        None
+                    })
+                })
+                .unwrap_or_default(),
+        ),
+        ConnectionOrigin::Personal => None,
     };
     connections
         .iter()
-        .filter_map(|conn| match entry_from_tree(conn, origin, &file_vars) {
-            Ok(entry) => Some(entry),
-            Err(err) => {
-                log::warn!("connection {} skipped: {err:#}", conn.id);
-                None
+        .filter_map(|conn| {
+            let entry = match &env_vars {
+                Some(vars) => conn
+                    .with_vars(vars)
+                    .and_then(|conn| entry_from_tree(&conn, origin, &file_vars)),
+                None => entry_from_tree(conn, origin, &file_vars),
+            };
+            match entry {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    log::warn!("connection {} skipped: {err:#}", conn.id);
+                    None
+                }
             }
         })
         .collect()
@@ -120,6 +138,64 @@ fn resolve_ssh(
             .as_ref()
             .map(|v| v.resolve_with(file_vars))
             .transpose()?,
+        alias: ssh.alias.clone(),
+        keepalive_interval: ssh.keepalive_interval,
+        keepalive_count_max: ssh.keepalive_count_max,
+        jump_hosts: ssh
+            .jump_hosts
+            .iter()
+            .map(|jump| {
+                Ok(SshJumpHost {
+                    host: jump.host.clone(),
+                    port: jump.port,
+                    user: jump.user.clone(),
+                    key_path: jump.key_path.clone(),
+                    key_passphrase: jump
+                        .key_passphrase
+                        .as_ref()
+                        .map(|v| v.resolve_with(file_vars))
+                        .transpose()?,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?,
+    })
+}
+
+fn map_ssl_mode(mode: PostgresSslMode) -> SslMode {
+    match mode {
+        PostgresSslMode::Disable => SslMode::Disable,
+        PostgresSslMode::Prefer => SslMode::Prefer,
+        PostgresSslMode::Require => SslMode::Require,
+        PostgresSslMode::VerifyCa => SslMode::VerifyCa,
+        PostgresSslMode::VerifyFull => SslMode::VerifyFull,
+    }
+}
+
+/// Driver settings for `s`; a `policy` that allows no writes opens read-only sessions.
+fn map_postgres_settings(
+    s: &PostgresSettings,
+    policy: &ConnectionPolicy,
+) -> anyhow::Result<PgSettings> {
+    Ok(PgSettings {
+        service: s.service.clone(),
+        ssl_root_cert: s.sslrootcert.as_ref().map(Into::into),
+        ssl_cert: s.sslcert.as_ref().map(Into::into),
+        ssl_key: s.sslkey.as_ref().map(Into::into),
+        application_name: s.application_name.clone(),
+        search_path: s.search_path.clone(),
+        options: s.options.clone(),
+        connect_timeout: s.connect_timeout,
+        statement_timeout: s.statement_timeout,
+        failover_hosts: s.failover_endpoints()?,
+        target_session_attrs: match s.target_session_attrs.unwrap_or_default() {
+            TargetSessionPgTargetSessionAttrs::Any => PgTargetSessionAttrs::Any,
+            TargetSessionPgTargetSessionAttrs::ReadWrite => PgTargetSessionAttrs::ReadWrite,
+            TargetSessionPgTargetSessionAttrs::ReadOnly => PgTargetSessionAttrs::ReadOnly,
+            TargetSessionPgTargetSessionAttrs::Primary => PgTargetSessionAttrs::Primary,
+            TargetSessionPgTargetSessionAttrs::Standby => PgTargetSessionAttrs::Standby,
+            TargetSessionPgTargetSessionAttrs::PreferStandby => PgTargetSessionAttrs::PreferStandby,
+        },
+        read_only: !policy.allows_writes(),
     })
 }
 
@@ -134,7 +210,7 @@ fn map_pragma(p: &PragmaSettings) -> SqlitePragma {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use based_project::{EnvOrString, write_connection_file};
+    use based_project::{ConnectionPolicy, EnvOrString, write_connection_file};
     use std::path::PathBuf;
 
     #[test]
@@ -152,6 +228,7 @@ mod tests {
                 pragma: None,
             },
             ssh: None,
+            policy: ConnectionPolicy::default(),
         };
         write_connection_file(based, &conn).unwrap();
         let entries = load_entries_from_based_dir(based, ConnectionOrigin::Personal);
@@ -176,9 +253,11 @@ mod tests {
                 password: EnvOrString::FromEnv {
                     var: "BASED_PG_PASSWORD".into(),
                 },
-                ssl: false,
+                sslmode: PostgresSslMode::Disable,
+                settings: Box::default(),
             },
             ssh: None,
+            policy: ConnectionPolicy::default(),
         };
         let mut vars = HashMap::new();
         vars.insert("BASED_PG_PASSWORD".into(), "from-file".into());
@@ -203,7 +282,8 @@ mod tests {
                 database: "db".into(),
                 username: "u".into(),
                 password: EnvOrString::Literal(String::new()),
-                ssl: true,
+                sslmode: PostgresSslMode::Require,
+                settings: Box::default(),
             },
             ssh: Some(SshSettings {
                 host: "bastion.example.com".into(),
@@ -213,7 +293,12 @@ mod tests {
                 key_passphrase: Some(EnvOrString::FromEnv {
                     var: "BASED_PG_SSH_KEY_PASSPHRASE".into(),
                 }),
+                alias: None,
+                keepalive_interval: None,
+                keepalive_count_max: None,
+                jump_hosts: vec![],
             }),
+            policy: ConnectionPolicy::default(),
         };
         let mut vars = HashMap::new();
         vars.insert("BASED_PG_SSH_KEY_PASSPHRASE".into(), "from-file".into());
@@ -227,4 +312,46 @@ mod tests {
             other => panic!("expected postgres, got {other:?}"),
         }
     }
+
+    #[test]
+    fn policies_without_writes_open_read_only_sessions() {
+        let mut conn = ProjectConnection {
+            id: "pg".into(),
+            label: "PG".into(),
+            engine: "postgres".into(),
+            tags: vec![],
+            read_only: false,
+            spec: ConnectionSpec::Postgres {
+                host: "localhost".into(),
+                port: 5432,
+                database: "db".into(),
+                username: "u".into(),
+                password: EnvOrString::Literal(String::new()),
+                sslmode: PostgresSslMode::Disable,
+                settings: Box::default(),
+            },
+            ssh: None,
+            policy: toml::from_str("allowed_statements = [\"select\"]\n").unwrap(),
+        };
+        match entry_from_project(&conn).unwrap().config {
+            ConnectionConfig::Postgres(c) => assert!(c.settings.read_only),
+            other => panic!("expected postgres, got {other:?}"),
+        }
+        conn.spec = ConnectionSpec::Sqlite {
+            file: PathBuf::from("/tmp/a.db"),
+            pragma: None,
+        };
+        match entry_from_project(&conn).unwrap().config {
+            ConnectionConfig::SQLite(c) => assert!(c.query_only && !c.read_only),
+            other => panic!("expected sqlite, got {other:?}"),
+        }
+        conn.policy = ConnectionPolicy {
+            confirm_writes: true,
+            ..ConnectionPolicy::default()
+        };
+        match entry_from_project(&conn).unwrap().config {
+            ConnectionConfig::SQLite(c) => assert!(!c.query_only),
+            other => panic!("expected sqlite, got {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,116 @@
+//! "Migrate Project Files": preview and rewrite `.based/` files older than this build.
+//!
+//! Loading already upgrades old files in memory; this writes the upgrade to disk so the
+//! change can be committed. The dialog shows a diff per file before anything is written.
+
+use std::rc::Rc;
+
+use based_project::{FileMigration, apply_project_migration, plan_project_migration};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    v_flex,
+};
+
+use crate::project::ProjectRoot;
+use crate::workspace::Workspace;
+use crate::workspace::notify::{push_error, push_info};
+
+/// Queue the migration preview for the next render, which has a window for the dialog.
+pub fn request_migrate_project(workspace: &mut Workspace, cx: &mut Context<Workspace>) {
+    workspace.pending_project_migrate = true;
+    cx.notify();
+}
+
+/// Show a queued migration preview during render (mirrors the close-project flow).
+pub fn maybe_show_pending_migrate_dialog(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if !workspace.pending_project_migrate {
+        return;
+    }
+    if window.has_active_dialog(cx) {
+        return;
+    }
+    workspace.pending_project_migrate = false;
+    let Some(root) = cx.try_global::<ProjectRoot>().map(|p| p.0.clone()) else {
+        return;
+    };
+    match plan_project_migration(&root) {
+        Ok(plan) if plan.is_empty() => push_info(cx, "Project files are up to date"),
+        Ok(plan) => show_migrate_dialog(Rc::new(plan), window, cx),
+        Err(e) => push_error(cx, "Migrate project", format!("{e:#}")),
+    }
+}
+
+fn show_migrate_dialog(plan: Rc<Vec<FileMigration>>, window: &mut Window, cx: &mut App) {
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let plan = plan.clone();
+        let mono = cx.theme().mono_font_family.clone();
+        let muted = cx.theme().muted_foreground;
+        let (added, removed) = (cx.theme().green, cx.theme().red);
+        let files =
+            plan.iter().map(|file| {
+                let lines = file.diff();
+                v_flex()
+                    .gap_1()
+                    .child(div().text_xs().text_color(muted).child(format!(
+                        "{} — {} schema_version {} → {}",
+                        file.path.display(),
+                        file.kind.label(),
+                        file.from,
+                        file.to
+                    )))
+                    .child(v_flex().font_family(mono.clone()).text_xs().children(
+                        lines.lines().map(|line| {
+                            let color = match line.chars().next() {
+                                Some('+') => Some(added),
+                                Some('-') => Some(removed),
+                                _ => None,
+                            };
+                            div()
+                                .when_some(color, |d, c| d.text_color(c))
+                                .child(line.to_string())
+                        }),
+                    ))
+            });
+        dialog
+            .title("Migrate project files")
+            .child(
+                v_flex()
+                    .id("migrate-project-diff")
+                    .gap_3()
+                    .max_h(px(420.0))
+                    .overflow_y_scroll()
+                    .children(files),
+            )
+            .footer(
+                DialogFooter::new()
+                    .child(
+                        DialogClose::new().child(
+                            Button::new("migrate-project-cancel")
+                                .outline()
+                                .label("Cancel"),
+                        ),
+                    )
+                    .child(
+                        DialogAction::new().child(
+                            Button::new("migrate-project-ok")
+                                .primary()
+                                .label("Rewrite files"),
+                        ),
+                    ),
+            )
+            .on_ok(move |_, _, cx| {
+                match apply_project_migration(&plan) {
+                    Ok(()) => push_info(cx, format!("Migrated {} project files", plan.len())),
+                    Err(e) => push_error(cx, "Migrate project", format!("{e:#}")),
+                }
+                true
+            })
+    });
+}
@@ -3,10 +3,12 @@
 pub mod context;
 pub mod discovery;
 pub mod loader;
+pub mod migrate;
 pub mod open;
 pub mod personal;
 pub mod pick;
 pub mod reload;
+pub mod secrets;
 pub mod settings;
 pub mod variables;
 pub mod watcher;
@@ -27,6 +29,8 @@ use gpui::Global;
 #[derive(Default)]
 pub struct ProjectVars {
     pub vars: HashMap<String, String>,
+    /// Active `environments/` values; `None` means "No Environment".
+    pub environment: Option<HashMap<String, String>>,
 }
 
 impl Global for ProjectVars {}
@@ -34,7 +38,7 @@ impl Global for ProjectVars {}
 /// Keeps the `.based/` filesystem watcher alive for the process lifetime.
 #[derive(Default)]
 pub struct ConfigWatcherGlobal {
-    _watcher: Option<watcher::ConfigWatcher>,
+    watcher: Option<watcher::ConfigWatcher>,
 }
 
 impl Global for ConfigWatcherGlobal {}
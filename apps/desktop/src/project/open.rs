@@ -27,7 +27,6 @@ use crate::workspace::WorkspaceRef;
 use crate::workspace::notify;
 
 use super::pick;
-use super::settings::apply_project_settings;
 
 /// After folder pick: open in the current window (may confirm when connections/tabs are dirty).
 pub fn prompt_open_project_in_window(cx: &mut App) {
@@ -384,15 +383,15 @@ fn close_project_in_workspace(
 }
 
 fn bind_project(root: &Path, registry: &Entity<ConnectionRegistry>, cx: &mut App) {
-    match ProjectContext::load(root.to_path_buf()) {
-        Ok(ctx) => {
-            cx.set_global(ctx.clone());
-            apply_project_settings(&ctx.snapshot.manifest, cx);
-        }
-        Err(e) => {
-            notify::push_error(cx, "Open Project", format!("Failed to load project: {e:#}"));
-            return;
-        }
+    let ctx = ProjectContext::load(root.to_path_buf());
+    let errors = ctx.error_count();
+    cx.set_global(ctx);
+    if errors > 0 {
+        notify::push_error(
+            cx,
+            "Open Project",
+            format!("{errors} project file problem(s) need fixing; see the Problems pane"),
+        );
     }
 
     install_reload_watcher(root.to_path_buf(), cx);
@@ -405,7 +404,10 @@ fn unbind_project(registry: &Entity<ConnectionRegistry>, cx: &mut App) {
     if cx.has_global::<ProjectContext>() {
         let _ = cx.remove_global::<ProjectContext>();
     }
-    cx.update_global(|pv: &mut ProjectVars, _| pv.vars.clear());
+    cx.update_global(|pv: &mut ProjectVars, _| {
+        pv.vars.clear();
+        pv.environment = None;
+    });
     cx.update_global(|store: &mut QueryStore, _| store.clear_project());
     registry.update(cx, |reg, cx| {
         reg.remove_project_owned(cx);
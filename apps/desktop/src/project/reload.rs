@@ -10,10 +10,9 @@ use crate::connection::ConnectionOrigin;
 use crate::connection::registry::ConnectionRegistry;
 use crate::project::context::ProjectContext;
 use crate::project::loader::load_entries_from_based_dir;
-use crate::project::settings::apply_project_settings;
 use crate::query_store::{QueryHistory, QueryStore};
 
-use super::{ProjectVars, load_variables, watcher::ConfigWatcher};
+use super::{ProjectVars, load_environment_vars, load_variables, watcher::ConfigWatcher};
 
 pub struct ConfigReloadSignal {
     tx: Sender<()>,
@@ -45,14 +44,10 @@ pub fn reload_from_disk(project_root: &Path, registry: &Entity<ConnectionRegistr
     if let Ok(vars) = load_variables(project_root) {
         cx.update_global(|pv: &mut ProjectVars, _| pv.vars = vars);
     }
+    let environment = load_environment_vars(project_root);
+    cx.update_global(|pv: &mut ProjectVars, _| pv.environment = environment);
 
-    let ctx = match ProjectContext::load(project_root.to_path_buf()) {
-        Ok(c) => c,
-        Err(e) => {
-            log::warn!("project reload failed: {e:#}");
-            return;
-        }
-    };
+    let ctx = ProjectContext::load(project_root.to_path_buf());
 
     let queries_dir = project_root.join(".based").join("local");
     let _ = fs::create_dir_all(&queries_dir);
@@ -61,6 +56,7 @@ pub fn reload_from_disk(project_root: &Path, registry: &Entity<ConnectionRegistr
         store.history = QueryHistory::load(&queries_dir);
         store.apply_snapshot(&ctx.snapshot);
     });
+    watch_query_bodies(cx);
 
     let entries =
         load_entries_from_based_dir(&project_root.join(".based"), ConnectionOrigin::Project);
@@ -69,8 +65,7 @@ pub fn reload_from_disk(project_root: &Path, registry: &Entity<ConnectionRegistr
         reg.sync_origin_entries(ConnectionOrigin::Project, entries, cx);
     });
 
-    cx.set_global(ctx.clone());
-    apply_project_settings(&ctx.snapshot.manifest, cx);
+    cx.set_global(ctx);
 
     log::info!("reloaded .based project for {}", project_root.display());
 }
@@ -106,19 +101,41 @@ pub fn install_reload_watcher(project_root: PathBuf, cx: &mut App) {
     }) {
         Ok(watcher) => {
             cx.set_global(super::ConfigWatcherGlobal {
-                _watcher: Some(watcher),
+                watcher: Some(watcher),
             });
+            watch_query_bodies(cx);
         }
         Err(e) => {
             log::warn!("config watcher install failed: {e:#}");
-            cx.set_global(super::ConfigWatcherGlobal { _watcher: None });
+            cx.set_global(super::ConfigWatcherGlobal { watcher: None });
         }
     }
 }
 
+/// Point the watcher at the external body files of the loaded project queries.
+fn watch_query_bodies(cx: &mut App) {
+    let files: Vec<PathBuf> = cx
+        .try_global::<QueryStore>()
+        .map(|store| {
+            store
+                .project_queries()
+                .iter()
+                .filter_map(|q| q.body_file.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    if cx.has_global::<super::ConfigWatcherGlobal>() {
+        cx.update_global(|global: &mut super::ConfigWatcherGlobal, _| {
+            if let Some(watcher) = &mut global.watcher {
+                watcher.watch_body_files(files);
+            }
+        });
+    }
+}
+
 /// Drop the `.based/` watcher and `ProjectRoot` so Close Project unbinds cleanly.
 pub fn stop_reload_watcher(cx: &mut App) {
-    cx.set_global(super::ConfigWatcherGlobal { _watcher: None });
+    cx.set_global(super::ConfigWatcherGlobal { watcher: None });
     take_global::<ConfigReloadSignal>(cx);
     take_global::<ProjectRoot>(cx);
 }
@@ -0,0 +1,155 @@
+//! Missing `{ env = "…" }` secrets: a form to fill them into `.based/.env`, and keeping
+//! `.based/.env.example` listing every referenced variable.
+//!
+//! The form opens once after a project is opened when a connection or environment names
+//! a variable set neither in `.env` nor the process environment.
+
+use std::rc::Rc;
+
+use based_project::{EnvVarRef, missing_env_vars, sync_env_example, upsert_env_file};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    input::{Input, InputState},
+    v_flex,
+};
+
+use crate::project::ProjectRoot;
+use crate::widgets::labeled_field;
+use crate::workspace::Workspace;
+use crate::workspace::notify::{push_error, push_info};
+
+/// "Fill Missing Secrets" from the palette: open the form, or say there is nothing to fill.
+pub fn request_fill_secrets(workspace: &mut Workspace, cx: &mut Context<Workspace>) {
+    let Some(root) = cx.try_global::<ProjectRoot>().map(|p| p.0.clone()) else {
+        return;
+    };
+    match missing_env_vars(&root) {
+        Ok(missing) if missing.is_empty() => {
+            push_info(cx, "Every referenced environment variable is set");
+        }
+        Ok(_) => {
+            workspace.pending_missing_secrets = true;
+            cx.notify();
+        }
+        Err(e) => push_error(cx, "Missing secrets", format!("{e:#}")),
+    }
+}
+
+/// "Update .env.example": regenerate it with the referenced variables and blank values.
+pub fn sync_env_example_now(cx: &mut App) {
+    let Some(root) = cx.try_global::<ProjectRoot>().map(|p| p.0.clone()) else {
+        return;
+    };
+    match sync_env_example(&root) {
+        Ok(sync) if !sync.changed => push_info(cx, ".env.example is up to date"),
+        Ok(sync) => {
+            let mut changes = Vec::new();
+            if !sync.added.is_empty() {
+                changes.push(format!("added {}", sync.added.join(", ")));
+            }
+            if !sync.removed.is_empty() {
+                changes.push(format!("removed {}", sync.removed.join(", ")));
+            }
+            if changes.is_empty() {
+                changes.push("cleared example values".to_string());
+            }
+            push_info(cx, format!("Updated .env.example: {}", changes.join("; ")))
+        }
+        Err(e) => push_error(cx, "Update .env.example", format!("{e:#}")),
+    }
+}
+
+/// Show the queued secrets form during render. Nothing is shown when no variable is missing.
+pub fn maybe_show_pending_secrets_dialog(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    if !workspace.pending_missing_secrets {
+        return;
+    }
+    if window.has_active_dialog(cx) {
+        return;
+    }
+    workspace.pending_missing_secrets = false;
+    let Some(root) = cx.try_global::<ProjectRoot>().map(|p| p.0.clone()) else {
+        return;
+    };
+    match missing_env_vars(&root) {
+        Ok(missing) if missing.is_empty() => {}
+        Ok(missing) => show_secrets_dialog(missing, window, cx),
+        Err(e) => log::warn!("missing secrets check failed: {e:#}"),
+    }
+}
+
+fn show_secrets_dialog(missing: Vec<EnvVarRef>, window: &mut Window, cx: &mut App) {
+    let Some(root) = cx.try_global::<ProjectRoot>().map(|p| p.0.clone()) else {
+        return;
+    };
+    let fields: Rc<Vec<(EnvVarRef, Entity<InputState>)>> = Rc::new(
+        missing
+            .into_iter()
+            .map(|var| {
+                let input = cx.new(|cx| {
+                    InputState::new(window, cx)
+                        .placeholder(var.used_by.join(", "))
+                        .masked(true)
+                });
+                (var, input)
+            })
+            .collect(),
+    );
+    let env_path = root.join(".based").join(".env");
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let muted = cx.theme().muted_foreground;
+        let fields = fields.clone();
+        let env_path = env_path.clone();
+        dialog
+            .title("Missing secrets")
+            .child(
+                v_flex()
+                    .gap_3()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(muted)
+                            .child("Values are saved to .based/.env, which is not committed."),
+                    )
+                    .children(fields.iter().map(|(var, input)| {
+                        labeled_field(&var.var, muted, Input::new(input).w_full())
+                    })),
+            )
+            .footer(
+                DialogFooter::new()
+                    .child(
+                        DialogClose::new()
+                            .child(Button::new("secrets-cancel").outline().label("Later")),
+                    )
+                    .child(
+                        DialogAction::new()
+                            .child(Button::new("secrets-save").primary().label("Save")),
+                    ),
+            )
+            .on_ok(move |_, _, cx| {
+                let mut saved = 0;
+                for (var, input) in fields.iter() {
+                    let value = input.read(cx).value().to_string();
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Err(e) = upsert_env_file(&env_path, &var.var, &value) {
+                        push_error(cx, "Missing secrets", format!("{e:#}"));
+                        return false;
+                    }
+                    saved += 1;
+                }
+                if saved > 0 {
+                    push_info(cx, format!("Saved {saved} secrets to .based/.env"));
+                }
+                true
+            })
+    });
+}
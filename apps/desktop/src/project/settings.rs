@@ -1,30 +1,23 @@
-use based_project::ProjectManifest;
-use gpui::App;
+use std::time::Duration;
 
-use crate::app::prefs::set_query_timeout_secs;
+use based_project::DEFAULT_MAX_RESULT_ROWS;
+use gpui::App;
 
-/// Convert project.toml `query_timeout` (milliseconds) to preference seconds.
-pub fn query_timeout_ms_to_secs(ms: u64) -> u32 {
-    ((ms / 1000).max(1)) as u32
-}
+use crate::app::prefs;
+use crate::project::ProjectContext;
 
-/// Apply optional `[settings]` from `project.toml` into app preferences.
-pub fn apply_project_settings(manifest: &ProjectManifest, cx: &mut App) {
-    let Some(settings) = &manifest.settings else {
-        return;
-    };
-    if let Some(timeout) = settings.query_timeout {
-        set_query_timeout_secs(query_timeout_ms_to_secs(timeout), cx);
-    }
+/// Row cap for query editor results: the open project's `max_result_rows`,
+/// else [`DEFAULT_MAX_RESULT_ROWS`].
+pub fn max_result_rows(cx: &App) -> u64 {
+    cx.try_global::<ProjectContext>()
+        .map(|ctx| ctx.snapshot.manifest.max_result_rows())
+        .unwrap_or(DEFAULT_MAX_RESULT_ROWS)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn converts_project_timeout_ms_to_secs() {
-        assert_eq!(query_timeout_ms_to_secs(30_000), 30);
-        assert_eq!(query_timeout_ms_to_secs(500), 1);
-    }
+/// Query timeout for every engine: the open project's `[settings] query_timeout`
+/// overrides the user preference without changing it.
+pub fn query_timeout(cx: &App) -> Duration {
+    cx.try_global::<ProjectContext>()
+        .and_then(|ctx| ctx.snapshot.manifest.query_timeout())
+        .unwrap_or_else(|| Duration::from_secs(prefs::query_timeout_secs(cx).into()))
 }
@@ -1,3 +1,19 @@
-//! Re-export variable helpers from `based-query`.
+//! Re-export variable helpers from `based-query`, plus the active project environment.
+
+use std::path::Path;
+
+use based_project::{active_environment_vars, load_env_file};
+use based_query::Variables;
 
 pub use based_query::variables::{load_variables, substitute_dollar_vars as substitute};
+
+/// Values of the active `environments/` file with secrets resolved from the process
+/// environment and `.based/.env`. `None` for No Environment, or when the file can't be
+/// resolved (logged).
+pub fn load_environment_vars(project_root: &Path) -> Option<Variables> {
+    let file_vars = load_env_file(&project_root.join(".based").join(".env")).unwrap_or_default();
+    active_environment_vars(project_root, &file_vars).unwrap_or_else(|e| {
+        log::warn!("active environment unavailable: {e:#}");
+        None
+    })
+}
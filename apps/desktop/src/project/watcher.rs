@@ -1,34 +1,83 @@
 // File-system watcher — watches `.based/` directory for config changes.
-// Calls `on_change` whenever any file under `.based/` is modified.
+// Calls `on_change` whenever any file under `.based/` is modified, or an external
+// query body file (`[sql] file = "…"`) that lives outside `.based/`.
 // The caller (Project entity) schedules a config reload in response.
 
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher, recommended_watcher};
 
 pub struct ConfigWatcher {
-    _watcher: RecommendedWatcher,
+    watcher: RecommendedWatcher,
+    based_dir: PathBuf,
+    /// External body files outside `.based/`; other events in their directories are ignored.
+    body_files: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Directories watched (non-recursively) for `body_files`.
+    body_dirs: HashSet<PathBuf>,
 }
 
 impl ConfigWatcher {
-    /// Start watching `project_dir/.based/` (non-recursively).
+    /// Start watching `project_dir/.based/` (recursively).
     /// On any file-system event `on_change` is called.
     pub fn new(project_dir: PathBuf, on_change: impl Fn() + Send + 'static) -> Result<Self> {
-        let watched_dir = project_dir.join(".based");
+        let based_dir = project_dir.join(".based");
+        let body_files = Arc::new(Mutex::new(HashSet::new()));
 
+        let filter_dir = based_dir.clone();
+        let filter_files = Arc::clone(&body_files);
         let mut watcher = recommended_watcher(move |res: notify::Result<Event>| {
-            if res.is_ok() {
+            let Ok(event) = res else {
+                return;
+            };
+            let relevant = event.paths.iter().any(|path| {
+                path.starts_with(&filter_dir)
+                    || filter_files.lock().is_ok_and(|files| files.contains(path))
+            });
+            if relevant {
                 on_change();
             }
         })?;
 
         // Best-effort: if `.based/` doesn't exist yet the watcher is still
         // constructed; the watch call is skipped gracefully.
-        if watched_dir.exists() {
-            watcher.watch(&watched_dir, RecursiveMode::Recursive)?;
+        if based_dir.exists() {
+            watcher.watch(&based_dir, RecursiveMode::Recursive)?;
         }
 
-        Ok(Self { _watcher: watcher })
+        Ok(Self {
+            watcher,
+            based_dir,
+            body_files,
+            body_dirs: HashSet::new(),
+        })
+    }
+
+    /// Replace the set of external query body files to watch. Files under `.based/` are
+    /// already covered. Their directories are watched rather than the files, so editors
+    /// that save by renaming a temp file over the original still trigger a reload.
+    pub fn watch_body_files(&mut self, files: impl IntoIterator<Item = PathBuf>) {
+        let files: HashSet<PathBuf> = files
+            .into_iter()
+            .filter(|file| !file.starts_with(&self.based_dir))
+            .collect();
+        let dirs: HashSet<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+        for dir in self.body_dirs.difference(&dirs) {
+            let _ = self.watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&self.body_dirs) {
+            if let Err(e) = self.watcher.watch(dir, RecursiveMode::NonRecursive) {
+                log::warn!("watch query body dir {}: {e}", dir.display());
+            }
+        }
+        self.body_dirs = dirs;
+        if let Ok(mut watched) = self.body_files.lock() {
+            *watched = files;
+        }
     }
 }
@@ -110,9 +110,11 @@ mod tests {
                 tags: vec![],
                 exclude_tags: vec![],
             },
+            params: Default::default(),
             body: QueryBody::Sql {
                 query: "SELECT 1".into(),
             },
+            body_file: None,
         }
     }
 
@@ -2,6 +2,8 @@
 
 use std::collections::HashMap;
 
+use based_core::Value;
+use based_sqlite::sqlite_row_values;
 use gpui::{prelude::*, *};
 use gpui_component::{
     ActiveTheme, Sizable as _,
@@ -20,6 +22,7 @@ use crate::connection::ConnectionId;
 use crate::connection::is_connection_read_only;
 use crate::db;
 use crate::db::column_catalog;
+use crate::editor::policy::{allows_browsing, cap_rows};
 use crate::widgets::cell_detail::{CellDetail, CellValue, interpret_cell_with_meta};
 use crate::widgets::column_header::GridColumnMeta;
 use crate::widgets::data_table::{configure_row_table, render_row_table};
@@ -27,9 +30,8 @@ use crate::widgets::export_popover::export_popover;
 use crate::widgets::filter_bar::FilterBar;
 use crate::widgets::pagination::sql_page_state;
 use crate::widgets::pagination::{offset_for_page, sql_pagination_controls, sql_row_range_label};
-use crate::widgets::row_cell::sqlite_cell_display;
 use crate::widgets::virtual_table::{
-    RowDelegate, align_meta_to_columns, data_column, replace_table_data,
+    RowDelegate, align_meta_to_columns, data_column, replace_table_values,
 };
 use crate::widgets::{
     metadata_pill,
@@ -91,6 +93,7 @@ impl DataViewerPanel {
         let filter_bar = cx.new(|cx| FilterBar::new(window, cx, vec![]));
         let cell_detail = cx.new(|_| CellDetail::new());
 
+        let page_size = cap_rows(&conn_id, prefs::page_size(cx), cx);
         let tab_label = table_name.clone().into();
         let mut panel = Self {
             focus_handle: cx.focus_handle(),
@@ -101,7 +104,7 @@ impl DataViewerPanel {
             cell_detail,
             filter_bar,
             offset: 0,
-            page_size: prefs::page_size(cx),
+            page_size,
             total_rows: 0,
             loading: false,
             last_load_ms: None,
@@ -131,6 +134,9 @@ impl DataViewerPanel {
         let st = self.table.read(cx);
         let del = st.delegate();
         let col_name = del.columns.get(col)?.key.to_string();
+        if let Some(value) = del.value(row, col) {
+            return Some((col_name, CellValue::from(value)));
+        }
         let txt = del.rows.get(row)?.get(col)?.to_string();
         let meta = del.column_meta.get(col).cloned().unwrap_or_default();
         Some((col_name, interpret_cell_with_meta(&txt, &meta)))
@@ -141,6 +147,9 @@ impl DataViewerPanel {
     }
 
     fn load_page(&mut self, offset: u64, cx: &mut Context<Self>) {
+        if !allows_browsing(&self.conn_id, cx) {
+            return;
+        }
         self.loading = true;
         self.offset = offset;
 
@@ -190,14 +199,7 @@ impl DataViewerPanel {
 
                 let columns: Vec<Column> = columns_from_rows_or_catalog(&rows, &catalog_for_rows);
 
-                let data_rows: Vec<Vec<SharedString>> = rows
-                    .iter()
-                    .map(|row| {
-                        (0..row.len())
-                            .map(|i| SharedString::from(sqlite_cell_display(row, i)))
-                            .collect()
-                    })
-                    .collect();
+                let data_rows: Vec<Vec<Value>> = rows.iter().map(sqlite_row_values).collect();
 
                 let elapsed_ms = start.elapsed().as_millis() as u64;
                 Ok((total, columns, data_rows, elapsed_ms))
@@ -220,7 +222,7 @@ impl DataViewerPanel {
                             &catalog,
                         );
                         panel.table.update(cx, |state, cx| {
-                            replace_table_data(state, columns, data_rows, column_meta, cx);
+                            replace_table_values(state, columns, data_rows, column_meta, cx);
                         });
                         cx.notify();
                     }
@@ -284,21 +286,7 @@ impl Render for DataViewerPanel {
 
         let muted = cx.theme().muted_foreground;
 
-        let (export_headers, export_rows) = {
-            let st = self.table.read(cx);
-            let d = st.delegate();
-            let h = d
-                .columns
-                .iter()
-                .map(|c| c.key.to_string())
-                .collect::<Vec<_>>();
-            let r = d
-                .rows
-                .iter()
-                .map(|row| row.iter().map(|c| c.to_string()).collect())
-                .collect::<Vec<Vec<String>>>();
-            (h, r)
-        };
+        let (export_headers, export_rows) = self.table.read(cx).delegate().export_snapshot();
         let export_popover = export_popover("sqlite-dv", export_headers, export_rows);
 
         let toolbar = data_viewer_toolbar(cx)
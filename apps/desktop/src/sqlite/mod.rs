@@ -54,6 +54,7 @@ impl Connectable for SqliteConnection {
             let pool = SqlitePool::connect_with(sqlite_connect_options(&SqliteOpenOptions {
                 path: &path,
                 read_only: config.read_only,
+                query_only: config.query_only,
             }))
             .await?;
             apply_sqlite_pragmas(&pool, &config).await?;
@@ -76,6 +77,7 @@ impl Connectable for SqliteConnection {
             let pool = SqlitePool::connect_with(sqlite_connect_options(&SqliteOpenOptions {
                 path: &path,
                 read_only: config.read_only,
+                query_only: config.query_only,
             }))
             .await?;
             let version: String = sqlx::query_scalar("SELECT sqlite_version()")
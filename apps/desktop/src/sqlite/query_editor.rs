@@ -1,8 +1,15 @@
 // sqlite::query_editor — QueryEditorPanel: run arbitrary SQL and view results.
 
+use std::cell::Cell;
 use std::rc::Rc;
-use std::time::Duration;
 
+use based_core::{QueryErrorDetail, QueryTimedOut, Value, categorize_query_error};
+use based_project::QueryBody;
+use based_query::{Dialect, VariableContext};
+use based_sqlite::{
+    BoundStatement, QueryResult, ScriptStep, SqliteInterrupt, SqliteManualTx, SqliteScriptSession,
+    execute_sql_interruptible, is_interrupted,
+};
 use gpui::{App, prelude::*, *};
 use gpui_component::{
     ActiveTheme, IconName, Sizable as _,
@@ -15,20 +22,28 @@ use gpui_component::{
     table::{Column, TableState},
     v_flex,
 };
-use sqlx::{AssertSqlSafe, Column as SqlxColumn, Row, SqlitePool, TypeInfo};
+use sqlx::{AssertSqlSafe, Row, SqlitePool};
 
 use super::eqp_parse::{EqpNode, parse_eqp};
 use super::eqp_viewer::render_eqp_body;
 
-use crate::app::prefs;
 use crate::connection::ConnectionId;
 use crate::connection::is_connection_read_only;
 use crate::db;
 use crate::editor::EditorContext;
 use crate::editor::VariableScope;
 use crate::editor::context::EditorContextEvent;
+use crate::editor::open_transactions::{mark_transaction_closed, mark_transaction_open};
+use crate::editor::param_form::{param_key, prompt_missing_params};
+use crate::editor::policy::{
+    check_connection_policy, connection_policy, next_row_cap, policy_summary, row_cap,
+};
+use crate::editor::query_file::{can_save_query, query_scope, save_query};
+use crate::editor::query_params::{BoundQuery, bind_editor_sql, editor_variable_context};
+use crate::editor::script_run::{ResultGrid, ScriptRun, StatementOutcome};
 use crate::editor::sqlite_schema;
-use crate::project::{ProjectRoot, ProjectVars, substitute};
+use crate::project::settings::{max_result_rows, query_timeout};
+use crate::project::{ProjectRoot, ProjectVars};
 use crate::query_store::{HistoryEntry, QueryStore};
 use crate::widgets::column_header::GridColumnMeta;
 use crate::widgets::data_table::{configure_row_table, render_row_table};
@@ -41,26 +56,32 @@ use crate::widgets::query_panel_extras;
 use crate::widgets::query_status::{
     QueryStatusDisplay, query_error_card, query_status_indicator, tab_breadcrumb_query_trailing,
 };
-use crate::widgets::result_tabs::{BottomTab, result_tab_strip};
-use crate::widgets::row_cell::sqlite_cell_display;
+use crate::widgets::result_tabs::{
+    BottomTab, result_tab_strip, statement_message_list, statement_tab_strip,
+};
 use crate::widgets::shortcut_run_kbd_in_primary_button;
 use crate::widgets::sql_editor::{self, new_sql_input, set_input_text, text_from_input};
 use crate::widgets::virtual_table::{
-    RowDelegate, data_column, meta_from_query_type, replace_table_data,
+    RowDelegate, data_column, meta_from_query_type, replace_table_values,
 };
 use crate::workspace::WorkspaceRef;
+use crate::workspace::notify::push_error;
 use crate::workspace::pop_out::PopOutWindowTitle;
 use crate::workspace::tabs::take_sql_inject;
 use gpui::Entity;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::time::timeout as run_with_timeout;
 
 pub enum QueryStatus {
     Idle,
     Running,
-    Done { rows: usize, elapsed_ms: u64 },
-    Error(String),
+    Done {
+        rows: usize,
+        elapsed_ms: u64,
+        truncated_at: Option<u64>,
+    },
+    Cancelled,
+    Error(QueryErrorDetail),
 }
 
 /// Result of an inline EXPLAIN QUERY PLAN run.
@@ -81,7 +102,24 @@ pub struct QueryEditorPanel {
     split_state: Entity<ResizableState>,
     bottom_tab: BottomTab,
     explain: ExplainView,
+    /// Bound query of the last run, re-executed by "Fetch more"; `None` when it wrote,
+    /// so a capped `… RETURNING` never runs twice.
+    last_query: Option<BoundQuery>,
+    /// Row cap of the last run; grows by one project cap per "Fetch more".
+    row_cap: u64,
+    /// Handle of the pooled connection running the script, for `sqlite3_interrupt`.
+    interrupt: SqliteInterrupt,
+    cancel_requested: bool,
+    /// Per-statement results of the last "Run script"; `None` after a plain run.
+    script: Option<ScriptRun>,
+    /// Connection of a script stopped on an error, kept so "Continue" runs the rest in
+    /// the same session.
+    script_session: Option<SqliteScriptSession>,
+    /// Manual transaction pinned to this tab; runs go through it until commit/rollback.
+    tx: Option<SqliteManualTx>,
     pub(crate) tab_label: SharedString,
+    /// Saved project query this tab was opened from; keys remembered parameter values.
+    pub(crate) query_path: Option<String>,
     pub editor_ctx: Entity<EditorContext>,
 }
 
@@ -127,9 +165,20 @@ impl QueryEditorPanel {
             split_state,
             bottom_tab: BottomTab::Results,
             explain: ExplainView::Empty,
+            last_query: None,
+            row_cap: max_result_rows(cx),
+            interrupt: SqliteInterrupt::new(),
+            cancel_requested: false,
+            script: None,
+            script_session: None,
+            tx: None,
             tab_label: "Query".into(),
+            query_path: None,
             editor_ctx,
         };
+        let panel_id = cx.entity_id();
+        cx.on_release(move |_, cx| mark_transaction_closed(panel_id, cx))
+            .detach();
         cx.subscribe_in(&sql_input, window, |panel, _, event, window, cx| {
             if let InputEvent::PressEnter {
                 secondary: true,
@@ -185,6 +234,15 @@ impl QueryEditorPanel {
         text_from_input(&self.sql_input, cx)
     }
 
+    /// Editor variables with the types the saved query declares.
+    fn variable_scope(&self, cx: &App) -> VariableScope {
+        query_scope(
+            &self.editor_ctx.read(cx).variables,
+            self.query_path.as_deref(),
+            cx,
+        )
+    }
+
     /// Switch the bottom dock to the Explain tab and (re)run EXPLAIN QUERY PLAN inline.
     fn switch_to_explain(&mut self, cx: &mut Context<Self>) {
         let sql = self.current_sql(cx);
@@ -233,84 +291,331 @@ impl QueryEditorPanel {
         .detach();
     }
 
-    fn run_query(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+    fn run_query(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(var_ctx) = self.prompt_params(window, cx, Self::run_query_with) {
+            self.run_query_with(var_ctx, window, cx);
+        }
+    }
+
+    /// Bind the editor text, then run it if the connection policy allows the bound SQL,
+    /// which is what reaches the database.
+    fn run_query_with(
+        &mut self,
+        var_ctx: VariableContext,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let sql = self.current_sql(cx);
+        let scope = self.variable_scope(cx);
+        let query = match bind_editor_sql(&sql, Dialect::Sqlite, &var_ctx, &scope) {
+            Ok(query) => query,
+            Err(e) => {
+                self.show_resolve_error(e.to_string(), cx);
+                return;
+            }
+        };
+        let confirmed = query.clone();
+        if check_connection_policy(
+            &query.sql,
+            Dialect::Sqlite,
+            &self.conn_id,
+            window,
+            cx,
+            move |panel, _, cx| panel.execute(confirmed.clone(), row_cap(&panel.conn_id, cx), cx),
+        ) {
+            self.execute(query, row_cap(&self.conn_id, cx), cx);
+        }
+    }
+
+    /// Variable context for a run of the editor text, or `None` when the parameter form
+    /// opened to ask for missing `{{name}}`s (it calls `run`).
+    fn prompt_params(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        run: fn(&mut Self, VariableContext, &mut Window, &mut Context<Self>),
+    ) -> Option<VariableContext> {
+        let sql = self.current_sql(cx);
+        let var_ctx = editor_variable_context(cx.global::<ProjectVars>());
+        let scope = self.variable_scope(cx);
+        let key = param_key(self.query_path.as_deref(), &self.conn_id);
+        prompt_missing_params(&sql, var_ctx, &scope, key, window, cx, run)
+    }
+
+    /// Run each statement of the editor on its own, one result tab per statement.
+    fn run_script(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(var_ctx) = self.prompt_params(window, cx, Self::run_script_with) {
+            self.run_script_with(var_ctx, window, cx);
+        }
+    }
+
+    /// Bind every statement, then start the script if the connection policy allows the
+    /// bound statements.
+    fn run_script_with(
+        &mut self,
+        var_ctx: VariableContext,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let sql = self.current_sql(cx);
+        let scope = self.variable_scope(cx);
+        let script = match ScriptRun::new(&sql, Dialect::Sqlite, &var_ctx, &scope) {
+            Ok(script) if script.is_empty() => return,
+            Ok(script) => script,
+            Err(e) => {
+                self.show_resolve_error(e.to_string(), cx);
+                return;
+            }
+        };
+        let bound_sql = script.bound_sql();
+        // The confirm dialog's Run starts the script; otherwise it starts right away.
+        let pending = Rc::new(Cell::new(Some(script)));
+        let confirmed = pending.clone();
+        let allowed = check_connection_policy(
+            &bound_sql,
+            Dialect::Sqlite,
+            &self.conn_id,
+            window,
+            cx,
+            move |panel, _, cx| {
+                if let Some(script) = confirmed.take() {
+                    panel.start_script(script, cx);
+                }
+            },
+        );
+        if allowed && let Some(script) = pending.take() {
+            self.start_script(script, cx);
+        }
+    }
+
+    fn start_script(&mut self, script: ScriptRun, cx: &mut Context<Self>) {
+        self.finish_script_session(cx);
+        self.script = Some(script);
+        self.execute_script_from(0, cx);
+    }
+
+    /// Write the editor text to its saved query, or save it as a new project query.
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let body = QueryBody::Sql {
+            query: self.current_sql(cx),
+        };
+        save_query(
+            self.query_path.as_deref(),
+            body,
+            &self.conn_id,
+            based_core::EngineKind::SQLite,
+            window,
+            cx,
+            |panel, path, _| panel.query_path = Some(path),
+        );
+    }
+
+    /// Report a variable that couldn't be resolved or coerced; nothing runs.
+    fn show_resolve_error(&mut self, message: String, cx: &mut Context<Self>) {
+        self.script = None;
+        self.status = QueryStatus::Error(QueryErrorDetail::failed(message));
+        self.bottom_tab = BottomTab::Messages;
+        cx.notify();
+    }
+
+    /// Resume a script that stopped on an error with the statement after the failure.
+    fn continue_script(&mut self, cx: &mut Context<Self>) {
+        let Some(from) = self.script.as_ref().and_then(|s| s.resume_at) else {
+            return;
+        };
+        self.execute_script_from(from, cx);
+    }
+
+    /// Release the connection of a stopped script, rolling back what it left open.
+    fn finish_script_session(&mut self, cx: &mut Context<Self>) {
+        let Some(session) = self.script_session.take() else {
+            return;
+        };
+        cx.spawn(async move |_, cx| {
+            if let Err(e) = db::run(cx, async move { session.finish().await }).await {
+                log::warn!("finish script session failed: {e:#}");
+            }
+        })
+        .detach();
+    }
+
+    /// Re-run the last query with the row cap raised by another project cap, up to the
+    /// connection's `max_rows`.
+    fn fetch_more(&mut self, cx: &mut Context<Self>) {
+        let Some(query) = self.last_query.clone() else {
+            return;
+        };
+        let cap = next_row_cap(&self.conn_id, self.row_cap, cx);
+        if cap == self.row_cap {
+            return;
+        }
+        self.execute(query, cap, cx);
+    }
+
+    /// Interrupt the running statement via `sqlite3_interrupt`.
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        if !matches!(self.status, QueryStatus::Running) {
+            return;
+        }
+        self.cancel_requested = true;
+        self.interrupt.interrupt();
+        cx.notify();
+    }
+
+    /// Pin a pooled connection to this tab and issue `BEGIN`.
+    fn begin_transaction(&mut self, cx: &mut Context<Self>) {
+        if self.tx.is_some() {
+            return;
+        }
+        self.finish_script_session(cx);
+        let pool = self.pool.clone();
+        cx.spawn(async move |this, cx| {
+            let outcome = db::run(cx, async move { SqliteManualTx::begin(&pool).await }).await;
+            let _ = this.update(cx, |panel, cx| {
+                match outcome {
+                    Ok(tx) => {
+                        panel.tx = Some(tx);
+                        mark_transaction_open(cx.entity_id(), panel.conn_id.clone(), cx);
+                    }
+                    Err(e) => push_error(cx, "Begin transaction", format!("{e:#}")),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Commit or roll back the open transaction and release its connection.
+    fn end_transaction(&mut self, commit: bool, cx: &mut Context<Self>) {
+        let Some(tx) = self.tx.take() else {
+            return;
+        };
+        mark_transaction_closed(cx.entity_id(), cx);
+        cx.spawn(async move |_, cx| {
+            let outcome = db::run(cx, async move {
+                if commit {
+                    tx.commit().await
+                } else {
+                    tx.rollback().await
+                }
+            })
+            .await;
+            if let Err(e) = outcome {
+                let title = if commit { "Commit" } else { "Rollback" };
+                let _ = cx.update(|cx| push_error(cx, title, format!("{e:#}")));
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn execute(&mut self, query: BoundQuery, row_cap: u64, cx: &mut Context<Self>) {
         let pool = self.pool.clone();
-        let sql_raw = self.current_sql(cx);
-        let vars = cx.global::<ProjectVars>().vars.clone();
-        let sql = substitute(&sql_raw, &vars);
-        let sql_executed = sql.clone();
+        let sql_executed = query.sql.clone();
+        self.script = None;
+        self.finish_script_session(cx);
+        self.last_query = query.can_rerun(Dialect::Sqlite).then(|| query.clone());
+        self.row_cap = row_cap;
         let conn_id = self.conn_id.clone();
-        let timeout_secs = prefs::query_timeout_secs(cx);
+        let timeout = query_timeout(cx);
         self.status = QueryStatus::Running;
         self.bottom_tab = BottomTab::Results;
+        self.interrupt = SqliteInterrupt::new();
+        self.cancel_requested = false;
+        let interrupt = self.interrupt.clone();
+        let tx = self.tx.clone();
 
         cx.spawn(async move |this, cx| {
             let start = Instant::now();
 
-            let result: anyhow::Result<(Vec<Column>, Vec<GridColumnMeta>, Vec<Vec<SharedString>>)> =
-                db::run(cx, async move {
-                    let stmts = based_query::statements_in_script(&sql);
-                    if stmts.is_empty() {
-                        return Ok((vec![], vec![], vec![]));
-                    }
+            let result: anyhow::Result<(
+                Vec<Column>,
+                Vec<GridColumnMeta>,
+                Vec<Vec<Value>>,
+                Option<u64>,
+            )> = db::run(cx, async move {
+                // Every statement gets the full list; each binds only the `?N` it uses.
+                let BoundQuery { sql, params } = query;
+                let stmts = based_query::statements_in_script(&sql, Dialect::Sqlite);
+                if stmts.is_empty() {
+                    return Ok((vec![], vec![], vec![], None));
+                }
 
-                    let timeout = Duration::from_secs(timeout_secs as u64);
-                    let mut last_columns = vec![];
-                    let mut last_column_meta = vec![];
-                    let mut last_data_rows = vec![];
+                let mut last_columns = vec![];
+                let mut last_column_meta = vec![];
+                let mut last_data_rows = vec![];
+                let mut last_truncated_at = None;
 
-                    for (index, stmt) in stmts.iter().enumerate() {
-                        let text = stmt.text(&sql);
-                        if text.is_empty() {
-                            continue;
-                        }
+                for (index, stmt) in stmts.iter().enumerate() {
+                    let text = stmt.text(&sql);
+                    if text.is_empty() {
+                        continue;
+                    }
 
-                        let fetch = sqlx::query(AssertSqlSafe(text.to_string())).fetch_all(&pool);
-                        let rows = match run_with_timeout(timeout, fetch).await {
-                            Err(_) => {
-                                anyhow::bail!(
-                                    "Query timed out after {timeout_secs}s (statement {})",
-                                    index + 1
-                                );
-                            }
-                            Ok(Err(e)) => {
-                                anyhow::bail!("Statement {} failed: {e}", index + 1);
-                            }
-                            Ok(Ok(rows)) => rows,
-                        };
-
-                        if let Some(first) = rows.first() {
-                            let cols = first.columns();
-                            last_columns = cols
-                                .iter()
-                                .map(|c| data_column(c.name().to_string(), c.name().to_string()))
-                                .collect();
-                            last_column_meta = cols
-                                .iter()
-                                .map(|c| meta_from_query_type(c.type_info().name()))
-                                .collect();
-                            last_data_rows = rows
-                                .iter()
-                                .map(|row| {
-                                    (0..row.len())
-                                        .map(|i| SharedString::from(sqlite_cell_display(row, i)))
-                                        .collect()
-                                })
-                                .collect();
+                    let fetch = match &tx {
+                        Some(tx) => {
+                            tx.execute(text, &params, Some(row_cap), Some(timeout), &interrupt)
+                                .await
+                        }
+                        None => {
+                            execute_sql_interruptible(
+                                &pool,
+                                text,
+                                &params,
+                                Some(row_cap),
+                                Some(timeout),
+                                &interrupt,
+                            )
+                            .await
+                        }
+                    };
+                    let result = match fetch {
+                        Ok(result) => result,
+                        Err(e) if is_interrupted(&e) => return Err(e),
+                        Err(e) if e.is::<QueryTimedOut>() => {
+                            return Err(e.context(format!("Statement {}", index + 1)));
                         }
+                        Err(e) => {
+                            anyhow::bail!("Statement {} failed: {e}", index + 1);
+                        }
+                    };
+
+                    // A row-returning statement replaces the grid even when it matched
+                    // nothing, so an empty SELECT doesn't leave an earlier result showing.
+                    let returns_rows =
+                        based_query::classify_statement(text, Dialect::Sqlite).returns_rows;
+                    if returns_rows || !result.columns.is_empty() {
+                        last_columns = result
+                            .columns
+                            .iter()
+                            .map(|c| data_column(c.name.clone(), c.name.clone()))
+                            .collect();
+                        last_column_meta = result
+                            .columns
+                            .iter()
+                            .map(|c| meta_from_query_type(&c.type_name))
+                            .collect();
+                        last_data_rows = result.rows;
+                        last_truncated_at = result.truncated_at;
                     }
+                }
 
-                    Ok((last_columns, last_column_meta, last_data_rows))
-                })
-                .await;
+                Ok((
+                    last_columns,
+                    last_column_meta,
+                    last_data_rows,
+                    last_truncated_at,
+                ))
+            })
+            .await;
 
             let elapsed_ms = start.elapsed().as_millis() as u64;
 
             let _ = this.update(cx, |panel, cx| match result {
-                Ok((columns, column_meta, data_rows)) => {
+                Ok((columns, column_meta, data_rows, truncated_at)) => {
                     let row_count = data_rows.len();
                     panel.result.update(cx, |state, cx| {
-                        replace_table_data(state, columns, data_rows, column_meta, cx);
+                        replace_table_values(state, columns, data_rows, column_meta, cx);
                     });
                     cx.update_global(|store: &mut QueryStore, _| {
                         store.push_history(HistoryEntry::new(
@@ -324,9 +629,23 @@ impl QueryEditorPanel {
                     panel.status = QueryStatus::Done {
                         rows: row_count,
                         elapsed_ms,
+                        truncated_at,
                     };
                     cx.notify();
                 }
+                Err(e) if panel.cancel_requested || is_interrupted(&e) => {
+                    cx.update_global(|store: &mut QueryStore, _| {
+                        store.push_history(HistoryEntry::new(
+                            conn_id.clone(),
+                            sql_executed,
+                            elapsed_ms,
+                            None,
+                            based_query::RunStatus::Cancelled,
+                        ));
+                    });
+                    panel.status = QueryStatus::Cancelled;
+                    cx.notify();
+                }
                 Err(e) => {
                     cx.update_global(|store: &mut QueryStore, _| {
                         store.push_history(HistoryEntry::new(
@@ -338,13 +657,185 @@ impl QueryEditorPanel {
                         ));
                     });
                     panel.bottom_tab = BottomTab::Messages;
-                    panel.status = QueryStatus::Error(e.to_string());
+                    panel.status = QueryStatus::Error(categorize_query_error(&e));
                     cx.notify();
                 }
             });
         })
         .detach();
     }
+
+    /// Run the script's statements from `from` on a single connection, stopping at the
+    /// first failure. A stopped script keeps its connection for "Continue".
+    fn execute_script_from(&mut self, from: usize, cx: &mut Context<Self>) {
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        let statements: Vec<BoundStatement> = script
+            .remaining(from)
+            .into_iter()
+            .map(|q| BoundStatement {
+                sql: q.sql,
+                params: q.params,
+            })
+            .collect();
+        script.resume_at = None;
+        self.last_query = None;
+        self.status = QueryStatus::Running;
+        self.bottom_tab = BottomTab::Results;
+        self.interrupt = SqliteInterrupt::new();
+        self.cancel_requested = false;
+        let pool = self.pool.clone();
+        let tx = self.tx.clone();
+        let interrupt = self.interrupt.clone();
+        let timeout = query_timeout(cx);
+        let row_cap = row_cap(&self.conn_id, cx);
+        let session = self.script_session.take();
+        cx.spawn(async move |this, cx| {
+            let outcome = db::run(cx, {
+                let statements = statements.clone();
+                async move {
+                    if let Some(tx) = tx {
+                        let steps = tx
+                            .execute_script(&statements, Some(row_cap), Some(timeout), &interrupt)
+                            .await?;
+                        return Ok((steps, None));
+                    }
+                    let session = match session {
+                        Some(session) => session,
+                        None => SqliteScriptSession::start(&pool).await?,
+                    };
+                    let steps = session
+                        .execute(&statements, Some(row_cap), Some(timeout), &interrupt)
+                        .await?;
+                    if steps.len() < statements.len() {
+                        return Ok((steps, Some(session)));
+                    }
+                    session.finish().await?;
+                    Ok::<_, anyhow::Error>((steps, None))
+                }
+            })
+            .await;
+            let _ = this.update(cx, |panel, cx| {
+                let outcome = outcome.map(|(steps, session)| {
+                    panel.script_session = session;
+                    steps
+                });
+                panel.finish_script(from, statements, outcome, cx);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn finish_script(
+        &mut self,
+        from: usize,
+        statements: Vec<BoundStatement>,
+        outcome: anyhow::Result<Vec<ScriptStep>>,
+        cx: &mut Context<Self>,
+    ) {
+        let steps = match outcome {
+            Ok(steps) => steps,
+            Err(e) => {
+                self.bottom_tab = BottomTab::Messages;
+                self.status = QueryStatus::Error(categorize_query_error(&e));
+                return;
+            }
+        };
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        let ran = steps.len();
+        let mut history = Vec::with_capacity(ran);
+        for (offset, (step, stmt)) in steps.into_iter().zip(statements).enumerate() {
+            let elapsed_ms = step.elapsed.as_millis() as u64;
+            let (outcome, rows, status) = match step.result {
+                Ok(result) => {
+                    let rows = result.rows.len() as u64;
+                    let grid = result_grid(result);
+                    (
+                        StatementOutcome::Done { grid, elapsed_ms },
+                        Some(rows),
+                        based_query::RunStatus::Ok,
+                    )
+                }
+                Err(e) if self.cancel_requested || is_interrupted(&e) => (
+                    StatementOutcome::Cancelled { elapsed_ms },
+                    None,
+                    based_query::RunStatus::Cancelled,
+                ),
+                Err(e) => (
+                    StatementOutcome::Failed {
+                        error: categorize_query_error(&e),
+                        elapsed_ms,
+                    },
+                    None,
+                    based_query::RunStatus::Error,
+                ),
+            };
+            script.record(from + offset, outcome);
+            history.push(HistoryEntry::new(
+                self.conn_id.clone(),
+                stmt.sql,
+                elapsed_ms,
+                rows,
+                status,
+            ));
+        }
+        script.finish_batch(from, ran);
+        cx.update_global(|store: &mut QueryStore, _| {
+            for entry in history {
+                store.push_history(entry);
+            }
+        });
+
+        self.status = if let Some(error) = script.active_error() {
+            self.bottom_tab = BottomTab::Messages;
+            QueryStatus::Error(error)
+        } else if script.was_cancelled() {
+            QueryStatus::Cancelled
+        } else {
+            QueryStatus::Done {
+                rows: script.active_grid().map_or(0, |g| g.rows.len()),
+                elapsed_ms: script.total_elapsed_ms(),
+                truncated_at: None,
+            }
+        };
+        let active = script.active;
+        self.show_script_statement(active, cx);
+    }
+
+    /// Load statement `ix` of the script run into the result grid.
+    fn show_script_statement(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        script.active = ix;
+        let grid = script.active_grid().cloned().unwrap_or_default();
+        self.result.update(cx, |state, cx| {
+            replace_table_values(state, grid.columns, grid.rows, grid.column_meta, cx);
+        });
+        cx.notify();
+    }
+}
+
+fn result_grid(result: QueryResult) -> ResultGrid {
+    ResultGrid {
+        columns: result
+            .columns
+            .iter()
+            .map(|c| data_column(c.name.clone(), c.name.clone()))
+            .collect(),
+        column_meta: result
+            .columns
+            .iter()
+            .map(|c| meta_from_query_type(&c.type_name))
+            .collect(),
+        rows: result.rows,
+        rows_affected: result.rows_affected,
+        truncated_at: result.truncated_at,
+    }
 }
 
 impl EventEmitter<PanelEvent> for QueryEditorPanel {}
@@ -381,12 +872,46 @@ impl PopOutWindowTitle for QueryEditorPanel {
 impl QueryEditorPanel {
     fn render_bottom_body(&self, cx: &mut Context<Self>) -> AnyElement {
         match self.bottom_tab {
-            BottomTab::Results => div()
-                .flex_1()
-                .min_h(px(0.0))
-                .child(render_row_table(&self.result, cx))
-                .into_any_element(),
-            BottomTab::Messages => self.render_messages(cx),
+            BottomTab::Results => {
+                let table = div()
+                    .flex_1()
+                    .min_h(px(0.0))
+                    .child(render_row_table(&self.result, cx));
+                match &self.script {
+                    Some(script) => {
+                        let entity = cx.entity();
+                        let on_select: Rc<dyn Fn(usize, &mut Window, &mut App)> =
+                            Rc::new(move |ix, _, cx| {
+                                entity.update(cx, |panel, cx| panel.show_script_statement(ix, cx));
+                            });
+                        v_flex()
+                            .flex_1()
+                            .min_h(px(0.0))
+                            .child(statement_tab_strip(
+                                "sqlite-stmt",
+                                &script.tabs(),
+                                script.active,
+                                on_select,
+                                cx,
+                            ))
+                            .child(table)
+                            .into_any_element()
+                    }
+                    None => table.into_any_element(),
+                }
+            }
+            BottomTab::Messages => match &self.script {
+                Some(script) if !matches!(self.status, QueryStatus::Running) => {
+                    statement_message_list(
+                        "sqlite-stmt-messages",
+                        &script.tabs(),
+                        script.stop_note(),
+                        cx,
+                    )
+                    .into_any_element()
+                }
+                _ => self.render_messages(cx),
+            },
             BottomTab::Explain => self.render_explain(cx),
         }
     }
@@ -401,17 +926,26 @@ impl QueryEditorPanel {
                 .p_3()
                 .child(query_error_card(
                     "sqlite-query-error-card",
-                    full.clone().into(),
+                    full.display_message().into(),
                     cx,
                 ))
                 .into_any_element(),
-            QueryStatus::Done { rows, elapsed_ms } => div()
+            QueryStatus::Done {
+                rows,
+                elapsed_ms,
+                truncated_at,
+            } => div()
                 .flex_1()
                 .min_h(px(0.0))
                 .p_3()
                 .text_xs()
                 .text_color(muted)
-                .child(format!("Query OK · {rows} rows · {elapsed_ms} ms"))
+                .child(match truncated_at {
+                    Some(n) => {
+                        format!("Query OK · {rows} rows (truncated at {n} rows) · {elapsed_ms} ms")
+                    }
+                    None => format!("Query OK · {rows} rows · {elapsed_ms} ms"),
+                })
                 .into_any_element(),
             QueryStatus::Running => div()
                 .flex_1()
@@ -421,6 +955,14 @@ impl QueryEditorPanel {
                 .text_color(muted)
                 .child("Running…")
                 .into_any_element(),
+            QueryStatus::Cancelled => div()
+                .flex_1()
+                .min_h(px(0.0))
+                .p_3()
+                .text_xs()
+                .text_color(muted)
+                .child("Query cancelled.")
+                .into_any_element(),
             QueryStatus::Idle => div()
                 .flex_1()
                 .min_h(px(0.0))
@@ -475,12 +1017,18 @@ fn query_status_display(status: &QueryStatus) -> QueryStatusDisplay {
     match status {
         QueryStatus::Idle => QueryStatusDisplay::Idle,
         QueryStatus::Running => QueryStatusDisplay::Running,
-        QueryStatus::Done { rows, elapsed_ms } => QueryStatusDisplay::Done {
+        QueryStatus::Done {
+            rows,
+            elapsed_ms,
+            truncated_at,
+        } => QueryStatusDisplay::Done {
             rows: *rows,
             affected: None,
             elapsed_ms: *elapsed_ms,
+            truncated_at: *truncated_at,
         },
-        QueryStatus::Error(e) => QueryStatusDisplay::Error(e.clone().into()),
+        QueryStatus::Cancelled => QueryStatusDisplay::Cancelled,
+        QueryStatus::Error(e) => QueryStatusDisplay::Error(e.clone()),
     }
 }
 
@@ -502,22 +1050,22 @@ impl Render for QueryEditorPanel {
         let var_map = cx.global::<ProjectVars>().vars.clone();
         let mono_font = cx.theme().mono_font_family.clone();
 
-        let (export_headers, export_rows) = {
-            let st = self.result.read(cx);
-            let d = st.delegate();
-            let h = d
-                .columns
-                .iter()
-                .map(|c| c.key.to_string())
-                .collect::<Vec<_>>();
-            let r = d
-                .rows
-                .iter()
-                .map(|row| row.iter().map(|c| c.to_string()).collect())
-                .collect::<Vec<Vec<String>>>();
-            (h, r)
-        };
+        let (export_headers, export_rows) = self.result.read(cx).delegate().export_snapshot();
         let export_popover = export_popover("sqlite-qe", export_headers, export_rows);
+        let running = matches!(self.status, QueryStatus::Running);
+        let cancel_requested = self.cancel_requested;
+        let can_continue = !running && self.script.as_ref().is_some_and(|s| s.resume_at.is_some());
+        let in_transaction = self.tx.is_some();
+        let can_save = can_save_query(cx);
+        let policy = policy_summary(&connection_policy(&self.conn_id, cx));
+        let can_fetch_more = self.last_query.is_some()
+            && matches!(
+                self.status,
+                QueryStatus::Done {
+                    truncated_at: Some(_),
+                    ..
+                }
+            );
 
         let read_only = cx
             .try_global::<WorkspaceRef>()
@@ -542,13 +1090,86 @@ impl Render for QueryEditorPanel {
                     .child(shortcut_run_kbd_in_primary_button(cx))
                     .on_click(cx.listener(|panel, _, window, cx| panel.run_query(window, cx))),
             )
+            .child(
+                Button::new("sqlite-run-script")
+                    .ghost()
+                    .small()
+                    .label("Run script")
+                    .disabled(running)
+                    .on_click(cx.listener(|panel, _, window, cx| panel.run_script(window, cx))),
+            )
+            .when(can_continue, |toolbar| {
+                toolbar.child(
+                    Button::new("sqlite-continue-script")
+                        .ghost()
+                        .small()
+                        .icon(IconName::ChevronRight)
+                        .label("Continue")
+                        .on_click(cx.listener(|panel, _, _, cx| panel.continue_script(cx))),
+                )
+            })
+            .when(running, |toolbar| {
+                toolbar.child(
+                    Button::new("sqlite-cancel")
+                        .ghost()
+                        .small()
+                        .icon(IconName::CircleX)
+                        .label("Cancel")
+                        .disabled(cancel_requested)
+                        .on_click(cx.listener(|panel, _, _, cx| panel.cancel(cx))),
+                )
+            })
+            .when(!in_transaction, |toolbar| {
+                toolbar.child(
+                    Button::new("sqlite-begin")
+                        .ghost()
+                        .small()
+                        .label("Transaction")
+                        .disabled(running)
+                        .on_click(cx.listener(|panel, _, _, cx| panel.begin_transaction(cx))),
+                )
+            })
+            .when(in_transaction, |toolbar| {
+                toolbar
+                    .child(metadata_pill("transaction", "open", cx))
+                    .child(
+                        Button::new("sqlite-commit")
+                            .ghost()
+                            .small()
+                            .icon(IconName::Check)
+                            .label("Commit")
+                            .disabled(running)
+                            .on_click(
+                                cx.listener(|panel, _, _, cx| panel.end_transaction(true, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("sqlite-rollback")
+                            .ghost()
+                            .small()
+                            .label("Rollback")
+                            .disabled(running)
+                            .on_click(
+                                cx.listener(|panel, _, _, cx| panel.end_transaction(false, cx)),
+                            ),
+                    )
+            })
             .child(
                 Button::new("sqlite-explain")
                     .ghost()
                     .small()
                     .label("Explain")
                     .on_click(cx.listener(|panel, _, _, cx| panel.switch_to_explain(cx))),
-            );
+            )
+            .when(can_save, |toolbar| {
+                toolbar.child(
+                    Button::new("sqlite-save")
+                        .ghost()
+                        .small()
+                        .label("Save")
+                        .on_click(cx.listener(|panel, _, window, cx| panel.save(window, cx))),
+                )
+            });
         if read_only {
             toolbar = toolbar
                 .child(metadata_pill("access", "Read-only", cx))
@@ -559,6 +1180,9 @@ impl Render for QueryEditorPanel {
                         .child("Writes will fail on this connection"),
                 );
         }
+        if let Some(summary) = policy {
+            toolbar = toolbar.child(metadata_pill("policy", summary, cx));
+        }
         toolbar = toolbar
             .child(query_panel_extras::variables_popover(
                 "sqlite-vars-popover",
@@ -568,6 +1192,15 @@ impl Render for QueryEditorPanel {
                 cx,
             ))
             .child(export_popover)
+            .when(can_fetch_more, |toolbar| {
+                toolbar.child(
+                    Button::new("sqlite-fetch-more")
+                        .ghost()
+                        .small()
+                        .label("Fetch more")
+                        .on_click(cx.listener(|panel, _, _, cx| panel.fetch_more(cx))),
+                )
+            })
             .child(div().flex_1())
             .child(render_status_cluster(&self.status, cx));
 
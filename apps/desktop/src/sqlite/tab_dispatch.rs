@@ -41,7 +41,12 @@ pub fn build_panel(
             Some(Arc::new(panel))
         }
         TabSpec::QueryEditor {
-            init: QueryEditorInit::Sql { sql, auto_run },
+            init:
+                QueryEditorInit::Sql {
+                    sql,
+                    auto_run,
+                    query_path,
+                },
             ..
         } => {
             let label = tab_label_for_spec(spec, false);
@@ -55,7 +60,10 @@ pub fn build_panel(
                     cx,
                 )
             });
-            panel.update(cx, |p, _| p.tab_label = label);
+            panel.update(cx, |p, _| {
+                p.tab_label = label;
+                p.query_path = query_path.clone();
+            });
             Some(Arc::new(panel))
         }
         TabSpec::QueryEditor {
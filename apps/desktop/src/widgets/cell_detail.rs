@@ -1,5 +1,6 @@
 //! Floating cell inspector: column, inferred type label, monospace body.
 
+use based_core::Value;
 use gpui::{Context, IntoElement, MouseButton, Render, Window, div, prelude::*, px};
 use gpui_component::{ActiveTheme, h_flex, scroll::ScrollableElement, v_flex};
 
@@ -15,6 +16,11 @@ pub enum CellValue {
     Json(String), // raw JSON string — will be pretty-printed
     Null,
     Blob(usize), // byte count only
+    /// Decimal, temporal, and UUID values: exact display text plus the engine type label.
+    Typed {
+        label: &'static str,
+        text: String,
+    },
 }
 
 impl CellValue {
@@ -27,6 +33,7 @@ impl CellValue {
             Self::Json(_) => "JSON",
             Self::Null => "NULL",
             Self::Blob(_) => "BLOB",
+            Self::Typed { label, .. } => label,
         }
     }
 
@@ -39,6 +46,25 @@ impl CellValue {
             Self::Json(s) => pretty_json(s),
             Self::Null => "NULL".to_string(),
             Self::Blob(n) => format!("<{n} bytes>"),
+            Self::Typed { text, .. } => text.clone(),
+        }
+    }
+}
+
+impl From<&Value> for CellValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(b) => Self::Boolean(*b),
+            Value::Int(n) => Self::Integer(*n),
+            Value::Float(f) => Self::Float(*f),
+            Value::Text(s) => Self::Text(s.clone()),
+            Value::Bytes(b) => Self::Blob(b.len()),
+            Value::Json(_) | Value::Array(_) => Self::Json(value.to_json().to_string()),
+            other => Self::Typed {
+                label: other.type_label(),
+                text: other.to_string(),
+            },
         }
     }
 }
@@ -234,6 +260,21 @@ mod tests {
         assert_eq!(d, "<1024 bytes>");
     }
 
+    #[test]
+    fn typed_values_keep_exact_text_and_label() {
+        let v = CellValue::from(&Value::Decimal("12345678901234567890.5".into()));
+        assert_eq!(v.type_label(), "DECIMAL");
+        assert_eq!(v.display(), "12345678901234567890.5");
+        assert!(matches!(
+            CellValue::from(&Value::Bytes(vec![0; 4])),
+            CellValue::Blob(4)
+        ));
+        assert!(matches!(
+            CellValue::from(&Value::Int(7)),
+            CellValue::Integer(7)
+        ));
+    }
+
     #[test]
     fn meta_text_column_keeps_string_numbers() {
         let meta = GridColumnMeta {
@@ -2,11 +2,11 @@
 // and save them to a user-chosen path via an rfd save-file dialog.
 
 use anyhow::Context as _;
+use based_core::Value;
 use gpui::AsyncApp;
 use rust_xlsxwriter::{Format, Workbook};
 
 use crate::db;
-use crate::widgets::virtual_table::NULL_CELL_DISPLAY;
 
 use std::fs;
 use std::path::PathBuf;
@@ -17,20 +17,17 @@ use tokio::task::spawn_blocking;
 
 /// Serialise tabular data to CSV bytes.
 ///
-/// Cells that display as `"NULL"` (or are empty) are written as empty fields
-/// so spreadsheet tools don't receive the literal string "NULL".
-pub fn to_csv(headers: &[String], rows: &[Vec<String>]) -> anyhow::Result<Vec<u8>> {
+/// NULL cells are written as empty fields so spreadsheet tools don't receive
+/// the literal string "NULL".
+pub fn to_csv(headers: &[String], rows: &[Vec<Value>]) -> anyhow::Result<Vec<u8>> {
     let mut wtr = csv::Writer::from_writer(vec![]);
     wtr.write_record(headers)?;
     for row in rows {
-        let cells: Vec<&str> = row
+        let cells: Vec<String> = row
             .iter()
-            .map(|c| {
-                if c.is_empty() || c == NULL_CELL_DISPLAY {
-                    ""
-                } else {
-                    c.as_str()
-                }
+            .map(|c| match c {
+                Value::Null => String::new(),
+                other => other.to_string(),
             })
             .collect();
         wtr.write_record(&cells)?;
@@ -40,9 +37,9 @@ pub fn to_csv(headers: &[String], rows: &[Vec<String>]) -> anyhow::Result<Vec<u8
 
 /// Serialise tabular data to an `.xlsx` workbook buffer.
 ///
-/// Row 0 is a bold header row; data starts at row 1. NULL cells are written
-/// as empty strings.
-pub fn to_xlsx(headers: &[String], rows: &[Vec<String>]) -> anyhow::Result<Vec<u8>> {
+/// Row 0 is a bold header row; data starts at row 1. Integers, floats, and
+/// booleans are written as native cells; NULL cells are left blank.
+pub fn to_xlsx(headers: &[String], rows: &[Vec<Value>]) -> anyhow::Result<Vec<u8>> {
     let mut workbook = Workbook::new();
     let sheet = workbook.add_worksheet();
 
@@ -54,15 +51,18 @@ pub fn to_xlsx(headers: &[String], rows: &[Vec<String>]) -> anyhow::Result<Vec<u
     }
 
     for (row_ix, row) in rows.iter().enumerate() {
+        let r = row_ix as u32 + 1;
         for (col_ix, cell) in row.iter().enumerate() {
-            let val = if cell.is_empty() || cell == NULL_CELL_DISPLAY {
-                ""
-            } else {
-                cell.as_str()
-            };
-            sheet
-                .write(row_ix as u32 + 1, col_ix as u16, val)
-                .context("write cell")?;
+            let c = col_ix as u16;
+            match cell {
+                Value::Null => continue,
+                // xlsx numbers are f64; larger integers would lose digits.
+                Value::Int(n) if n.unsigned_abs() < (1 << 53) => sheet.write(r, c, *n as f64),
+                Value::Float(f) if f.is_finite() => sheet.write(r, c, *f),
+                Value::Bool(b) => sheet.write(r, c, *b),
+                other => sheet.write(r, c, other.to_string()),
+            }
+            .context("write cell")?;
         }
     }
 
@@ -71,20 +71,18 @@ pub fn to_xlsx(headers: &[String], rows: &[Vec<String>]) -> anyhow::Result<Vec<u
 
 /// Serialise tabular data to a pretty-printed JSON array of objects.
 ///
-/// Cells that display as `"NULL"` (or are empty) are written as
-/// `null` values rather than the string `"NULL"`.
-pub fn to_json(headers: &[String], rows: &[Vec<String>]) -> String {
+/// Numbers, booleans, JSON documents, and arrays keep their JSON types;
+/// NULL cells become `null`.
+pub fn to_json(headers: &[String], rows: &[Vec<Value>]) -> String {
     let arr: Vec<serde_json::Value> = rows
         .iter()
         .map(|row| {
             let mut obj = serde_json::Map::new();
             for (col_ix, header) in headers.iter().enumerate() {
-                let val = match row.get(col_ix) {
-                    Some(c) if !c.is_empty() && c != NULL_CELL_DISPLAY => {
-                        serde_json::Value::String(c.clone())
-                    }
-                    _ => serde_json::Value::Null,
-                };
+                let val = row
+                    .get(col_ix)
+                    .map(Value::to_json)
+                    .unwrap_or(serde_json::Value::Null);
                 obj.insert(header.clone(), val);
             }
             serde_json::Value::Object(obj)
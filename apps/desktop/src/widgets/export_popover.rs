@@ -1,5 +1,6 @@
 //! Export popover — CSV / Excel download trigger for any tabular data view.
 
+use based_core::Value;
 use gpui::{prelude::*, *};
 use gpui_component::{
     Sizable,
@@ -18,7 +19,7 @@ use crate::workspace::notify::push_export_success;
 pub fn export_popover(
     id_prefix: &'static str,
     headers: Vec<String>,
-    rows: Vec<Vec<String>>,
+    rows: Vec<Vec<Value>>,
 ) -> impl IntoElement {
     let (h, r) = (headers.clone(), rows.clone());
     let (h2, r2) = (headers, rows);
@@ -1,5 +1,6 @@
 //! Query status widgets — toolbar indicator and error card for SQL query panels.
 
+use based_core::{QueryErrorCategory, QueryErrorDetail};
 use gpui::{prelude::*, *};
 use gpui_component::{
     ActiveTheme, Icon, IconName, Sizable,
@@ -23,8 +24,13 @@ pub enum QueryStatusDisplay {
         /// Present for engines that report affected-row counts (e.g. Postgres DML).
         affected: Option<u64>,
         elapsed_ms: u64,
+        /// Row cap that stopped the fetch early, if any.
+        truncated_at: Option<u64>,
     },
-    Error(SharedString),
+    /// Stopped by the user; not an error.
+    Cancelled,
+    /// Categorized so a timeout reads differently from a failure.
+    Error(QueryErrorDetail),
 }
 
 /// Right-aligned status cluster shown at the end of a query-editor toolbar.
@@ -49,6 +55,16 @@ pub fn query_status_indicator(status: &QueryStatusDisplay, cx: &mut App) -> AnyE
             .child(Spinner::new().xsmall().color(cx.theme().primary))
             .child(div().text_xs().text_color(muted).child("Running"))
             .into_any_element(),
+        QueryStatusDisplay::Cancelled => h_flex()
+            .gap(px(6.0))
+            .items_center()
+            .child(
+                Icon::new(IconName::CircleX)
+                    .text_color(cx.theme().warning_foreground)
+                    .xsmall(),
+            )
+            .child(div().text_xs().text_color(muted).child("Cancelled"))
+            .into_any_element(),
         QueryStatusDisplay::Done { .. } => {
             let success = cx.theme().success_foreground;
             h_flex()
@@ -61,8 +77,12 @@ pub fn query_status_indicator(status: &QueryStatusDisplay, cx: &mut App) -> AnyE
                 )
                 .into_any_element()
         }
-        QueryStatusDisplay::Error(_) => {
+        QueryStatusDisplay::Error(detail) => {
             let danger = cx.theme().danger_foreground;
+            let label = match detail.category {
+                QueryErrorCategory::Timeout => "Timed out",
+                QueryErrorCategory::Failed => "Failed",
+            };
             h_flex()
                 .gap(px(6.0))
                 .items_center()
@@ -76,7 +96,7 @@ pub fn query_status_indicator(status: &QueryStatusDisplay, cx: &mut App) -> AnyE
                         .text_xs()
                         .font_weight(FontWeight::SEMIBOLD)
                         .text_color(danger)
-                        .child("Failed"),
+                        .child(label),
                 )
                 .into_any_element()
         }
@@ -93,12 +113,16 @@ pub fn tab_breadcrumb_query_trailing(
             rows,
             affected,
             elapsed_ms,
+            truncated_at,
         } => {
             let mut row = h_flex()
                 .flex_shrink_0()
                 .items_center()
                 .gap(px(6.0))
                 .child(metadata_pill("rows", rows.to_string(), cx));
+            if let Some(n) = truncated_at {
+                row = row.child(metadata_pill("limit", format!("truncated at {n} rows"), cx));
+            }
             if let Some(n) = affected {
                 row = row.child(metadata_pill("affected", n.to_string(), cx));
             }
@@ -1,13 +1,18 @@
 //! Bottom tab strip used by the Postgres/SQLite query editors to switch between
-//! the result table, status messages, and the EXPLAIN plan inside a single tab.
+//! the result table, status messages, and the EXPLAIN plan inside a single tab,
+//! plus the per-statement strip and message list of a "Run script" run.
 
 use std::rc::Rc;
 
-use gpui::{App, ElementId, IntoElement, ParentElement, Styled, Window, div, prelude::*, px};
+use gpui::{
+    App, ElementId, IntoElement, ParentElement, SharedString, Styled, Window, div, prelude::*, px,
+};
 use gpui_component::{
     ActiveTheme, Selectable as _, Sizable as _,
     button::{Button, ButtonVariants},
     h_flex,
+    scroll::ScrollableElement as _,
+    v_flex,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -89,3 +94,120 @@ pub fn result_tab_strip(
 
     row
 }
+
+/// How one statement of a script run ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatementTabState {
+    Ok,
+    Failed,
+    Cancelled,
+    /// Not reached: an earlier statement failed or the run was stopped.
+    NotRun,
+}
+
+/// One entry of the statement strip / message list.
+#[derive(Clone, Debug)]
+pub struct StatementTab {
+    /// Short label such as `"3 · INSERT"`.
+    pub label: SharedString,
+    pub state: StatementTabState,
+    /// One-line outcome (`"2 rows · 4 ms"`, the error message, …).
+    pub summary: SharedString,
+}
+
+/// Render one ghost button per statement; failed statements get a danger dot and
+/// statements that never ran are dimmed.
+pub fn statement_tab_strip(
+    id_prefix: &'static str,
+    tabs: &[StatementTab],
+    active: usize,
+    on_select: Rc<dyn Fn(usize, &mut Window, &mut App)>,
+    cx: &App,
+) -> impl IntoElement + use<> {
+    let theme = cx.theme();
+    let danger = theme.danger;
+
+    let strip_id: ElementId = format!("{id_prefix}-strip").into();
+    let mut row = h_flex()
+        .id(strip_id)
+        .gap(px(2.0))
+        .px(px(6.0))
+        .py(px(2.0))
+        .items_center()
+        .overflow_x_scrollbar()
+        .border_b_1()
+        .border_color(theme.border.opacity(0.72));
+
+    for (ix, tab) in tabs.iter().enumerate() {
+        let cb = on_select.clone();
+        let id: ElementId = format!("{id_prefix}-{ix}").into();
+        let button = Button::new(id)
+            .ghost()
+            .small()
+            .label(tab.label.clone())
+            .selected(active == ix)
+            .disabled(tab.state == StatementTabState::NotRun)
+            .on_click(move |_, window, cx| cb(ix, window, cx));
+
+        row = row
+            .child(button)
+            .when(tab.state == StatementTabState::Failed, |row| {
+                row.child(
+                    div()
+                        .w(px(6.0))
+                        .h(px(6.0))
+                        .rounded_full()
+                        .bg(danger)
+                        .ml(px(-2.0)),
+                )
+            });
+    }
+
+    row
+}
+
+/// Messages-tab body for a script run: one line per statement with its outcome.
+pub fn statement_message_list(
+    id: &'static str,
+    tabs: &[StatementTab],
+    footer: Option<SharedString>,
+    cx: &App,
+) -> impl IntoElement + use<> {
+    let theme = cx.theme();
+    let muted = theme.muted_foreground;
+    let danger = theme.danger;
+    let mono = theme.mono_font_family.clone();
+
+    let mut list = v_flex()
+        .id(id)
+        .flex_1()
+        .min_h(px(0.0))
+        .overflow_y_scrollbar()
+        .p_3()
+        .gap(px(4.0))
+        .text_xs();
+
+    for tab in tabs {
+        let color = match tab.state {
+            StatementTabState::Failed => danger,
+            _ => muted,
+        };
+        list = list.child(
+            h_flex()
+                .gap(px(8.0))
+                .items_start()
+                .child(
+                    div()
+                        .flex_none()
+                        .font_family(mono.clone())
+                        .text_color(theme.foreground)
+                        .child(tab.label.clone()),
+                )
+                .child(div().flex_1().text_color(color).child(tab.summary.clone())),
+        );
+    }
+
+    list.when_some(footer, |list, footer| {
+        list.child(div().pt_2().text_color(muted).child(footer))
+    })
+}
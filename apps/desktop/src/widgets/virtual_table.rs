@@ -2,6 +2,9 @@
 // The DataTable widget in gpui-component already virtualizes rows internally,
 // so this is a thin wrapper / type alias for the RowDelegate-based table.
 
+use std::mem;
+
+use based_core::Value;
 use gpui::{prelude::*, *};
 use gpui_component::table::{Column, ColumnSort, TableDelegate, TableState};
 
@@ -18,12 +21,23 @@ pub fn data_column(key: impl Into<SharedString>, label: impl Into<SharedString>)
     Column::new(key, label).sortable().resizable(true)
 }
 
+/// Display text for a typed cell in the grid (blobs show their size, not hex).
+pub fn value_display(value: &Value) -> SharedString {
+    match value {
+        Value::Null => NULL_CELL_DISPLAY.into(),
+        Value::Bytes(b) => format!("<{} bytes>", b.len()).into(),
+        other => other.to_string().into(),
+    }
+}
+
 /// Generic row data: column names + string-valued cells.
 #[derive(Default)]
 pub struct RowDelegate {
     pub columns: Vec<Column>,
     pub column_meta: Vec<GridColumnMeta>,
     pub rows: Vec<Vec<SharedString>>,
+    /// Typed cells parallel to `rows`; empty when the source only produced display text.
+    pub values: Vec<Vec<Value>>,
     pub sort_col: Option<usize>,
     pub sort_asc: bool,
 }
@@ -96,12 +110,10 @@ impl TableDelegate for RowDelegate {
         reorder_column_meta(&mut self.column_meta, col_ix, to_ix);
 
         for row in &mut self.rows {
-            if col_ix >= row.len() {
-                continue;
-            }
-            let cell = row.remove(col_ix);
-            let insert_at = insert_at.min(row.len());
-            row.insert(insert_at, cell);
+            move_cell(row, col_ix, insert_at);
+        }
+        for row in &mut self.values {
+            move_cell(row, col_ix, insert_at);
         }
 
         if let Some(sort_col) = self.sort_col {
@@ -129,13 +141,68 @@ impl TableDelegate for RowDelegate {
         let asc = self.sort_asc;
         let meta = self.column_meta.get(col_ix).cloned().unwrap_or_default();
         let kind = column_value_kind(meta.data_type.as_deref());
-        self.rows.sort_by(|a, b| {
-            let ord = compare_cells(kind, a[col_ix].as_ref(), b[col_ix].as_ref());
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        order.sort_by(|&a, &b| {
+            let ord = compare_cells(
+                kind,
+                self.rows[a][col_ix].as_ref(),
+                self.rows[b][col_ix].as_ref(),
+            );
             if asc { ord } else { ord.reverse() }
         });
+        self.rows = permute(mem::take(&mut self.rows), &order);
+        if self.values.len() == order.len() {
+            self.values = permute(mem::take(&mut self.values), &order);
+        }
+    }
+}
+
+impl RowDelegate {
+    /// Typed cell at `(row_ix, col_ix)` when the grid was filled from typed values.
+    pub fn value(&self, row_ix: usize, col_ix: usize) -> Option<&Value> {
+        self.values.get(row_ix)?.get(col_ix)
+    }
+
+    /// Column keys and typed rows for export. Text-only grids map each display
+    /// cell to [`Value::Text`] (or [`Value::Null`] for the NULL placeholder).
+    pub fn export_snapshot(&self) -> (Vec<String>, Vec<Vec<Value>>) {
+        let headers = self.columns.iter().map(|c| c.key.to_string()).collect();
+        if self.values.len() == self.rows.len() {
+            return (headers, self.values.clone());
+        }
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|c| {
+                        if c.is_empty() || c.as_ref() == NULL_CELL_DISPLAY {
+                            Value::Null
+                        } else {
+                            Value::Text(c.to_string())
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        (headers, rows)
     }
 }
 
+fn move_cell<T>(row: &mut Vec<T>, col_ix: usize, insert_at: usize) {
+    if col_ix >= row.len() {
+        return;
+    }
+    let cell = row.remove(col_ix);
+    let insert_at = insert_at.min(row.len());
+    row.insert(insert_at, cell);
+}
+
+fn permute<T>(items: Vec<T>, order: &[usize]) -> Vec<T> {
+    let mut slots: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    order.iter().filter_map(|&i| slots[i].take()).collect()
+}
+
 pub type VirtualTable = Entity<TableState<RowDelegate>>;
 
 /// Replace delegate data and rebuild gpui-component column layout.
@@ -161,11 +228,29 @@ pub fn replace_table_data(
         empty_column_meta(delegate.columns.len())
     };
     delegate.rows = rows;
+    delegate.values.clear();
     delegate.sort_col = None;
     state.refresh(cx);
     cx.notify();
 }
 
+/// Like [`replace_table_data`], but keeps the typed cells for export and the
+/// cell inspector alongside their display text.
+pub fn replace_table_values(
+    state: &mut TableState<RowDelegate>,
+    columns: Vec<Column>,
+    values: Vec<Vec<Value>>,
+    column_meta: Vec<GridColumnMeta>,
+    cx: &mut Context<TableState<RowDelegate>>,
+) {
+    let rows = values
+        .iter()
+        .map(|row| row.iter().map(value_display).collect())
+        .collect();
+    replace_table_data(state, columns, rows, column_meta, cx);
+    state.delegate_mut().values = values;
+}
+
 /// Replace row data when columns are unchanged.
 pub fn replace_table_rows(
     state: &mut TableState<RowDelegate>,
@@ -173,6 +258,7 @@ pub fn replace_table_rows(
     cx: &mut Context<TableState<RowDelegate>>,
 ) {
     state.delegate_mut().rows = rows;
+    state.delegate_mut().values.clear();
     state.delegate_mut().sort_col = None;
     cx.notify();
 }
@@ -3,11 +3,17 @@
 use std::slice;
 use std::sync::Arc;
 
-use gpui::{App, Context, Entity, EntityId, Focusable, Window, prelude::*};
-use gpui_component::dock::{Panel, PanelView};
+use gpui::{App, Context, Entity, EntityId, Focusable, SharedString, Window, prelude::*};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    dock::{Panel, PanelView},
+};
 
 use super::Workspace;
 use crate::connection::ConnectionId;
+use crate::editor::open_transactions::{has_open_transaction, rollback_warning};
 use crate::workspace::panels::ConnectionWizardPanel;
 use crate::workspace::wizard_logic::can_edit_saved_connection;
 
@@ -236,15 +242,85 @@ impl Workspace {
         if !self.can_close_center_panel(panel_id, cx) {
             return;
         }
-        let Some(panel) = self.find_center_panel(panel_id, cx) else {
+        self.close_center_panels_confirmed(vec![panel_id], window, cx);
+    }
+
+    /// Close `panel_ids`, asking first when any of them holds an open manual transaction.
+    pub(crate) fn close_center_panels_confirmed(
+        &mut self,
+        panel_ids: Vec<EntityId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let open = panel_ids
+            .iter()
+            .filter(|id| has_open_transaction(**id, cx))
+            .count();
+        if open == 0 {
+            self.remove_center_panels(&panel_ids, window, cx);
+            return;
+        }
+        if window.has_active_dialog(cx) {
             return;
+        }
+        let title = if panel_ids.len() == 1 {
+            "Close tab with an open transaction?"
+        } else {
+            "Close tabs with open transactions?"
         };
-        self.dock_area.update(cx, |dock, ecx| {
-            remove_presentation_panel(dock, &panel, window, ecx);
+        let description: SharedString = rollback_warning(open).into();
+        let workspace = cx.entity().downgrade();
+        window.open_alert_dialog(cx, move |alert, _window, cx| {
+            let close_btn = Button::new("close-tab-transaction-confirm")
+                .label("Close")
+                .primary()
+                .bg(cx.theme().red)
+                .border_color(cx.theme().red)
+                .text_color(cx.theme().primary_foreground);
+            let workspace = workspace.clone();
+            let panel_ids = panel_ids.clone();
+            alert
+                .title(title)
+                .description(description.clone())
+                .footer(
+                    DialogFooter::new()
+                        .child(
+                            DialogClose::new().child(
+                                Button::new("close-tab-transaction-cancel")
+                                    .outline()
+                                    .label("Cancel"),
+                            ),
+                        )
+                        .child(DialogAction::new().child(close_btn)),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = workspace.update(cx, |ws, cx| {
+                        ws.remove_center_panels(&panel_ids, window, cx);
+                    });
+                    true
+                })
+                .on_cancel(|_, _, _| true)
         });
-        self.unregister_center_panel(panel_id);
+    }
+
+    fn remove_center_panels(
+        &mut self,
+        panel_ids: &[EntityId],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        for &panel_id in panel_ids {
+            let Some(panel) = self.find_center_panel(panel_id, cx) else {
+                continue;
+            };
+            self.dock_area.update(cx, |dock, ecx| {
+                remove_presentation_panel(dock, &panel, window, ecx);
+            });
+            self.unregister_center_panel(panel_id);
+        }
         self.sync_tab_manager_from_dock(cx);
         self.ensure_home_tab(window, cx);
+        cx.notify();
     }
 
     /// Close the active center tab (⌘W / CloseTab).
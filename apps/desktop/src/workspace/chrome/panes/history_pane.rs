@@ -84,6 +84,7 @@ pub fn render_history_pane(
                         init: QueryEditorInit::Sql {
                             sql: Some(full_query.clone()),
                             auto_run: false,
+                            query_path: None,
                         },
                     },
                     cx,
@@ -2,4 +2,5 @@
 //! shared chrome (320 px column + header) comes from `chrome::side_pane`.
 
 pub mod history_pane;
+pub mod problems_pane;
 pub mod saved_pane;
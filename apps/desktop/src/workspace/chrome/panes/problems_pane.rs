@@ -0,0 +1,80 @@
+//! Problems found while loading `.based/`: parse errors, unknown keys, bad targets,
+//! duplicate ids, dangling connection references, and unset `.env.example` keys.
+
+use std::path::Path;
+
+use based_project::{Diagnostic, Severity};
+use gpui::{
+    AnyElement, App, IntoElement, MouseButton, ParentElement, SharedString, Styled, div, prelude::*,
+};
+use gpui_component::{ActiveTheme, Icon, IconName, Sizable as _, h_flex, v_flex};
+
+use crate::project::ProjectContext;
+use crate::widgets::empty_state::pane_empty_hint;
+use crate::workspace::notify::open_path;
+
+pub fn render_problems_pane(cx: &mut App) -> AnyElement {
+    let Some(pctx) = cx.try_global::<ProjectContext>() else {
+        return pane_empty_hint("Open a project to check its .based/ files.", cx)
+            .into_any_element();
+    };
+    if pctx.diagnostics.is_empty() {
+        return pane_empty_hint("No problems in .based/.", cx).into_any_element();
+    }
+
+    let border = cx.theme().border;
+    let muted = cx.theme().muted_foreground;
+    let fg = cx.theme().foreground;
+    let (error, warning) = (cx.theme().red, cx.theme().warning);
+
+    let mut diagnostics = pctx.diagnostics.clone();
+    diagnostics.sort_by(|a, b| a.severity.cmp(&b.severity));
+    let root = pctx.root.clone();
+
+    v_flex()
+        .id("ws-problems-list")
+        .size_full()
+        .min_h_0()
+        .overflow_y_scroll()
+        .children(diagnostics.into_iter().enumerate().map(|(i, d)| {
+            let (icon, color) = match d.severity {
+                Severity::Error => (IconName::CircleX, error),
+                Severity::Warning => (IconName::TriangleAlert, warning),
+            };
+            let location: SharedString = location(&d, &root).into();
+            let message: SharedString = d.message.clone().into();
+            let file = d.file.clone();
+            h_flex()
+                .id(SharedString::from(format!("ws-problem-{i}")))
+                .px_3()
+                .py_2()
+                .gap_2()
+                .items_start()
+                .border_b_1()
+                .border_color(border)
+                .cursor_pointer()
+                .child(Icon::new(icon).small().text_color(color))
+                .child(
+                    v_flex()
+                        .min_w_0()
+                        .gap_1()
+                        .child(div().text_xs().text_color(fg).child(message))
+                        .child(div().text_xs().text_color(muted).truncate().child(location)),
+                )
+                .on_mouse_down(MouseButton::Left, move |_, _, _| open_path(&file))
+        }))
+        .into_any_element()
+}
+
+/// `.based/queries/orders.toml:4:2`, relative to the project root when possible.
+fn location(d: &Diagnostic, root: &Path) -> String {
+    let file = d.file.strip_prefix(root).unwrap_or(&d.file);
+    let mut out = file.display().to_string();
+    if let Some(line) = d.line {
+        out.push_str(&format!(":{line}"));
+        if let Some(column) = d.column {
+            out.push_str(&format!(":{column}"));
+        }
+    }
+    out
+}
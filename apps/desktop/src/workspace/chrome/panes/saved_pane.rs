@@ -1,17 +1,28 @@
 //! Workspace-level saved project queries pane.
 
+use based_project::rename_query_file;
 use gpui::{
     AnyElement, App, Entity, FontWeight, IntoElement, MouseButton, ParentElement, SharedString,
-    Styled, div, prelude::*,
+    Styled, Window, div, prelude::*,
+};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::{Button, ButtonVariants},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    h_flex,
+    input::{Input, InputState},
+    menu::{ContextMenuExt, PopupMenuItem},
+    v_flex,
 };
-use gpui_component::{ActiveTheme, h_flex, v_flex};
 
 use crate::connection::ConnectionId;
 use crate::connection::registry::ConnectionRegistry;
 use crate::project::ProjectRoot;
 use crate::query_store::QueryStore;
 use crate::widgets::empty_state::pane_empty_hint;
+use crate::widgets::labeled_field;
 use crate::workspace::Workspace;
+use crate::workspace::notify::{push_error, push_info};
 use crate::workspace::project_query::{OpenQueryResult, open_project_query, target_hint};
 use crate::workspace::tabs::enqueue_open_tab;
 
@@ -52,6 +63,7 @@ pub fn render_saved_pane(
             let sub: SharedString = hint.into();
             let starred = store.is_favorite(&q.path);
             let path = q.path.clone();
+            let menu_path = q.path.clone();
             let query = q.clone();
             let reg = reg.clone();
             let ws = ws.clone();
@@ -110,6 +122,69 @@ pub fn render_saved_pane(
                         OpenQueryResult::Error(msg) => log::warn!("{msg}"),
                     }
                 })
+                .context_menu(move |menu, _, _| {
+                    let path = menu_path.clone();
+                    menu.item(PopupMenuItem::new("Move / rename…").on_click(
+                        move |_, window, cx| {
+                            open_move_query_dialog(path.clone(), window, cx);
+                        },
+                    ))
+                })
         }))
         .into_any_element()
 }
+
+/// Ask for a new path under `.based/queries/` and move the query file there. A starred
+/// query stays starred under its new path.
+fn open_move_query_dialog(from: String, window: &mut Window, cx: &mut App) {
+    let Some(root) = cx.try_global::<ProjectRoot>().map(|p| p.0.clone()) else {
+        return;
+    };
+    let input = cx.new(|cx| InputState::new(window, cx).default_value(from.clone()));
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let muted = cx.theme().muted_foreground;
+        let input = input.clone();
+        let from = from.clone();
+        let root = root.clone();
+        dialog
+            .title("Move query")
+            .child(labeled_field(
+                "Path in .based/queries",
+                muted,
+                Input::new(&input).w_full(),
+            ))
+            .footer(
+                DialogFooter::new()
+                    .child(
+                        DialogClose::new()
+                            .child(Button::new("move-query-cancel").outline().label("Cancel")),
+                    )
+                    .child(
+                        DialogAction::new()
+                            .child(Button::new("move-query-ok").primary().label("Move")),
+                    ),
+            )
+            .on_ok(move |_, _, cx| {
+                let to = input.read(cx).value().trim().trim_matches('/').to_string();
+                if to == from {
+                    return true;
+                }
+                match rename_query_file(&root.join(".based"), &from, &to) {
+                    Ok(path) => {
+                        cx.update_global(|store: &mut QueryStore, _| {
+                            if store.is_favorite(&from) {
+                                store.toggle_favorite(&root, &from);
+                                store.toggle_favorite(&root, &to);
+                            }
+                        });
+                        push_info(cx, format!("Moved query to {}", path.display()));
+                        true
+                    }
+                    Err(e) => {
+                        push_error(cx, "Move query", format!("{e:#}"));
+                        false
+                    }
+                }
+            })
+    });
+}
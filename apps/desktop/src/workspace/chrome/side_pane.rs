@@ -1,6 +1,6 @@
 //! Right-hand workspace pane: enum + shared 320 px column chrome.
 //!
-//! The pane swaps between Inspector / History / Saved based on the activity
+//! The pane swaps between Inspector / History / Saved / Problems based on the activity
 //! rail. Each variant renders its own body; this module supplies the surrounding
 //! border, header strip, and width so every pane looks consistent.
 
@@ -13,16 +13,18 @@ pub enum SidePane {
     Inspector,
     History,
     Saved,
+    Problems,
 }
 
 impl SidePane {
-    pub const ALL: [Self; 3] = [Self::Inspector, Self::History, Self::Saved];
+    pub const ALL: [Self; 4] = [Self::Inspector, Self::History, Self::Saved, Self::Problems];
 
     pub fn label(self) -> &'static str {
         match self {
             Self::Inspector => "INSPECTOR",
             Self::History => "HISTORY",
             Self::Saved => "SAVED",
+            Self::Problems => "PROBLEMS",
         }
     }
 
@@ -31,6 +33,7 @@ impl SidePane {
             Self::Inspector => IconName::Inspector,
             Self::History => IconName::Inbox,
             Self::Saved => IconName::Star,
+            Self::Problems => IconName::TriangleAlert,
         }
     }
 
@@ -39,6 +42,7 @@ impl SidePane {
             Self::Inspector => "Inspector",
             Self::History => "Query history",
             Self::Saved => "Saved queries",
+            Self::Problems => "Project problems",
         }
     }
 }
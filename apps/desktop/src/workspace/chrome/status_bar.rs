@@ -1,3 +1,4 @@
+use based_ssh::TunnelHealth;
 use gpui::{App, Entity, IntoElement, RenderOnce, SharedString, div, prelude::*, px};
 use gpui_component::{
     ActiveTheme, Icon, IconName, Sizable as _,
@@ -8,9 +9,14 @@ use gpui_component::{
 
 use crate::app::updater::UpdateBarSnapshot;
 use crate::app::updater::{self, UpdatePhase};
-use crate::bindings::{ToggleHistoryPane, ToggleInspectorPane, ToggleSavedPane};
+use crate::bindings::{
+    ToggleHistoryPane, ToggleInspectorPane, ToggleProblemsPane, ToggleSavedPane,
+};
 use crate::connection::registry::ConnectionRegistry;
-use crate::connection::{ConnectionEntry, ConnectionId, ConnectionState, EngineKind};
+use crate::connection::{
+    AnyConnection, ConnectionEntry, ConnectionId, ConnectionState, EngineKind,
+};
+use crate::project::ProjectContext;
 use crate::widgets::engine_icon;
 use crate::widgets::status_item::{STATUS_BAR_HEIGHT, status_divider, status_segment, status_text};
 use crate::workspace::ConnectionTree;
@@ -27,6 +33,8 @@ pub struct StatusBarConnection {
     pub is_connected: bool,
     pub state_color: gpui::Hsla,
     pub muted: bool,
+    /// Set when the live connection runs through an SSH tunnel.
+    pub tunnel: Option<TunnelHealth>,
 }
 
 impl StatusBarConnection {
@@ -43,6 +51,12 @@ impl StatusBarConnection {
                 ConnectionState::Failed { .. } => cx.theme().danger_foreground,
             },
             muted,
+            tunnel: match &entry.state {
+                ConnectionState::Connected(AnyConnection::Postgres(conn)) => {
+                    conn.read(cx).tunnel_health()
+                }
+                _ => None,
+            },
         }
     }
 }
@@ -53,6 +67,8 @@ pub struct StatusBarModel {
     pub history_ready: bool,
     pub update: UpdateBarSnapshot,
     pub focused_connection: Option<StatusBarConnection>,
+    /// Query tabs with a manual transaction still open.
+    pub open_transactions: usize,
 }
 
 /// A thin status bar rendered at the bottom of the workspace.
@@ -82,8 +98,14 @@ impl StatusBar {
 
 fn side_pane_button(pane: SidePane, active: Option<SidePane>, cx: &App) -> impl IntoElement {
     let is_active = active == Some(pane);
+    let has_errors = pane == SidePane::Problems
+        && cx
+            .try_global::<ProjectContext>()
+            .is_some_and(|p| p.error_count() > 0);
     let color = if is_active {
         cx.theme().accent_foreground
+    } else if has_errors {
+        cx.theme().red
     } else {
         cx.theme().muted_foreground
     };
@@ -91,11 +113,13 @@ fn side_pane_button(pane: SidePane, active: Option<SidePane>, cx: &App) -> impl
         SidePane::Inspector => "status-inspector",
         SidePane::History => "status-history",
         SidePane::Saved => "status-saved",
+        SidePane::Problems => "status-problems",
     };
     let (action, tooltip_text) = match pane {
         SidePane::Inspector => (&ToggleInspectorPane as &dyn gpui::Action, pane.tooltip()),
         SidePane::History => (&ToggleHistoryPane as &dyn gpui::Action, pane.tooltip()),
         SidePane::Saved => (&ToggleSavedPane as &dyn gpui::Action, pane.tooltip()),
+        SidePane::Problems => (&ToggleProblemsPane as &dyn gpui::Action, pane.tooltip()),
     };
 
     Button::new(id)
@@ -167,6 +191,41 @@ fn connection_chip(
         })
 }
 
+/// "ssh" segment for a connection behind a tunnel, coloured by the tunnel's health.
+fn tunnel_widget(health: &TunnelHealth, cx: &App) -> impl IntoElement {
+    let color = match health {
+        TunnelHealth::Connected => cx.theme().green_light,
+        TunnelHealth::Reconnecting { .. } => cx.theme().warning_foreground,
+        TunnelHealth::Failed(_) => cx.theme().danger_foreground,
+    };
+    let value = match health {
+        TunnelHealth::Reconnecting { attempt } => format!("reconnecting ({attempt})"),
+        _ => health.label().to_string(),
+    };
+    let muted = cx.theme().muted_foreground;
+    status_segment("ssh", value, muted, color, Some(color))
+}
+
+/// Warning-coloured "transaction open" segment; `None` when no transaction is open.
+fn transaction_widget(open: usize, cx: &App) -> Option<impl IntoElement> {
+    if open == 0 {
+        return None;
+    }
+    let warning = cx.theme().warning_foreground;
+    let (label, value) = if open == 1 {
+        ("transaction", SharedString::from("open"))
+    } else {
+        ("transactions", SharedString::from(format!("{open} open")))
+    };
+    Some(status_segment(
+        label,
+        value,
+        cx.theme().muted_foreground,
+        warning,
+        Some(warning),
+    ))
+}
+
 fn update_widget(
     snapshot: &UpdateBarSnapshot,
     registry: gpui::Entity<ConnectionRegistry>,
@@ -298,6 +357,11 @@ impl RenderOnce for StatusBar {
         let update_snapshot = self.model.update.clone();
         let connection_tree = self.connection_tree.clone();
         let focused_connection = self.model.focused_connection.clone();
+        let tunnel = focused_connection
+            .as_ref()
+            .and_then(|chip| chip.tunnel.as_ref())
+            .map(|health| tunnel_widget(health, cx));
+        let open_transactions = self.model.open_transactions;
 
         h_flex()
             .h(px(STATUS_BAR_HEIGHT))
@@ -315,13 +379,17 @@ impl RenderOnce for StatusBar {
                     .min_w_0()
                     .when_some(focused_connection, |row, chip| {
                         row.child(connection_chip(chip, connection_tree, cx))
-                    }),
+                    })
+                    .children(tunnel),
             )
             .child(
                 h_flex()
                     .gap(px(6.0))
                     .items_center()
                     .flex_shrink_0()
+                    .when_some(transaction_widget(open_transactions, cx), |row, widget| {
+                        row.child(widget).child(status_divider(muted))
+                    })
                     .child(status_segment(
                         "history",
                         history_value,
@@ -13,7 +13,7 @@ use gpui_component::{
     ActiveTheme as _, Icon, IconName, Sizable as _, TitleBar,
     button::{Button, ButtonVariants},
     h_flex,
-    menu::{DropdownMenu, PopupMenuItem},
+    menu::{DropdownMenu, PopupMenu, PopupMenuItem},
 };
 
 const GIT_BRANCH_ICON_PATH: &str = "icons/git-branch.svg";
@@ -21,8 +21,10 @@ const GIT_BRANCH_ICON_PATH: &str = "icons/git-branch.svg";
 use crate::app::{prefs, shell};
 use crate::bindings::CycleAppearance;
 use crate::connection::registry::ConnectionRegistry;
-use crate::project::ProjectContext;
+use crate::project::{ProjectContext, ProjectRoot};
 use crate::widgets::{SIDEBAR_INSET, command_shell};
+use crate::workspace::notify::push_error;
+use based_project::persist_active_environment;
 
 /// A `RenderOnce` top bar that renders inside the window's `TitleBar`.
 #[derive(IntoElement)]
@@ -84,6 +86,16 @@ impl RenderOnce for ContextRail {
                     "default".into(),
                 )
             });
+        let environments: Vec<String> = cx
+            .try_global::<ProjectContext>()
+            .map(|ctx| {
+                ctx.snapshot
+                    .environments
+                    .iter()
+                    .map(|e| e.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
 
         let has_project = !project_path.is_empty();
         let project_tooltip = if has_project {
@@ -170,13 +182,9 @@ impl RenderOnce for ContextRail {
                                 .tooltip(SharedString::from("Active environment"))
                                 .dropdown_menu({
                                     let env_item = env.clone();
+                                    let environments = environments.clone();
                                     move |menu, _window, _cx| {
-                                        menu.item(
-                                            PopupMenuItem::new(SharedString::from(
-                                                env_item.clone(),
-                                            ))
-                                            .disabled(true),
-                                        )
+                                        environment_menu(menu, &environments, &env_item)
                                     }
                                 }),
                         )
@@ -185,6 +193,31 @@ impl RenderOnce for ContextRail {
     }
 }
 
+/// Committed `environments/` to switch between; picking one rewrites
+/// `state/active_environment.toml` and the project watcher reloads variables and connections.
+fn environment_menu(menu: PopupMenu, environments: &[String], active: &str) -> PopupMenu {
+    if environments.is_empty() {
+        return menu.item(PopupMenuItem::new("No files in .based/environments").disabled(true));
+    }
+    environments.iter().fold(menu, |menu, name| {
+        let pick = name.clone();
+        let item = PopupMenuItem::new(SharedString::from(name.clone()));
+        let item = if name == active {
+            item.icon(IconName::Check)
+        } else {
+            item
+        };
+        menu.item(item.on_click(move |_, _, cx| {
+            let Some(root) = cx.try_global::<ProjectRoot>().map(|r| r.0.clone()) else {
+                return;
+            };
+            if let Err(e) = persist_active_environment(&root, &pick) {
+                push_error(cx, "Switch environment", format!("{e:#}"));
+            }
+        }))
+    })
+}
+
 /// Title bar center: command palette trigger.
 #[derive(IntoElement)]
 struct TopbarCenter;
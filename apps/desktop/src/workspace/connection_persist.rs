@@ -3,12 +3,14 @@
 use std::path::Path;
 
 use based_project::{
-    ConnectionSpec, EnvOrString, PragmaSettings, ProjectConnection, SshSettings, secret_env_key,
-    slug_from_label, upsert_env_file, write_connection_file,
+    ConnectionPolicy, ConnectionSpec, EnvOrString, PostgresSettings, PostgresSslMode,
+    PragmaSettings, ProjectConnection, SshJumpSettings, SshSettings, TargetSessionAttrs,
+    load_connections_lenient, load_env_file, secret_env_key, slug_from_label, upsert_env_file,
+    write_connection_file,
 };
 
 use crate::connection::ConnectionConfig;
-use crate::postgres::SslMode;
+use crate::postgres::{PgSettings, PgTargetSessionAttrs, SslMode};
 use crate::sqlite::SqlitePragma;
 
 pub fn persist_config_to_based_dir(
@@ -21,32 +23,102 @@ pub fn persist_config_to_based_dir(
         .filter(|id| !id.is_empty())
         .map(str::to_string)
         .unwrap_or_else(|| slug_from_label(config.label()));
-    persist_secret(based_dir, &relative_id, config)?;
-    let conn = project_connection_from_config(config, relative_id, tags);
+    let mut conn = project_connection_from_config(config, relative_id.clone(), tags);
+    carry_over_existing(based_dir, config, &mut conn);
+    persist_secret(based_dir, &relative_id, config, &conn)?;
     write_connection_file(based_dir, &conn)?;
     Ok(conn)
 }
 
+/// When editing, keep what the wizard does not show: the file's `[policy]`, and a secret
+/// still equal to what the existing file (or a connection it inherits from) references
+/// keeps that `{ env = "…" }`, so shared `_defaults.toml` or `extends` references are not
+/// replaced by a per-connection variable.
+fn carry_over_existing(based_dir: &Path, config: &ConnectionConfig, conn: &mut ProjectConnection) {
+    let (existing, _) = load_connections_lenient(based_dir);
+    let Some(existing) = existing.into_iter().find(|c| c.id == conn.id) else {
+        return;
+    };
+    conn.policy = existing.policy.clone();
+    let file_vars = load_env_file(&based_dir.join(".env")).unwrap_or_default();
+    let keep = |new: &mut EnvOrString, old: &EnvOrString, entered: &str| {
+        if matches!(old, EnvOrString::FromEnv { .. })
+            && old.resolve_with(&file_vars).is_ok_and(|v| v == entered)
+        {
+            *new = old.clone();
+        }
+    };
+    match (config, &mut conn.spec, &existing.spec) {
+        (
+            ConnectionConfig::Postgres(c),
+            ConnectionSpec::Postgres { password, .. },
+            ConnectionSpec::Postgres { password: old, .. },
+        ) => keep(password, old, &c.password),
+        (
+            ConnectionConfig::MongoDB(c),
+            ConnectionSpec::MongoDB { url, .. },
+            ConnectionSpec::MongoDB { url: old, .. },
+        ) => keep(url, old, &c.uri),
+        _ => {}
+    }
+    if let ConnectionConfig::Postgres(c) = config
+        && let Some(entered) = c.ssh.as_ref().and_then(|s| s.key_passphrase.as_deref())
+        && let Some(new) = conn.ssh.as_mut().and_then(|s| s.key_passphrase.as_mut())
+        && let Some(old) = existing
+            .ssh
+            .as_ref()
+            .and_then(|s| s.key_passphrase.as_ref())
+    {
+        keep(new, old, entered);
+    }
+    if let ConnectionConfig::Postgres(c) = config
+        && let (Some(entered), Some(new), Some(old)) =
+            (c.ssh.as_ref(), conn.ssh.as_mut(), existing.ssh.as_ref())
+    {
+        for ((hop, new), old) in entered
+            .jump_hosts
+            .iter()
+            .zip(&mut new.jump_hosts)
+            .zip(&old.jump_hosts)
+        {
+            if let (Some(entered), Some(new), Some(old)) = (
+                hop.key_passphrase.as_deref(),
+                new.key_passphrase.as_mut(),
+                old.key_passphrase.as_ref(),
+            ) {
+                keep(new, old, entered);
+            }
+        }
+    }
+}
+
+/// Save entered secrets to `.env` under the variables `conn` references, unless an
+/// existing reference was kept.
 fn persist_secret(
     based_dir: &Path,
     relative_id: &str,
     config: &ConnectionConfig,
+    conn: &ProjectConnection,
 ) -> anyhow::Result<()> {
     let env_path = based_dir.join(".env");
-    match config {
-        ConnectionConfig::Postgres(c) => {
-            if !c.password.is_empty() {
+    let generated = |value: Option<&EnvOrString>, suffix: &str| match value {
+        Some(EnvOrString::FromEnv { var }) => *var == secret_env_key(relative_id, suffix),
+        _ => false,
+    };
+    match (config, &conn.spec) {
+        (ConnectionConfig::Postgres(c), ConnectionSpec::Postgres { password, .. }) => {
+            if generated(Some(password), "PASSWORD") {
                 upsert_env_file(
                     &env_path,
                     &secret_env_key(relative_id, "PASSWORD"),
                     &c.password,
                 )?;
             }
-            if let Some(pass) = c
-                .ssh
-                .as_ref()
-                .and_then(|s| s.key_passphrase.as_deref())
-                .filter(|p| !p.is_empty())
+            if let Some(pass) = c.ssh.as_ref().and_then(|s| s.key_passphrase.as_deref())
+                && generated(
+                    conn.ssh.as_ref().and_then(|s| s.key_passphrase.as_ref()),
+                    "SSH_KEY_PASSPHRASE",
+                )
             {
                 upsert_env_file(
                     &env_path,
@@ -54,15 +126,32 @@ fn persist_secret(
                     pass,
                 )?;
             }
+            if let (Some(entered), Some(ssh)) = (c.ssh.as_ref(), conn.ssh.as_ref()) {
+                for (i, (hop, jump)) in entered.jump_hosts.iter().zip(&ssh.jump_hosts).enumerate() {
+                    let suffix = jump_passphrase_suffix(i);
+                    if let Some(pass) = hop.key_passphrase.as_deref()
+                        && generated(jump.key_passphrase.as_ref(), &suffix)
+                    {
+                        upsert_env_file(&env_path, &secret_env_key(relative_id, &suffix), pass)?;
+                    }
+                }
+            }
         }
-        ConnectionConfig::MongoDB(c) if !c.uri.is_empty() => {
-            upsert_env_file(&env_path, &secret_env_key(relative_id, "URL"), &c.uri)?;
+        (ConnectionConfig::MongoDB(c), ConnectionSpec::MongoDB { url, .. }) => {
+            if generated(Some(url), "URL") {
+                upsert_env_file(&env_path, &secret_env_key(relative_id, "URL"), &c.uri)?;
+            }
         }
         _ => {}
     }
     Ok(())
 }
 
+/// `.env` suffix for the key passphrase of the `i`th (0-based) jump host.
+fn jump_passphrase_suffix(i: usize) -> String {
+    format!("SSH_JUMP{}_KEY_PASSPHRASE", i + 1)
+}
+
 fn project_connection_from_config(
     config: &ConnectionConfig,
     relative_id: String,
@@ -78,21 +167,44 @@ fn project_connection_from_config(
                     var: secret_env_key(&relative_id, "PASSWORD"),
                 }
             };
-            let ssh =
-                c.ssh
-                    .as_ref()
-                    .filter(|s| s.is_configured())
-                    .map(|s| SshSettings {
-                        host: s.host.clone(),
-                        port: s.port,
-                        user: s.user.clone(),
-                        key_path: s.key_path.clone(),
-                        key_passphrase: s.key_passphrase.as_ref().filter(|p| !p.is_empty()).map(
-                            |_| EnvOrString::FromEnv {
-                                var: secret_env_key(&relative_id, "SSH_KEY_PASSPHRASE"),
-                            },
-                        ),
-                    });
+            let ssh = c
+                .ssh
+                .as_ref()
+                .filter(|s| s.is_configured())
+                .map(|s| SshSettings {
+                    host: s.host.clone(),
+                    port: s.port,
+                    user: s.user.clone(),
+                    key_path: s.key_path.clone(),
+                    key_passphrase: s
+                        .key_passphrase
+                        .as_ref()
+                        .filter(|p| !p.is_empty())
+                        .map(|_| EnvOrString::FromEnv {
+                            var: secret_env_key(&relative_id, "SSH_KEY_PASSPHRASE"),
+                        }),
+                    alias: s.alias().map(str::to_string),
+                    keepalive_interval: s.keepalive_interval,
+                    keepalive_count_max: s.keepalive_count_max,
+                    jump_hosts: s
+                        .jump_hosts
+                        .iter()
+                        .enumerate()
+                        .map(|(i, j)| SshJumpSettings {
+                            host: j.host.clone(),
+                            port: j.port,
+                            user: j.user.clone(),
+                            key_path: j.key_path.clone(),
+                            key_passphrase: j
+                                .key_passphrase
+                                .as_ref()
+                                .filter(|p| !p.is_empty())
+                                .map(|_| EnvOrString::FromEnv {
+                                    var: secret_env_key(&relative_id, &jump_passphrase_suffix(i)),
+                                }),
+                        })
+                        .collect(),
+                });
             ProjectConnection {
                 id: relative_id,
                 label: c.label.clone(),
@@ -105,9 +217,11 @@ fn project_connection_from_config(
                     database: c.database.clone(),
                     username: c.username.clone(),
                     password,
-                    ssl: !matches!(c.ssl_mode, SslMode::Disable),
+                    sslmode: ssl_mode_name(c.ssl_mode),
+                    settings: Box::new(settings_from_postgres(&c.settings)),
                 },
                 ssh,
+                policy: ConnectionPolicy::default(),
             }
         }
         ConnectionConfig::MongoDB(c) => {
@@ -129,6 +243,7 @@ fn project_connection_from_config(
                     database: c.database.clone(),
                 },
                 ssh: None,
+                policy: ConnectionPolicy::default(),
             }
         }
         ConnectionConfig::SQLite(c) => ProjectConnection {
@@ -142,6 +257,50 @@ fn project_connection_from_config(
                 pragma: c.pragma.as_ref().map(pragma_from_sqlite),
             },
             ssh: None,
+            policy: ConnectionPolicy::default(),
+        },
+    }
+}
+
+fn ssl_mode_name(mode: SslMode) -> PostgresSslMode {
+    match mode {
+        SslMode::Disable => PostgresSslMode::Disable,
+        SslMode::Prefer => PostgresSslMode::Prefer,
+        SslMode::Require => PostgresSslMode::Require,
+        SslMode::VerifyCa => PostgresSslMode::VerifyCa,
+        SslMode::VerifyFull => PostgresSslMode::VerifyFull,
+    }
+}
+
+fn settings_from_postgres(s: &PgSettings) -> PostgresSettings {
+    PostgresSettings {
+        service: s.service.clone(),
+        sslrootcert: s.ssl_root_cert.as_ref().map(|p| p.display().to_string()),
+        sslcert: s.ssl_cert.as_ref().map(|p| p.display().to_string()),
+        sslkey: s.ssl_key.as_ref().map(|p| p.display().to_string()),
+        application_name: s.application_name.clone(),
+        search_path: s.search_path.clone(),
+        options: s.options.clone(),
+        connect_timeout: s.connect_timeout,
+        statement_timeout: s.statement_timeout,
+        failover_hosts: s
+            .failover_hosts
+            .iter()
+            .map(|(host, port)| {
+                if host.contains(':') {
+                    format!("[{host}]:{port}")
+                } else {
+                    format!("{host}:{port}")
+                }
+            })
+            .collect(),
+        target_session_attrs: match s.target_session_attrs {
+            PgTargetSessionAttrs::Any => None,
+            PgTargetSessionAttrs::ReadWrite => Some(TargetSessionAttrs::ReadWrite),
+            PgTargetSessionAttrs::ReadOnly => Some(TargetSessionAttrs::ReadOnly),
+            PgTargetSessionAttrs::Primary => Some(TargetSessionAttrs::Primary),
+            PgTargetSessionAttrs::Standby => Some(TargetSessionAttrs::Standby),
+            PgTargetSessionAttrs::PreferStandby => Some(TargetSessionAttrs::PreferStandby),
         },
     }
 }
@@ -157,7 +316,7 @@ fn pragma_from_sqlite(p: &SqlitePragma) -> PragmaSettings {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use based_project::{load_connections_from_based_dir, load_env_file};
+    use based_project::load_connections_from_based_dir;
     use std::path::PathBuf;
 
     use crate::postgres::PostgresConfig;
@@ -176,6 +335,7 @@ mod tests {
             username: "alice".into(),
             password: "s3cret".into(),
             ssl_mode: SslMode::Require,
+            settings: PgSettings::default(),
             ssh: None,
         });
         let conn = persist_config_to_based_dir(based, &config, &[], None).unwrap();
@@ -200,6 +360,7 @@ mod tests {
             label: "Northwind".into(),
             path: PathBuf::from("/tmp/northwind.db"),
             read_only: false,
+            query_only: false,
             pragma: None,
         });
         persist_config_to_based_dir(based, &config, &[], None).unwrap();
@@ -220,6 +381,7 @@ mod tests {
             username: "alice".into(),
             password: String::new(),
             ssl_mode: SslMode::Disable,
+            settings: PgSettings::default(),
             ssh: None,
         });
         persist_config_to_based_dir(based, &config, &["local".into(), "dev".into()], None).unwrap();
@@ -242,6 +404,7 @@ mod tests {
             username: "alice".into(),
             password: String::new(),
             ssl_mode: SslMode::Disable,
+            settings: PgSettings::default(),
             ssh: None,
         });
         persist_config_to_based_dir(based, &original, &[], None).unwrap();
@@ -253,6 +416,7 @@ mod tests {
             username: "alice".into(),
             password: String::new(),
             ssl_mode: SslMode::Disable,
+            settings: PgSettings::default(),
             ssh: None,
         });
         let conn = persist_config_to_based_dir(based, &renamed, &[], Some("analytics")).unwrap();
@@ -267,7 +431,7 @@ mod tests {
 
     #[test]
     fn persist_postgres_writes_ssh_and_key_passphrase_env() {
-        use based_core::SshTunnelConfig;
+        use based_core::{SshJumpHost, SshTunnelConfig};
         let dir = tempfile::tempdir().unwrap();
         let based = dir.path();
         let config = ConnectionConfig::Postgres(PostgresConfig {
@@ -278,12 +442,23 @@ mod tests {
             username: "app".into(),
             password: "dbpass".into(),
             ssl_mode: SslMode::Require,
+            settings: PgSettings::default(),
             ssh: Some(SshTunnelConfig {
                 host: "bastion.example.com".into(),
                 port: 22,
                 user: "ec2-user".into(),
                 key_path: Some("~/.ssh/id_ed25519".into()),
                 key_passphrase: Some("keypass".into()),
+                alias: None,
+                keepalive_interval: None,
+                keepalive_count_max: None,
+                jump_hosts: vec![SshJumpHost {
+                    host: "edge.example.com".into(),
+                    port: 2222,
+                    user: "edge".into(),
+                    key_path: None,
+                    key_passphrase: Some("jumppass".into()),
+                }],
             }),
         });
         persist_config_to_based_dir(based, &config, &[], None).unwrap();
@@ -292,10 +467,117 @@ mod tests {
         assert!(raw.contains("bastion.example.com"));
         assert!(raw.contains("BASED_PROD_SSH_KEY_PASSPHRASE"));
         assert!(!raw.contains("keypass"));
+        assert!(raw.contains("[[ssh.jump_hosts]]"));
+        assert!(!raw.contains("jumppass"));
         let env = load_env_file(&based.join(".env")).unwrap();
         assert_eq!(
             env.get("BASED_PROD_SSH_KEY_PASSPHRASE").map(String::as_str),
             Some("keypass")
         );
+        assert_eq!(
+            env.get("BASED_PROD_SSH_JUMP1_KEY_PASSPHRASE")
+                .map(String::as_str),
+            Some("jumppass")
+        );
+    }
+
+    #[test]
+    fn persist_postgres_writes_sslmode_and_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        let config = ConnectionConfig::Postgres(PostgresConfig {
+            label: "Prod".into(),
+            host: "primary.internal".into(),
+            port: 5432,
+            database: "app".into(),
+            username: "app".into(),
+            password: String::new(),
+            ssl_mode: SslMode::VerifyFull,
+            settings: PgSettings {
+                ssl_root_cert: Some("certs/root.crt".into()),
+                statement_timeout: Some(30_000),
+                failover_hosts: vec![("replica".into(), 6432), ("::1".into(), 5432)],
+                target_session_attrs: PgTargetSessionAttrs::ReadWrite,
+                ..PgSettings::default()
+            },
+            ssh: None,
+        });
+        persist_config_to_based_dir(based, &config, &[], None).unwrap();
+        let raw = fs::read_to_string(based.join("connections/prod.toml")).unwrap();
+        assert!(raw.contains("sslmode = \"verify-full\""), "{raw}");
+        let loaded = load_connections_from_based_dir(based).unwrap();
+        let ConnectionSpec::Postgres {
+            sslmode, settings, ..
+        } = &loaded[0].spec
+        else {
+            panic!("postgres spec");
+        };
+        assert_eq!(*sslmode, PostgresSslMode::VerifyFull);
+        assert_eq!(settings.sslrootcert.as_deref(), Some("certs/root.crt"));
+        assert_eq!(settings.failover_hosts, ["replica:6432", "[::1]:5432"]);
+        assert_eq!(
+            settings.target_session_attrs,
+            Some(TargetSessionAttrs::ReadWrite)
+        );
+    }
+
+    #[test]
+    fn persist_keeps_inherited_password_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        fs::create_dir_all(based.join("connections/prod")).unwrap();
+        fs::write(
+            based.join("connections/prod/_defaults.toml"),
+            "schema_version = 1\nengine = \"postgres\"\nhost = \"prod-db\"\nport = 5432\nusername = \"app\"\npassword = { env = \"BASED_PROD_PASSWORD\" }\n",
+        )
+        .unwrap();
+        fs::write(based.join(".env"), "BASED_PROD_PASSWORD=shared\n").unwrap();
+        fs::write(
+            based.join("connections/prod/orders.toml"),
+            "schema_version = 1\nlabel = \"Orders\"\ndatabase = \"orders\"\n",
+        )
+        .unwrap();
+        let config = ConnectionConfig::Postgres(PostgresConfig {
+            label: "Orders".into(),
+            host: "prod-db".into(),
+            port: 5432,
+            database: "orders_v2".into(),
+            username: "app".into(),
+            password: "shared".into(),
+            ssl_mode: SslMode::Disable,
+            settings: PgSettings::default(),
+            ssh: None,
+        });
+        persist_config_to_based_dir(based, &config, &[], Some("prod/orders")).unwrap();
+        let raw = fs::read_to_string(based.join("connections/prod/orders.toml")).unwrap();
+        assert!(raw.contains("orders_v2"));
+        assert!(!raw.contains("password"));
+        assert!(!raw.contains("host"));
+        let env = load_env_file(&based.join(".env")).unwrap();
+        assert!(!env.contains_key("BASED_PROD_ORDERS_PASSWORD"));
+    }
+
+    #[test]
+    fn persist_keeps_connection_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        fs::create_dir_all(based.join("connections")).unwrap();
+        fs::write(
+            based.join("connections/northwind.toml"),
+            "schema_version = 1\nlabel = \"Northwind\"\nengine = \"sqlite\"\nfile = \"/tmp/northwind.db\"\n\n[policy]\nforbid_ddl = true\nmax_rows = 50\n",
+        )
+        .unwrap();
+        let config = ConnectionConfig::SQLite(SqliteConfig {
+            label: "Northwind (local)".into(),
+            path: PathBuf::from("/tmp/northwind.db"),
+            read_only: false,
+            query_only: false,
+            pragma: None,
+        });
+        let conn = persist_config_to_based_dir(based, &config, &[], Some("northwind")).unwrap();
+        assert!(conn.policy.forbid_ddl);
+        let loaded = load_connections_from_based_dir(based).unwrap();
+        assert_eq!(loaded[0].label, "Northwind (local)");
+        assert_eq!(loaded[0].policy.max_rows, Some(50));
     }
 }
@@ -5,7 +5,7 @@ use gpui_component::menu::{PopupMenu, PopupMenuItem};
 
 use crate::connection::{ConnectionConfig, ConnectionId, ConnectionState, EngineKind};
 use crate::mongodb::{mongo_uri, mongosh_command};
-use crate::postgres::{postgres_uri, psql_command};
+use crate::postgres::{PostgresConfig, postgres_uri, psql_command, resolve_libpq};
 use crate::sqlite::{resolve_sqlite_path, sqlite_uri, sqlite3_command};
 use crate::workspace::notify;
 use crate::workspace::tabs::WorkspaceRef;
@@ -44,6 +44,13 @@ fn copy_config_item(
     })
 }
 
+/// `c` as Based connects with it (service and `.pgpass` applied), so copied strings match.
+fn resolved(c: &PostgresConfig) -> Option<PostgresConfig> {
+    resolve_libpq(c)
+        .inspect_err(|e| log::warn!("resolve {}: {e:#}", c.label))
+        .ok()
+}
+
 fn add_copy_items(
     menu: PopupMenu,
     tree: WeakEntity<ConnectionTree>,
@@ -57,7 +64,7 @@ fn add_copy_items(
                 tree.clone(),
                 conn_id.clone(),
                 |cfg, _| match cfg {
-                    ConnectionConfig::Postgres(c) => Some(postgres_uri(c, false)),
+                    ConnectionConfig::Postgres(c) => resolved(c).map(|c| postgres_uri(&c, false)),
                     _ => None,
                 },
             ))
@@ -66,7 +73,7 @@ fn add_copy_items(
                 tree.clone(),
                 conn_id.clone(),
                 |cfg, _| match cfg {
-                    ConnectionConfig::Postgres(c) => Some(postgres_uri(c, true)),
+                    ConnectionConfig::Postgres(c) => resolved(c).map(|c| postgres_uri(&c, true)),
                     _ => None,
                 },
             ))
@@ -75,7 +82,7 @@ fn add_copy_items(
                 tree.clone(),
                 conn_id.clone(),
                 |cfg, _| match cfg {
-                    ConnectionConfig::Postgres(c) => Some(psql_command(c, false)),
+                    ConnectionConfig::Postgres(c) => resolved(c).map(|c| psql_command(&c, false)),
                     _ => None,
                 },
             ))
@@ -84,7 +91,7 @@ fn add_copy_items(
                 tree,
                 conn_id.clone(),
                 |cfg, _| match cfg {
-                    ConnectionConfig::Postgres(c) => Some(psql_command(c, true)),
+                    ConnectionConfig::Postgres(c) => resolved(c).map(|c| psql_command(&c, true)),
                     _ => None,
                 },
             )),
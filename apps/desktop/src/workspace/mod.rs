@@ -8,7 +8,8 @@ pub mod tabs;
 pub use pop_out::PopOutManager;
 pub use tabs::{
     DockAreaRef, QueryEditorInit, SqlInject, TabManager, TabManagerRef, TabOpenQueue, TabSpec,
-    WorkspaceNavQueue, WorkspaceRef, enqueue_sql_inject, mark_query_tab_dirty,
+    WorkspaceNavQueue, WorkspaceRef, enqueue_sql_inject, mark_query_tab_clean,
+    mark_query_tab_dirty,
 };
 
 pub mod connection_tree;
@@ -91,6 +92,10 @@ pub struct Workspace {
     pub(crate) pending_project_switch_confirm: bool,
     /// Queued Close Project; confirm dialog on next [`Render`].
     pub(crate) pending_project_close_confirm: bool,
+    /// Queued Migrate Project Files; diff preview on next [`Render`].
+    pub(crate) pending_project_migrate: bool,
+    /// Queued check for unset `{ env = … }` secrets; fill-in form on next [`Render`].
+    pub(crate) pending_missing_secrets: bool,
     tab_navigation: TabNavigationHistory,
     /// Live center tab panels, kept as styled handles for TabManager downcasts.
     center_panels: Vec<Arc<dyn PanelView>>,
@@ -101,7 +106,7 @@ impl Workspace {
         let project_dir = find_project_root();
         let project_context = project_dir
             .as_ref()
-            .and_then(|root| ProjectContext::load(root.clone()).ok());
+            .map(|root| ProjectContext::load(root.clone()));
 
         let workspace_ctx = WorkspaceContext::load_initial(cx).unwrap_or_else(|e| {
             log::error!("workspace context load failed: {e:#}");
@@ -187,6 +192,8 @@ impl Workspace {
             pending_project_switch: None,
             pending_project_switch_confirm: false,
             pending_project_close_confirm: false,
+            pending_project_migrate: false,
+            pending_missing_secrets: project_dir.is_some(),
             tab_navigation: TabNavigationHistory::default(),
             center_panels: vec![home_arc],
         };
@@ -220,6 +227,7 @@ impl Workspace {
                             init: QueryEditorInit::Sql {
                                 sql: Some(sql.clone()),
                                 auto_run: false,
+                                query_path: None,
                             },
                         });
                     }
@@ -121,7 +121,7 @@ pub fn push_export_success(app: &mut App, path: &Path) {
 }
 
 /// Open a file with the system default handler.
-fn open_path(path: &Path) {
+pub(crate) fn open_path(path: &Path) {
     #[cfg(target_os = "macos")]
     let _ = process::Command::new("open").arg(path).spawn();
     #[cfg(target_os = "linux")]
@@ -23,7 +23,8 @@ use gpui_component::{
 use tokio::task::spawn_blocking;
 use uuid::Uuid;
 
-use based_core::SshTunnelConfig;
+use based_core::{SshJumpHost, SshTunnelConfig};
+use based_ssh::resolve_ssh_config;
 
 use crate::app::prefs;
 use crate::connection::{
@@ -35,7 +36,7 @@ use crate::connection::{
 use crate::db;
 use crate::mongodb::{MongoConfig, MongoConnection};
 use crate::postgres::wizard::parse_postgres_uri;
-use crate::postgres::{PgConnection, PostgresConfig, SslMode};
+use crate::postgres::{PgConnection, PgSettings, PostgresConfig, SslMode};
 use crate::project::ProjectRoot;
 use crate::sqlite::{SqliteConfig, SqliteConnection, SqlitePragma};
 use crate::widgets::{labeled_field, labeled_fixed, new_field, set_field};
@@ -44,8 +45,9 @@ use crate::workspace::connection_destination::{
     ConnectionDestination, destination_row, resolve_wizard_destination,
 };
 use crate::workspace::wizard_logic::{
-    add_wizard_tag, can_edit_saved_connection, remove_wizard_tag, save_label_from_config,
-    ssl_mode_from_toggle, ssl_toggle_enabled, wizard_engine_label, wizard_session_id,
+    add_wizard_tag, can_edit_saved_connection, jump_hosts_from_text, jump_hosts_text,
+    remove_wizard_tag, save_label_from_config, ssh_resolved_summary, ssl_mode_from_toggle,
+    ssl_toggle_enabled, wizard_engine_label, wizard_session_id,
 };
 
 const ENGINE_LABELS: &[&str] = &["PostgreSQL", "MongoDB", "SQLite"];
@@ -76,17 +78,27 @@ pub struct ConnectionWizardPanel {
     ssl_enabled: bool,
     ssl_mode: Entity<SelectState<Vec<&'static str>>>,
     ssh_enabled: bool,
+    ssh_alias: Entity<InputState>,
+    /// What `ssh_alias` resolves to in `~/.ssh/config`, or why it does not.
+    ssh_resolved: Option<Result<String, String>>,
     ssh_host: Entity<InputState>,
     ssh_port: Entity<InputState>,
     ssh_user: Entity<InputState>,
     ssh_key_path: Entity<InputState>,
     ssh_key_passphrase: Entity<InputState>,
+    ssh_jump_hosts: Entity<InputState>,
+    /// Jump hosts of the edited connection, for the keys the jump hosts field omits.
+    loaded_jump_hosts: Vec<SshJumpHost>,
+    /// Keepalive interval and count of the edited connection, which the form does not show.
+    loaded_keepalive: (Option<u64>, Option<u32>),
     uri: Entity<InputState>,
     mongo_uri: Entity<InputState>,
     mongo_database: Entity<InputState>,
     sqlite_path: Entity<InputState>,
     sqlite_read_only: bool,
     sqlite_pragma: Option<SqlitePragma>,
+    /// Postgres settings the form does not show, kept from the edited connection.
+    pg_settings: PgSettings,
     name: Entity<InputState>,
     tag_input: Entity<InputState>,
     tags: Vec<String>,
@@ -120,6 +132,15 @@ impl ConnectionWizardPanel {
         })
         .detach();
 
+        let ssh_alias = new_field(window, cx, "", "prod-bastion");
+        cx.subscribe_in(&ssh_alias, window, |panel, _, event, _, cx| {
+            if matches!(event, InputEvent::Change) {
+                panel.refresh_ssh_resolved(cx);
+                cx.notify();
+            }
+        })
+        .detach();
+
         let tag_input = new_field(window, cx, "", "Add a tag");
         cx.subscribe_in(&tag_input, window, |panel, _, event, window, cx| {
             if let InputEvent::PressEnter {
@@ -150,6 +171,8 @@ impl ConnectionWizardPanel {
                 SelectState::new(SSL_ON_LABELS.to_vec(), Some(IndexPath::new(0)), window, cx)
             }),
             ssh_enabled: false,
+            ssh_alias,
+            ssh_resolved: None,
             ssh_host: new_field(window, cx, "", "bastion.example.com"),
             ssh_port: new_field(window, cx, "22", "22"),
             ssh_user: new_field(window, cx, "", "ec2-user"),
@@ -159,6 +182,9 @@ impl ConnectionWizardPanel {
                     .placeholder("Key passphrase (optional)")
                     .masked(true)
             }),
+            ssh_jump_hosts: new_field(window, cx, "", "ops@edge.example.com, bastion:2222"),
+            loaded_jump_hosts: Vec::new(),
+            loaded_keepalive: (None, None),
             uri,
             mongo_uri: new_field(
                 window,
@@ -170,6 +196,7 @@ impl ConnectionWizardPanel {
             sqlite_path: new_field(window, cx, "", "/path/to/database.db"),
             sqlite_read_only: false,
             sqlite_pragma: None,
+            pg_settings: PgSettings::default(),
             name: new_field(window, cx, "", "Name"),
             tag_input,
             tags: Vec::new(),
@@ -234,6 +261,7 @@ impl ConnectionWizardPanel {
                     });
                 }
                 self.apply_ssh(c.ssh.as_ref(), window, cx);
+                self.pg_settings = c.settings.clone();
             }
             ConnectionConfig::MongoDB(c) => {
                 set_field(&self.mongo_uri, &c.uri, window, cx);
@@ -269,6 +297,12 @@ impl ConnectionWizardPanel {
         match ssh {
             Some(ssh) => {
                 self.ssh_enabled = true;
+                set_field(
+                    &self.ssh_alias,
+                    ssh.alias.as_deref().unwrap_or(""),
+                    window,
+                    cx,
+                );
                 set_field(&self.ssh_host, &ssh.host, window, cx);
                 set_field(&self.ssh_port, &ssh.port.to_string(), window, cx);
                 set_field(&self.ssh_user, &ssh.user, window, cx);
@@ -284,6 +318,15 @@ impl ConnectionWizardPanel {
                     window,
                     cx,
                 );
+                set_field(
+                    &self.ssh_jump_hosts,
+                    &jump_hosts_text(&ssh.jump_hosts),
+                    window,
+                    cx,
+                );
+                self.loaded_jump_hosts = ssh.jump_hosts.clone();
+                self.loaded_keepalive = (ssh.keepalive_interval, ssh.keepalive_count_max);
+                self.refresh_ssh_resolved(cx);
             }
             None => {
                 self.ssh_enabled = false;
@@ -297,15 +340,64 @@ impl ConnectionWizardPanel {
         }
         let key_path = self.ssh_key_path.read(cx).value().to_string();
         let key_passphrase = self.ssh_key_passphrase.read(cx).value().to_string();
+        let alias = self.ssh_alias.read(cx).value().to_string();
+        let user = self.ssh_user.read(cx).value().to_string();
+        let jump_hosts = jump_hosts_from_text(
+            &self.ssh_jump_hosts.read(cx).value(),
+            &user,
+            &self.loaded_jump_hosts,
+        )
+        .unwrap_or_else(|err| {
+            log::warn!("SSH jump hosts ignored: {err:#}");
+            Vec::new()
+        });
         Some(SshTunnelConfig {
             host: self.ssh_host.read(cx).value().to_string(),
             port: self.ssh_port.read(cx).value().parse().unwrap_or(22),
-            user: self.ssh_user.read(cx).value().to_string(),
+            user,
             key_path: nonempty_opt(&key_path),
             key_passphrase: nonempty_opt(&key_passphrase),
+            alias: nonempty_opt(&alias),
+            keepalive_interval: self.loaded_keepalive.0,
+            keepalive_count_max: self.loaded_keepalive.1,
+            jump_hosts,
         })
     }
 
+    fn refresh_ssh_resolved(&mut self, cx: &App) {
+        self.ssh_resolved = self
+            .current_ssh(cx)
+            .filter(|ssh| ssh.alias().is_some())
+            .map(|ssh| {
+                resolve_ssh_config(&ssh)
+                    .map(|resolved| ssh_resolved_summary(&resolved))
+                    .map_err(|err| format!("{err:#}"))
+            });
+    }
+
+    /// The `~/.ssh/config` alias field and what it resolved to.
+    fn ssh_alias_field(&self, muted: Hsla, cx: &App) -> impl IntoElement {
+        let hint = match &self.ssh_resolved {
+            Some(Ok(summary)) => div()
+                .text_color(muted)
+                .child(format!("From ~/.ssh/config: {summary}")),
+            Some(Err(err)) => div().text_color(cx.theme().red).child(err.clone()),
+            None => div()
+                .text_color(muted)
+                .child("A Host from ~/.ssh/config fills the fields left blank."),
+        };
+        v_flex()
+            .gap_1()
+            .child(labeled_field(
+                "SSH config host (optional)",
+                muted,
+                Input::new(&self.ssh_alias)
+                    .cleanable(true)
+                    .aria_label("SSH config host"),
+            ))
+            .child(hint.text_xs())
+    }
+
     fn current_ssl_mode(&self, cx: &App) -> SslMode {
         let selected = self
             .ssl_mode
@@ -326,6 +418,7 @@ impl ConnectionWizardPanel {
                 username: self.username.read(cx).value().to_string(),
                 password: self.password.read(cx).value().to_string(),
                 ssl_mode: self.current_ssl_mode(cx),
+                settings: self.pg_settings.clone(),
                 ssh: self.current_ssh(cx),
             }),
             EngineKind::MongoDB => {
@@ -341,6 +434,7 @@ impl ConnectionWizardPanel {
                 label: self.name.read(cx).value().to_string(),
                 path: PathBuf::from(self.sqlite_path.read(cx).value().as_ref()),
                 read_only: self.sqlite_read_only,
+                query_only: false,
                 pragma: self.sqlite_pragma.clone(),
             }),
         }
@@ -891,7 +985,7 @@ impl Render for ConnectionWizardPanel {
                                 ),
                         )
                         .when(self.ssh_enabled, |v| {
-                            v.child(
+                            v.child(self.ssh_alias_field(muted, cx)).child(
                                 h_flex()
                                     .gap_2()
                                     .child(labeled_field(
@@ -957,6 +1051,20 @@ impl Render for ConnectionWizardPanel {
                                     .mask_toggle()
                                     .aria_label("SSH key passphrase"),
                             ))
+                            .child(
+                                v_flex()
+                                    .gap_1()
+                                    .child(labeled_field(
+                                        "Jump hosts",
+                                        muted,
+                                        Input::new(&self.ssh_jump_hosts)
+                                            .cleanable(true)
+                                            .aria_label("SSH jump hosts"),
+                                    ))
+                                    .child(div().text_xs().text_color(muted).child(
+                                        "Bastions before the SSH host, in order, like ProxyJump.",
+                                    )),
+                            )
                         })
                     })
                     .when(engine == EngineKind::MongoDB, |v| {
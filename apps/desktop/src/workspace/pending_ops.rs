@@ -10,9 +10,11 @@ use crate::app::logging::open_logs;
 use crate::app::shell::open_onboarding;
 use crate::app::updater::check_now;
 use crate::command_palette::WorkspacePaletteAction;
+use crate::project::migrate::request_migrate_project;
 use crate::project::prompt_open_project_in_new_window;
 use crate::project::prompt_open_project_in_window;
 use crate::project::request_close_project_in_window;
+use crate::project::secrets::{request_fill_secrets, sync_env_example_now};
 
 impl Workspace {
     pub(crate) fn handle_palette_workspace_action(
@@ -41,6 +43,9 @@ impl Workspace {
             WorkspacePaletteAction::CloseProject => {
                 request_close_project_in_window(cx);
             }
+            WorkspacePaletteAction::MigrateProject => request_migrate_project(self, cx),
+            WorkspacePaletteAction::FillMissingSecrets => request_fill_secrets(self, cx),
+            WorkspacePaletteAction::SyncEnvExample => sync_env_example_now(cx),
         }
     }
 
@@ -4,7 +4,7 @@ use std::mem;
 use std::path::PathBuf;
 use std::time::Instant;
 
-use based_project::{ProjectQuery, load_env_file};
+use based_project::{ProjectQuery, load_env_file, sync_env_example};
 use gpui::Context;
 
 use crate::connection::registry::ConnectionRegistry;
@@ -67,6 +67,7 @@ impl Workspace {
 
     pub fn apply_opened_project(&mut self, root: PathBuf, cx: &mut Context<Self>) {
         self.project_dir = Some(root);
+        self.pending_missing_secrets = true;
         if let Some(pctx) = cx.try_global::<ProjectContext>() {
             self.project_title = pctx.project_name().into();
         }
@@ -183,6 +184,13 @@ impl Workspace {
             .and_then(super::wizard_logic::relative_id_for_saved);
         let mut entry = match persist_config_to_based_dir(&based_dir, &config, &tags, keep_id) {
             Ok(conn) => {
+                // Keep `.env.example` listing the `{ env = … }` secret the file now references.
+                if origin == ConnectionOrigin::Project
+                    && let Some(root) = based_dir.parent()
+                    && let Err(err) = sync_env_example(root)
+                {
+                    log::warn!("update .env.example failed: {err:#}");
+                }
                 let vars = load_env_file(&based_dir.join(".env")).unwrap_or_default();
                 entry_from_tree(&conn, origin, &vars).unwrap_or_else(|err| {
                     log::warn!("resolve persisted connection failed: {err:#}");
@@ -48,12 +48,14 @@ pub fn open_project_query(
 }
 
 pub fn tab_spec_for_query(query: &ProjectQuery, conn_id: ConnectionId) -> TabSpec {
+    let query_path = Some(query.path.clone());
     match &query.body {
         QueryBody::Sql { query } => TabSpec::QueryEditor {
             conn_id,
             init: QueryEditorInit::Sql {
                 sql: Some(query.clone()),
                 auto_run: false,
+                query_path,
             },
         },
         QueryBody::Aggregate {
@@ -64,6 +66,7 @@ pub fn tab_spec_for_query(query: &ProjectQuery, conn_id: ConnectionId) -> TabSpe
             init: QueryEditorInit::MongoPipeline {
                 pipeline: Some(pipeline.clone()),
                 collection: collection.clone(),
+                query_path,
             },
         },
     }
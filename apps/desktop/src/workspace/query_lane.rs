@@ -207,6 +207,7 @@ fn open_loose_query(sql: &str, cx: &mut App) {
             init: QueryEditorInit::Sql {
                 sql: Some(sql.to_string()),
                 auto_run: false,
+                query_path: None,
             },
         },
         cx,
@@ -11,8 +11,8 @@ use crate::bindings::{
     CloseAllTabs, CloseCleanTabs, CloseOtherTabs, CloseTab, CloseTabsLeft, CloseTabsRight,
     CycleAppearance, DismissCommandPalette, GoBackTab, GoForwardTab, NewQuery, OpenHome,
     OpenSettings, PinTab, SplitPaneBottom, SplitPaneLeft, SplitPaneRight, SplitPaneTop,
-    ToggleCommandPalette, ToggleHistoryPane, ToggleInspectorPane, ToggleSavedPane,
-    ToggleSidebarRail,
+    ToggleCommandPalette, ToggleHistoryPane, ToggleInspectorPane, ToggleProblemsPane,
+    ToggleSavedPane, ToggleSidebarRail,
 };
 use crate::project::ProjectContext;
 
@@ -20,7 +20,10 @@ use super::Workspace;
 use super::chrome::{
     layout,
     left_pane::LeftPane,
-    panes::{history_pane::render_history_pane, saved_pane::render_saved_pane},
+    panes::{
+        history_pane::render_history_pane, problems_pane::render_problems_pane,
+        saved_pane::render_saved_pane,
+    },
     side_pane::{SidePane, render_side_pane},
     status_bar::{StatusBar, StatusBarConnection, StatusBarModel},
     target_picker::render_target_picker,
@@ -31,10 +34,13 @@ use super::panels::inspector::render_inspector_body;
 use crate::app::prefs::cycle_theme;
 use crate::app::quit::maybe_show_pending_close_dialog;
 use crate::app::updater::coordinator_snapshot;
+use crate::editor::open_transactions::open_transaction_count;
 use crate::project::drain_pending_reload;
+use crate::project::migrate::maybe_show_pending_migrate_dialog;
 use crate::project::open::{
     maybe_show_pending_project_close_dialog, maybe_show_pending_project_switch_dialog,
 };
+use crate::project::secrets::maybe_show_pending_secrets_dialog;
 use crate::query_store::QueryStore;
 use crate::workspace::query_lane::render_query_lane;
 
@@ -43,6 +49,8 @@ impl Render for Workspace {
         maybe_show_pending_close_dialog(self, window, cx);
         maybe_show_pending_project_switch_dialog(self, window, cx);
         maybe_show_pending_project_close_dialog(self, window, cx);
+        maybe_show_pending_migrate_dialog(self, window, cx);
+        maybe_show_pending_secrets_dialog(self, window, cx);
         if drain_pending_reload(cx)
             && let Some(pctx) = cx.try_global::<ProjectContext>()
         {
@@ -83,6 +91,7 @@ impl Render for Workspace {
                     cx,
                 )
                 .into_any_element(),
+                SidePane::Problems => render_problems_pane(cx).into_any_element(),
             };
             render_side_pane(pane, body, cx).into_any_element()
         });
@@ -229,6 +238,11 @@ impl Render for Workspace {
                     ws.toggle_side_pane(SidePane::Saved, cx);
                 }),
             )
+            .on_action(
+                window.listener_for(&this, |ws, _: &ToggleProblemsPane, _, cx| {
+                    ws.toggle_side_pane(SidePane::Problems, cx);
+                }),
+            )
             .bg(cx.theme().background)
             .child(Topbar::new(self.registry.clone()))
             .child(body)
@@ -245,6 +259,7 @@ impl Render for Workspace {
                                 .as_ref()
                                 .map(|ent| StatusBarConnection::from_entry(ent.read(cx), true, cx))
                         }),
+                    open_transactions: open_transaction_count(cx),
                 },
                 active_pane,
                 self.registry.clone(),
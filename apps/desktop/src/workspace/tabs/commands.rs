@@ -313,31 +313,16 @@ impl Workspace {
         cx: &mut Context<Self>,
         mut pred: impl FnMut(EntityId, &super::manager::Tab, bool) -> bool,
     ) {
-        let candidates: Vec<(EntityId, Arc<dyn PanelView>)> = self
+        let candidates: Vec<EntityId> = self
             .center_panels
             .iter()
             .filter_map(|p| {
                 let panel_id = panel_entity_id(p);
                 let tab = self.tab_manager.read(cx).tab_for_panel_id(panel_id)?;
-                if pred(panel_id, tab, true) {
-                    Some((panel_id, p.clone()))
-                } else {
-                    None
-                }
+                pred(panel_id, tab, true).then_some(panel_id)
             })
+            .filter(|panel_id| self.can_close_center_panel(*panel_id, cx))
             .collect();
-
-        for (panel_id, panel) in candidates {
-            if !self.can_close_center_panel(panel_id, cx) {
-                continue;
-            }
-            self.dock_area.update(cx, |dock, ecx| {
-                remove_presentation_panel(dock, &panel, window, ecx);
-            });
-            self.unregister_center_panel(panel_id);
-        }
-        self.sync_tab_manager_from_dock(cx);
-        self.ensure_home_tab(window, cx);
-        cx.notify();
+        self.close_center_panels_confirmed(candidates, window, cx);
     }
 }
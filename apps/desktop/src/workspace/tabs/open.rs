@@ -28,6 +28,15 @@ impl Global for WorkspaceRef {}
 
 /// Mark the active query tab for this connection as having unsaved edits.
 pub fn mark_query_tab_dirty(conn_id: &ConnectionId, cx: &mut App) {
+    set_query_tab_dirty(conn_id, true, cx);
+}
+
+/// Clear the unsaved-edits mark after the active query tab wrote its text back.
+pub fn mark_query_tab_clean(conn_id: &ConnectionId, cx: &mut App) {
+    set_query_tab_dirty(conn_id, false, cx);
+}
+
+fn set_query_tab_dirty(conn_id: &ConnectionId, dirty: bool, cx: &mut App) {
     let Some(handle) = cx.try_global::<TabManagerRef>().map(|h| h.0.clone()) else {
         return;
     };
@@ -39,7 +48,7 @@ pub fn mark_query_tab_dirty(conn_id: &ConnectionId, cx: &mut App) {
             return;
         };
         if tab.spec.conn_id() == Some(conn_id) && matches!(tab.spec, TabSpec::QueryEditor { .. }) {
-            tab.dirty = true;
+            tab.dirty = dirty;
             cx.notify();
         }
     });
@@ -32,6 +32,9 @@ pub enum QueryEditorInit {
         sql: Option<String>,
         #[serde(default = "default_auto_run")]
         auto_run: bool,
+        /// Saved project query the editor was opened from; keys remembered parameters.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query_path: Option<String>,
     },
     /// MongoDB aggregation pipeline editor.
     MongoPipeline {
@@ -40,6 +43,9 @@ pub enum QueryEditorInit {
         /// Target collection name. `None` means use a default collection name.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         collection: Option<String>,
+        /// Saved project query the editor was opened from; keys remembered parameters.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query_path: Option<String>,
     },
 }
 
@@ -52,6 +58,7 @@ impl Default for QueryEditorInit {
         Self::Sql {
             sql: None,
             auto_run: true,
+            query_path: None,
         }
     }
 }
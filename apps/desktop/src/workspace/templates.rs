@@ -9,7 +9,7 @@ use uuid::Uuid;
 
 use crate::connection::{ConnectionConfig, ConnectionEntry};
 use crate::mongodb::MongoConfig;
-use crate::postgres::{PostgresConfig, SslMode};
+use crate::postgres::{PgSettings, PostgresConfig, SslMode};
 use crate::sqlite::SqliteConfig;
 
 const TEMPLATE_KEY_PREFIX: &str = ConnectionId::WORKSPACE_TEMPLATE_PREFIX;
@@ -116,6 +116,7 @@ pub fn resolve_template_entry(
                 username: resolved.username,
                 password: resolved.password,
                 ssl_mode,
+                settings: PgSettings::default(),
                 ssh: None,
             })
         }
@@ -129,6 +130,7 @@ pub fn resolve_template_entry(
             label: template.label.clone(),
             path: PathBuf::from(resolved.host),
             read_only: false,
+            query_only: false,
             pragma: None,
         }),
     };
@@ -199,6 +201,7 @@ mod tests {
             username: "alice".into(),
             password: "s3cret".into(),
             ssl_mode: SslMode::Require,
+            settings: PgSettings::default(),
             ssh: None,
         });
         let template = template_from_config(&config, None);
@@ -254,6 +257,7 @@ mod tests {
             username: "postgres".into(),
             password: String::new(),
             ssl_mode: SslMode::Prefer,
+            settings: PgSettings::default(),
             ssh: None,
         });
         let existing = template_from_config(&config, None);
@@ -275,6 +279,7 @@ mod tests {
             label: "Northwind".into(),
             path: PathBuf::from("/tmp/northwind.db"),
             read_only: false,
+            query_only: false,
             pragma: None,
         });
         let template = template_from_config(&config, None);
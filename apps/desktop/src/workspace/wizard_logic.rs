@@ -2,6 +2,8 @@
 
 use std::path::Path;
 
+use based_core::{SshJumpHost, SshTunnelConfig};
+
 use crate::connection::{ConnectionConfig, ConnectionId, ConnectionOrigin, EngineKind};
 use crate::postgres::SslMode;
 
@@ -170,6 +172,49 @@ pub fn remove_wizard_tag(tags: &mut Vec<String>, tag: &str) {
     tags.retain(|existing| existing != tag);
 }
 
+/// Jump hosts as the wizard's `ProxyJump`-style field shows them.
+pub fn jump_hosts_text(jump_hosts: &[SshJumpHost]) -> String {
+    jump_hosts
+        .iter()
+        .map(SshJumpHost::display_name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parse the jump hosts field. Hops the form had loaded keep their key and passphrase,
+/// which the field does not show; hops without a user log in as the SSH user.
+pub fn jump_hosts_from_text(
+    text: &str,
+    default_user: &str,
+    loaded: &[SshJumpHost],
+) -> anyhow::Result<Vec<SshJumpHost>> {
+    let mut hops = SshJumpHost::parse_list(text, default_user)?;
+    for hop in &mut hops {
+        if let Some(old) = loaded
+            .iter()
+            .find(|old| old.host == hop.host && old.port == hop.port && old.user == hop.user)
+        {
+            hop.key_path = old.key_path.clone();
+            hop.key_passphrase = old.key_passphrase.clone();
+        }
+    }
+    Ok(hops)
+}
+
+/// One-line summary of tunnel settings resolved from `~/.ssh/config`, e.g.
+/// `ops@10.0.0.5:22 · key ~/.ssh/prod · via edge@edge.example.com:22`.
+pub fn ssh_resolved_summary(ssh: &SshTunnelConfig) -> String {
+    let mut parts = vec![format!("{}@{}:{}", ssh.user, ssh.host, ssh.port)];
+    parts.push(match ssh.key_path.as_deref() {
+        Some(key) => format!("key {key}"),
+        None => "ssh-agent".into(),
+    });
+    if !ssh.jump_hosts.is_empty() {
+        parts.push(format!("via {}", jump_hosts_text(&ssh.jump_hosts)));
+    }
+    parts.join(" · ")
+}
+
 fn first_nonempty(parts: &[&str]) -> String {
     parts
         .iter()
@@ -207,7 +252,7 @@ fn sqlite_stem(path: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::postgres::PostgresConfig;
+    use crate::postgres::{PgSettings, PostgresConfig};
     use crate::sqlite::SqliteConfig;
     use std::path::PathBuf;
 
@@ -289,6 +334,7 @@ mod tests {
             label: String::new(),
             path: PathBuf::from("/data/shop.sqlite"),
             read_only: false,
+            query_only: false,
             pragma: None,
         });
         assert_eq!(save_label_from_config("", &config), "shop");
@@ -357,6 +403,7 @@ mod tests {
             username: "postgres".into(),
             password: String::new(),
             ssl_mode: SslMode::Disable,
+            settings: PgSettings::default(),
             ssh: None,
         })
         .with_label("Analytics".into());
@@ -392,6 +439,7 @@ mod tests {
             username: "postgres".into(),
             password: "s3cret".into(),
             ssl_mode: SslMode::Disable,
+            settings: PgSettings::default(),
             ssh: None,
         })
     }
@@ -444,8 +492,52 @@ mod tests {
                 user: "ec2-user".into(),
                 key_path: None,
                 key_passphrase: None,
+                alias: None,
+                keepalive_interval: None,
+                keepalive_count_max: None,
+                jump_hosts: vec![],
             });
         }
         assert!(open_params_changed(&old, &new));
     }
+
+    #[test]
+    fn jump_hosts_field_keeps_loaded_keys() {
+        let loaded = vec![SshJumpHost {
+            host: "edge.example.com".into(),
+            port: 2222,
+            user: "edge".into(),
+            key_path: Some("~/.ssh/edge".into()),
+            key_passphrase: Some("pass".into()),
+        }];
+        assert_eq!(jump_hosts_text(&loaded), "edge@edge.example.com:2222");
+        let hops =
+            jump_hosts_from_text("edge@edge.example.com:2222, middle", "ops", &loaded).unwrap();
+        assert_eq!(hops[0], loaded[0]);
+        assert_eq!(hops[1].display_name(), "ops@middle:22");
+        assert!(hops[1].key_path.is_none());
+        assert!(jump_hosts_from_text("edge:x", "ops", &loaded).is_err());
+    }
+
+    #[test]
+    fn ssh_resolved_summary_names_key_and_jumps() {
+        let mut ssh = SshTunnelConfig {
+            host: "10.0.0.5".into(),
+            port: 2222,
+            user: "ops".into(),
+            key_path: None,
+            key_passphrase: None,
+            alias: Some("prod-bastion".into()),
+            keepalive_interval: None,
+            keepalive_count_max: None,
+            jump_hosts: vec![],
+        };
+        assert_eq!(ssh_resolved_summary(&ssh), "ops@10.0.0.5:2222 · ssh-agent");
+        ssh.key_path = Some("~/.ssh/prod".into());
+        ssh.jump_hosts = SshJumpHost::parse_list("edge@edge.example.com", "").unwrap();
+        assert_eq!(
+            ssh_resolved_summary(&ssh),
+            "ops@10.0.0.5:2222 · key ~/.ssh/prod · via edge@edge.example.com:22"
+        );
+    }
 }
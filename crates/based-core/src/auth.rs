@@ -11,7 +11,8 @@
 //! has password/SSL fields). The planned migration is:
 //! 1. Add `auth: AuthMethod` to `ConnectionConfig` (with `#[serde(default)]`)
 //! 2. Move per-engine auth fields into `AuthMethod` variants
-//! 3. Bump `.based/` `schema_version` and add a migration in `based-project`
+//! 3. Bump the connection `schema_version` and add an upgrade step to `MIGRATIONS` in
+//!    `based-project`'s `migrate` module
 //!
 //! Until step 1 lands, `AuthMethod` is referenced by new code only (CLI flag
 //! parsing, future connection wizard fields, CI usage).
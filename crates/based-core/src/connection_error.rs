@@ -19,6 +19,7 @@ pub enum ConnectionErrorCategory {
     ConfigInvalid,
     UriParse,
     SshFailed,
+    SshHostKeyChanged,
     Internal,
 }
 
@@ -36,6 +37,7 @@ impl ConnectionErrorCategory {
             Self::ConfigInvalid => "Connection settings incomplete",
             Self::UriParse => "Could not parse connection URI",
             Self::SshFailed => "SSH tunnel failed",
+            Self::SshHostKeyChanged => "SSH host key has changed",
             Self::Internal => "Something went wrong",
         }
     }
@@ -53,6 +55,7 @@ impl ConnectionErrorCategory {
             Self::ConfigInvalid => "Fill all required connection fields.",
             Self::UriParse => "Fix the connection URI format.",
             Self::SshFailed => "Check SSH host, user, and keys.",
+            Self::SshHostKeyChanged => "Verify the new key with the server's admin first.",
             Self::Internal => "Retry; report if this persists.",
         }
     }
@@ -87,7 +90,9 @@ impl fmt::Display for ConnectionErrorDetail {
 /// Best-effort mapping from driver error strings (sqlx, etc.).
 pub fn categorize_connect_error(err: &str) -> ConnectionErrorDetail {
     let lower = err.to_ascii_lowercase();
-    let category = if lower.contains("ssh tunnel") || lower.contains("known_hosts") {
+    let category = if lower.contains("host key has changed") {
+        ConnectionErrorCategory::SshHostKeyChanged
+    } else if lower.contains("ssh tunnel") || lower.contains("known_hosts") {
         ConnectionErrorCategory::SshFailed
     } else if lower.contains("password authentication failed")
         || lower.contains("authentication failed")
@@ -138,4 +143,12 @@ mod tests {
         let d = categorize_connect_error("SSH tunnel: host is not in known_hosts");
         assert_eq!(d.category, ConnectionErrorCategory::SshFailed);
     }
+
+    #[test]
+    fn maps_changed_host_key_apart_from_other_ssh_failures() {
+        let d = categorize_connect_error(
+            "SSH tunnel: WARNING: the host key has changed for db.internal:22.",
+        );
+        assert_eq!(d.category, ConnectionErrorCategory::SshHostKeyChanged);
+    }
 }
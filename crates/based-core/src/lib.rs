@@ -4,9 +4,11 @@ pub mod auth;
 pub mod connection_error;
 pub mod connection_id;
 pub mod engine;
+pub mod query_error;
 pub mod session;
 pub mod ssh;
 pub mod tab;
+pub mod value;
 
 pub use auth::AuthMethod;
 pub use connection_error::{
@@ -14,6 +16,10 @@ pub use connection_error::{
 };
 pub use connection_id::ConnectionId;
 pub use engine::EngineKind;
+pub use query_error::{
+    QueryErrorCategory, QueryErrorDetail, QueryTimedOut, categorize_query_error,
+};
 pub use session::{PersistedConnection, WorkspaceState};
-pub use ssh::SshTunnelConfig;
+pub use ssh::{SshJumpHost, SshTunnelConfig};
 pub use tab::{TabId, TabKind};
+pub use value::Value;
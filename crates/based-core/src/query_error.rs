@@ -0,0 +1,138 @@
+//! Categorized query execution errors, so a timeout reads differently from a failure.
+
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// High-level failure category for a query run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QueryErrorCategory {
+    /// The statement exceeded the configured query timeout.
+    Timeout,
+    /// The server or driver rejected or failed the statement.
+    Failed,
+}
+
+impl QueryErrorCategory {
+    pub fn summary(self) -> &'static str {
+        match self {
+            Self::Timeout => "Query timed out",
+            Self::Failed => "Query failed",
+        }
+    }
+
+    pub fn suggested_action(self) -> &'static str {
+        match self {
+            Self::Timeout => "Narrow the query or raise the query timeout.",
+            Self::Failed => "See details and fix the statement.",
+        }
+    }
+}
+
+/// Driver error for a statement stopped by the query timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryTimedOut {
+    pub after: Duration,
+}
+
+impl fmt::Display for QueryTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = self.after.as_millis();
+        if ms.is_multiple_of(1000) {
+            write!(f, "no result within {}s", ms / 1000)
+        } else {
+            write!(f, "no result within {ms}ms")
+        }
+    }
+}
+
+impl Error for QueryTimedOut {}
+
+/// User-facing query error with category and raw details.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryErrorDetail {
+    pub category: QueryErrorCategory,
+    pub details: Option<String>,
+}
+
+impl QueryErrorDetail {
+    /// A [`QueryErrorCategory::Failed`] detail carrying `message`.
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self {
+            category: QueryErrorCategory::Failed,
+            details: Some(message.into()),
+        }
+    }
+
+    pub fn display_message(&self) -> String {
+        match self.category {
+            QueryErrorCategory::Failed => self.details.clone().unwrap_or_default(),
+            _ => {
+                let mut msg = self.category.summary().to_string();
+                if let Some(d) = &self.details
+                    && !d.is_empty()
+                {
+                    msg.push_str(": ");
+                    msg.push_str(d);
+                }
+                msg
+            }
+        }
+    }
+}
+
+impl fmt::Display for QueryErrorDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_message())
+    }
+}
+
+/// Categorize a driver error; timeouts are recognised by a [`QueryTimedOut`] in the chain.
+pub fn categorize_query_error(err: &anyhow::Error) -> QueryErrorDetail {
+    let category = if err.chain().any(|e| e.is::<QueryTimedOut>()) {
+        QueryErrorCategory::Timeout
+    } else {
+        QueryErrorCategory::Failed
+    };
+    QueryErrorDetail {
+        category,
+        details: Some(format!("{err:#}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_in_chain_is_categorized() {
+        let err = anyhow::Error::new(QueryTimedOut {
+            after: Duration::from_secs(30),
+        })
+        .context("statement 2");
+        let d = categorize_query_error(&err);
+        assert_eq!(d.category, QueryErrorCategory::Timeout);
+        assert_eq!(
+            d.display_message(),
+            "Query timed out: statement 2: no result within 30s"
+        );
+    }
+
+    #[test]
+    fn other_errors_are_failures() {
+        let d = categorize_query_error(&anyhow::anyhow!("syntax error at or near \"SELEC\""));
+        assert_eq!(d.category, QueryErrorCategory::Failed);
+        assert_eq!(d.display_message(), "syntax error at or near \"SELEC\"");
+    }
+
+    #[test]
+    fn sub_second_timeouts_print_millis() {
+        let e = QueryTimedOut {
+            after: Duration::from_millis(250),
+        };
+        assert_eq!(e.to_string(), "no result within 250ms");
+    }
+}
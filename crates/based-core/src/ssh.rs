@@ -1,11 +1,19 @@
 //! Runtime SSH tunnel settings (resolved secrets, no project-file types).
 
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
 
 fn default_ssh_port() -> u16 {
     22
 }
 
+/// Seconds between keepalives when `keepalive_interval` is unset.
+const DEFAULT_SSH_KEEPALIVE_INTERVAL: u64 = 15;
+/// Unanswered keepalives tolerated when `keepalive_count_max` is unset.
+const DEFAULT_SSH_KEEPALIVE_COUNT_MAX: u32 = 3;
+
 /// One SSH hop used as a local-forward transport to a database.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SshTunnelConfig {
@@ -17,11 +25,119 @@ pub struct SshTunnelConfig {
     pub key_path: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub key_passphrase: Option<String>,
+    /// `Host` in `~/.ssh/config` supplying whatever of the above is left unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Bastions reached in order before `host`, like OpenSSH `ProxyJump`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jump_hosts: Vec<SshJumpHost>,
+    /// Seconds between keepalives, like OpenSSH `ServerAliveInterval`; `0` turns them off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive_interval: Option<u64>,
+    /// Unanswered keepalives before the session counts as dead (`ServerAliveCountMax`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive_count_max: Option<u32>,
 }
 
 impl SshTunnelConfig {
     pub fn is_configured(&self) -> bool {
-        !self.host.trim().is_empty() && !self.user.trim().is_empty()
+        self.alias().is_some() || (!self.host.trim().is_empty() && !self.user.trim().is_empty())
+    }
+
+    /// The `~/.ssh/config` alias, if one is set.
+    pub fn alias(&self) -> Option<&str> {
+        self.alias
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+    }
+
+    /// Time between keepalives, or `None` when they are turned off.
+    pub fn keepalive(&self) -> Option<Duration> {
+        match self
+            .keepalive_interval
+            .unwrap_or(DEFAULT_SSH_KEEPALIVE_INTERVAL)
+        {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Unanswered keepalives tolerated before the session is dropped.
+    pub fn keepalive_max(&self) -> u32 {
+        self.keepalive_count_max
+            .unwrap_or(DEFAULT_SSH_KEEPALIVE_COUNT_MAX)
+    }
+}
+
+/// A bastion in front of the tunnel host. Each hop verifies its own host key and
+/// authenticates with its own key, or ssh-agent when `key_path` is unset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SshJumpHost {
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_passphrase: Option<String>,
+}
+
+impl SshJumpHost {
+    /// Parse an OpenSSH `ProxyJump` list: comma-separated `[user@]host[:port]`, with an
+    /// optional `ssh://` prefix and IPv6 hosts in brackets. Hops without a user get
+    /// `default_user`, which may be empty to leave the user to `~/.ssh/config`.
+    pub fn parse_list(spec: &str, default_user: &str) -> Result<Vec<Self>> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                Self::parse(entry, default_user).with_context(|| format!("jump host {entry:?}"))
+            })
+            .collect()
+    }
+
+    fn parse(entry: &str, default_user: &str) -> Result<Self> {
+        let entry = entry.strip_prefix("ssh://").unwrap_or(entry);
+        let (user, rest) = match entry.rsplit_once('@') {
+            Some((user, rest)) => (user, rest),
+            None => (default_user, entry),
+        };
+        let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+            let Some((host, after)) = bracketed.split_once(']') else {
+                bail!("missing `]`");
+            };
+            (host, after.strip_prefix(':'))
+        } else {
+            match rest.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (rest, None),
+            }
+        };
+        if host.is_empty() {
+            bail!("empty host");
+        }
+        let port = match port {
+            Some(port) => port.parse().context("invalid port")?,
+            None => default_ssh_port(),
+        };
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            user: user.to_string(),
+            key_path: None,
+            key_passphrase: None,
+        })
+    }
+
+    /// `user@host:port`, the inverse of [`SshJumpHost::parse_list`] for one hop.
+    pub fn display_name(&self) -> String {
+        if self.host.contains(':') {
+            format!("{}@[{}]:{}", self.user, self.host, self.port)
+        } else {
+            format!("{}@{}:{}", self.user, self.host, self.port)
+        }
     }
 }
 
@@ -35,7 +151,15 @@ mod tests {
             serde_json::from_str(r#"{"host":"bastion.example.com","user":"ec2-user"}"#).unwrap();
         assert_eq!(m.port, 22);
         assert!(m.key_path.is_none());
+        assert!(m.jump_hosts.is_empty());
         assert!(m.is_configured());
+        assert_eq!(m.keepalive(), Some(Duration::from_secs(15)));
+        assert_eq!(m.keepalive_max(), 3);
+        let off = SshTunnelConfig {
+            keepalive_interval: Some(0),
+            ..m
+        };
+        assert_eq!(off.keepalive(), None);
     }
 
     #[test]
@@ -46,7 +170,36 @@ mod tests {
             user: "ec2-user".into(),
             key_path: None,
             key_passphrase: None,
+            alias: None,
+            keepalive_interval: None,
+            keepalive_count_max: None,
+            jump_hosts: vec![],
         };
         assert!(!m.is_configured());
+        let aliased = SshTunnelConfig {
+            host: String::new(),
+            user: String::new(),
+            alias: Some("prod-bastion".into()),
+            ..m
+        };
+        assert!(aliased.is_configured());
+    }
+
+    #[test]
+    fn parse_proxy_jump_list() {
+        let hops =
+            SshJumpHost::parse_list("ops@edge.example.com:2222, ssh://inner, [fd00::1]:22", "me")
+                .unwrap();
+        let names: Vec<_> = hops.iter().map(SshJumpHost::display_name).collect();
+        assert_eq!(
+            names,
+            [
+                "ops@edge.example.com:2222",
+                "me@inner:22",
+                "me@[fd00::1]:22"
+            ]
+        );
+        let err = SshJumpHost::parse_list("edge:ssh", "me").unwrap_err();
+        assert!(format!("{err:#}").contains("edge:ssh"), "{err:#}");
     }
 }
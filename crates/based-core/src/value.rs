@@ -0,0 +1,206 @@
+//! Engine-agnostic typed cell values for query results.
+//!
+//! Engine crates decode driver rows into [`Value`] per SQL type so that grids,
+//! exports, and the cell inspector never have to re-guess a type from text.
+
+use std::fmt::{self, Display, Formatter};
+
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+/// One decoded result cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    /// Arbitrary-precision decimal (`NUMERIC`) kept as its canonical text so no
+    /// digits are lost to `f64` rounding.
+    Decimal(String),
+    Text(String),
+    Bytes(Vec<u8>),
+    Json(serde_json::Value),
+    Date(Date),
+    Time(Time),
+    /// Timestamp without time zone.
+    Timestamp(PrimitiveDateTime),
+    /// Timestamp with time zone.
+    TimestampTz(OffsetDateTime),
+    Uuid([u8; 16]),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Upper-case type label for the cell inspector and column tooltips.
+    pub fn type_label(&self) -> &'static str {
+        match self {
+            Self::Null => "NULL",
+            Self::Bool(_) => "BOOLEAN",
+            Self::Int(_) => "INTEGER",
+            Self::Float(_) => "FLOAT",
+            Self::Decimal(_) => "DECIMAL",
+            Self::Text(_) => "TEXT",
+            Self::Bytes(_) => "BYTES",
+            Self::Json(_) => "JSON",
+            Self::Date(_) => "DATE",
+            Self::Time(_) => "TIME",
+            Self::Timestamp(_) => "TIMESTAMP",
+            Self::TimestampTz(_) => "TIMESTAMPTZ",
+            Self::Uuid(_) => "UUID",
+            Self::Array(_) => "ARRAY",
+        }
+    }
+
+    /// JSON form used by exports: numbers and booleans stay native, decimals
+    /// stay strings (precision), temporal and binary values use their display text.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value as Json;
+        match self {
+            Self::Null => Json::Null,
+            Self::Bool(b) => Json::Bool(*b),
+            Self::Int(n) => Json::from(*n),
+            Self::Float(f) => serde_json::Number::from_f64(*f)
+                .map(Json::Number)
+                .unwrap_or_else(|| Json::String(f.to_string())),
+            Self::Json(v) => v.clone(),
+            Self::Array(items) => Json::Array(items.iter().map(Self::to_json).collect()),
+            Self::Text(s) | Self::Decimal(s) => Json::String(s.clone()),
+            other => Json::String(other.to_string()),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("NULL"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Int(n) => write!(f, "{n}"),
+            Self::Float(v) => write!(f, "{v}"),
+            Self::Decimal(s) | Self::Text(s) => f.write_str(s),
+            Self::Bytes(bytes) => {
+                f.write_str("\\x")?;
+                for b in bytes {
+                    write!(f, "{b:02x}")?;
+                }
+                Ok(())
+            }
+            Self::Json(v) => write!(f, "{v}"),
+            Self::Date(d) => write_date(f, *d),
+            Self::Time(t) => write_time(f, *t),
+            Self::Timestamp(ts) => {
+                write_date(f, ts.date())?;
+                f.write_str(" ")?;
+                write_time(f, ts.time())
+            }
+            Self::TimestampTz(ts) => {
+                write_date(f, ts.date())?;
+                f.write_str(" ")?;
+                write_time(f, ts.time())?;
+                let offset = ts.offset();
+                let sign = if offset.is_negative() { '-' } else { '+' };
+                write!(
+                    f,
+                    "{sign}{:02}:{:02}",
+                    offset.whole_hours().unsigned_abs(),
+                    offset.minutes_past_hour().unsigned_abs()
+                )
+            }
+            Self::Uuid(bytes) => {
+                for (i, b) in bytes.iter().enumerate() {
+                    if matches!(i, 4 | 6 | 8 | 10) {
+                        f.write_str("-")?;
+                    }
+                    write!(f, "{b:02x}")?;
+                }
+                Ok(())
+            }
+            Self::Array(_) => write!(f, "{}", self.to_json()),
+        }
+    }
+}
+
+fn write_date(f: &mut Formatter<'_>, d: Date) -> fmt::Result {
+    write!(
+        f,
+        "{:04}-{:02}-{:02}",
+        d.year(),
+        u8::from(d.month()),
+        d.day()
+    )
+}
+
+/// `HH:MM:SS` plus microseconds with trailing zeros trimmed (Postgres style).
+fn write_time(f: &mut Formatter<'_>, t: Time) -> fmt::Result {
+    write!(f, "{:02}:{:02}:{:02}", t.hour(), t.minute(), t.second())?;
+    let micros = t.microsecond();
+    if micros != 0 {
+        let frac = format!("{micros:06}");
+        write!(f, ".{}", frac.trim_end_matches('0'))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Month, UtcOffset};
+
+    #[test]
+    fn null_displays_and_labels() {
+        assert_eq!(Value::Null.to_string(), "NULL");
+        assert_eq!(Value::Null.type_label(), "NULL");
+        assert!(Value::Null.is_null());
+    }
+
+    #[test]
+    fn uuid_displays_hyphenated() {
+        let v = Value::Uuid([
+            0x55, 0x0e, 0x84, 0x00, 0xe2, 0x9b, 0x41, 0xd4, 0xa7, 0x16, 0x44, 0x66, 0x55, 0x44,
+            0x00, 0x00,
+        ]);
+        assert_eq!(v.to_string(), "550e8400-e29b-41d4-a716-446655440000");
+    }
+
+    #[test]
+    fn bytes_display_as_hex() {
+        assert_eq!(Value::Bytes(vec![0xde, 0xad]).to_string(), "\\xdead");
+    }
+
+    #[test]
+    fn timestamps_trim_trailing_zero_micros() {
+        let date = Date::from_calendar_date(2024, Month::March, 5).unwrap();
+        let time = Time::from_hms_micro(7, 8, 9, 120_000).unwrap();
+        let ts = PrimitiveDateTime::new(date, time);
+        assert_eq!(Value::Timestamp(ts).to_string(), "2024-03-05 07:08:09.12");
+        let tz = ts.assume_offset(UtcOffset::from_hms(-5, -30, 0).unwrap());
+        assert_eq!(
+            Value::TimestampTz(tz).to_string(),
+            "2024-03-05 07:08:09.12-05:30"
+        );
+    }
+
+    #[test]
+    fn to_json_keeps_numbers_native_and_decimals_exact() {
+        assert_eq!(Value::Int(42).to_json(), serde_json::json!(42));
+        assert_eq!(Value::Bool(true).to_json(), serde_json::json!(true));
+        assert_eq!(
+            Value::Decimal("12345678901234567890.01".into()).to_json(),
+            serde_json::json!("12345678901234567890.01")
+        );
+        assert_eq!(
+            Value::Array(vec![Value::Int(1), Value::Null]).to_json(),
+            serde_json::json!([1, null])
+        );
+    }
+
+    #[test]
+    fn array_displays_as_json_list() {
+        let v = Value::Array(vec![Value::Text("a".into()), Value::Int(2)]);
+        assert_eq!(v.to_string(), r#"["a",2]"#);
+    }
+}
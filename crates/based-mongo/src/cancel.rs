@@ -0,0 +1,112 @@
+//! Cancel a running find/aggregate.
+//!
+//! Each operation is tagged with a unique `comment`. Cancelling sets a flag the cursor
+//! loop checks between batches (dropping the cursor sends `killCursors`), and issues
+//! `killOp` for any server operation still carrying the tag.
+
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+use mongodb::Client;
+use mongodb::bson::{Bson, Document, doc};
+use mongodb::error::{Error, ErrorKind};
+
+/// Server error code for an operation stopped by `killOp`.
+const INTERRUPTED: i32 = 11601;
+
+static NEXT_TAG: AtomicU64 = AtomicU64::new(1);
+
+/// Tag and flag shared between a running operation and whoever may cancel it.
+#[derive(Debug, Clone)]
+pub struct MongoCancelHandle {
+    tag: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Default for MongoCancelHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MongoCancelHandle {
+    pub fn new() -> Self {
+        let n = NEXT_TAG.fetch_add(1, Ordering::Relaxed);
+        Self {
+            tag: format!("based:{}:{n}", process::id()),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Value for the `comment` option of the find/aggregate being run.
+    pub fn comment(&self) -> Bson {
+        Bson::String(self.tag.clone())
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Flag the operation as cancelled and `killOp` any server op with this tag.
+    /// Returns the number of operations killed.
+    pub async fn cancel(&self, client: &Client) -> Result<usize> {
+        self.cancelled.store(true, Ordering::Relaxed);
+        let admin = client.database("admin");
+        let pipeline = [
+            doc! { "$currentOp": { "allUsers": true } },
+            doc! { "$match": { "command.comment": &self.tag } },
+        ];
+        let mut ops = admin
+            .aggregate(pipeline, None)
+            .await
+            .context("list current operations")?;
+        let mut opids = Vec::new();
+        while ops.advance().await.context("read current operations")? {
+            let op: Document = ops.deserialize_current()?;
+            if let Some(opid) = op.get("opid") {
+                opids.push(opid.clone());
+            }
+        }
+        for opid in &opids {
+            admin
+                .run_command(doc! { "killOp": 1, "op": opid.clone() }, None)
+                .await
+                .with_context(|| format!("killOp {opid}"))?;
+        }
+        Ok(opids.len())
+    }
+}
+
+/// Whether `err` is the server reporting an operation stopped by `killOp`.
+pub fn is_interrupted(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.downcast_ref::<Error>().is_some_and(
+            |e| matches!(e.kind.as_ref(), ErrorKind::Command(c) if c.code == INTERRUPTED),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_get_distinct_tags_and_share_the_flag() {
+        let a = MongoCancelHandle::new();
+        let b = MongoCancelHandle::new();
+        assert_ne!(a.comment(), b.comment());
+        let clone = a.clone();
+        a.cancelled.store(true, Ordering::Relaxed);
+        assert!(clone.is_cancelled());
+        assert!(!b.is_cancelled());
+    }
+
+    #[test]
+    fn plain_errors_are_not_interruptions() {
+        assert!(!is_interrupted(&anyhow::anyhow!(
+            "operation was interrupted"
+        )));
+    }
+}
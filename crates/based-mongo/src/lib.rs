@@ -1,9 +1,14 @@
-//! MongoDB configuration, client helpers, and document mutations (no UI).
+//! MongoDB configuration, client helpers, document mutations, cancellation, and query
+//! timeouts (no UI).
 
+pub mod cancel;
 pub mod client;
 pub mod config;
 pub mod mutations;
+pub mod timeout;
 
+pub use cancel::{MongoCancelHandle, is_interrupted};
 pub use client::{apply_auth_source, resolve_database_name, test_database_name};
 pub use config::{MongoConfig, mongo_uri, mongosh_command};
 pub use mutations::{delete_by_id, document_from_json, replace_by_id, update_fields_by_id};
+pub use timeout::{is_max_time_expired, map_timeout_error};
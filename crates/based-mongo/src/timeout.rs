@@ -0,0 +1,39 @@
+//! `maxTimeMS` for find/aggregate, surfaced as [`QueryTimedOut`].
+
+use std::time::Duration;
+
+use based_core::QueryTimedOut;
+use mongodb::error::{Error, ErrorKind};
+
+/// Server error code for an operation that exceeded `maxTimeMS`.
+const MAX_TIME_MS_EXPIRED: i32 = 50;
+
+/// Whether `err` is the server stopping an operation at `maxTimeMS`.
+pub fn is_max_time_expired(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.downcast_ref::<Error>().is_some_and(
+            |e| matches!(e.kind.as_ref(), ErrorKind::Command(c) if c.code == MAX_TIME_MS_EXPIRED),
+        )
+    })
+}
+
+/// Replace a `MaxTimeMSExpired` error with [`QueryTimedOut`]; other errors pass through.
+pub fn map_timeout_error(err: anyhow::Error, max_time: Duration) -> anyhow::Error {
+    if is_max_time_expired(&err) {
+        anyhow::Error::new(QueryTimedOut { after: max_time })
+    } else {
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_errors_pass_through() {
+        let err = map_timeout_error(anyhow::anyhow!("boom"), Duration::from_secs(1));
+        assert!(err.downcast_ref::<QueryTimedOut>().is_none());
+        assert_eq!(err.to_string(), "boom");
+    }
+}
@@ -0,0 +1,133 @@
+//! Cancel a running statement from another task.
+//!
+//! The executing connection records its backend PID in a [`PgCancelHandle`]; cancelling
+//! issues `pg_cancel_backend(pid)` on a separate pooled connection, so the server aborts
+//! the statement with SQLSTATE `57014` and the connection stays usable. A statement
+//! timeout ends with the same code, so the handle also records that a cancel was asked
+//! for.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use based_core::Value;
+use sqlx::{Executor, PgConnection, PgPool};
+
+use crate::stream::QueryResult;
+use crate::timeout::{as_timeout, controls_transaction, is_query_canceled, run_with_timeout};
+
+/// Shared slot holding the backend PID of the statement that may be cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct PgCancelHandle {
+    pid: Arc<Mutex<Option<i32>>>,
+    /// [`cancel`](Self::cancel) was called since the last [`attach`](Self::attach).
+    requested: Arc<AtomicBool>,
+}
+
+impl PgCancelHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Backend PID of the attached connection, if a statement is running.
+    pub fn backend_pid(&self) -> Option<i32> {
+        *self.pid.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether a cancel was sent to the attached statement.
+    pub fn cancel_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Record the backend PID of `conn` before it starts executing.
+    pub async fn attach(&self, conn: &mut PgConnection) -> Result<()> {
+        let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(conn)
+            .await
+            .context("read backend pid")?;
+        self.requested.store(false, Ordering::SeqCst);
+        self.set(Some(pid));
+        Ok(())
+    }
+
+    /// Forget the PID once the statement has finished.
+    pub fn detach(&self) {
+        self.set(None);
+    }
+
+    /// Ask the server to cancel the attached backend's current statement.
+    /// Returns `false` when nothing is running.
+    pub async fn cancel(&self, pool: &PgPool) -> Result<bool> {
+        let Some(pid) = self.backend_pid() else {
+            return Ok(false);
+        };
+        // Set first: the statement may fail before the query below returns.
+        self.requested.store(true, Ordering::SeqCst);
+        let sent: bool = sqlx::query_scalar("SELECT pg_cancel_backend($1)")
+            .bind(pid)
+            .fetch_one(pool)
+            .await
+            .with_context(|| format!("cancel backend {pid}"))?;
+        Ok(sent)
+    }
+
+    fn set(&self, pid: Option<i32>) {
+        *self.pid.lock().unwrap_or_else(PoisonError::into_inner) = pid;
+    }
+}
+
+/// [`execute_sql_with_timeout`] on a dedicated pooled connection whose PID is exposed
+/// via `handle`. A transaction the SQL opens and leaves open (or aborted) is rolled back
+/// before the connection returns to the pool.
+pub async fn execute_sql_cancellable(
+    pool: &PgPool,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+    handle: &PgCancelHandle,
+) -> Result<QueryResult> {
+    let mut conn = pool.acquire().await.context("acquire connection")?;
+    handle.attach(&mut conn).await?;
+    let result = run_with_timeout(&mut conn, sql, params, max_rows, timeout).await;
+    handle.detach();
+    if controls_transaction(sql) {
+        // Outside a transaction this is only a server warning. The `RESET` after it
+        // undoes a timeout that the rollback restored.
+        conn.execute("ROLLBACK").await?;
+        conn.execute("RESET statement_timeout").await?;
+    }
+    result.map_err(|e| as_timeout(e, timeout, handle.cancel_requested()))
+}
+
+/// Whether `err` is the server's response to a cancel sent through `handle` (as opposed
+/// to a statement timeout, which shares SQLSTATE `57014`).
+pub fn is_query_cancelled(err: &anyhow::Error, handle: &PgCancelHandle) -> bool {
+    handle.cancel_requested() && is_query_canceled(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detached_handle_has_no_pid() {
+        let handle = PgCancelHandle::new();
+        handle.set(Some(42));
+        let clone = handle.clone();
+        assert_eq!(clone.backend_pid(), Some(42));
+        handle.detach();
+        assert_eq!(clone.backend_pid(), None);
+    }
+
+    #[test]
+    fn non_database_errors_are_not_cancellations() {
+        let handle = PgCancelHandle::new();
+        handle.requested.store(true, Ordering::SeqCst);
+        assert!(!is_query_cancelled(
+            &anyhow::anyhow!("canceling statement due to user request"),
+            &handle
+        ));
+    }
+}
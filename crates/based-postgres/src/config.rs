@@ -1,6 +1,12 @@
+use std::mem;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
 use based_core::SshTunnelConfig;
 use serde::{Deserialize, Serialize};
-use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use sqlx::PgPool;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostgresConfig {
@@ -11,10 +17,56 @@ pub struct PostgresConfig {
     pub username: String,
     pub password: String,
     pub ssl_mode: SslMode,
+    #[serde(default, skip_serializing_if = "PgSettings::is_default")]
+    pub settings: PgSettings,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ssh: Option<SshTunnelConfig>,
 }
 
+/// libpq connection parameters beyond host, credentials, and `sslmode`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PgSettings {
+    /// `pg_service.conf` entry; see [`crate::libpq::resolve_libpq`].
+    pub service: Option<String>,
+    pub ssl_root_cert: Option<PathBuf>,
+    pub ssl_cert: Option<PathBuf>,
+    pub ssl_key: Option<PathBuf>,
+    pub application_name: Option<String>,
+    pub search_path: Option<String>,
+    /// Raw libpq `options`: `-c name=value` / `--name=value` settings.
+    pub options: Option<String>,
+    /// Seconds to wait for each host.
+    pub connect_timeout: Option<u64>,
+    /// Session `statement_timeout` in milliseconds.
+    pub statement_timeout: Option<u64>,
+    /// `(host, port)` tried in order after the primary host.
+    pub failover_hosts: Vec<(String, u16)>,
+    pub target_session_attrs: TargetSessionAttrs,
+    /// Sessions start with `default_transaction_read_only`, so the server refuses writes,
+    /// including those a function called from a `SELECT` makes.
+    pub read_only: bool,
+}
+
+impl PgSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Which server [`connect_pool`] accepts (libpq `target_session_attrs`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetSessionAttrs {
+    #[default]
+    Any,
+    ReadWrite,
+    ReadOnly,
+    Primary,
+    Standby,
+    PreferStandby,
+}
+
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SslMode {
@@ -36,17 +88,181 @@ pub fn pg_ssl_mode(m: SslMode) -> PgSslMode {
     }
 }
 
-pub fn pg_connect_options(config: &PostgresConfig) -> PgConnectOptions {
-    PgConnectOptions::new()
+/// Options for the primary host. Fails when `options` is not a list of `-c name=value`.
+pub fn pg_connect_options(config: &PostgresConfig) -> Result<PgConnectOptions> {
+    let settings = &config.settings;
+    let mut opts = PgConnectOptions::new()
         .host(&config.host)
         .port(config.port)
         .username(&config.username)
         .password(&config.password)
         .database(&config.database)
-        .ssl_mode(pg_ssl_mode(config.ssl_mode))
+        .ssl_mode(pg_ssl_mode(config.ssl_mode));
+    if let Some(path) = &settings.ssl_root_cert {
+        opts = opts.ssl_root_cert(path);
+    }
+    if let Some(path) = &settings.ssl_cert {
+        opts = opts.ssl_client_cert(path);
+    }
+    if let Some(path) = &settings.ssl_key {
+        opts = opts.ssl_client_key(path);
+    }
+    if let Some(name) = &settings.application_name {
+        opts = opts.application_name(name);
+    }
+    Ok(opts.options(server_settings(settings)?))
+}
+
+/// `-c` settings sent at startup: the raw `options`, then `search_path`,
+/// `statement_timeout`, and `default_transaction_read_only`.
+fn server_settings(settings: &PgSettings) -> Result<Vec<(String, String)>> {
+    let mut out = match &settings.options {
+        Some(raw) => parse_pg_options(raw).context("invalid Postgres `options`")?,
+        None => vec![],
+    };
+    if let Some(path) = &settings.search_path {
+        out.push(("search_path".into(), path.clone()));
+    }
+    if let Some(ms) = settings.statement_timeout {
+        out.push(("statement_timeout".into(), ms.to_string()));
+    }
+    if settings.read_only {
+        out.push(("default_transaction_read_only".into(), "on".into()));
+    }
+    Ok(out)
+}
+
+/// Split libpq `options` (`-c a=1 -cb=2 --c=3`, `\ ` escapes a space) into pairs.
+fn parse_pg_options(raw: &str) -> Result<Vec<(String, String)>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => word.extend(chars.next()),
+            c if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    let mut out = Vec::new();
+    let mut words = words.into_iter();
+    while let Some(word) = words.next() {
+        let setting = if word == "-c" {
+            words.next().context("`-c` without a setting")?
+        } else if let Some(rest) = word.strip_prefix("--") {
+            rest.to_string()
+        } else if let Some(rest) = word.strip_prefix("-c") {
+            rest.to_string()
+        } else {
+            bail!("unexpected {word:?}; expected `-c name=value`");
+        };
+        let Some((name, value)) = setting.split_once('=') else {
+            bail!("{setting:?} is not `name=value`");
+        };
+        out.push((name.replace('-', "_"), value.to_string()));
+    }
+    Ok(out)
 }
 
-/// libpq URI (`postgresql://…`). Password is omitted unless requested and non-empty.
+/// Open a pool on the first host (primary, then `failover_hosts`) that accepts a
+/// connection within `connect_timeout` and matches `target_session_attrs`. The error
+/// lists why each host was skipped. Pass `config` through
+/// [`crate::libpq::resolve_libpq`] first to apply its service and `.pgpass`.
+pub async fn connect_pool(config: &PostgresConfig, pool: PgPoolOptions) -> Result<PgPool> {
+    let settings = &config.settings;
+    let base = pg_connect_options(config)?;
+    let pool = match settings.connect_timeout {
+        Some(secs) => pool.acquire_timeout(Duration::from_secs(secs.max(1))),
+        None => pool,
+    };
+    let attrs = settings.target_session_attrs;
+    let mut fallback: Option<PgPool> = None;
+    let mut failures = Vec::new();
+    for (host, port) in hosts(config) {
+        let endpoint = format!("{}:{port}", format_host(&host));
+        let opts = base.clone().host(&host).port(port);
+        let candidate = match pool.clone().connect_with(opts).await {
+            Ok(candidate) => candidate,
+            Err(e) => {
+                failures.push(format!("{endpoint}: {e}"));
+                continue;
+            }
+        };
+        match session_matches(&candidate, attrs).await {
+            Ok(true) => {
+                if let Some(fallback) = fallback.take() {
+                    fallback.close().await;
+                }
+                return Ok(candidate);
+            }
+            Ok(false) if attrs == TargetSessionAttrs::PreferStandby && fallback.is_none() => {
+                fallback = Some(candidate);
+            }
+            Ok(false) => {
+                candidate.close().await;
+                failures.push(format!("{endpoint}: not {}", attrs_label(attrs)));
+            }
+            Err(e) => {
+                candidate.close().await;
+                failures.push(format!("{endpoint}: {e:#}"));
+            }
+        }
+    }
+    if let Some(fallback) = fallback {
+        return Ok(fallback);
+    }
+    bail!("could not connect to any host ({})", failures.join("; "))
+}
+
+fn hosts(config: &PostgresConfig) -> Vec<(String, u16)> {
+    let mut out = vec![(config.host.clone(), config.port)];
+    out.extend(config.settings.failover_hosts.iter().cloned());
+    out
+}
+
+async fn session_matches(pool: &PgPool, attrs: TargetSessionAttrs) -> Result<bool> {
+    Ok(match attrs {
+        TargetSessionAttrs::Any => true,
+        TargetSessionAttrs::ReadWrite | TargetSessionAttrs::ReadOnly => {
+            let read_only: String = sqlx::query_scalar("SHOW transaction_read_only")
+                .fetch_one(pool)
+                .await
+                .context("SHOW transaction_read_only")?;
+            (read_only == "on") == (attrs == TargetSessionAttrs::ReadOnly)
+        }
+        TargetSessionAttrs::Primary
+        | TargetSessionAttrs::Standby
+        | TargetSessionAttrs::PreferStandby => {
+            let in_recovery: bool = sqlx::query_scalar("SELECT pg_is_in_recovery()")
+                .fetch_one(pool)
+                .await
+                .context("SELECT pg_is_in_recovery()")?;
+            in_recovery == (attrs != TargetSessionAttrs::Primary)
+        }
+    })
+}
+
+fn attrs_label(attrs: TargetSessionAttrs) -> &'static str {
+    match attrs {
+        TargetSessionAttrs::Any => "any",
+        TargetSessionAttrs::ReadWrite => "read-write",
+        TargetSessionAttrs::ReadOnly => "read-only",
+        TargetSessionAttrs::Primary => "primary",
+        TargetSessionAttrs::Standby => "standby",
+        TargetSessionAttrs::PreferStandby => "prefer-standby",
+    }
+}
+
+/// libpq URI (`postgresql://…`) carrying every host and setting, plus `service` so `psql`
+/// reads the same service entry. Expects a [`crate::libpq::resolve_libpq`] result.
+/// Password is omitted unless requested and non-empty.
 pub fn postgres_uri(config: &PostgresConfig, include_password: bool) -> String {
     let user = percent_encode(&config.username);
     let userinfo = if include_password && !config.password.is_empty() {
@@ -54,13 +270,70 @@ pub fn postgres_uri(config: &PostgresConfig, include_password: bool) -> String {
     } else {
         user
     };
-    let host = format_host(&config.host);
+    let hosts: Vec<String> = hosts(config)
+        .iter()
+        .map(|(host, port)| format!("{}:{port}", format_host(host)))
+        .collect();
     let database = percent_encode(&config.database);
-    format!(
-        "postgresql://{userinfo}@{host}:{port}/{database}?sslmode={sslmode}",
-        port = config.port,
+    let mut uri = format!(
+        "postgresql://{userinfo}@{hosts}/{database}?sslmode={sslmode}",
+        hosts = hosts.join(","),
         sslmode = sslmode_label(config.ssl_mode),
-    )
+    );
+    for (key, value) in uri_params(&config.settings) {
+        uri.push_str(&format!("&{key}={}", percent_encode(&value)));
+    }
+    uri
+}
+
+fn uri_params(settings: &PgSettings) -> Vec<(&'static str, String)> {
+    let mut out = Vec::new();
+    out.extend(settings.service.clone().map(|name| ("service", name)));
+    let path = |p: &PathBuf| p.display().to_string();
+    out.extend(
+        settings
+            .ssl_root_cert
+            .as_ref()
+            .map(|p| ("sslrootcert", path(p))),
+    );
+    out.extend(settings.ssl_cert.as_ref().map(|p| ("sslcert", path(p))));
+    out.extend(settings.ssl_key.as_ref().map(|p| ("sslkey", path(p))));
+    out.extend(
+        settings
+            .application_name
+            .clone()
+            .map(|name| ("application_name", name)),
+    );
+    let mut options: Vec<String> = settings.options.iter().cloned().collect();
+    if let Some(path) = &settings.search_path {
+        options.push(format!("-c search_path={}", escape_option(path)));
+    }
+    if let Some(ms) = settings.statement_timeout {
+        options.push(format!("-c statement_timeout={ms}"));
+    }
+    if settings.read_only {
+        options.push("-c default_transaction_read_only=on".into());
+    }
+    if !options.is_empty() {
+        out.push(("options", options.join(" ")));
+    }
+    out.extend(
+        settings
+            .connect_timeout
+            .map(|secs| ("connect_timeout", secs.to_string())),
+    );
+    if settings.target_session_attrs != TargetSessionAttrs::Any {
+        out.push((
+            "target_session_attrs",
+            attrs_label(settings.target_session_attrs).to_string(),
+        ));
+    }
+    out
+}
+
+/// Backslash-escape spaces and backslashes inside one libpq `options` value.
+fn escape_option(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(' ', "\\ ")
 }
 
 /// `psql` invocation using the same URI as [`postgres_uri`].
@@ -112,6 +385,7 @@ mod tests {
             username: "alice".into(),
             password: "s3cret".into(),
             ssl_mode: SslMode::Require,
+            settings: PgSettings::default(),
             ssh: None,
         }
     }
@@ -153,6 +427,7 @@ mod tests {
             username: "al ice".into(),
             password: "p@ss:w/rd".into(),
             ssl_mode: SslMode::Prefer,
+            settings: PgSettings::default(),
             ssh: None,
         };
         assert_eq!(
@@ -188,6 +463,59 @@ mod tests {
         assert!(postgres_uri(&cfg, false).ends_with("sslmode=verify-full"));
     }
 
+    #[test]
+    fn uri_lists_failover_hosts_and_settings() {
+        let mut cfg = sample();
+        cfg.settings = PgSettings {
+            ssl_root_cert: Some("/etc/certs/root.crt".into()),
+            application_name: Some("based".into()),
+            search_path: Some("app, public".into()),
+            statement_timeout: Some(30_000),
+            connect_timeout: Some(5),
+            failover_hosts: vec![("replica".into(), 5432), ("::1".into(), 5433)],
+            target_session_attrs: TargetSessionAttrs::ReadWrite,
+            ..PgSettings::default()
+        };
+        assert_eq!(
+            postgres_uri(&cfg, false),
+            "postgresql://alice@db.example:6543,replica:5432,[::1]:5433/analytics?sslmode=require\
+             &sslrootcert=%2Fetc%2Fcerts%2Froot.crt&application_name=based\
+             &options=-c%20search_path%3Dapp%2C%5C%20public%20-c%20statement_timeout%3D30000\
+             &connect_timeout=5&target_session_attrs=read-write"
+        );
+    }
+
+    #[test]
+    fn uri_names_the_service_for_psql() {
+        let mut cfg = sample();
+        cfg.settings.service = Some("analytics".into());
+        assert_eq!(
+            psql_command(&cfg, false),
+            "psql 'postgresql://alice@db.example:6543/analytics?sslmode=require&service=analytics'"
+        );
+    }
+
+    #[test]
+    fn connect_options_merge_raw_options_with_settings() {
+        let mut cfg = sample();
+        cfg.settings.options = Some("-c work_mem=64MB -cgeqo=off --lock-timeout=5s".into());
+        cfg.settings.search_path = Some("app, public".into());
+        cfg.settings.statement_timeout = Some(1500);
+        cfg.settings.read_only = true;
+        let opts = pg_connect_options(&cfg).unwrap();
+        assert_eq!(
+            opts.get_options(),
+            Some(
+                "-c work_mem=64MB -c geqo=off -c lock_timeout=5s -c search_path=app,\\ public \
+                 -c statement_timeout=1500 -c default_transaction_read_only=on"
+            )
+        );
+
+        cfg.settings.options = Some("work_mem=64MB".into());
+        let err = format!("{:#}", pg_connect_options(&cfg).unwrap_err());
+        assert!(err.contains("work_mem=64MB"), "{err}");
+    }
+
     #[test]
     fn psql_wraps_the_same_uri() {
         assert_eq!(
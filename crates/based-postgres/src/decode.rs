@@ -0,0 +1,287 @@
+//! Decode Postgres result cells into [`based_core::Value`] per SQL type.
+
+use std::str;
+
+use based_core::Value;
+use sqlx::postgres::types::{Oid, PgInterval, PgMoney};
+use sqlx::postgres::{PgHasArrayType, PgRow, PgValueFormat, PgValueRef};
+use sqlx::{Decode, Postgres, Row, Type, TypeInfo, ValueRef};
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+/// Decode every column of `row`.
+pub fn pg_row_values(row: &PgRow) -> Vec<Value> {
+    (0..row.len()).map(|i| pg_value(row, i)).collect()
+}
+
+/// Decode column `index` of `row`. Types without a dedicated mapping fall back
+/// to their wire text (enums, domains over text) or raw bytes — never blank.
+pub fn pg_value(row: &PgRow, index: usize) -> Value {
+    let Ok(raw) = row.try_get_raw(index) else {
+        return Value::Null;
+    };
+    if raw.is_null() {
+        return Value::Null;
+    }
+    let type_name = raw.type_info().name().to_string();
+    decode_named(row, index, &raw, &type_name).unwrap_or_else(|| fallback(&raw))
+}
+
+fn decode_named(row: &PgRow, i: usize, raw: &PgValueRef<'_>, type_name: &str) -> Option<Value> {
+    let value = match type_name {
+        "BOOL" => Value::Bool(row.try_get(i).ok()?),
+        "INT2" => Value::Int(row.try_get::<i16, _>(i).ok()?.into()),
+        "INT4" => Value::Int(row.try_get::<i32, _>(i).ok()?.into()),
+        "INT8" => Value::Int(row.try_get(i).ok()?),
+        "OID" => Value::Int(row.try_get::<Oid, _>(i).ok()?.0.into()),
+        "FLOAT4" => Value::Float(row.try_get::<f32, _>(i).ok()?.into()),
+        "FLOAT8" => Value::Float(row.try_get(i).ok()?),
+        "NUMERIC" => Value::Decimal(numeric_text(raw)?),
+        "MONEY" => Value::Decimal(money_text(row.try_get::<PgMoney, _>(i).ok()?)),
+        "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" | "CITEXT" | "UNKNOWN" => {
+            Value::Text(row.try_get(i).ok()?)
+        }
+        "CHAR" => Value::Text(char::from(row.try_get::<i8, _>(i).ok()? as u8).to_string()),
+        "BYTEA" => Value::Bytes(row.try_get(i).ok()?),
+        "JSON" | "JSONB" => Value::Json(row.try_get(i).ok()?),
+        "DATE" => Value::Date(row.try_get(i).ok()?),
+        "TIME" => Value::Time(row.try_get(i).ok()?),
+        "TIMESTAMP" => Value::Timestamp(row.try_get(i).ok()?),
+        "TIMESTAMPTZ" => Value::TimestampTz(row.try_get(i).ok()?),
+        "INTERVAL" => Value::Text(interval_text(&row.try_get::<PgInterval, _>(i).ok()?)),
+        "UUID" => uuid_value(raw)?,
+        "BOOL[]" => array_of::<bool>(row, i, Value::Bool)?,
+        "INT2[]" => array_of::<i16>(row, i, |n| Value::Int(n.into()))?,
+        "INT4[]" => array_of::<i32>(row, i, |n| Value::Int(n.into()))?,
+        "INT8[]" => array_of::<i64>(row, i, Value::Int)?,
+        "FLOAT4[]" => array_of::<f32>(row, i, |n| Value::Float(n.into()))?,
+        "FLOAT8[]" => array_of::<f64>(row, i, Value::Float)?,
+        "TEXT[]" | "VARCHAR[]" | "BPCHAR[]" | "NAME[]" => array_of::<String>(row, i, Value::Text)?,
+        "BYTEA[]" => array_of::<Vec<u8>>(row, i, Value::Bytes)?,
+        "JSON[]" | "JSONB[]" => array_of::<serde_json::Value>(row, i, Value::Json)?,
+        "DATE[]" => array_of::<Date>(row, i, Value::Date)?,
+        "TIME[]" => array_of::<Time>(row, i, Value::Time)?,
+        "TIMESTAMP[]" => array_of::<PrimitiveDateTime>(row, i, Value::Timestamp)?,
+        "TIMESTAMPTZ[]" => array_of::<OffsetDateTime>(row, i, Value::TimestampTz)?,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn array_of<T>(row: &PgRow, i: usize, wrap: impl Fn(T) -> Value) -> Option<Value>
+where
+    T: for<'a> Decode<'a, Postgres> + Type<Postgres> + PgHasArrayType,
+{
+    let items: Vec<Option<T>> = row.try_get(i).ok()?;
+    Some(Value::Array(
+        items
+            .into_iter()
+            .map(|item| item.map(&wrap).unwrap_or(Value::Null))
+            .collect(),
+    ))
+}
+
+/// Wire text for unmapped types; binary payloads that are not UTF-8 stay bytes.
+fn fallback(raw: &PgValueRef<'_>) -> Value {
+    match raw.as_bytes() {
+        Ok(bytes) => match str::from_utf8(bytes) {
+            Ok(text) => Value::Text(text.to_string()),
+            Err(_) => Value::Bytes(bytes.to_vec()),
+        },
+        Err(_) => Value::Null,
+    }
+}
+
+fn uuid_value(raw: &PgValueRef<'_>) -> Option<Value> {
+    let bytes = raw.as_bytes().ok()?;
+    match raw.format() {
+        PgValueFormat::Binary => Some(Value::Uuid(bytes.try_into().ok()?)),
+        PgValueFormat::Text => Some(Value::Text(str::from_utf8(bytes).ok()?.to_string())),
+    }
+}
+
+fn numeric_text(raw: &PgValueRef<'_>) -> Option<String> {
+    let bytes = raw.as_bytes().ok()?;
+    match raw.format() {
+        PgValueFormat::Text => Some(str::from_utf8(bytes).ok()?.to_string()),
+        PgValueFormat::Binary => numeric_binary_text(bytes),
+    }
+}
+
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+/// Render the binary `NUMERIC` wire format (base-10000 digit groups) as decimal text.
+fn numeric_binary_text(bytes: &[u8]) -> Option<String> {
+    let word = |at: usize| -> Option<u16> {
+        Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+    };
+    let ndigits = word(0)? as usize;
+    let weight = word(2)? as i16 as i32;
+    let sign = word(4)?;
+    let dscale = word(6)? as usize;
+    match sign {
+        NUMERIC_NAN => return Some("NaN".into()),
+        NUMERIC_PINF => return Some("Infinity".into()),
+        NUMERIC_NINF => return Some("-Infinity".into()),
+        _ => {}
+    }
+    let digits: Vec<u16> = (0..ndigits)
+        .map(|k| word(8 + k * 2))
+        .collect::<Option<_>>()?;
+    let group = |idx: i32| -> u16 {
+        usize::try_from(idx)
+            .ok()
+            .and_then(|i| digits.get(i).copied())
+            .unwrap_or(0)
+    };
+
+    let mut out = String::new();
+    if sign == NUMERIC_NEG {
+        out.push('-');
+    }
+    if weight < 0 {
+        out.push('0');
+    } else {
+        out.push_str(&group(0).to_string());
+        for idx in 1..=weight {
+            out.push_str(&format!("{:04}", group(idx)));
+        }
+    }
+    if dscale > 0 {
+        let mut frac = String::with_capacity(dscale + 4);
+        let mut idx = weight + 1;
+        while frac.len() < dscale {
+            frac.push_str(&format!("{:04}", group(idx)));
+            idx += 1;
+        }
+        frac.truncate(dscale);
+        out.push('.');
+        out.push_str(&frac);
+    }
+    Some(out)
+}
+
+/// `MONEY` is a 64-bit count of the smallest currency unit; assume two decimals.
+fn money_text(money: PgMoney) -> String {
+    let cents = money.0;
+    let sign = if cents < 0 { "-" } else { "" };
+    let abs = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", abs / 100, abs % 100)
+}
+
+fn interval_text(iv: &PgInterval) -> String {
+    let mut parts = Vec::new();
+    let (years, months) = (iv.months / 12, iv.months % 12);
+    if years != 0 {
+        parts.push(format!(
+            "{years} year{}",
+            if years.abs() == 1 { "" } else { "s" }
+        ));
+    }
+    if months != 0 {
+        parts.push(format!(
+            "{months} mon{}",
+            if months.abs() == 1 { "" } else { "s" }
+        ));
+    }
+    if iv.days != 0 {
+        parts.push(format!(
+            "{} day{}",
+            iv.days,
+            if iv.days.abs() == 1 { "" } else { "s" }
+        ));
+    }
+    if iv.microseconds != 0 || parts.is_empty() {
+        let sign = if iv.microseconds < 0 { "-" } else { "" };
+        let us = iv.microseconds.unsigned_abs();
+        let secs = us / 1_000_000;
+        let mut clock = format!(
+            "{sign}{:02}:{:02}:{:02}",
+            secs / 3600,
+            (secs / 60) % 60,
+            secs % 60
+        );
+        let frac = us % 1_000_000;
+        if frac != 0 {
+            let digits = format!("{frac:06}");
+            clock.push('.');
+            clock.push_str(digits.trim_end_matches('0'));
+        }
+        parts.push(clock);
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numeric_bytes(weight: i16, sign: u16, dscale: u16, digits: &[u16]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(digits.len() as u16).to_be_bytes());
+        out.extend_from_slice(&weight.to_be_bytes());
+        out.extend_from_slice(&sign.to_be_bytes());
+        out.extend_from_slice(&dscale.to_be_bytes());
+        for d in digits {
+            out.extend_from_slice(&d.to_be_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn numeric_integer_and_fraction() {
+        // 12345.678 → groups [1, 2345, 6780], weight 1, dscale 3
+        let bytes = numeric_bytes(1, 0, 3, &[1, 2345, 6780]);
+        assert_eq!(numeric_binary_text(&bytes).unwrap(), "12345.678");
+    }
+
+    #[test]
+    fn numeric_small_negative_fraction() {
+        // -0.00012 → groups [1, 2000], weight -1, dscale 5
+        let bytes = numeric_bytes(-1, NUMERIC_NEG, 5, &[1, 2000]);
+        assert_eq!(numeric_binary_text(&bytes).unwrap(), "-0.00012");
+    }
+
+    #[test]
+    fn numeric_trailing_zero_groups_are_implicit() {
+        // 1000000 → groups [100], weight 1, dscale 0
+        let bytes = numeric_bytes(1, 0, 0, &[100]);
+        assert_eq!(numeric_binary_text(&bytes).unwrap(), "1000000");
+    }
+
+    #[test]
+    fn numeric_zero_and_nan() {
+        assert_eq!(
+            numeric_binary_text(&numeric_bytes(0, 0, 2, &[])).unwrap(),
+            "0.00"
+        );
+        assert_eq!(
+            numeric_binary_text(&numeric_bytes(0, NUMERIC_NAN, 0, &[])).unwrap(),
+            "NaN"
+        );
+    }
+
+    #[test]
+    fn money_renders_two_decimals() {
+        assert_eq!(money_text(PgMoney(-1205)), "-12.05");
+        assert_eq!(money_text(PgMoney(7)), "0.07");
+    }
+
+    #[test]
+    fn interval_renders_postgres_style() {
+        let iv = PgInterval {
+            months: 14,
+            days: 3,
+            microseconds: 3_723_500_000,
+        };
+        assert_eq!(interval_text(&iv), "1 year 2 mons 3 days 01:02:03.5");
+        let zero = PgInterval {
+            months: 0,
+            days: 0,
+            microseconds: 0,
+        };
+        assert_eq!(interval_text(&zero), "00:00:00");
+    }
+}
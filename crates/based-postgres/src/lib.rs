@@ -1,11 +1,29 @@
-//! PostgreSQL configuration, connection options, query execution, and EXPLAIN parsing.
+//! PostgreSQL configuration, connection options, libpq service and password files, streaming query execution, script runs,
+//! manual transactions, cancellation, statement timeouts, typed value decoding, and EXPLAIN
+//! parsing.
 
+pub mod cancel;
 pub mod config;
+pub mod decode;
 pub mod explain;
+pub mod libpq;
 pub mod mutations;
+mod params;
+pub mod script;
+pub mod stream;
+pub mod timeout;
+pub mod transaction;
 
+pub use cancel::{PgCancelHandle, execute_sql_cancellable, is_query_cancelled};
 pub use config::{
-    PostgresConfig, SslMode, pg_connect_options, pg_ssl_mode, postgres_uri, psql_command,
+    PgSettings, PostgresConfig, SslMode, TargetSessionAttrs, connect_pool, pg_connect_options,
+    pg_ssl_mode, postgres_uri, psql_command,
 };
+pub use decode::{pg_row_values, pg_value};
 pub use explain::{PlanNode, parse_pg_explain_json};
-pub use mutations::{QueryColumn, delete_row, execute_sql, insert_row};
+pub use libpq::{lookup_service, pgpass_password, resolve_libpq};
+pub use mutations::{QueryColumn, delete_row, insert_row};
+pub use script::{BoundStatement, PgScriptSession, ScriptStep, execute_script};
+pub use stream::{QueryResult, STREAM_BATCH_ROWS, StreamSummary, execute_sql, stream_sql};
+pub use timeout::execute_sql_with_timeout;
+pub use transaction::PgManualTx;
@@ -0,0 +1,360 @@
+//! libpq client files: `service` entries from `pg_service.conf` and passwords from
+//! `.pgpass`, resolved the way `psql` resolves them so both connect to the same server.
+//!
+//! Service lookup: the user file (`PGSERVICEFILE`, else `~/.pg_service.conf`) is searched
+//! first; only when it lacks the service is `$PGSYSCONFDIR/pg_service.conf` tried. The
+//! password file is `PGPASSFILE`, else `~/.pgpass`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::config::{PostgresConfig, SslMode, TargetSessionAttrs};
+
+/// `config` with its `service` applied and, when it still has no password, the first
+/// matching `.pgpass` entry. Values set on `config` win over the service; `port` 5432 and
+/// `ssl_mode` prefer count as unset, as they are libpq's defaults.
+pub fn resolve_libpq(config: &PostgresConfig) -> Result<PostgresConfig> {
+    let mut out = config.clone();
+    if let Some(name) = &config.settings.service {
+        let params = lookup_service(name)?;
+        apply_service(&mut out, &params).with_context(|| format!("service {name:?}"))?;
+    }
+    if out.host.is_empty() {
+        out.host = "localhost".into();
+    }
+    if out.database.is_empty() {
+        out.database = out.username.clone();
+    }
+    if out.password.is_empty()
+        && let Some(password) = pgpass_password(&out.host, out.port, &out.database, &out.username)?
+    {
+        out.password = password;
+    }
+    Ok(out)
+}
+
+/// Keywords of the service `name`, from the first file that defines it.
+pub fn lookup_service(name: &str) -> Result<HashMap<String, String>> {
+    let mut searched = Vec::new();
+    for path in service_files() {
+        if !path.is_file() {
+            continue;
+        }
+        let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        if let Some(params) = find_service(&raw, name) {
+            return Ok(params);
+        }
+        searched.push(path.display().to_string());
+    }
+    if searched.is_empty() {
+        bail!("service {name:?} not found: no pg_service.conf (set PGSERVICEFILE or PGSYSCONFDIR)");
+    }
+    bail!("service {name:?} not found in {}", searched.join(", "))
+}
+
+fn service_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    match env::var_os("PGSERVICEFILE") {
+        Some(path) => files.push(PathBuf::from(path)),
+        None => files.extend(user_file(".pg_service.conf")),
+    }
+    if let Some(dir) = env::var_os("PGSYSCONFDIR") {
+        files.push(Path::new(&dir).join("pg_service.conf"));
+    }
+    files
+}
+
+/// `~/<unix_name>`, or `%APPDATA%\postgresql\` on Windows, where libpq drops the dot.
+fn user_file(unix_name: &str) -> Option<PathBuf> {
+    if cfg!(windows) {
+        dirs::config_dir().map(|dir| {
+            dir.join("postgresql")
+                .join(unix_name.trim_start_matches('.'))
+        })
+    } else {
+        dirs::home_dir().map(|home| home.join(unix_name))
+    }
+}
+
+/// The `[name]` section of a `pg_service.conf`, keyword → value.
+fn find_service(raw: &str, name: &str) -> Option<HashMap<String, String>> {
+    let mut current: Option<&str> = None;
+    let mut params: Option<HashMap<String, String>> = None;
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if params.is_some() {
+                break;
+            }
+            current = Some(section.trim());
+            if current == Some(name) {
+                params = Some(HashMap::new());
+            }
+            continue;
+        }
+        if current == Some(name)
+            && let Some(params) = params.as_mut()
+            && let Some((key, value)) = line.split_once('=')
+        {
+            params.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    params
+}
+
+/// Fill what `config` leaves unset from service keywords. Keywords Based does not use
+/// (`hostaddr`, `keepalives`, …) are ignored.
+fn apply_service(config: &mut PostgresConfig, params: &HashMap<String, String>) -> Result<()> {
+    let get = |key: &str| params.get(key).filter(|v| !v.is_empty());
+    let ports: Vec<u16> = match get("port") {
+        Some(raw) => raw
+            .split(',')
+            .map(|p| {
+                p.trim()
+                    .parse()
+                    .with_context(|| format!("invalid port {p:?}"))
+            })
+            .collect::<Result<_>>()?,
+        None => vec![],
+    };
+    if config.port == 5432
+        && let Some(port) = ports.first()
+    {
+        config.port = *port;
+    }
+    if config.host.is_empty()
+        && let Some(hosts) = get("host")
+    {
+        let hosts: Vec<&str> = hosts.split(',').map(str::trim).collect();
+        config.host = hosts[0].to_string();
+        if config.settings.failover_hosts.is_empty() {
+            // libpq: one port for every host, or one per host.
+            let port_of = |i: usize| match ports.as_slice() {
+                [] => 5432,
+                [port] => *port,
+                ports => ports.get(i).copied().unwrap_or(5432),
+            };
+            config.settings.failover_hosts = hosts[1..]
+                .iter()
+                .enumerate()
+                .map(|(i, host)| (host.to_string(), port_of(i + 1)))
+                .collect();
+        }
+    }
+    let fill = |field: &mut String, key: &str| {
+        if field.is_empty()
+            && let Some(value) = get(key)
+        {
+            *field = value.clone();
+        }
+    };
+    fill(&mut config.database, "dbname");
+    fill(&mut config.username, "user");
+    fill(&mut config.password, "password");
+    if matches!(config.ssl_mode, SslMode::Prefer)
+        && let Some(mode) = get("sslmode")
+    {
+        config.ssl_mode = parse_ssl_mode(mode)?;
+    }
+    let settings = &mut config.settings;
+    let fill_opt = |field: &mut Option<String>, key: &str| {
+        if field.is_none() {
+            *field = get(key).cloned();
+        }
+    };
+    let fill_path = |field: &mut Option<PathBuf>, key: &str| {
+        if field.is_none() {
+            *field = get(key).map(PathBuf::from);
+        }
+    };
+    fill_path(&mut settings.ssl_root_cert, "sslrootcert");
+    fill_path(&mut settings.ssl_cert, "sslcert");
+    fill_path(&mut settings.ssl_key, "sslkey");
+    fill_opt(&mut settings.application_name, "application_name");
+    fill_opt(&mut settings.options, "options");
+    if settings.connect_timeout.is_none()
+        && let Some(secs) = get("connect_timeout")
+    {
+        settings.connect_timeout = Some(
+            secs.parse()
+                .with_context(|| format!("invalid connect_timeout {secs:?}"))?,
+        );
+    }
+    if settings.target_session_attrs == TargetSessionAttrs::Any
+        && let Some(attrs) = get("target_session_attrs")
+    {
+        settings.target_session_attrs = parse_target_session_attrs(attrs)?;
+    }
+    Ok(())
+}
+
+fn parse_ssl_mode(raw: &str) -> Result<SslMode> {
+    Ok(match raw {
+        "disable" => SslMode::Disable,
+        // Based has no `allow`; both try plain and TLS.
+        "allow" | "prefer" => SslMode::Prefer,
+        "require" => SslMode::Require,
+        "verify-ca" => SslMode::VerifyCa,
+        "verify-full" => SslMode::VerifyFull,
+        other => bail!("invalid sslmode {other:?}"),
+    })
+}
+
+fn parse_target_session_attrs(raw: &str) -> Result<TargetSessionAttrs> {
+    Ok(match raw {
+        "any" => TargetSessionAttrs::Any,
+        "read-write" => TargetSessionAttrs::ReadWrite,
+        "read-only" => TargetSessionAttrs::ReadOnly,
+        "primary" => TargetSessionAttrs::Primary,
+        "standby" => TargetSessionAttrs::Standby,
+        "prefer-standby" => TargetSessionAttrs::PreferStandby,
+        other => bail!("invalid target_session_attrs {other:?}"),
+    })
+}
+
+/// Password of the first `.pgpass` line matching `host:port:database:username` (`*`
+/// matches anything). On Unix a file readable by group or others is ignored, as libpq
+/// does.
+pub fn pgpass_password(
+    host: &str,
+    port: u16,
+    database: &str,
+    username: &str,
+) -> Result<Option<String>> {
+    let Some(path) = env::var_os("PGPASSFILE")
+        .map(PathBuf::from)
+        .or_else(|| user_file(".pgpass"))
+    else {
+        return Ok(None);
+    };
+    if !path.is_file() || !private_file(&path) {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    Ok(match_pgpass(&raw, host, port, database, username))
+}
+
+#[cfg(unix)]
+fn private_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    let private = fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o077 == 0);
+    if !private {
+        log::warn!(
+            "ignoring {}: permissions should be u=rw (0600) or less",
+            path.display()
+        );
+    }
+    private
+}
+
+#[cfg(not(unix))]
+fn private_file(_path: &Path) -> bool {
+    true
+}
+
+fn match_pgpass(
+    raw: &str,
+    host: &str,
+    port: u16,
+    database: &str,
+    username: &str,
+) -> Option<String> {
+    let port = port.to_string();
+    let wanted = [host, port.as_str(), database, username];
+    raw.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .find_map(|line| {
+            let fields = split_pgpass_line(line);
+            let [h, p, d, u, password] = fields.as_slice() else {
+                return None;
+            };
+            [h, p, d, u]
+                .iter()
+                .zip(wanted)
+                .all(|(field, want)| *field == "*" || *field == want)
+                .then(|| password.clone())
+        })
+}
+
+/// Split on unescaped `:`; `\:` and `\\` stand for `:` and `\`.
+fn split_pgpass_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => fields.last_mut().unwrap().extend(chars.next()),
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PgSettings;
+
+    fn service_config() -> PostgresConfig {
+        PostgresConfig {
+            label: "analytics".into(),
+            host: String::new(),
+            port: 5432,
+            database: "events".into(),
+            username: String::new(),
+            password: String::new(),
+            ssl_mode: SslMode::Prefer,
+            settings: PgSettings {
+                service: Some("analytics".into()),
+                ..PgSettings::default()
+            },
+            ssh: None,
+        }
+    }
+
+    #[test]
+    fn service_fills_only_unset_fields() {
+        let raw = "# shared\n[other]\nhost=wrong\n\n[analytics]\nhost=pg-a,pg-b\nport=6432\ndbname=analytics\nuser = reporter\nsslmode=verify-full\napplication_name=psql\nkeepalives=1\n";
+        let params = find_service(raw, "analytics").unwrap();
+        assert!(find_service(raw, "missing").is_none());
+        let mut config = service_config();
+        apply_service(&mut config, &params).unwrap();
+        assert_eq!(config.host, "pg-a");
+        assert_eq!(config.port, 6432);
+        assert_eq!(config.settings.failover_hosts, [("pg-b".to_string(), 6432)]);
+        assert_eq!(config.database, "events");
+        assert_eq!(config.username, "reporter");
+        assert!(matches!(config.ssl_mode, SslMode::VerifyFull));
+        assert_eq!(config.settings.application_name.as_deref(), Some("psql"));
+
+        let mut explicit = service_config();
+        explicit.host = "db.local".into();
+        explicit.ssl_mode = SslMode::Disable;
+        apply_service(&mut explicit, &params).unwrap();
+        assert_eq!(explicit.host, "db.local");
+        assert!(explicit.settings.failover_hosts.is_empty());
+        assert!(matches!(explicit.ssl_mode, SslMode::Disable));
+    }
+
+    #[test]
+    fn pgpass_first_match_with_wildcards_and_escapes() {
+        let raw =
+            "# comment\nother:5432:*:app:nope\ndb.example:*:*:app:first\\:pw\n*:*:*:*:fallback\n";
+        assert_eq!(
+            match_pgpass(raw, "db.example", 6543, "analytics", "app").as_deref(),
+            Some("first:pw")
+        );
+        assert_eq!(
+            match_pgpass(raw, "db.example", 6543, "analytics", "alice").as_deref(),
+            Some("fallback")
+        );
+        assert_eq!(match_pgpass("h:1:d:u\n", "h", 1, "d", "u"), None);
+    }
+}
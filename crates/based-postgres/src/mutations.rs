@@ -1,5 +1,5 @@
 use anyhow::Result;
-use sqlx::{AssertSqlSafe, Column as SqlxColumn, PgPool, Row, TypeInfo};
+use sqlx::{AssertSqlSafe, PgPool};
 
 #[derive(Debug, Clone)]
 pub struct QueryColumn {
@@ -48,45 +48,3 @@ pub async fn delete_row(
         .await?;
     Ok(r.rows_affected())
 }
-
-pub async fn execute_sql(
-    pool: &PgPool,
-    sql: &str,
-) -> Result<(Vec<QueryColumn>, Vec<Vec<String>>, u64)> {
-    let t = sql.trim_start();
-    let lower = t.to_ascii_lowercase();
-    if lower.starts_with("select")
-        || lower.starts_with("with")
-        || lower.starts_with("explain")
-        || lower.starts_with("show")
-    {
-        let rows = sqlx::query(AssertSqlSafe(sql)).fetch_all(pool).await?;
-        let columns: Vec<QueryColumn> = rows
-            .first()
-            .map(|r| {
-                r.columns()
-                    .iter()
-                    .map(|c| QueryColumn {
-                        name: c.name().to_string(),
-                        type_name: c.type_info().name().to_string(),
-                    })
-                    .collect()
-            })
-            .unwrap_or_default();
-        let data: Vec<Vec<String>> = rows
-            .iter()
-            .map(|row| {
-                (0..row.len())
-                    .map(|i| {
-                        row.try_get::<String, _>(i)
-                            .unwrap_or_else(|_| "".to_string())
-                    })
-                    .collect()
-            })
-            .collect();
-        Ok((columns, data, 0))
-    } else {
-        let r = sqlx::query(AssertSqlSafe(sql)).execute(pool).await?;
-        Ok((vec![], vec![], r.rows_affected()))
-    }
-}
@@ -0,0 +1,143 @@
+//! Parameter types for text values.
+//!
+//! Variables without a declared type reach the driver as [`Value::Text`]. Sent as `TEXT`,
+//! `WHERE id = $1` on an integer column fails with "operator does not exist: integer =
+//! text", so statements with text parameters are first prepared without parameter types
+//! and each text value is read as the type the server infers for it, the way Postgres
+//! reads a quoted literal. Types without a driver mapping (`NUMERIC`, `UUID`, `JSONB`, …)
+//! keep the text, so the SQL casts them (`{{amount}}::numeric`).
+
+use std::borrow::Cow;
+
+use anyhow::{Result, anyhow};
+use based_core::Value;
+use sqlx::{AssertSqlSafe, Either, Executor, PgConnection, SqlSafeStr, Statement, TypeInfo};
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+/// Prepared ahead of `sql` when inferring types. Inference prepares the statement
+/// without parameter types, so it is cached under its own text instead of the one the
+/// run prepares with the bound types.
+const INFER_PREFIX: &str = "/* based: parameter types */\n";
+
+/// `params` with each text value read as the type the server infers for its parameter.
+/// Statements without text parameters are not prepared here.
+pub(crate) async fn infer_text_params<'p>(
+    conn: &mut PgConnection,
+    sql: &str,
+    params: &'p [Value],
+) -> Result<Cow<'p, [Value]>> {
+    if !params.iter().any(|p| matches!(p, Value::Text(_))) {
+        return Ok(Cow::Borrowed(params));
+    }
+    let statement = conn
+        .prepare(AssertSqlSafe(format!("{INFER_PREFIX}{sql}")).into_sql_str())
+        .await?;
+    let Some(Either::Left(types)) = statement.parameters() else {
+        return Ok(Cow::Borrowed(params));
+    };
+    params
+        .iter()
+        .enumerate()
+        .map(|(i, param)| match (param, types.get(i)) {
+            (Value::Text(raw), Some(ty)) => text_as(raw, ty.name()).map_err(|expected| {
+                anyhow!("parameter ${}: expected {expected}, got {raw:?}", i + 1)
+            }),
+            _ => Ok(param.clone()),
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Cow::Owned)
+}
+
+/// `raw` as a value of the Postgres type `type_name`; text when the type has no driver
+/// mapping. The error names what the text should have looked like.
+fn text_as(raw: &str, type_name: &str) -> Result<Value, &'static str> {
+    let trimmed = raw.trim();
+    let value = match type_name {
+        "INT2" | "INT4" | "INT8" | "OID" => Value::Int(trimmed.parse().map_err(|_| "an integer")?),
+        "FLOAT4" | "FLOAT8" => Value::Float(trimmed.parse().map_err(|_| "a number")?),
+        "BOOL" => Value::Bool(parse_bool(trimmed).ok_or("true or false")?),
+        "DATE" => Value::Date(parse_date(trimmed).ok_or("a YYYY-MM-DD date")?),
+        "TIME" => Value::Time(parse_time(trimmed).ok_or("an HH:MM[:SS] time")?),
+        "TIMESTAMP" => Value::Timestamp(parse_timestamp(trimmed).ok_or("a timestamp")?),
+        // Without an offset it stays a plain timestamp, which the server reads in the
+        // session time zone like a literal.
+        "TIMESTAMPTZ" => OffsetDateTime::parse(&trimmed.replacen(' ', "T", 1), &Rfc3339)
+            .map(Value::TimestampTz)
+            .ok()
+            .or_else(|| parse_timestamp(trimmed).map(Value::Timestamp))
+            .ok_or("a timestamp")?,
+        _ => Value::Text(raw.to_string()),
+    };
+    Ok(value)
+}
+
+/// Boolean spellings Postgres accepts in a literal.
+fn parse_bool(raw: &str) -> Option<bool> {
+    match raw.to_ascii_lowercase().as_str() {
+        "t" | "true" | "y" | "yes" | "on" | "1" => Some(true),
+        "f" | "false" | "n" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_date(raw: &str) -> Option<Date> {
+    Date::parse(raw, format_description!("[year]-[month]-[day]")).ok()
+}
+
+fn parse_time(raw: &str) -> Option<Time> {
+    Time::parse(
+        raw,
+        format_description!("[hour]:[minute]:[second].[subsecond]"),
+    )
+    .or_else(|_| Time::parse(raw, format_description!("[hour]:[minute]:[second]")))
+    .or_else(|_| Time::parse(raw, format_description!("[hour]:[minute]")))
+    .ok()
+}
+
+/// `YYYY-MM-DD[ HH:MM[:SS]]`, with a space or `T` before the time.
+fn parse_timestamp(raw: &str) -> Option<PrimitiveDateTime> {
+    let (date, time) = match raw.split_once([' ', 'T']) {
+        Some((date, time)) => (date, parse_time(time)?),
+        None => (raw, Time::MIDNIGHT),
+    };
+    Some(PrimitiveDateTime::new(parse_date(date)?, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_takes_the_inferred_type() {
+        assert_eq!(text_as(" 42 ", "INT4"), Ok(Value::Int(42)));
+        assert_eq!(text_as("1.5", "FLOAT8"), Ok(Value::Float(1.5)));
+        assert_eq!(text_as("off", "BOOL"), Ok(Value::Bool(false)));
+        assert_eq!(text_as("02134", "TEXT"), Ok(Value::Text("02134".into())));
+        assert_eq!(text_as("9.99", "NUMERIC"), Ok(Value::Text("9.99".into())));
+        assert_eq!(text_as("abc", "INT8"), Err("an integer"));
+    }
+
+    #[test]
+    fn timestamps_keep_the_literal_meaning() {
+        let day = parse_date("2024-03-01").unwrap();
+        assert_eq!(text_as("2024-03-01", "DATE"), Ok(Value::Date(day)));
+        assert_eq!(
+            text_as("2024-03-01", "TIMESTAMPTZ"),
+            Ok(Value::Timestamp(PrimitiveDateTime::new(
+                day,
+                Time::MIDNIGHT
+            )))
+        );
+        assert!(matches!(
+            text_as("2024-03-01 10:30:00+02:00", "TIMESTAMPTZ"),
+            Ok(Value::TimestampTz(ts)) if ts.offset().whole_hours() == 2
+        ));
+        assert!(matches!(
+            text_as("2024-03-01T10:30", "TIMESTAMP"),
+            Ok(Value::Timestamp(ts)) if ts.hour() == 10 && ts.minute() == 30
+        ));
+        assert_eq!(text_as("10:30:05.25", "TIME").map(|_| ()), Ok(()));
+    }
+}
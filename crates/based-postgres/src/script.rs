@@ -0,0 +1,249 @@
+//! Run a script statement by statement on one connection ("Run script").
+//!
+//! Session state (temp tables, `SET`, an explicit `BEGIN`) carries from one statement to
+//! the next, and on to the statements "Continue" runs after a failure: a
+//! [`PgScriptSession`] keeps its connection until the script is finished. The timeout
+//! is a session-level `statement_timeout` rather than the per-statement `SET LOCAL`
+//! transaction of [`execute_sql_with_timeout`]. While a transaction the script opened
+//! is in progress, each statement runs under a savepoint, so a failure rolls back only
+//! that statement and the transaction stays usable.
+//!
+//! [`execute_sql_with_timeout`]: crate::timeout::execute_sql_with_timeout
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use based_core::Value;
+use based_query::{Dialect, TokenKind, tokenize};
+use sqlx::pool::PoolConnection;
+use sqlx::{AssertSqlSafe, Executor, PgPool, Postgres};
+use tokio::sync::Mutex;
+
+use crate::cancel::PgCancelHandle;
+use crate::stream::{QueryResult, execute_sql};
+use crate::timeout::{as_timeout, controls_transaction, statement_timeout_setting};
+
+/// SQLSTATE `no_active_sql_transaction`.
+const NO_ACTIVE_SQL_TRANSACTION: &str = "25P01";
+
+/// One statement of a script with the values bound to its `$1`, `$2`, ….
+#[derive(Debug, Clone, Default)]
+pub struct BoundStatement {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+/// Outcome of one statement of [`execute_script`].
+#[derive(Debug)]
+pub struct ScriptStep {
+    pub elapsed: Duration,
+    pub result: Result<QueryResult>,
+}
+
+/// Pooled connection a script runs on; clones share it. It stays checked out between
+/// batches, so "Continue" after a failure sees the session the script built up.
+#[derive(Clone)]
+pub struct PgScriptSession {
+    conn: Arc<Mutex<Option<ScriptConnection>>>,
+}
+
+struct ScriptConnection {
+    conn: PoolConnection<Postgres>,
+    /// A transaction the script opened is in progress.
+    in_transaction: bool,
+}
+
+/// Run `statements` in order on a single pooled connection whose PID is exposed via
+/// `handle`, stopping after the first failure, then [`finish`](PgScriptSession::finish)
+/// the session. Each statement is bounded by `timeout`.
+///
+/// `Err` means the script never started; statement errors are in the returned steps.
+pub async fn execute_script(
+    pool: &PgPool,
+    statements: &[BoundStatement],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+    handle: &PgCancelHandle,
+) -> Result<Vec<ScriptStep>> {
+    let session = PgScriptSession::start(pool).await?;
+    let steps = session
+        .execute(statements, max_rows, timeout, handle)
+        .await?;
+    session.finish().await?;
+    Ok(steps)
+}
+
+impl PgScriptSession {
+    /// Check out a connection from `pool` for a script.
+    pub async fn start(pool: &PgPool) -> Result<Self> {
+        let conn = pool.acquire().await.context("acquire connection")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(Some(ScriptConnection {
+                conn,
+                in_transaction: false,
+            }))),
+        })
+    }
+
+    /// Run `statements` in order, stopping after the first failure; see
+    /// [`execute_script`]. The next call continues on the same connection.
+    ///
+    /// A session that stopped on a failure closes its connection when the last clone is
+    /// dropped without [`finish`](Self::finish), rather than return its state to the
+    /// pool.
+    pub async fn execute(
+        &self,
+        statements: &[BoundStatement],
+        max_rows: Option<u64>,
+        timeout: Option<Duration>,
+        handle: &PgCancelHandle,
+    ) -> Result<Vec<ScriptStep>> {
+        let mut guard = self.conn.lock().await;
+        let Some(script) = guard.as_mut() else {
+            bail!("script has already finished");
+        };
+        handle.attach(&mut script.conn).await?;
+        let steps = script
+            .run_steps(statements, max_rows, timeout, handle)
+            .await;
+        handle.detach();
+        steps
+    }
+
+    /// Roll back a transaction the script left open (or aborted), reset its timeout,
+    /// and return the connection to the pool.
+    pub async fn finish(&self) -> Result<()> {
+        let Some(mut script) = self.conn.lock().await.take() else {
+            return Ok(());
+        };
+        // Outside a transaction this is only a server warning.
+        script.conn.execute("ROLLBACK").await?;
+        script.conn.execute("RESET statement_timeout").await?;
+        Ok(())
+    }
+}
+
+impl ScriptConnection {
+    async fn run_steps(
+        &mut self,
+        statements: &[BoundStatement],
+        max_rows: Option<u64>,
+        timeout: Option<Duration>,
+        handle: &PgCancelHandle,
+    ) -> Result<Vec<ScriptStep>> {
+        if let Some(timeout) = timeout {
+            let setting = statement_timeout_setting(timeout);
+            self.conn
+                .execute(AssertSqlSafe(format!("SET statement_timeout = {setting}")))
+                .await?;
+        }
+        let mut steps = Vec::with_capacity(statements.len());
+        for stmt in statements {
+            let start = Instant::now();
+            let result = self
+                .run_statement(stmt, max_rows)
+                .await
+                .map_err(|e| as_timeout(e, timeout, handle.cancel_requested()));
+            let failed = result.is_err();
+            steps.push(ScriptStep {
+                elapsed: start.elapsed(),
+                result,
+            });
+            if failed {
+                self.conn.close_on_drop();
+                break;
+            }
+        }
+        Ok(steps)
+    }
+
+    /// Run `stmt`, under a savepoint while the script's transaction is in progress.
+    async fn run_statement(
+        &mut self,
+        stmt: &BoundStatement,
+        max_rows: Option<u64>,
+    ) -> Result<QueryResult> {
+        // `SAVEPOINT`, `RELEASE`, and `ROLLBACK TO` would interleave with ours.
+        let savepoint =
+            self.in_transaction && !controls_transaction(&stmt.sql) && self.savepoint().await?;
+        let result = execute_sql(&mut self.conn, &stmt.sql, &stmt.params, max_rows).await;
+        if savepoint {
+            if result.is_err() {
+                self.conn
+                    .execute("ROLLBACK TO SAVEPOINT based_script_statement")
+                    .await?;
+            }
+            self.conn
+                .execute("RELEASE SAVEPOINT based_script_statement")
+                .await?;
+        }
+        if result.is_ok()
+            && let Some(open) = transaction_effect(&stmt.sql)
+        {
+            self.in_transaction = open;
+        }
+        result
+    }
+
+    /// Set the savepoint for the next statement; `false` when no transaction is in
+    /// progress after all (e.g. `PREPARE TRANSACTION` ended it).
+    async fn savepoint(&mut self) -> Result<bool> {
+        match self.conn.execute("SAVEPOINT based_script_statement").await {
+            Ok(_) => Ok(true),
+            Err(e)
+                if e.as_database_error().and_then(|db| db.code()).as_deref()
+                    == Some(NO_ACTIVE_SQL_TRANSACTION) =>
+            {
+                self.in_transaction = false;
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// How `sql` changes the transaction state when it succeeds: `Some(true)` when a
+/// transaction is in progress afterwards, `Some(false)` when none is, `None` for
+/// everything else (including `SAVEPOINT` and `ROLLBACK TO`).
+pub(crate) fn transaction_effect(sql: &str) -> Option<bool> {
+    let words: Vec<String> = tokenize(sql, Dialect::Postgres)
+        .iter()
+        .filter(|t| t.kind == TokenKind::Word)
+        .map(|t| t.text(sql).to_ascii_uppercase())
+        .collect();
+    let has = |word: &str| words.iter().any(|w| w == word);
+    match words.first()?.as_str() {
+        "BEGIN" | "START" => Some(true),
+        "ROLLBACK" if has("TO") => None,
+        "COMMIT" | "END" | "ROLLBACK" | "ABORT" if has("PREPARED") => None,
+        "PREPARE" if has("TRANSACTION") => Some(false),
+        // `AND CHAIN` starts the next transaction straight away.
+        "COMMIT" | "END" | "ROLLBACK" | "ABORT" => Some(has("CHAIN") && !has("NO")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transaction_effect_follows_begin_and_commit() {
+        assert_eq!(transaction_effect("-- go\nBEGIN"), Some(true));
+        assert_eq!(
+            transaction_effect("start transaction isolation level serializable"),
+            Some(true)
+        );
+        assert_eq!(transaction_effect("COMMIT"), Some(false));
+        assert_eq!(transaction_effect("rollback work"), Some(false));
+        assert_eq!(transaction_effect("COMMIT AND CHAIN"), Some(true));
+        assert_eq!(transaction_effect("COMMIT AND NO CHAIN"), Some(false));
+        assert_eq!(transaction_effect("ROLLBACK TO SAVEPOINT a"), None);
+        assert_eq!(transaction_effect("COMMIT PREPARED 'tx1'"), None);
+        assert_eq!(transaction_effect("PREPARE TRANSACTION 'tx1'"), Some(false));
+        assert_eq!(transaction_effect("PREPARE q AS SELECT 1"), None);
+        assert_eq!(transaction_effect("SAVEPOINT a"), None);
+        assert_eq!(transaction_effect("UPDATE t SET note = 'begin'"), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Streaming query execution with a row cap.
+//!
+//! Rows are pulled through a sqlx `fetch_many` stream and handed out in batches, so a
+//! `SELECT *` on a large table never materialises more than the cap in memory.
+
+use std::mem;
+
+use anyhow::Result;
+use based_core::Value;
+use based_query::{Dialect, statements_in_script};
+use futures::TryStreamExt;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::Query;
+use sqlx::{
+    AssertSqlSafe, Column as SqlxColumn, Either, Executor, PgConnection, Postgres, Row, TypeInfo,
+};
+
+use crate::decode::pg_row_values;
+use crate::mutations::QueryColumn;
+use crate::params::infer_text_params;
+
+/// Rows per batch handed to the [`stream_sql`] callback.
+pub const STREAM_BATCH_ROWS: usize = 500;
+
+/// Outcome of [`stream_sql`]; rows themselves went to the batch callback.
+#[derive(Debug, Clone, Default)]
+pub struct StreamSummary {
+    pub columns: Vec<QueryColumn>,
+    pub row_count: u64,
+    /// Rows changed by a statement without a result set.
+    pub rows_affected: u64,
+    /// `Some(cap)` when the stream was stopped at the cap with rows remaining.
+    pub truncated_at: Option<u64>,
+}
+
+/// Collected result of [`execute_sql`].
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    pub columns: Vec<QueryColumn>,
+    pub rows: Vec<Vec<Value>>,
+    /// Rows changed by a statement without a result set.
+    pub rows_affected: u64,
+    /// `Some(cap)` when more rows were available than were fetched.
+    pub truncated_at: Option<u64>,
+}
+
+impl QueryResult {
+    /// `"truncated at N rows"` when the row cap stopped the fetch.
+    pub fn truncation_notice(&self) -> Option<String> {
+        self.truncated_at.map(|n| format!("truncated at {n} rows"))
+    }
+}
+
+/// Run `sql` with `params` bound to `$1`, `$2`, … and stream result rows to `on_batch`
+/// in chunks of [`STREAM_BATCH_ROWS`]. Stops after `max_rows` rows (`None` = unbounded).
+///
+/// Text params take the type the server infers for them, as a quoted literal would.
+/// Several statements without params (`BEGIN; …; COMMIT`) go over the simple query
+/// protocol, as psql sends them; rows come from the first statement that returns any,
+/// and the rest still run so their errors surface. Anything else is prepared, so with
+/// params the text must be a single statement.
+pub async fn stream_sql<F>(
+    conn: &mut PgConnection,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    mut on_batch: F,
+) -> Result<StreamSummary>
+where
+    F: FnMut(&[QueryColumn], Vec<Vec<Value>>),
+{
+    let several = params.is_empty() && statements_in_script(sql, Dialect::Postgres).len() > 1;
+    let mut stream = if several {
+        conn.fetch_many(sqlx::raw_sql(AssertSqlSafe(sql)))
+    } else {
+        let params = infer_text_params(conn, sql, params).await?;
+        let query = params
+            .iter()
+            .fold(sqlx::query(AssertSqlSafe(sql)), bind_value);
+        conn.fetch_many(query)
+    };
+    let mut summary = StreamSummary::default();
+    let mut batch = Vec::with_capacity(STREAM_BATCH_ROWS);
+    // The result set is complete (or capped); later rows are drained, not kept.
+    let mut done_with_rows = false;
+    while let Some(step) = stream.try_next().await? {
+        let row = match step {
+            Either::Left(done) => {
+                if summary.columns.is_empty() {
+                    summary.rows_affected += done.rows_affected();
+                } else {
+                    done_with_rows = true;
+                }
+                continue;
+            }
+            Either::Right(row) => row,
+        };
+        if done_with_rows {
+            continue;
+        }
+        if max_rows.is_some_and(|cap| summary.row_count >= cap) {
+            summary.truncated_at = max_rows;
+            if !several {
+                break;
+            }
+            done_with_rows = true;
+            continue;
+        }
+        if summary.columns.is_empty() {
+            summary.columns = row_columns(&row);
+        }
+        batch.push(pg_row_values(&row));
+        summary.row_count += 1;
+        if batch.len() == STREAM_BATCH_ROWS {
+            on_batch(&summary.columns, mem::take(&mut batch));
+        }
+    }
+    if !batch.is_empty() {
+        on_batch(&summary.columns, batch);
+    }
+    Ok(summary)
+}
+
+/// Run `sql` with `params` and collect up to `max_rows` rows.
+pub async fn execute_sql(
+    conn: &mut PgConnection,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+) -> Result<QueryResult> {
+    let mut rows = Vec::new();
+    let summary = stream_sql(conn, sql, params, max_rows, |_, batch| rows.extend(batch)).await?;
+    Ok(QueryResult {
+        columns: summary.columns,
+        rows,
+        rows_affected: summary.rows_affected,
+        truncated_at: summary.truncated_at,
+    })
+}
+
+/// Bind one parameter with its native Postgres type. Kinds without a driver mapping
+/// (decimal, JSON, UUID, arrays) go as text, so the SQL casts them (`$1::jsonb`).
+/// Text binds as `TEXT` once [`infer_text_params`] has read what it could.
+fn bind_value<'q>(
+    query: Query<'q, Postgres, PgArguments>,
+    value: &Value,
+) -> Query<'q, Postgres, PgArguments> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Int(n) => query.bind(*n),
+        Value::Float(f) => query.bind(*f),
+        Value::Text(s) => query.bind(s.clone()),
+        Value::Bytes(b) => query.bind(b.clone()),
+        Value::Date(d) => query.bind(*d),
+        Value::Time(t) => query.bind(*t),
+        Value::Timestamp(ts) => query.bind(*ts),
+        Value::TimestampTz(ts) => query.bind(*ts),
+        Value::Decimal(_) | Value::Json(_) | Value::Uuid(_) | Value::Array(_) => {
+            query.bind(value.to_string())
+        }
+    }
+}
+
+fn row_columns(row: &PgRow) -> Vec<QueryColumn> {
+    row.columns()
+        .iter()
+        .map(|c| QueryColumn {
+            name: c.name().to_string(),
+            type_name: c.type_info().name().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncation_notice_reports_cap() {
+        let result = QueryResult {
+            truncated_at: Some(1000),
+            ..Default::default()
+        };
+        assert_eq!(
+            result.truncation_notice().as_deref(),
+            Some("truncated at 1000 rows")
+        );
+        assert!(QueryResult::default().truncation_notice().is_none());
+    }
+}
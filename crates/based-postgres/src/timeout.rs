@@ -0,0 +1,175 @@
+//! Server-side query timeout via `statement_timeout`.
+//!
+//! The statement runs in a transaction that starts with `SET LOCAL statement_timeout`, so
+//! the limit never leaks to the next user of the pooled connection. Statements that
+//! refuse to run in a transaction block (`VACUUM`, `CREATE DATABASE`, …) are retried
+//! with a session-level `SET` that is `RESET` afterwards. SQL with its own `BEGIN`,
+//! `COMMIT`, or `ROLLBACK` gets the session-level `SET` straight away, so it never ends
+//! or nests inside the wrapper transaction.
+//!
+//! The server aborts a timed-out statement with SQLSTATE `57014`, the code a cancel
+//! request gets too; the [`PgCancelHandle`] a caller cancels through tells them apart.
+//!
+//! [`PgCancelHandle`]: crate::cancel::PgCancelHandle
+
+use std::time::Duration;
+
+use anyhow::Result;
+use based_core::{QueryTimedOut, Value};
+use based_query::{Dialect, StatementKind, classify_statement, statements_in_script};
+use sqlx::{AssertSqlSafe, Connection, Executor, PgConnection};
+
+use crate::stream::{QueryResult, execute_sql};
+
+/// SQLSTATE `query_canceled`, shared by cancel requests and statement timeouts.
+const QUERY_CANCELED: &str = "57014";
+/// SQLSTATE `active_sql_transaction` ("cannot run inside a transaction block").
+const ACTIVE_SQL_TRANSACTION: &str = "25001";
+
+/// [`execute_sql`] on `conn` bounded by `timeout` (`None` = unbounded). A timed-out
+/// statement fails with [`QueryTimedOut`].
+pub async fn execute_sql_with_timeout(
+    conn: &mut PgConnection,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+) -> Result<QueryResult> {
+    run_with_timeout(conn, sql, params, max_rows, timeout)
+        .await
+        .map_err(|e| as_timeout(e, timeout, false))
+}
+
+/// [`execute_sql_with_timeout`] with the server's errors as they are, for callers that
+/// know whether a cancel was requested; see [`as_timeout`].
+pub(crate) async fn run_with_timeout(
+    conn: &mut PgConnection,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+) -> Result<QueryResult> {
+    let Some(timeout) = timeout else {
+        return execute_sql(conn, sql, params, max_rows).await;
+    };
+    let setting = statement_timeout_setting(timeout);
+    if controls_transaction(sql) {
+        return session_scoped(conn, sql, params, max_rows, &setting).await;
+    }
+    match in_local_transaction(conn, sql, params, max_rows, &setting).await {
+        Err(e) if sqlstate(&e).as_deref() == Some(ACTIVE_SQL_TRANSACTION) => {
+            session_scoped(conn, sql, params, max_rows, &setting).await
+        }
+        other => other,
+    }
+}
+
+/// `err` as [`QueryTimedOut`] when the server aborted the statement under a `timeout`
+/// and no cancel was requested, so `statement_timeout` is what stopped it.
+pub(crate) fn as_timeout(
+    err: anyhow::Error,
+    timeout: Option<Duration>,
+    cancel_requested: bool,
+) -> anyhow::Error {
+    match timeout {
+        Some(after) if !cancel_requested && is_query_canceled(&err) => {
+            anyhow::Error::new(QueryTimedOut { after })
+        }
+        _ => err,
+    }
+}
+
+/// Whether `err` is the server aborting a statement (SQLSTATE `57014`), whether at
+/// `statement_timeout` or on a cancel request.
+pub(crate) fn is_query_canceled(err: &anyhow::Error) -> bool {
+    sqlstate(err).as_deref() == Some(QUERY_CANCELED)
+}
+
+/// Whether `sql` begins, ends, or rolls back a transaction itself.
+pub(crate) fn controls_transaction(sql: &str) -> bool {
+    statements_in_script(sql, Dialect::Postgres)
+        .iter()
+        .any(|stmt| {
+            classify_statement(stmt.text(sql), Dialect::Postgres).kind == StatementKind::Transaction
+        })
+}
+
+/// Quoted millisecond literal for `statement_timeout`; zero would disable the limit.
+pub(crate) fn statement_timeout_setting(timeout: Duration) -> String {
+    format!("'{}ms'", timeout.as_millis().max(1))
+}
+
+async fn in_local_transaction(
+    conn: &mut PgConnection,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    setting: &str,
+) -> Result<QueryResult> {
+    let mut tx = conn.begin().await?;
+    tx.execute(AssertSqlSafe(format!(
+        "SET LOCAL statement_timeout = {setting}"
+    )))
+    .await?;
+    let result = execute_sql(&mut tx, sql, params, max_rows).await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+async fn session_scoped(
+    conn: &mut PgConnection,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    setting: &str,
+) -> Result<QueryResult> {
+    conn.execute(AssertSqlSafe(format!("SET statement_timeout = {setting}")))
+        .await?;
+    let result = execute_sql(&mut *conn, sql, params, max_rows).await;
+    // A transaction the statement aborted refuses the `RESET`; its own error wins.
+    let reset = conn.execute("RESET statement_timeout").await;
+    let result = result?;
+    reset?;
+    Ok(result)
+}
+
+fn sqlstate(err: &anyhow::Error) -> Option<String> {
+    err.chain().find_map(|cause| {
+        cause
+            .downcast_ref::<sqlx::Error>()
+            .and_then(|e| e.as_database_error())
+            .and_then(|db| db.code().map(|c| c.into_owned()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn setting_is_quoted_millis_and_never_zero() {
+        assert_eq!(
+            statement_timeout_setting(Duration::from_secs(30)),
+            "'30000ms'"
+        );
+        assert_eq!(statement_timeout_setting(Duration::ZERO), "'1ms'");
+    }
+
+    #[test]
+    fn own_transaction_control_skips_the_wrapper() {
+        assert!(controls_transaction("BEGIN; UPDATE t SET a = 1; COMMIT"));
+        assert!(controls_transaction("-- undo\nROLLBACK"));
+        assert!(controls_transaction("START TRANSACTION READ ONLY"));
+        assert!(!controls_transaction("UPDATE t SET note = 'commit'"));
+        assert!(!controls_transaction("SELECT 1"));
+    }
+
+    #[test]
+    fn only_server_aborts_without_a_cancel_are_timeouts() {
+        let after = Some(Duration::from_secs(1));
+        let message = || anyhow::anyhow!("canceling statement due to statement timeout");
+        assert!(!is_query_canceled(&message()));
+        let err = as_timeout(message(), after, false);
+        assert!(err.downcast_ref::<QueryTimedOut>().is_none());
+    }
+}
@@ -0,0 +1,158 @@
+//! Manual transaction mode: one pooled connection pinned to an editor tab.
+//!
+//! [`PgManualTx`] owns a sqlx transaction, so the transaction wrapper of
+//! [`execute_sql_with_timeout`] becomes a savepoint: a failing statement rolls back to it
+//! and the open transaction stays usable until the user commits or rolls back. SQL that
+//! would begin or end the transaction itself (`COMMIT`, `ROLLBACK`, `PREPARE
+//! TRANSACTION`, …) is refused, so the tab never shows a transaction the server has
+//! already closed; savepoints stay usable.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use based_core::Value;
+use based_query::{Dialect, statements_in_script};
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::sync::Mutex;
+
+use crate::cancel::PgCancelHandle;
+use crate::script::{BoundStatement, ScriptStep, transaction_effect};
+use crate::stream::QueryResult;
+use crate::timeout::{as_timeout, run_with_timeout};
+
+/// Open transaction on a pinned connection; clones share it. Dropping the last clone
+/// without [`commit`](Self::commit) rolls back.
+#[derive(Clone)]
+pub struct PgManualTx {
+    tx: Arc<Mutex<Option<Transaction<'static, Postgres>>>>,
+}
+
+impl PgManualTx {
+    /// Pin a connection from `pool` and issue `BEGIN`.
+    pub async fn begin(pool: &PgPool) -> Result<Self> {
+        let tx = pool.begin().await.context("begin transaction")?;
+        Ok(Self {
+            tx: Arc::new(Mutex::new(Some(tx))),
+        })
+    }
+
+    /// Run `sql` inside the transaction; see [`crate::execute_sql_cancellable`].
+    pub async fn execute(
+        &self,
+        sql: &str,
+        params: &[Value],
+        max_rows: Option<u64>,
+        timeout: Option<Duration>,
+        handle: &PgCancelHandle,
+    ) -> Result<QueryResult> {
+        let mut guard = self.tx.lock().await;
+        let Some(tx) = guard.as_mut() else {
+            bail!("transaction is already closed");
+        };
+        ensure_keeps_transaction(sql)?;
+        handle.attach(tx).await?;
+        let result = run_with_timeout(tx, sql, params, max_rows, timeout).await;
+        handle.detach();
+        result.map_err(|e| as_timeout(e, timeout, handle.cancel_requested()))
+    }
+
+    /// Run `statements` in order inside the transaction, stopping after the first
+    /// failure; see [`crate::execute_script`]. A script with transaction control is
+    /// refused before any of it runs.
+    pub async fn execute_script(
+        &self,
+        statements: &[BoundStatement],
+        max_rows: Option<u64>,
+        timeout: Option<Duration>,
+        handle: &PgCancelHandle,
+    ) -> Result<Vec<ScriptStep>> {
+        let mut guard = self.tx.lock().await;
+        let Some(tx) = guard.as_mut() else {
+            bail!("transaction is already closed");
+        };
+        for stmt in statements {
+            ensure_keeps_transaction(&stmt.sql)?;
+        }
+        handle.attach(tx).await?;
+        let mut steps = Vec::with_capacity(statements.len());
+        for stmt in statements {
+            let start = Instant::now();
+            let result = run_with_timeout(tx, &stmt.sql, &stmt.params, max_rows, timeout)
+                .await
+                .map_err(|e| as_timeout(e, timeout, handle.cancel_requested()));
+            let failed = result.is_err();
+            steps.push(ScriptStep {
+                elapsed: start.elapsed(),
+                result,
+            });
+            if failed {
+                break;
+            }
+        }
+        handle.detach();
+        Ok(steps)
+    }
+
+    pub async fn commit(&self) -> Result<()> {
+        let tx = self.take().await?;
+        tx.commit().await.context("commit transaction")
+    }
+
+    pub async fn rollback(&self) -> Result<()> {
+        let tx = self.take().await?;
+        tx.rollback().await.context("roll back transaction")
+    }
+
+    async fn take(&self) -> Result<Transaction<'static, Postgres>> {
+        match self.tx.lock().await.take() {
+            Some(tx) => Ok(tx),
+            None => bail!("transaction is already closed"),
+        }
+    }
+}
+
+/// Refuse `sql` when it would begin or end the pinned transaction behind the tab's back.
+fn ensure_keeps_transaction(sql: &str) -> Result<()> {
+    for stmt in statements_in_script(sql, Dialect::Postgres) {
+        let text = stmt.text(sql);
+        if transaction_effect(text).is_some() {
+            bail!(
+                "`{}` is not allowed in a manual transaction; end it with Commit or Rollback",
+                text.trim()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transaction_control_is_refused() {
+        for sql in [
+            "COMMIT",
+            "rollback",
+            "END",
+            "BEGIN",
+            "SELECT 1; COMMIT",
+            "PREPARE TRANSACTION 'tx1'",
+        ] {
+            let err = ensure_keeps_transaction(sql).unwrap_err();
+            assert!(
+                err.to_string().contains("Commit or Rollback"),
+                "{sql}: {err}"
+            );
+        }
+        for sql in [
+            "SAVEPOINT a",
+            "ROLLBACK TO SAVEPOINT a",
+            "RELEASE a",
+            "UPDATE t SET note = 'commit'",
+        ] {
+            assert!(ensure_keeps_transaction(sql).is_ok(), "{sql}");
+        }
+    }
+}
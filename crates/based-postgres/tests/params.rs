@@ -0,0 +1,86 @@
+//! Bind parameters against a live server. Set `BASED_TEST_POSTGRES_URL` (e.g.
+//! `postgres://postgres@localhost/postgres`) to run these; without it they pass
+//! without connecting.
+
+use std::env;
+
+use based_core::Value;
+use based_postgres::execute_sql;
+use sqlx::{Connection, PgConnection};
+
+async fn connect() -> Option<PgConnection> {
+    let url = env::var("BASED_TEST_POSTGRES_URL").ok()?;
+    Some(PgConnection::connect(&url).await.expect("connect"))
+}
+
+fn text(s: &str) -> Value {
+    Value::Text(s.into())
+}
+
+#[tokio::test]
+async fn untyped_text_takes_the_column_type() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    execute_sql(
+        &mut conn,
+        "CREATE TEMP TABLE orders (id int4, created_at timestamptz, amount numeric, zip text)",
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+    execute_sql(
+        &mut conn,
+        "INSERT INTO orders VALUES ($1, $2, $3::numeric, $4), (2, now(), 5, '10001')",
+        &[
+            text("1"),
+            text("2024-03-01 10:00"),
+            text("9.99"),
+            text("02134"),
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let by_id = execute_sql(
+        &mut conn,
+        "SELECT zip FROM orders WHERE id = $1",
+        &[text("1")],
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(by_id.rows, vec![vec![text("02134")]]);
+
+    let since = execute_sql(
+        &mut conn,
+        "SELECT id FROM orders WHERE created_at >= $1 ORDER BY id LIMIT $2",
+        &[text("2024-01-01"), text("1")],
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(since.rows, vec![vec![Value::Int(1)]]);
+
+    let zip = execute_sql(
+        &mut conn,
+        "SELECT id FROM orders WHERE zip = $1",
+        &[text("02134")],
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(zip.rows, vec![vec![Value::Int(1)]]);
+
+    let err = execute_sql(
+        &mut conn,
+        "SELECT 1 FROM orders WHERE id = $1",
+        &[text("one")],
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("expected an integer"), "{err:#}");
+}
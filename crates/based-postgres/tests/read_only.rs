@@ -0,0 +1,83 @@
+//! Read-only sessions against a live server. Set `BASED_TEST_POSTGRES_URL` (e.g.
+//! `postgres://postgres@localhost/postgres`) to run these; without it they pass
+//! without connecting.
+
+use std::env;
+use std::str::FromStr;
+
+use based_postgres::{PgSettings, PostgresConfig, SslMode, execute_sql, pg_connect_options};
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{ConnectOptions, Connection, PgConnection};
+
+/// The test server as a [`PostgresConfig`], so connections go through
+/// [`pg_connect_options`].
+fn config(read_only: bool) -> Option<PostgresConfig> {
+    let url = env::var("BASED_TEST_POSTGRES_URL").ok()?;
+    let opts = PgConnectOptions::from_str(&url).expect("parse BASED_TEST_POSTGRES_URL");
+    Some(PostgresConfig {
+        label: "test".into(),
+        host: opts.get_host().to_string(),
+        port: opts.get_port(),
+        database: opts.get_database().unwrap_or("postgres").to_string(),
+        username: opts.get_username().to_string(),
+        password: String::new(),
+        ssl_mode: SslMode::Disable,
+        settings: PgSettings {
+            read_only,
+            ..PgSettings::default()
+        },
+        ssh: None,
+    })
+}
+
+#[tokio::test]
+async fn read_only_sessions_refuse_writes_hidden_in_a_select() {
+    let Some(writable) = config(false) else {
+        return;
+    };
+    let mut conn = PgConnection::connect(&env::var("BASED_TEST_POSTGRES_URL").unwrap())
+        .await
+        .unwrap();
+    execute_sql(
+        &mut conn,
+        "CREATE SEQUENCE IF NOT EXISTS based_read_only_seq",
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut read_only = pg_connect_options(&config(true).unwrap())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let err = execute_sql(
+        &mut read_only,
+        "SELECT nextval('based_read_only_seq')",
+        &[],
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("read-only"), "{err:#}");
+    let ok = execute_sql(&mut read_only, "SELECT 1", &[], None).await;
+    assert!(ok.is_ok());
+
+    let mut plain = pg_connect_options(&writable)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    execute_sql(
+        &mut plain,
+        "SELECT nextval('based_read_only_seq')",
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+    execute_sql(&mut conn, "DROP SEQUENCE based_read_only_seq", &[], None)
+        .await
+        .unwrap();
+}
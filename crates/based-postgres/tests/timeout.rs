@@ -0,0 +1,95 @@
+//! Timeouts, cancels, and transaction-controlling text against a live server. Set
+//! `BASED_TEST_POSTGRES_URL` (e.g. `postgres://postgres@localhost/postgres`) to run
+//! these; without it they pass without connecting.
+
+use std::env;
+use std::time::Duration;
+
+use based_core::{QueryTimedOut, Value};
+use based_postgres::{
+    PgCancelHandle, execute_sql, execute_sql_cancellable, execute_sql_with_timeout,
+    is_query_cancelled,
+};
+use sqlx::{Connection, PgConnection, PgPool};
+use tokio::time::sleep;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(30));
+
+async fn connect() -> Option<PgConnection> {
+    let url = env::var("BASED_TEST_POSTGRES_URL").ok()?;
+    Some(PgConnection::connect(&url).await.expect("connect"))
+}
+
+#[tokio::test]
+async fn own_transaction_text_runs_as_one_batch() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    execute_sql(
+        &mut conn,
+        "CREATE TEMP TABLE based_timeout_batch (n int)",
+        &[],
+        None,
+    )
+    .await
+    .unwrap();
+    let result = execute_sql_with_timeout(
+        &mut conn,
+        "BEGIN; INSERT INTO based_timeout_batch VALUES (1), (2); COMMIT",
+        &[],
+        None,
+        TIMEOUT,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.rows_affected, 2);
+
+    let result = execute_sql_with_timeout(
+        &mut conn,
+        "BEGIN; SELECT n FROM based_timeout_batch ORDER BY n; COMMIT",
+        &[],
+        None,
+        TIMEOUT,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.rows, vec![vec![Value::Int(1)], vec![Value::Int(2)]]);
+}
+
+#[tokio::test]
+async fn statement_timeout_fails_with_query_timed_out() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+    let err = execute_sql_with_timeout(
+        &mut conn,
+        "SELECT pg_sleep(5)",
+        &[],
+        None,
+        Some(Duration::from_millis(50)),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.downcast_ref::<QueryTimedOut>().is_some(), "{err:#}");
+}
+
+#[tokio::test]
+async fn cancel_is_not_reported_as_a_timeout() {
+    let Ok(url) = env::var("BASED_TEST_POSTGRES_URL") else {
+        return;
+    };
+    let pool = PgPool::connect(&url).await.unwrap();
+    let handle = PgCancelHandle::new();
+    let run = execute_sql_cancellable(&pool, "SELECT pg_sleep(5)", &[], None, TIMEOUT, &handle);
+    let cancel = async {
+        // Past `attach`, so the cancel lands on the sleep rather than before it.
+        sleep(Duration::from_millis(200)).await;
+        while !handle.cancel(&pool).await.unwrap() {
+            sleep(Duration::from_millis(20)).await;
+        }
+    };
+    let (result, ()) = tokio::join!(run, cancel);
+    let err = result.unwrap_err();
+    assert!(err.downcast_ref::<QueryTimedOut>().is_none(), "{err:#}");
+    assert!(is_query_cancelled(&err, &handle), "{err:#}");
+}
@@ -1,10 +1,16 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use toml_edit::{Array, DocumentMut, Item, value};
 
 use crate::env_value::EnvOrString;
+use crate::inherit::{existing_extends, inherited_keys, inherited_table, resolve_connection_table};
+use crate::policy::ConnectionPolicy;
+use crate::postgres::{PostgresSettings, PostgresSslMode};
 use crate::walk::{rel_id, walk_toml_files};
 
 pub const CONNECTION_SCHEMA_VERSION: u64 = 1;
@@ -18,11 +24,133 @@ pub struct ProjectConnection {
     pub read_only: bool,
     pub spec: ConnectionSpec,
     pub ssh: Option<SshSettings>,
+    pub policy: ConnectionPolicy,
+}
+
+impl ProjectConnection {
+    /// Copy with `{{name}}` placeholders in host, failover hosts, database, username, SQLite
+    /// file, literal MongoDB URL, and SSH host/user filled from `vars` (the active
+    /// environment), so one file serves dev, staging, and prod. A placeholder `vars` lacks
+    /// is an error.
+    pub fn with_vars(&self, vars: &HashMap<String, String>) -> Result<Self> {
+        let field = |name: &str, text: &str| {
+            expand_vars(text, vars).with_context(|| format!("connection {} `{name}`", self.id))
+        };
+        let spec = match &self.spec {
+            ConnectionSpec::Sqlite { file, pragma } => ConnectionSpec::Sqlite {
+                file: PathBuf::from(field("file", &file.to_string_lossy())?),
+                pragma: pragma.clone(),
+            },
+            ConnectionSpec::Postgres {
+                host,
+                port,
+                database,
+                username,
+                password,
+                sslmode,
+                settings,
+            } => ConnectionSpec::Postgres {
+                host: field("host", host)?,
+                port: *port,
+                database: field("database", database)?,
+                username: field("username", username)?,
+                password: password.clone(),
+                sslmode: *sslmode,
+                settings: Box::new(PostgresSettings {
+                    failover_hosts: settings
+                        .failover_hosts
+                        .iter()
+                        .map(|h| field("failover_hosts", h))
+                        .collect::<Result<_>>()?,
+                    ..(**settings).clone()
+                }),
+            },
+            ConnectionSpec::MongoDB { url, database } => ConnectionSpec::MongoDB {
+                url: match url {
+                    EnvOrString::Literal(s) => EnvOrString::Literal(field("url", s)?),
+                    from_env => from_env.clone(),
+                },
+                database: database
+                    .as_deref()
+                    .map(|d| field("database", d))
+                    .transpose()?,
+            },
+        };
+        let ssh = match &self.ssh {
+            Some(ssh) => Some(SshSettings {
+                host: field("ssh.host", &ssh.host)?,
+                user: field("ssh.user", &ssh.user)?,
+                alias: ssh
+                    .alias
+                    .as_deref()
+                    .map(|a| field("ssh.alias", a))
+                    .transpose()?,
+                jump_hosts: ssh
+                    .jump_hosts
+                    .iter()
+                    .map(|jump| {
+                        Ok(SshJumpSettings {
+                            host: field("ssh.jump_hosts.host", &jump.host)?,
+                            user: field("ssh.jump_hosts.user", &jump.user)?,
+                            ..jump.clone()
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+                ..ssh.clone()
+            }),
+            None => None,
+        };
+        Ok(Self {
+            spec,
+            ssh,
+            ..self.clone()
+        })
+    }
+}
+
+/// Replace each `{{name}}` in `text` with `vars[name]`.
+fn expand_vars(text: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        let Some(value) = vars.get(name) else {
+            bail!("references {{{{{name}}}}}, which the active environment does not define");
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[start + 4 + len..];
+    }
+    out.push_str(rest);
+    Ok(out)
 }
 
 /// Optional SSH hop persisted as `[ssh]` on a connection file.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SshSettings {
+    /// Empty when `alias` leaves it to `~/.ssh/config`.
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub key_path: Option<String>,
+    pub key_passphrase: Option<EnvOrString>,
+    /// `Host` in `~/.ssh/config` supplying the fields left unset (port 22 counts as
+    /// unset).
+    pub alias: Option<String>,
+    /// `[[ssh.jump_hosts]]`: bastions reached in order before `host`.
+    pub jump_hosts: Vec<SshJumpSettings>,
+    /// Seconds between SSH keepalives; `0` turns them off.
+    pub keepalive_interval: Option<u64>,
+    /// Unanswered keepalives before the tunnel reconnects.
+    pub keepalive_count_max: Option<u32>,
+}
+
+/// One `[[ssh.jump_hosts]]` entry, authenticated on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshJumpSettings {
     pub host: String,
     pub port: u16,
     pub user: String,
@@ -42,7 +170,8 @@ pub enum ConnectionSpec {
         database: String,
         username: String,
         password: EnvOrString,
-        ssl: bool,
+        sslmode: PostgresSslMode,
+        settings: Box<PostgresSettings>,
     },
     MongoDB {
         url: EnvOrString,
@@ -62,7 +191,6 @@ pub struct PragmaSettings {
 
 #[derive(Debug, Deserialize)]
 struct RawConnectionFile {
-    schema_version: u64,
     label: String,
     engine: String,
     #[serde(default)]
@@ -79,9 +207,14 @@ struct RawConnectionFile {
     username: Option<String>,
     #[serde(default)]
     password: Option<EnvOrString>,
+    /// Legacy on/off switch; `sslmode` wins when both are set.
     #[serde(default)]
     ssl: Option<bool>,
     #[serde(default)]
+    sslmode: Option<PostgresSslMode>,
+    #[serde(flatten)]
+    postgres: PostgresSettings,
+    #[serde(default)]
     url: Option<EnvOrString>,
     #[serde(default)]
     pragma: Option<PragmaSettings>,
@@ -89,10 +222,34 @@ struct RawConnectionFile {
     read_only: Option<bool>,
     #[serde(default)]
     ssh: Option<RawSsh>,
+    #[serde(default)]
+    policy: ConnectionPolicy,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct RawSsh {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_passphrase: Option<EnvOrString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keepalive_interval: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keepalive_count_max: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    jump_hosts: Vec<RawSshJump>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RawSshJump {
     #[serde(default)]
     host: Option<String>,
     #[serde(default)]
@@ -145,22 +302,76 @@ pub fn slug_from_label(label: &str) -> String {
 }
 
 /// Write `connections/{id}.toml` under `based_dir`, creating parent folders as needed.
+///
+/// Only fields that differ from what the file inherits (folder `_defaults.toml`, and the
+/// `extends` an existing file already names) are written.
 pub fn write_connection_file(based_dir: &Path, conn: &ProjectConnection) -> Result<PathBuf> {
-    let rel = format!("{}.toml", conn.id);
-    let path = based_dir.join("connections").join(rel);
+    let dir = based_dir.join("connections");
+    let path = dir.join(format!("{}.toml", conn.id));
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
     }
-    let raw = WriteConnectionFile::from(conn);
+    let extends = existing_extends(&path)?;
+    let base = inherited_table(&dir, &path, extends.as_deref())?;
+    let raw = WriteConnectionFile {
+        extends,
+        ..WriteConnectionFile::from(conn)
+    };
     let content = toml::to_string_pretty(&raw)
         .with_context(|| format!("serialize connection {}", conn.id))?;
+    let content = if base.is_empty() {
+        content
+    } else {
+        without_inherited(&content, &base, conn)?
+    };
     fs::write(&path, content).with_context(|| format!("write {}", path.display()))?;
     Ok(path)
 }
 
+/// Drop keys of the serialized `content` that `base` already provides. `tags` and
+/// `read_only` are written explicitly when they clear an inherited value.
+fn without_inherited(content: &str, base: &Table, conn: &ProjectConnection) -> Result<String> {
+    let own: Table = toml::from_str(content)?;
+    let mut doc: DocumentMut = content.parse()?;
+    // An inherited `[ssh]` without `port` already means port 22, and a legacy `ssl`
+    // already means its `sslmode`.
+    let mut base = base.clone();
+    if let Some(Value::Table(ssh)) = base.get_mut("ssh") {
+        ssh.entry("port").or_insert(Value::Integer(22));
+    }
+    if let Some(Value::Boolean(ssl)) = base.get("ssl")
+        && !base.contains_key("sslmode")
+    {
+        let mode = PostgresSslMode::from_legacy_ssl(*ssl).name();
+        base.insert("sslmode".into(), Value::String(mode.into()));
+    }
+    for key in inherited_keys(&own, &base) {
+        match key.as_slice() {
+            [key] => {
+                doc.remove(key);
+            }
+            [table, key] => {
+                if let Some(table) = doc.get_mut(table).and_then(Item::as_table_like_mut) {
+                    table.remove(key);
+                }
+            }
+            _ => {}
+        }
+    }
+    if conn.tags.is_empty() && base.get("tags").is_some_and(|t| t != &Value::Array(vec![])) {
+        doc["tags"] = value(Array::new());
+    }
+    if !conn.read_only && base.get("read_only") == Some(&Value::Boolean(true)) {
+        doc["read_only"] = value(false);
+    }
+    Ok(doc.to_string())
+}
+
 #[derive(Serialize)]
 struct WriteConnectionFile {
     schema_version: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extends: Option<String>,
     label: String,
     engine: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -180,19 +391,24 @@ struct WriteConnectionFile {
     #[serde(skip_serializing_if = "Option::is_none")]
     password: Option<EnvOrString>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    ssl: Option<bool>,
+    sslmode: Option<PostgresSslMode>,
+    #[serde(flatten)]
+    postgres: PostgresSettings,
     #[serde(skip_serializing_if = "Option::is_none")]
     url: Option<EnvOrString>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pragma: Option<PragmaSettings>,
     #[serde(skip_serializing_if = "Option::is_none")]
     ssh: Option<RawSsh>,
+    #[serde(skip_serializing_if = "ConnectionPolicy::is_default")]
+    policy: ConnectionPolicy,
 }
 
 impl From<&ProjectConnection> for WriteConnectionFile {
     fn from(conn: &ProjectConnection) -> Self {
         let mut out = Self {
             schema_version: CONNECTION_SCHEMA_VERSION,
+            extends: None,
             label: conn.label.clone(),
             engine: conn.engine.clone(),
             tags: conn.tags.clone(),
@@ -203,16 +419,32 @@ impl From<&ProjectConnection> for WriteConnectionFile {
             database: None,
             username: None,
             password: None,
-            ssl: None,
+            sslmode: None,
+            postgres: PostgresSettings::default(),
             url: None,
             pragma: None,
             ssh: conn.ssh.as_ref().map(|s| RawSsh {
-                host: Some(s.host.clone()),
-                port: Some(s.port),
-                user: Some(s.user.clone()),
+                alias: s.alias.clone(),
+                keepalive_interval: s.keepalive_interval,
+                keepalive_count_max: s.keepalive_count_max,
+                host: Some(s.host.clone()).filter(|h| !h.is_empty()),
+                port: Some(s.port).filter(|&p| s.alias.is_none() || p != 22),
+                user: Some(s.user.clone()).filter(|u| !u.is_empty()),
                 key_path: s.key_path.clone(),
                 key_passphrase: s.key_passphrase.clone(),
+                jump_hosts: s
+                    .jump_hosts
+                    .iter()
+                    .map(|j| RawSshJump {
+                        host: Some(j.host.clone()),
+                        port: Some(j.port),
+                        user: Some(j.user.clone()),
+                        key_path: j.key_path.clone(),
+                        key_passphrase: j.key_passphrase.clone(),
+                    })
+                    .collect(),
             }),
+            policy: conn.policy.clone(),
         };
         match &conn.spec {
             ConnectionSpec::Sqlite { file, pragma } => {
@@ -225,14 +457,20 @@ impl From<&ProjectConnection> for WriteConnectionFile {
                 database,
                 username,
                 password,
-                ssl,
+                sslmode,
+                settings,
             } => {
-                out.host = Some(host.clone());
+                // With a service, blank fields are left for the service to fill.
+                let has_service = settings.service.is_some();
+                let own =
+                    |value: &String| (!has_service || !value.is_empty()).then(|| value.clone());
+                out.host = own(host);
                 out.port = Some(*port);
-                out.database = Some(database.clone());
-                out.username = Some(username.clone());
+                out.database = own(database);
+                out.username = own(username);
                 out.password = Some(password.clone());
-                out.ssl = Some(*ssl);
+                out.sslmode = Some(*sslmode);
+                out.postgres = (**settings).clone();
             }
             ConnectionSpec::MongoDB { url, database } => {
                 out.url = Some(url.clone());
@@ -243,25 +481,21 @@ impl From<&ProjectConnection> for WriteConnectionFile {
     }
 }
 
-fn is_false(value: &bool) -> bool {
+pub(crate) fn is_false(value: &bool) -> bool {
     !*value
 }
 
-fn parse_connection_file(connections_dir: &Path, path: &Path) -> Result<ProjectConnection> {
+pub(crate) fn parse_connection_file(
+    connections_dir: &Path,
+    path: &Path,
+) -> Result<ProjectConnection> {
     let rel = path
         .strip_prefix(connections_dir)
         .with_context(|| format!("connection path not under {}", connections_dir.display()))?;
     let id = rel_id(rel);
-    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
-    let file: RawConnectionFile =
-        toml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
-    if file.schema_version != CONNECTION_SCHEMA_VERSION {
-        bail!(
-            "unsupported schema_version {} in {} (expected {CONNECTION_SCHEMA_VERSION})",
-            file.schema_version,
-            path.display()
-        );
-    }
+    let file: RawConnectionFile = Value::Table(resolve_connection_table(connections_dir, path)?)
+        .try_into()
+        .with_context(|| format!("parse {}", path.display()))?;
     let engine = file.engine.to_lowercase();
     let spec = match engine.as_str() {
         "sqlite" => {
@@ -274,26 +508,32 @@ fn parse_connection_file(connections_dir: &Path, path: &Path) -> Result<ProjectC
                 pragma: file.pragma,
             }
         }
-        "postgres" | "postgresql" => ConnectionSpec::Postgres {
-            host: file
-                .host
-                .clone()
-                .with_context(|| format!("postgres connection {id} requires `host`"))?,
-            port: file.port.unwrap_or(5432),
-            database: file
-                .database
-                .clone()
-                .with_context(|| format!("postgres connection {id} requires `database`"))?,
-            username: file
-                .username
-                .clone()
-                .with_context(|| format!("postgres connection {id} requires `username`"))?,
-            password: file
-                .password
-                .clone()
-                .unwrap_or(EnvOrString::Literal(String::new())),
-            ssl: file.ssl.unwrap_or(false),
-        },
+        "postgres" | "postgresql" => {
+            file.postgres.check(&id)?;
+            // A libpq service supplies what the file leaves out, as libpq does.
+            let has_service = file.postgres.service.is_some();
+            let required = |value: &Option<String>, key: &str| match value {
+                Some(value) => Ok(value.clone()),
+                None if has_service => Ok(String::new()),
+                None => Err(anyhow!("postgres connection {id} requires `{key}`")),
+            };
+            ConnectionSpec::Postgres {
+                host: required(&file.host, "host")?,
+                port: file.port.unwrap_or(5432),
+                database: required(&file.database, "database")?,
+                username: required(&file.username, "username")?,
+                password: file
+                    .password
+                    .clone()
+                    .unwrap_or(EnvOrString::Literal(String::new())),
+                sslmode: file.sslmode.unwrap_or_else(|| match file.ssl {
+                    Some(ssl) => PostgresSslMode::from_legacy_ssl(ssl),
+                    None if has_service => PostgresSslMode::Prefer,
+                    None => PostgresSslMode::Disable,
+                }),
+                settings: Box::new(file.postgres.clone()),
+            }
+        }
         "mongodb" | "mongo" => {
             let url = file
                 .url
@@ -308,6 +548,7 @@ fn parse_connection_file(connections_dir: &Path, path: &Path) -> Result<ProjectC
     };
     let read_only = resolve_read_only(file.read_only, &file.tags);
     let ssh = parse_ssh(file.ssh, &id)?;
+    file.policy.check(&id)?;
     Ok(ProjectConnection {
         id,
         label: file.label,
@@ -316,6 +557,7 @@ fn parse_connection_file(connections_dir: &Path, path: &Path) -> Result<ProjectC
         read_only,
         spec,
         ssh,
+        policy: file.policy,
     })
 }
 
@@ -323,17 +565,44 @@ fn parse_ssh(raw: Option<RawSsh>, connection_id: &str) -> Result<Option<SshSetti
     let Some(raw) = raw else {
         return Ok(None);
     };
+    let alias = raw.alias.filter(|a| !a.trim().is_empty());
     let host = raw.host.unwrap_or_default();
     let user = raw.user.unwrap_or_default();
-    if host.trim().is_empty() || user.trim().is_empty() {
-        bail!("connection {connection_id} [ssh] requires `host` and `user`");
+    if alias.is_none() && (host.trim().is_empty() || user.trim().is_empty()) {
+        bail!("connection {connection_id} [ssh] requires `host` and `user`, or `alias`");
     }
+    let jump_hosts = raw
+        .jump_hosts
+        .into_iter()
+        .enumerate()
+        .map(|(i, jump)| {
+            let host = jump.host.unwrap_or_default();
+            let user = jump.user.unwrap_or_default();
+            if host.trim().is_empty() || user.trim().is_empty() {
+                bail!(
+                    "connection {connection_id} [[ssh.jump_hosts]] entry {} requires `host` and `user`",
+                    i + 1
+                );
+            }
+            Ok(SshJumpSettings {
+                host,
+                port: jump.port.unwrap_or(22),
+                user,
+                key_path: jump.key_path.filter(|p| !p.trim().is_empty()),
+                key_passphrase: jump.key_passphrase,
+            })
+        })
+        .collect::<Result<_>>()?;
     Ok(Some(SshSettings {
         host,
         port: raw.port.unwrap_or(22),
         user,
         key_path: raw.key_path.filter(|p| !p.trim().is_empty()),
         key_passphrase: raw.key_passphrase,
+        alias,
+        keepalive_interval: raw.keepalive_interval,
+        keepalive_count_max: raw.keepalive_count_max,
+        jump_hosts,
     }))
 }
 
@@ -347,6 +616,7 @@ fn resolve_read_only(explicit: Option<bool>, tags: &[String]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::postgres::TargetSessionAttrs;
 
     #[test]
     fn read_only_explicit_true() {
@@ -472,6 +742,270 @@ key_path = "~/.ssh/id_ed25519"
         assert!(ssh.key_passphrase.is_none());
     }
 
+    #[test]
+    fn ssh_alias_leaves_host_and_user_to_ssh_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        let conn_dir = based.join("connections");
+        fs::create_dir_all(&conn_dir).unwrap();
+        let path = conn_dir.join("prod.toml");
+        fs::write(
+            &path,
+            r#"
+schema_version = 1
+label = "Prod"
+engine = "postgres"
+host = "db.internal"
+database = "app"
+username = "app"
+ssh = { alias = "prod-bastion", keepalive_interval = 30 }
+"#,
+        )
+        .unwrap();
+        let conn = parse_connection_file(&conn_dir, &path).unwrap();
+        let ssh = conn.ssh.clone().expect("ssh");
+        assert_eq!(ssh.alias.as_deref(), Some("prod-bastion"));
+        assert!(ssh.host.is_empty() && ssh.user.is_empty());
+        assert_eq!(ssh.port, 22);
+        assert_eq!(ssh.keepalive_interval, Some(30));
+        assert_eq!(ssh.keepalive_count_max, None);
+
+        write_connection_file(based, &conn).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("alias = \"prod-bastion\""), "{raw}");
+        assert!(raw.contains("keepalive_interval = 30"), "{raw}");
+        assert!(!raw.contains("port = 22"), "{raw}");
+        let loaded = load_connections_from_based_dir(based).unwrap();
+        assert_eq!(loaded[0].ssh, conn.ssh);
+    }
+
+    #[test]
+    fn ssh_jump_hosts_parse_in_order_and_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        let conn_dir = based.join("connections");
+        fs::create_dir_all(&conn_dir).unwrap();
+        let path = conn_dir.join("prod.toml");
+        fs::write(
+            &path,
+            r#"
+schema_version = 1
+label = "Prod"
+engine = "postgres"
+host = "db.internal"
+database = "app"
+username = "app"
+
+[ssh]
+host = "inner-bastion"
+user = "ops"
+
+[[ssh.jump_hosts]]
+host = "edge.example.com"
+port = 2222
+user = "edge"
+key_path = "~/.ssh/edge"
+
+[[ssh.jump_hosts]]
+host = "middle"
+user = "ops"
+key_passphrase = { env = "BASED_MIDDLE_PASSPHRASE" }
+"#,
+        )
+        .unwrap();
+        let conn = parse_connection_file(&conn_dir, &path).unwrap();
+        let ssh = conn.ssh.clone().expect("ssh table");
+        let hops: Vec<_> = ssh
+            .jump_hosts
+            .iter()
+            .map(|j| (j.host.as_str(), j.port, j.user.as_str()))
+            .collect();
+        assert_eq!(
+            hops,
+            [("edge.example.com", 2222, "edge"), ("middle", 22, "ops")]
+        );
+        assert_eq!(ssh.jump_hosts[0].key_path.as_deref(), Some("~/.ssh/edge"));
+
+        write_connection_file(based, &conn).unwrap();
+        let loaded = load_connections_from_based_dir(based).unwrap();
+        assert_eq!(loaded[0].ssh, conn.ssh);
+
+        fs::write(
+            &path,
+            "schema_version = 1\nlabel = \"Prod\"\nengine = \"postgres\"\nhost = \"db\"\ndatabase = \"app\"\nusername = \"app\"\n\n[ssh]\nhost = \"b\"\nuser = \"ops\"\n\n[[ssh.jump_hosts]]\nhost = \"edge\"\n",
+        )
+        .unwrap();
+        let err = parse_connection_file(&conn_dir, &path).unwrap_err();
+        assert!(err.to_string().contains("jump_hosts"), "{err}");
+    }
+
+    #[test]
+    fn policy_table_parses_and_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        let conn_dir = based.join("connections");
+        fs::create_dir_all(&conn_dir).unwrap();
+        let path = conn_dir.join("prod.toml");
+        fs::write(
+            &path,
+            r#"
+schema_version = 1
+label = "Prod"
+engine = "postgres"
+host = "mydb.internal"
+database = "app"
+username = "app"
+
+[policy]
+confirm_writes = true
+allowed_statements = ["select", "explain", "update"]
+max_rows = 200
+require_where_for_update_delete = true
+"#,
+        )
+        .unwrap();
+        let conn = parse_connection_file(&conn_dir, &path).unwrap();
+        assert!(conn.policy.confirm_writes);
+        assert!(!conn.policy.forbid_ddl);
+        assert_eq!(conn.policy.max_rows, Some(200));
+        assert!(conn.policy.allows_kind("update"));
+        assert!(!conn.policy.allows_kind("delete"));
+
+        write_connection_file(based, &conn).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("[policy]"));
+        assert!(!raw.contains("forbid_ddl"));
+        let loaded = parse_connection_file(&conn_dir, &path).unwrap();
+        assert_eq!(loaded.policy, conn.policy);
+
+        fs::write(
+            &path,
+            "schema_version = 1\nlabel = \"P\"\nengine = \"sqlite\"\nfile = \"p.db\"\n\n[policy]\nallowed_statements = [\"selct\"]\n",
+        )
+        .unwrap();
+        let err = format!("{:#}", parse_connection_file(&conn_dir, &path).unwrap_err());
+        assert!(err.contains("selct"), "{err}");
+    }
+
+    #[test]
+    fn postgres_settings_parse_and_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        let conn_dir = based.join("connections");
+        fs::create_dir_all(&conn_dir).unwrap();
+        let path = conn_dir.join("prod.toml");
+        fs::write(
+            &path,
+            r#"
+schema_version = 1
+label = "Prod"
+engine = "postgres"
+host = "primary.internal"
+database = "app"
+username = "app"
+ssl = false
+sslmode = "verify-full"
+sslrootcert = "certs/root.crt"
+application_name = "based"
+search_path = "app, public"
+options = "-c work_mem=64MB"
+connect_timeout = 5
+statement_timeout = 30000
+failover_hosts = ["replica-1", "replica-2:6432"]
+target_session_attrs = "read-write"
+"#,
+        )
+        .unwrap();
+        let conn = parse_connection_file(&conn_dir, &path).unwrap();
+        let ConnectionSpec::Postgres {
+            sslmode, settings, ..
+        } = &conn.spec
+        else {
+            panic!("postgres spec");
+        };
+        assert_eq!(*sslmode, PostgresSslMode::VerifyFull);
+        assert_eq!(settings.sslrootcert.as_deref(), Some("certs/root.crt"));
+        assert_eq!(settings.connect_timeout, Some(5));
+        assert_eq!(
+            settings.target_session_attrs,
+            Some(TargetSessionAttrs::ReadWrite)
+        );
+
+        write_connection_file(based, &conn).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("sslmode = \"verify-full\""), "{raw}");
+        assert!(!raw.contains("ssl ="), "{raw}");
+        assert!(!raw.contains("sslkey"), "{raw}");
+        let loaded = parse_connection_file(&conn_dir, &path).unwrap();
+        let ConnectionSpec::Postgres {
+            sslmode: loaded_mode,
+            settings: loaded_settings,
+            ..
+        } = &loaded.spec
+        else {
+            panic!("postgres spec");
+        };
+        assert_eq!(loaded_mode, sslmode);
+        assert_eq!(loaded_settings, settings);
+
+        fs::write(
+            &path,
+            "schema_version = 1\nlabel = \"P\"\nengine = \"postgres\"\nhost = \"h\"\ndatabase = \"d\"\nusername = \"u\"\nssl = true\n",
+        )
+        .unwrap();
+        let legacy = parse_connection_file(&conn_dir, &path).unwrap();
+        assert!(matches!(
+            legacy.spec,
+            ConnectionSpec::Postgres {
+                sslmode: PostgresSslMode::Require,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn service_connections_leave_fields_to_the_service() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        let conn_dir = based.join("connections");
+        fs::create_dir_all(&conn_dir).unwrap();
+        let path = conn_dir.join("analytics.toml");
+        fs::write(
+            &path,
+            "schema_version = 1\nlabel = \"Analytics\"\nengine = \"postgres\"\nservice = \"analytics\"\ndatabase = \"events\"\n",
+        )
+        .unwrap();
+        let conn = parse_connection_file(&conn_dir, &path).unwrap();
+        let ConnectionSpec::Postgres {
+            host,
+            database,
+            username,
+            sslmode,
+            settings,
+            ..
+        } = &conn.spec
+        else {
+            panic!("postgres spec");
+        };
+        assert!(host.is_empty() && username.is_empty());
+        assert_eq!(database, "events");
+        assert_eq!(*sslmode, PostgresSslMode::Prefer);
+        assert_eq!(settings.service.as_deref(), Some("analytics"));
+
+        write_connection_file(based, &conn).unwrap();
+        let own: Table = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(!own.contains_key("host") && !own.contains_key("username"));
+        assert_eq!(own["service"].as_str(), Some("analytics"));
+
+        fs::write(
+            &path,
+            "schema_version = 1\nlabel = \"A\"\nengine = \"postgres\"\ndatabase = \"events\"\n",
+        )
+        .unwrap();
+        let err = format!("{:#}", parse_connection_file(&conn_dir, &path).unwrap_err());
+        assert!(err.contains("requires `host`"), "{err}");
+    }
+
     #[test]
     fn empty_ssh_table_is_invalid() {
         let dir = tempfile::tempdir().unwrap();
@@ -518,7 +1052,8 @@ ssl = false
                 database: "app".into(),
                 username: "app".into(),
                 password: EnvOrString::Literal(String::new()),
-                ssl: true,
+                sslmode: PostgresSslMode::Require,
+                settings: Box::default(),
             },
             ssh: Some(SshSettings {
                 host: "bastion.example.com".into(),
@@ -528,7 +1063,12 @@ ssl = false
                 key_passphrase: Some(EnvOrString::FromEnv {
                     var: "BASED_PROD_SSH_KEY_PASSPHRASE".into(),
                 }),
+                alias: None,
+                keepalive_interval: None,
+                keepalive_count_max: None,
+                jump_hosts: vec![],
             }),
+            policy: ConnectionPolicy::default(),
         };
         write_connection_file(based, &conn).unwrap();
         let raw = fs::read_to_string(based.join("connections/prod.toml")).unwrap();
@@ -565,9 +1105,11 @@ ssl = false
                 password: EnvOrString::FromEnv {
                     var: "BASED_ANALYTICS_PASSWORD".into(),
                 },
-                ssl: true,
+                sslmode: PostgresSslMode::Require,
+                settings: Box::default(),
             },
             ssh: None,
+            policy: ConnectionPolicy::default(),
         };
         let sqlite = ProjectConnection {
             id: "northwind".into(),
@@ -580,6 +1122,7 @@ ssl = false
                 pragma: None,
             },
             ssh: None,
+            policy: ConnectionPolicy::default(),
         };
         let pg_path = write_connection_file(based, &postgres).unwrap();
         let sqlite_path = write_connection_file(based, &sqlite).unwrap();
@@ -603,7 +1146,8 @@ ssl = false
                 database,
                 username,
                 password,
-                ssl,
+                sslmode,
+                ..
             } => {
                 assert_eq!(host, "db.example");
                 assert_eq!(port, &6543);
@@ -615,7 +1159,7 @@ ssl = false
                         var: "BASED_ANALYTICS_PASSWORD".into()
                     }
                 );
-                assert!(ssl);
+                assert_eq!(sslmode, &PostgresSslMode::Require);
             }
             other => panic!("expected postgres, got {other:?}"),
         }
@@ -628,4 +1172,105 @@ ssl = false
             other => panic!("expected sqlite, got {other:?}"),
         }
     }
+
+    #[test]
+    fn write_keeps_extends_and_omits_inherited_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let based = dir.path();
+        let conns = based.join("connections");
+        fs::create_dir_all(conns.join("prod")).unwrap();
+        fs::write(
+            conns.join("prod/_defaults.toml"),
+            "schema_version = 1\nengine = \"postgres\"\nhost = \"prod-db\"\nport = 5432\nssl = true\ntags = [\"prod\"]\n\n[ssh]\nhost = \"bastion\"\nuser = \"ops\"\n",
+        )
+        .unwrap();
+        fs::write(
+            conns.join("prod/_app.toml"),
+            "schema_version = 1\nusername = \"app\"\npassword = { env = \"BASED_PROD_PASSWORD\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            conns.join("prod/orders.toml"),
+            "schema_version = 1\nextends = \"prod/_app\"\nlabel = \"Orders\"\ndatabase = \"orders\"\n",
+        )
+        .unwrap();
+
+        let mut conn = load_connections_from_based_dir(based).unwrap().remove(0);
+        assert_eq!(conn.id, "prod/orders");
+        assert_eq!(conn.tags, ["prod"]);
+        assert_eq!(conn.ssh.as_ref().unwrap().user, "ops");
+        let ConnectionSpec::Postgres { database, .. } = &mut conn.spec else {
+            panic!("postgres spec");
+        };
+        *database = "orders_v2".into();
+        conn.tags.clear();
+        write_connection_file(based, &conn).unwrap();
+
+        let own: Table =
+            toml::from_str(&fs::read_to_string(conns.join("prod/orders.toml")).unwrap()).unwrap();
+        let mut keys: Vec<&str> = own.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["database", "extends", "label", "schema_version", "tags"]
+        );
+        assert_eq!(own["tags"], Value::Array(vec![]));
+        let loaded = load_connections_from_based_dir(based).unwrap().remove(0);
+        assert!(loaded.tags.is_empty());
+        assert_eq!(loaded.ssh, conn.ssh);
+        let ConnectionSpec::Postgres {
+            host,
+            database,
+            password,
+            ..
+        } = loaded.spec
+        else {
+            panic!("postgres spec");
+        };
+        assert_eq!(host, "prod-db");
+        assert_eq!(database, "orders_v2");
+        assert_eq!(
+            password,
+            EnvOrString::FromEnv {
+                var: "BASED_PROD_PASSWORD".into()
+            }
+        );
+    }
+
+    #[test]
+    fn placeholders_fill_from_environment_vars() {
+        let conn = ProjectConnection {
+            id: "app".into(),
+            label: "App".into(),
+            engine: "postgres".into(),
+            tags: vec![],
+            read_only: false,
+            spec: ConnectionSpec::Postgres {
+                host: "{{db_host}}".into(),
+                port: 5432,
+                database: "app_{{ stage }}".into(),
+                username: "app".into(),
+                password: EnvOrString::FromEnv {
+                    var: "BASED_APP_PASSWORD".into(),
+                },
+                sslmode: PostgresSslMode::Require,
+                settings: Box::default(),
+            },
+            ssh: None,
+            policy: ConnectionPolicy::default(),
+        };
+        let vars = HashMap::from([
+            ("db_host".to_string(), "staging-db.internal".to_string()),
+            ("stage".to_string(), "staging".to_string()),
+        ]);
+        let ConnectionSpec::Postgres { host, database, .. } = conn.with_vars(&vars).unwrap().spec
+        else {
+            panic!("postgres spec");
+        };
+        assert_eq!(host, "staging-db.internal");
+        assert_eq!(database, "app_staging");
+
+        let err = conn.with_vars(&HashMap::new()).unwrap_err();
+        assert!(format!("{err:#}").contains("{{db_host}}"), "{err:#}");
+    }
 }
@@ -34,24 +34,31 @@ pub fn upsert_env_file(path: &Path, key: &str, value: &str) -> Result<()> {
     Ok(())
 }
 
+/// Keys assigned in `.env`-style text (e.g. `.env.example`), with 1-based line numbers.
+pub(crate) fn env_keys(raw: &str) -> Vec<(usize, String)> {
+    env_entries(raw)
+        .map(|(line, key, _)| (line, key.to_string()))
+        .collect()
+}
+
 fn parse_env(raw: &str) -> HashMap<String, String> {
-    let mut out = HashMap::new();
-    for line in raw.lines() {
+    env_entries(raw)
+        .map(|(_, key, value)| (key.to_string(), unquote(value)))
+        .collect()
+}
+
+/// `(line, key, raw value)` for each assignment, skipping blanks and `#` comments.
+fn env_entries(raw: &str) -> impl Iterator<Item = (usize, &str, &str)> {
+    raw.lines().enumerate().filter_map(|(i, line)| {
         let line = line.trim();
         if line.is_empty() || line.starts_with('#') {
-            continue;
+            return None;
         }
         let line = line.strip_prefix("export ").unwrap_or(line).trim();
-        let Some((key, value)) = line.split_once('=') else {
-            continue;
-        };
+        let (key, value) = line.split_once('=')?;
         let key = key.trim();
-        if key.is_empty() {
-            continue;
-        }
-        out.insert(key.to_string(), unquote(value.trim()));
-    }
-    out
+        (!key.is_empty()).then_some((i + 1, key, value.trim()))
+    })
 }
 
 fn upsert_env(raw: &str, key: &str, value: &str) -> String {
@@ -0,0 +1,235 @@
+//! `{ env = "…" }` references across a project: which variables teammates need, which
+//! are unset on this machine, and keeping `.env.example` listing them.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::connection::{ConnectionSpec, ProjectConnection};
+use crate::dotenv::{env_keys, load_env_file};
+use crate::env_value::EnvOrString;
+use crate::environment::{ProjectEnvironment, load_environments};
+use crate::validate::load_connections_lenient;
+
+/// An environment variable named by `{ env = "…" }`, with the settings that read it
+/// (e.g. `connection local/pg password`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvVarRef {
+    pub var: String,
+    pub used_by: Vec<String>,
+}
+
+/// Every variable referenced by connection secrets, SSH key passphrases, and committed
+/// environments, sorted by name.
+pub fn env_var_refs(
+    connections: &[ProjectConnection],
+    environments: &[ProjectEnvironment],
+) -> Vec<EnvVarRef> {
+    let mut refs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut add = |value: Option<&EnvOrString>, used_by: String| {
+        if let Some(EnvOrString::FromEnv { var }) = value {
+            refs.entry(var.clone()).or_default().push(used_by);
+        }
+    };
+    for conn in connections {
+        match &conn.spec {
+            ConnectionSpec::Postgres { password, .. } => {
+                add(Some(password), format!("connection {} password", conn.id));
+            }
+            ConnectionSpec::MongoDB { url, .. } => {
+                add(Some(url), format!("connection {} url", conn.id));
+            }
+            ConnectionSpec::Sqlite { .. } => {}
+        }
+        if let Some(ssh) = &conn.ssh {
+            add(
+                ssh.key_passphrase.as_ref(),
+                format!("connection {} [ssh] key_passphrase", conn.id),
+            );
+            for jump in &ssh.jump_hosts {
+                add(
+                    jump.key_passphrase.as_ref(),
+                    format!(
+                        "connection {} [[ssh.jump_hosts]] {} key_passphrase",
+                        conn.id, jump.host
+                    ),
+                );
+            }
+        }
+    }
+    for environment in environments {
+        for (key, value) in &environment.vars {
+            add(
+                Some(value),
+                format!("environment {} var {key}", environment.name),
+            );
+        }
+    }
+    refs.into_iter()
+        .map(|(var, used_by)| EnvVarRef { var, used_by })
+        .collect()
+}
+
+/// References in the project at `project_root`. Files that fail to load are skipped;
+/// the Problems pane reports them.
+pub fn project_env_var_refs(project_root: &Path) -> Vec<EnvVarRef> {
+    let (connections, _) = load_connections_lenient(&project_root.join(".based"));
+    let environments = load_environments(project_root).unwrap_or_default();
+    env_var_refs(&connections, &environments)
+}
+
+/// References set neither in the project `.env` nor the process environment.
+pub fn missing_env_vars(project_root: &Path) -> Result<Vec<EnvVarRef>> {
+    let file_vars = load_env_file(&project_root.join(".based").join(".env"))?;
+    Ok(project_env_var_refs(project_root)
+        .into_iter()
+        .filter(|r| !file_vars.contains_key(&r.var) && env::var_os(&r.var).is_none())
+        .collect())
+}
+
+/// What [`sync_env_example`] changed in `.env.example`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvExampleSync {
+    /// Referenced variables the file did not list.
+    pub added: Vec<String>,
+    /// Listed keys nothing references any more.
+    pub removed: Vec<String>,
+    /// The file was rewritten (also when only a value or comment differed).
+    pub changed: bool,
+}
+
+/// Regenerate `.based/.env.example` from the references: every referenced variable with
+/// a blank value and a comment naming what reads it, and nothing else. The file is
+/// committed, so keys nothing references and example values, which may be real secrets,
+/// are dropped rather than kept; hand-written lines do not survive either.
+pub fn sync_env_example(project_root: &Path) -> Result<EnvExampleSync> {
+    let path = project_root.join(".based").join(".env.example");
+    let existing = if path.exists() {
+        fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let listed: Vec<String> = env_keys(&existing)
+        .into_iter()
+        .map(|(_, key)| key)
+        .collect();
+    let refs = project_env_var_refs(project_root);
+    let added = refs
+        .iter()
+        .filter(|r| !listed.contains(&r.var))
+        .map(|r| r.var.clone())
+        .collect();
+    let removed = listed
+        .into_iter()
+        .filter(|key| !refs.iter().any(|r| &r.var == key))
+        .collect();
+    let content = example_file(&refs);
+    let changed = content != existing;
+    if changed {
+        fs::write(&path, content).with_context(|| format!("write {}", path.display()))?;
+    }
+    Ok(EnvExampleSync {
+        added,
+        removed,
+        changed,
+    })
+}
+
+fn example_file(refs: &[EnvVarRef]) -> String {
+    let mut out = "# Copy to .env and fill in values for this machine.\n".to_string();
+    for r in refs {
+        out.push('\n');
+        for used_by in &r.used_by {
+            out.push_str(&format!("# {used_by}\n"));
+        }
+        out.push_str(&format!("{}=\n", r.var));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, raw: &str) {
+        let path = root.join(".based").join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, raw).unwrap();
+    }
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "connections/prod.toml",
+            r#"schema_version = 1
+label = "Prod"
+engine = "postgres"
+host = "db.internal"
+database = "app"
+username = "app"
+password = { env = "BASED_REFS_PROD_PASSWORD" }
+
+[ssh]
+host = "bastion"
+user = "deploy"
+key_passphrase = { env = "BASED_REFS_BASTION_PASSPHRASE" }
+"#,
+        );
+        write(
+            dir.path(),
+            "environments/staging.toml",
+            "schema_version = 1\n\n[vars]\nregion = \"eu\"\ntoken = { env = \"BASED_REFS_PROD_PASSWORD\" }\n",
+        );
+        dir
+    }
+
+    #[test]
+    fn refs_cover_passwords_passphrases_and_environments() {
+        let dir = project();
+        let refs = project_env_var_refs(dir.path());
+        let vars: Vec<&str> = refs.iter().map(|r| r.var.as_str()).collect();
+        assert_eq!(
+            vars,
+            ["BASED_REFS_BASTION_PASSPHRASE", "BASED_REFS_PROD_PASSWORD"]
+        );
+        assert_eq!(
+            refs[1].used_by,
+            ["connection prod password", "environment staging var token"]
+        );
+
+        write(dir.path(), ".env", "BASED_REFS_PROD_PASSWORD=x\n");
+        let missing = missing_env_vars(dir.path()).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].var, "BASED_REFS_BASTION_PASSPHRASE");
+    }
+
+    #[test]
+    fn sync_lists_only_referenced_vars_with_blank_values() {
+        let dir = project();
+        write(
+            dir.path(),
+            ".env.example",
+            "# Team secrets\nBASED_REFS_PROD_PASSWORD=hunter2\nOTHER=1",
+        );
+        let sync = sync_env_example(dir.path()).unwrap();
+        assert_eq!(sync.added, ["BASED_REFS_BASTION_PASSPHRASE"]);
+        assert_eq!(sync.removed, ["OTHER"]);
+        assert!(sync.changed);
+        let raw = fs::read_to_string(dir.path().join(".based/.env.example")).unwrap();
+        assert_eq!(
+            raw,
+            "# Copy to .env and fill in values for this machine.\n\
+             \n# connection prod [ssh] key_passphrase\nBASED_REFS_BASTION_PASSPHRASE=\n\
+             \n# connection prod password\n# environment staging var token\n\
+             BASED_REFS_PROD_PASSWORD=\n"
+        );
+        assert_eq!(
+            sync_env_example(dir.path()).unwrap(),
+            EnvExampleSync::default()
+        );
+    }
+}
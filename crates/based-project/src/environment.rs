@@ -1,10 +1,16 @@
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::env_value::EnvOrString;
+use crate::migrate::{FileKind, parse_versioned};
+use crate::walk::{rel_id, walk_toml_files};
+
 pub const ENVIRONMENT_SCHEMA_VERSION: u64 = 1;
+pub const PROJECT_ENVIRONMENT_SCHEMA_VERSION: u64 = 1;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ActiveEnvironment {
@@ -30,15 +36,7 @@ pub fn load_active_environment(project_root: &Path) -> Result<ActiveEnvironment>
         return Ok(ActiveEnvironment::default());
     }
     let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
-    let env: ActiveEnvironment =
-        toml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
-    if env.schema_version != ENVIRONMENT_SCHEMA_VERSION {
-        bail!(
-            "unsupported active_environment schema_version {} (expected {ENVIRONMENT_SCHEMA_VERSION})",
-            env.schema_version
-        );
-    }
-    Ok(env)
+    parse_versioned(FileKind::ActiveEnvironment, &raw, &path)
 }
 
 pub fn persist_active_environment(project_root: &Path, name: &str) -> Result<()> {
@@ -53,3 +51,127 @@ pub fn persist_active_environment(project_root: &Path, name: &str) -> Result<()>
     fs::write(&path, content).with_context(|| format!("write {}", path.display()))?;
     Ok(())
 }
+
+/// A committed `environments/**/*.toml`: plain values plus `{ env = "…" }` references
+/// for secrets, so the file itself never holds one.
+#[derive(Debug, Clone)]
+pub struct ProjectEnvironment {
+    /// Path relative to `environments/` without the `.toml` suffix (e.g. `staging`).
+    pub name: String,
+    pub description: Option<String>,
+    pub vars: BTreeMap<String, EnvOrString>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEnvironmentFile {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    vars: BTreeMap<String, EnvOrString>,
+}
+
+impl ProjectEnvironment {
+    /// Values with env references resolved from the process environment, then `file_vars`
+    /// (the project `.env`).
+    pub fn resolve_with(
+        &self,
+        file_vars: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>> {
+        self.vars
+            .iter()
+            .map(|(key, value)| {
+                let value = value
+                    .resolve_with(file_vars)
+                    .with_context(|| format!("environment {} var `{key}`", self.name))?;
+                Ok((key.clone(), value))
+            })
+            .collect()
+    }
+}
+
+pub fn load_environments(project_root: &Path) -> Result<Vec<ProjectEnvironment>> {
+    let dir = project_root.join(".based").join("environments");
+    walk_toml_files(&dir)?
+        .iter()
+        .map(|path| parse_environment_file(&dir, path))
+        .collect()
+}
+
+pub(crate) fn parse_environment_file(
+    environments_dir: &Path,
+    path: &Path,
+) -> Result<ProjectEnvironment> {
+    let rel = path
+        .strip_prefix(environments_dir)
+        .with_context(|| format!("environment path not under {}", environments_dir.display()))?;
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let file: RawEnvironmentFile = parse_versioned(FileKind::Environment, &raw, path)?;
+    Ok(ProjectEnvironment {
+        name: rel_id(rel),
+        description: file.description,
+        vars: file.vars,
+    })
+}
+
+/// Resolved values of the active environment, or `None` when the active name has no
+/// `environments/` file ("No Environment").
+pub fn active_environment_vars(
+    project_root: &Path,
+    file_vars: &HashMap<String, String>,
+) -> Result<Option<HashMap<String, String>>> {
+    let active = load_active_environment(project_root)?.name;
+    load_environments(project_root)?
+        .into_iter()
+        .find(|env| env.name == active)
+        .map(|env| env.resolve_with(file_vars))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_env(root: &Path, name: &str, raw: &str) {
+        let dir = root.join(".based").join("environments");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(format!("{name}.toml")), raw).unwrap();
+    }
+
+    #[test]
+    fn active_environment_resolves_env_references() {
+        let dir = tempfile::tempdir().unwrap();
+        write_env(
+            dir.path(),
+            "staging",
+            r#"
+schema_version = 1
+description = "Shared staging cluster"
+
+[vars]
+db_host = "staging-db.internal"
+db_password = { env = "BASED_TEST_STAGING_DB_PASSWORD" }
+"#,
+        );
+        write_env(dir.path(), "prod", "schema_version = 1\n");
+
+        assert!(
+            active_environment_vars(dir.path(), &HashMap::new())
+                .unwrap()
+                .is_none()
+        );
+
+        persist_active_environment(dir.path(), "staging").unwrap();
+        let file_vars = HashMap::from([(
+            "BASED_TEST_STAGING_DB_PASSWORD".to_string(),
+            "s3cret".to_string(),
+        )]);
+        let vars = active_environment_vars(dir.path(), &file_vars)
+            .unwrap()
+            .expect("staging is active");
+        assert_eq!(vars["db_host"], "staging-db.internal");
+        assert_eq!(vars["db_password"], "s3cret");
+
+        let err = active_environment_vars(dir.path(), &HashMap::new()).unwrap_err();
+        assert!(format!("{err:#}").contains("db_password"), "{err:#}");
+    }
+}
@@ -1,9 +1,10 @@
 use std::fs;
 use std::path::Path;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::migrate::{FileKind, parse_versioned};
 use crate::walk::strip_based_toml_suffix;
 
 pub const FAVORITES_SCHEMA_VERSION: u64 = 1;
@@ -29,14 +30,7 @@ pub fn load_favorites(project_root: &Path) -> Result<Vec<String>> {
         return Ok(vec![]);
     }
     let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
-    let file: FavoritesFile =
-        toml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
-    if file.schema_version != FAVORITES_SCHEMA_VERSION {
-        bail!(
-            "unsupported favorites schema_version {} (expected {FAVORITES_SCHEMA_VERSION})",
-            file.schema_version
-        );
-    }
+    let file: FavoritesFile = parse_versioned(FileKind::Favorites, &raw, &path)?;
     Ok(file
         .favorites
         .into_iter()
@@ -0,0 +1,305 @@
+//! Connection inheritance: `_defaults.toml` per folder and `extends = "<id>"`.
+//!
+//! A connection file is merged over, lowest first: every `_defaults.toml` from
+//! `connections/` down to its own folder, then the keys of the connection it `extends`
+//! and of that connection's own `extends` chain. The base's folder defaults are not
+//! carried along, so they never override the file's own folder. Tables such as `[ssh]`
+//! merge key by key; other values replace. Inherited values can be overridden but not
+//! removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use toml::{Table, Value};
+
+use crate::migrate::{FileKind, parse_versioned};
+
+pub(crate) const DEFAULTS_FILE: &str = "_defaults.toml";
+
+/// The file's own keys merged over everything it inherits.
+pub(crate) fn resolve_connection_table(connections_dir: &Path, path: &Path) -> Result<Table> {
+    resolve(connections_dir, path, &mut Vec::new())
+}
+
+/// What a connection at `path` inherits before its own keys apply: folder defaults, then
+/// `extends` when set.
+pub(crate) fn inherited_table(
+    connections_dir: &Path,
+    path: &Path,
+    extends: Option<&str>,
+) -> Result<Table> {
+    inherited(connections_dir, path, extends, &mut Vec::new())
+}
+
+/// `extends` of an existing connection file, so a rewrite keeps it.
+pub(crate) fn existing_extends(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(read_layer(path)?.1)
+}
+
+fn resolve(connections_dir: &Path, path: &Path, chain: &mut Vec<PathBuf>) -> Result<Table> {
+    enter(chain, path)?;
+    let (own, extends) = read_layer(path)?;
+    let mut merged = inherited(connections_dir, path, extends.as_deref(), chain)?;
+    merge(&mut merged, own);
+    chain.pop();
+    Ok(merged)
+}
+
+fn inherited(
+    connections_dir: &Path,
+    path: &Path,
+    extends: Option<&str>,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Table> {
+    let mut merged = folder_defaults(connections_dir, path)?;
+    if let Some(base) = extends {
+        let base = extended(connections_dir, path, base, chain)
+            .with_context(|| format!("resolve `extends` of {}", path.display()))?;
+        merge(&mut merged, base);
+    }
+    Ok(merged)
+}
+
+/// The keys of `base` merged over its own `extends` chain, without any folder defaults.
+fn extended(
+    connections_dir: &Path,
+    path: &Path,
+    base: &str,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Table> {
+    let base_path = connections_dir.join(format!("{base}.toml"));
+    if !base_path.is_file() {
+        bail!(
+            "{} extends `{base}`, but {} does not exist",
+            path.display(),
+            base_path.display()
+        );
+    }
+    enter(chain, &base_path)?;
+    let (own, extends) = read_layer(&base_path)?;
+    let mut merged = match extends {
+        Some(next) => extended(connections_dir, &base_path, &next, chain)?,
+        None => Table::new(),
+    };
+    merge(&mut merged, own);
+    chain.pop();
+    Ok(merged)
+}
+
+/// Push `path` onto the `extends` chain, or report the cycle it closes.
+fn enter(chain: &mut Vec<PathBuf>, path: &Path) -> Result<()> {
+    if chain.iter().any(|p| p == path) {
+        let names: Vec<String> = chain
+            .iter()
+            .chain([&path.to_path_buf()])
+            .map(|p| p.display().to_string())
+            .collect();
+        bail!("connection `extends` cycle: {}", names.join(" → "));
+    }
+    chain.push(path.to_path_buf());
+    Ok(())
+}
+
+/// `_defaults.toml` files from `connections_dir` down to the folder of `path`, merged.
+fn folder_defaults(connections_dir: &Path, path: &Path) -> Result<Table> {
+    let mut merged = Table::new();
+    let Some(folder) = path.parent() else {
+        return Ok(merged);
+    };
+    let mut dirs: Vec<&Path> = folder
+        .ancestors()
+        .take_while(|dir| dir.starts_with(connections_dir))
+        .collect();
+    dirs.reverse();
+    for dir in dirs {
+        let defaults = dir.join(DEFAULTS_FILE);
+        if !defaults.is_file() {
+            continue;
+        }
+        let (table, extends) = read_layer(&defaults)?;
+        if extends.is_some() {
+            bail!("{} cannot use `extends`", defaults.display());
+        }
+        merge(&mut merged, table);
+    }
+    Ok(merged)
+}
+
+/// A file's own keys without `schema_version` and `extends`, plus its `extends`.
+fn read_layer(path: &Path) -> Result<(Table, Option<String>)> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let mut table: Table = parse_versioned(FileKind::Connection, &raw, path)?;
+    table.remove("schema_version");
+    let extends = match table.remove("extends") {
+        None => None,
+        Some(Value::String(id)) => Some(id),
+        Some(_) => bail!(
+            "{}: `extends` must be a connection id string",
+            path.display()
+        ),
+    };
+    Ok((table, extends))
+}
+
+fn merge(base: &mut Table, over: Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(over)) => merge(base, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Keys of `own` whose value `base` already provides, as `key` or `table.key` paths.
+/// A table whose every key is inherited is returned whole.
+pub(crate) fn inherited_keys(own: &Table, base: &Table) -> Vec<Vec<String>> {
+    let mut out = Vec::new();
+    for (key, value) in own {
+        match (value, base.get(key)) {
+            (Value::Table(own), Some(Value::Table(base))) => {
+                let sub = inherited_keys(own, base);
+                if sub.len() == own.len() {
+                    out.push(vec![key.clone()]);
+                } else {
+                    out.extend(sub.into_iter().map(|mut path| {
+                        path.insert(0, key.clone());
+                        path
+                    }));
+                }
+            }
+            (value, Some(base)) if value == base => out.push(vec![key.clone()]),
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, rel: &str, raw: &str) -> PathBuf {
+        let path = dir.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, raw).unwrap();
+        path
+    }
+
+    #[test]
+    fn defaults_then_extends_then_own_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "_defaults.toml",
+            "schema_version = 1\nengine = \"postgres\"\nport = 5432\n\n[ssh]\nhost = \"bastion\"\nuser = \"ops\"\n",
+        );
+        write(
+            root,
+            "prod/_defaults.toml",
+            "schema_version = 1\nhost = \"prod-db\"\nssl = true\n",
+        );
+        write(
+            root,
+            "base/_pg.toml",
+            "schema_version = 1\nusername = \"app\"\nport = 6432\n\n[ssh]\nport = 2222\n",
+        );
+        let orders = write(
+            root,
+            "prod/orders.toml",
+            "schema_version = 1\nextends = \"base/_pg\"\nlabel = \"Orders\"\ndatabase = \"orders\"\n",
+        );
+        let table = resolve_connection_table(root, &orders).unwrap();
+        assert_eq!(table["host"].as_str(), Some("prod-db"));
+        assert_eq!(table["port"].as_integer(), Some(6432));
+        assert_eq!(table["username"].as_str(), Some("app"));
+        assert_eq!(table["database"].as_str(), Some("orders"));
+        assert_eq!(table["ssh"]["host"].as_str(), Some("bastion"));
+        assert_eq!(table["ssh"]["port"].as_integer(), Some(2222));
+        assert!(!table.contains_key("extends"));
+
+        let base = inherited_table(root, &orders, Some("base/_pg")).unwrap();
+        let own: Table = toml::from_str(
+            "label = \"Orders\"\nhost = \"prod-db\"\nport = 5433\n\n[ssh]\nhost = \"bastion\"\nuser = \"ops\"\nport = 2222\n",
+        )
+        .unwrap();
+        let mut keys = inherited_keys(&own, &base);
+        keys.sort();
+        assert_eq!(keys, [vec!["host".to_string()], vec!["ssh".to_string()]]);
+    }
+
+    #[test]
+    fn folder_defaults_are_not_overridden_by_the_base_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "_defaults.toml",
+            "schema_version = 1\nhost = \"localhost\"\nport = 5432\nssl = false\n",
+        );
+        write(
+            root,
+            "prod/_defaults.toml",
+            "schema_version = 1\nhost = \"prod-db\"\nssl = true\n",
+        );
+        write(
+            root,
+            "base/_defaults.toml",
+            "schema_version = 1\nhost = \"base-db\"\n",
+        );
+        write(root, "base/_root.toml", "schema_version = 1\nport = 6432\n");
+        write(
+            root,
+            "base/pg.toml",
+            "schema_version = 1\nextends = \"base/_root\"\nusername = \"app\"\n",
+        );
+        let orders = write(
+            root,
+            "prod/orders.toml",
+            "schema_version = 1\nextends = \"base/pg\"\ndatabase = \"orders\"\n",
+        );
+        let table = resolve_connection_table(root, &orders).unwrap();
+        assert_eq!(table["host"].as_str(), Some("prod-db"));
+        assert_eq!(table["ssl"].as_bool(), Some(true));
+        assert_eq!(table["port"].as_integer(), Some(6432));
+        assert_eq!(table["username"].as_str(), Some("app"));
+
+        let base = write(
+            root,
+            "base/pg.toml",
+            "schema_version = 1\nhost = \"pg-db\"\n",
+        );
+        assert_eq!(
+            resolve_connection_table(root, &base).unwrap()["host"].as_str(),
+            Some("pg-db")
+        );
+        let table = resolve_connection_table(root, &orders).unwrap();
+        assert_eq!(table["host"].as_str(), Some("pg-db"));
+    }
+
+    #[test]
+    fn extends_cycles_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = write(
+            dir.path(),
+            "a.toml",
+            "schema_version = 1\nextends = \"b\"\n",
+        );
+        write(
+            dir.path(),
+            "b.toml",
+            "schema_version = 1\nextends = \"a\"\n",
+        );
+        let err = format!(
+            "{:#}",
+            resolve_connection_table(dir.path(), &a).unwrap_err()
+        );
+        assert!(err.contains("cycle"), "{err}");
+    }
+}
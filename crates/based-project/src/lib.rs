@@ -2,24 +2,48 @@
 
 mod connection;
 mod dotenv;
+mod env_refs;
 mod env_value;
 mod environment;
 mod favorites;
+mod inherit;
 mod load;
+mod migrate;
+mod policy;
+mod postgres;
 mod project;
 mod query;
+mod query_params;
 mod target;
+mod validate;
 mod walk;
 
 pub use connection::{
-    ConnectionSpec, PragmaSettings, ProjectConnection, SshSettings, load_connections,
-    load_connections_from_based_dir, slug_from_label, write_connection_file,
+    ConnectionSpec, PragmaSettings, ProjectConnection, SshJumpSettings, SshSettings,
+    load_connections, load_connections_from_based_dir, slug_from_label, write_connection_file,
 };
 pub use dotenv::{load_env_file, secret_env_key, upsert_env_file};
+pub use env_refs::{
+    EnvExampleSync, EnvVarRef, env_var_refs, missing_env_vars, project_env_var_refs,
+    sync_env_example,
+};
 pub use env_value::EnvOrString;
-pub use environment::{ActiveEnvironment, load_active_environment, persist_active_environment};
+pub use environment::{
+    ActiveEnvironment, ProjectEnvironment, active_environment_vars, load_active_environment,
+    load_environments, persist_active_environment,
+};
 pub use favorites::{FavoriteEntry, FavoritesFile, load_favorites, persist_favorites};
 pub use load::{ProjectSnapshot, load_project};
-pub use project::{ProjectManifest, ProjectSettings};
-pub use query::{ProjectQuery, QueryBody};
+pub use migrate::{FileKind, FileMigration, apply_project_migration, plan_project_migration};
+pub use policy::{ConnectionPolicy, STATEMENT_KIND_NAMES};
+pub use postgres::{PostgresSettings, PostgresSslMode, TargetSessionAttrs};
+pub use project::{DEFAULT_MAX_RESULT_ROWS, ProjectManifest, ProjectSettings};
+pub use query::{
+    PARAM_TYPE_NAMES, ProjectQuery, QueryBody, rename_query_file, write_query_body,
+    write_query_file,
+};
+pub use query_params::{QueryParamsFile, load_query_params, persist_query_params};
 pub use target::{ConnectionRef, QueryTarget, ResolveError, TargetConnection, resolve_target};
+pub use validate::{
+    Diagnostic, ProjectValidation, Severity, load_connections_lenient, validate_project,
+};
@@ -5,7 +5,7 @@ use anyhow::Result;
 use crate::connection::ProjectConnection;
 
 use crate::connection::load_connections;
-use crate::environment::load_active_environment;
+use crate::environment::{ProjectEnvironment, load_active_environment, load_environments};
 use crate::favorites::load_favorites;
 use crate::project::{ProjectManifest, load_manifest};
 use crate::query::{ProjectQuery, load_queries};
@@ -16,6 +16,7 @@ pub struct ProjectSnapshot {
     pub connections: Vec<ProjectConnection>,
     pub queries: Vec<ProjectQuery>,
     pub favorites: Vec<String>,
+    pub environments: Vec<ProjectEnvironment>,
     pub active_environment: String,
 }
 
@@ -24,12 +25,14 @@ pub fn load_project(project_root: &Path) -> Result<ProjectSnapshot> {
     let connections = load_connections(project_root)?;
     let queries = load_queries(project_root)?;
     let favorites = load_favorites(project_root)?;
+    let environments = load_environments(project_root)?;
     let active_environment = load_active_environment(project_root)?.name;
     Ok(ProjectSnapshot {
         manifest,
         connections,
         queries,
         favorites,
+        environments,
         active_environment,
     })
 }
@@ -0,0 +1,349 @@
+//! Upgrade `.based/` files written by older builds to the current `schema_version`.
+//!
+//! Loaders upgrade in memory, so a project keeps opening after a format bump. "Migrate
+//! project" rewrites the files on disk once the team is ready, after a diff preview.
+//! Files newer than this build are rejected with a request to upgrade Based.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
+use toml_edit::{DocumentMut, Item, value};
+
+use crate::connection::CONNECTION_SCHEMA_VERSION;
+use crate::environment::{ENVIRONMENT_SCHEMA_VERSION, PROJECT_ENVIRONMENT_SCHEMA_VERSION};
+use crate::favorites::FAVORITES_SCHEMA_VERSION;
+use crate::inherit::DEFAULTS_FILE;
+use crate::project::PROJECT_SCHEMA_VERSION;
+use crate::query::QUERY_SCHEMA_VERSION;
+use crate::query_params::QUERY_PARAMS_SCHEMA_VERSION;
+use crate::walk::{walk_named_files, walk_toml_files};
+
+/// Each `.based/` file type carries its own `schema_version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Project,
+    Connection,
+    Query,
+    Environment,
+    Favorites,
+    ActiveEnvironment,
+    QueryParams,
+}
+
+impl FileKind {
+    pub fn current_version(self) -> u64 {
+        match self {
+            Self::Project => PROJECT_SCHEMA_VERSION,
+            Self::Connection => CONNECTION_SCHEMA_VERSION,
+            Self::Query => QUERY_SCHEMA_VERSION,
+            Self::Environment => PROJECT_ENVIRONMENT_SCHEMA_VERSION,
+            Self::Favorites => FAVORITES_SCHEMA_VERSION,
+            Self::ActiveEnvironment => ENVIRONMENT_SCHEMA_VERSION,
+            Self::QueryParams => QUERY_PARAMS_SCHEMA_VERSION,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Project => "project.toml",
+            Self::Connection => "connection",
+            Self::Query => "query",
+            Self::Environment => "environment",
+            Self::Favorites => "favorites",
+            Self::ActiveEnvironment => "active_environment",
+            Self::QueryParams => "query_params",
+        }
+    }
+}
+
+/// Upgrades a `kind` document from `schema_version = from` to `from + 1`. The step edits
+/// the document; the version stamp is bumped by the caller.
+struct Migration {
+    kind: FileKind,
+    from: u64,
+    apply: fn(&mut DocumentMut) -> Result<()>,
+}
+
+/// Upgrade steps, oldest first. Every format is still at version 1; a version bump adds
+/// its step here alongside the raised `*_SCHEMA_VERSION` constant.
+const MIGRATIONS: &[Migration] = &[];
+
+fn schema_version(doc: &DocumentMut) -> Option<u64> {
+    doc.get("schema_version")
+        .and_then(Item::as_integer)
+        .and_then(|v| u64::try_from(v).ok())
+}
+
+/// Upgrade `doc` to the current version of `kind`. Returns the version it started at.
+fn upgrade_with(
+    kind: FileKind,
+    doc: &mut DocumentMut,
+    path: &Path,
+    steps: &[Migration],
+) -> Result<u64> {
+    let current = kind.current_version();
+    let Some(from) = schema_version(doc) else {
+        bail!("{} has no integer schema_version", path.display());
+    };
+    if from > current {
+        bail!(
+            "{} uses {} schema_version {from}, but this build of Based reads up to {current}; upgrade Based to open it",
+            path.display(),
+            kind.label()
+        );
+    }
+    for version in from..current {
+        let Some(step) = steps.iter().find(|s| s.kind == kind && s.from == version) else {
+            bail!(
+                "no migration for {} schema_version {version} in {}",
+                kind.label(),
+                path.display()
+            );
+        };
+        (step.apply)(doc)
+            .with_context(|| format!("migrate {} from schema_version {version}", path.display()))?;
+        doc["schema_version"] = value((version + 1) as i64);
+    }
+    Ok(from)
+}
+
+pub(crate) fn upgrade_document(kind: FileKind, doc: &mut DocumentMut, path: &Path) -> Result<()> {
+    upgrade_with(kind, doc, path, MIGRATIONS).map(|_| ())
+}
+
+/// Parse a `kind` file, upgrading an older schema in memory. The file on disk is untouched.
+pub(crate) fn parse_versioned<T: DeserializeOwned>(
+    kind: FileKind,
+    raw: &str,
+    path: &Path,
+) -> Result<T> {
+    let mut doc: DocumentMut = raw
+        .parse()
+        .with_context(|| format!("parse {}", path.display()))?;
+    upgrade_document(kind, &mut doc, path)?;
+    toml::from_str(&doc.to_string()).with_context(|| format!("parse {}", path.display()))
+}
+
+/// One file "Migrate project" would rewrite.
+#[derive(Debug, Clone)]
+pub struct FileMigration {
+    pub path: PathBuf,
+    pub kind: FileKind,
+    pub from: u64,
+    pub to: u64,
+    pub before: String,
+    pub after: String,
+}
+
+impl FileMigration {
+    /// Line diff of the rewrite, `-` / `+` / ` ` prefixed, for the preview.
+    pub fn diff(&self) -> String {
+        line_diff(&self.before, &self.after)
+    }
+}
+
+/// Versioned files of a project, with their kinds.
+fn project_files(project_root: &Path) -> Result<Vec<(FileKind, PathBuf)>> {
+    let based = project_root.join(".based");
+    let mut files = vec![(FileKind::Project, based.join("project.toml"))];
+    for path in walk_toml_files(&based.join("connections"))? {
+        files.push((FileKind::Connection, path));
+    }
+    for path in walk_named_files(&based.join("connections"), DEFAULTS_FILE)? {
+        files.push((FileKind::Connection, path));
+    }
+    for path in walk_toml_files(&based.join("queries"))? {
+        files.push((FileKind::Query, path));
+    }
+    for path in walk_toml_files(&based.join("environments"))? {
+        files.push((FileKind::Environment, path));
+    }
+    let state = based.join("state");
+    files.push((FileKind::Favorites, state.join("favorites.toml")));
+    files.push((
+        FileKind::ActiveEnvironment,
+        state.join("active_environment.toml"),
+    ));
+    files.push((FileKind::QueryParams, state.join("query_params.toml")));
+    Ok(files
+        .into_iter()
+        .filter(|(_, path)| path.is_file())
+        .collect())
+}
+
+fn plan_with(project_root: &Path, steps: &[Migration]) -> Result<Vec<FileMigration>> {
+    let mut plan = Vec::new();
+    for (kind, path) in project_files(project_root)? {
+        let before =
+            fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        let mut doc: DocumentMut = before
+            .parse()
+            .with_context(|| format!("parse {}", path.display()))?;
+        let from = upgrade_with(kind, &mut doc, &path, steps)?;
+        let to = kind.current_version();
+        if from < to {
+            plan.push(FileMigration {
+                after: doc.to_string(),
+                path,
+                kind,
+                from,
+                to,
+                before,
+            });
+        }
+    }
+    Ok(plan)
+}
+
+/// Files under `.based/` that are older than this build, with their upgraded text.
+/// Nothing is written; pass the plan to [`apply_project_migration`] after review.
+pub fn plan_project_migration(project_root: &Path) -> Result<Vec<FileMigration>> {
+    plan_with(project_root, MIGRATIONS)
+}
+
+/// Write a reviewed plan. If any file changed since it was planned nothing is written,
+/// so a concurrent edit is never overwritten.
+pub fn apply_project_migration(plan: &[FileMigration]) -> Result<()> {
+    for file in plan {
+        let now = fs::read_to_string(&file.path)
+            .with_context(|| format!("read {}", file.path.display()))?;
+        if now != file.before {
+            bail!(
+                "{} changed since the migration was previewed; preview again",
+                file.path.display()
+            );
+        }
+    }
+    for file in plan {
+        fs::write(&file.path, &file.after)
+            .with_context(|| format!("write {}", file.path.display()))?;
+    }
+    Ok(())
+}
+
+/// Longest-common-subsequence line diff. `.based/` files are small, so the quadratic
+/// table is fine.
+fn line_diff(before: &str, after: &str) -> String {
+    let a: Vec<&str> = before.lines().collect();
+    let b: Vec<&str> = after.lines().collect();
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push_str(&format!(" {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out.push_str(&format!("+{}\n", b[j]));
+            j += 1;
+        } else {
+            out.push_str(&format!("-{}\n", a[i]));
+            i += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pretend `query` is at version 2, where v1 `[sql] text` became `[sql] query`.
+    fn rename_text(doc: &mut DocumentMut) -> Result<()> {
+        let sql = doc["sql"].as_table_like_mut().context("missing [sql]")?;
+        if let Some(text) = sql.remove("text") {
+            sql.insert("query", text);
+        }
+        Ok(())
+    }
+
+    const V0_TO_V1: &[Migration] = &[Migration {
+        kind: FileKind::Query,
+        from: 0,
+        apply: rename_text,
+    }];
+
+    const OLD_QUERY: &str = r#"schema_version = 0
+# Team report
+name = "Orders"
+
+[target]
+engine = "sqlite"
+
+[sql]
+text = "SELECT 1"
+"#;
+
+    fn project_with_query(raw: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let queries = dir.path().join(".based").join("queries");
+        fs::create_dir_all(&queries).unwrap();
+        fs::write(queries.join("orders.toml"), raw).unwrap();
+        dir
+    }
+
+    #[test]
+    fn newer_files_ask_for_an_upgrade() {
+        let raw = "schema_version = 9\nname = \"x\"\n";
+        let mut doc: DocumentMut = raw.parse().unwrap();
+        let err = upgrade_with(FileKind::Query, &mut doc, Path::new("q.toml"), MIGRATIONS)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("upgrade Based"), "{err}");
+    }
+
+    #[test]
+    fn older_files_upgrade_step_by_step_and_keep_comments() {
+        let mut doc: DocumentMut = OLD_QUERY.parse().unwrap();
+        let from = upgrade_with(FileKind::Query, &mut doc, Path::new("q.toml"), V0_TO_V1).unwrap();
+        assert_eq!(from, 0);
+        let text = doc.to_string();
+        assert!(text.contains("schema_version = 1"));
+        assert!(text.contains("# Team report"));
+        assert!(text.contains("query = \"SELECT 1\""));
+        assert!(!text.contains("text ="));
+
+        let mut doc: DocumentMut = OLD_QUERY.parse().unwrap();
+        let err = upgrade_with(FileKind::Query, &mut doc, Path::new("q.toml"), MIGRATIONS)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no migration"), "{err}");
+    }
+
+    #[test]
+    fn plan_previews_and_apply_rewrites() {
+        let dir = project_with_query(OLD_QUERY);
+        let plan = plan_with(dir.path(), V0_TO_V1).unwrap();
+        assert_eq!(plan.len(), 1);
+        assert_eq!((plan[0].from, plan[0].to), (0, 1));
+        let diff = plan[0].diff();
+        assert!(diff.contains("-schema_version = 0\n"), "{diff}");
+        assert!(diff.contains("+schema_version = 1\n"), "{diff}");
+        assert!(diff.contains(" # Team report\n"), "{diff}");
+
+        apply_project_migration(&plan).unwrap();
+        assert_eq!(fs::read_to_string(&plan[0].path).unwrap(), plan[0].after);
+        assert!(plan_with(dir.path(), V0_TO_V1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn apply_refuses_files_edited_after_preview() {
+        let dir = project_with_query(OLD_QUERY);
+        let plan = plan_with(dir.path(), V0_TO_V1).unwrap();
+        fs::write(&plan[0].path, format!("{OLD_QUERY}# edited\n")).unwrap();
+        let err = apply_project_migration(&plan).unwrap_err().to_string();
+        assert!(err.contains("preview again"), "{err}");
+    }
+}
@@ -0,0 +1,122 @@
+//! `[policy]` on a connection file: committed guardrails the SQL editors and data viewers
+//! enforce before anything runs, so a shared production connection is safe to browse.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::connection::is_false;
+
+/// Statement kinds `allowed_statements` may name, as the SQL classifier labels them
+/// (lower-case).
+pub const STATEMENT_KIND_NAMES: &[&str] = &[
+    "select",
+    "values",
+    "table",
+    "insert",
+    "update",
+    "delete",
+    "merge",
+    "copy",
+    "ddl",
+    "dcl",
+    "transaction",
+    "explain",
+    "show",
+    "set",
+    "pragma",
+    "call",
+    "maintenance",
+    "other",
+];
+
+/// Kinds in [`STATEMENT_KIND_NAMES`] that can change data, schema, or privileges.
+const WRITE_KIND_NAMES: &[&str] = &[
+    "insert",
+    "update",
+    "delete",
+    "merge",
+    "copy",
+    "ddl",
+    "dcl",
+    "pragma",
+    "call",
+    "maintenance",
+    "other",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ConnectionPolicy {
+    /// Ask before running statements that change data, schema, or privileges.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub confirm_writes: bool,
+    /// Refuse `CREATE`, `ALTER`, `DROP`, `TRUNCATE`, … outright.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub forbid_ddl: bool,
+    /// When set, only these statement kinds run (see [`STATEMENT_KIND_NAMES`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_statements: Option<Vec<String>>,
+    /// Row cap for this connection, below the project `max_result_rows`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<u64>,
+    /// Refuse `UPDATE` and `DELETE` without a top-level `WHERE`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub require_where_for_update_delete: bool,
+}
+
+impl ConnectionPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether `kind` (a classifier label, any case) passes `allowed_statements`.
+    pub fn allows_kind(&self, kind: &str) -> bool {
+        self.allowed_statements
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|k| k.eq_ignore_ascii_case(kind)))
+    }
+
+    /// Whether an allow-list leaves room for any write. A read kind that writes, like
+    /// `SELECT … INTO`, runs only when this holds.
+    pub fn allows_writes(&self) -> bool {
+        WRITE_KIND_NAMES.iter().any(|kind| self.allows_kind(kind))
+    }
+
+    /// Reject unknown statement kinds and a zero `max_rows`.
+    pub(crate) fn check(&self, connection_id: &str) -> Result<()> {
+        for kind in self.allowed_statements.iter().flatten() {
+            if !STATEMENT_KIND_NAMES.contains(&kind.to_ascii_lowercase().as_str()) {
+                bail!(
+                    "connection {connection_id} [policy] allowed_statements: unknown kind {kind:?} (expected one of {})",
+                    STATEMENT_KIND_NAMES.join(", ")
+                );
+            }
+        }
+        if self.max_rows == Some(0) {
+            bail!("connection {connection_id} [policy] max_rows must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allow_list_is_case_insensitive_and_checked() {
+        let policy: ConnectionPolicy =
+            toml::from_str("allowed_statements = [\"SELECT\", \"explain\"]\nmax_rows = 500\n")
+                .unwrap();
+        assert!(policy.allows_kind("select"));
+        assert!(policy.allows_kind("EXPLAIN"));
+        assert!(!policy.allows_kind("delete"));
+        assert!(ConnectionPolicy::default().allows_kind("delete"));
+        assert!(!policy.allows_writes());
+        assert!(ConnectionPolicy::default().allows_writes());
+        policy.check("prod").unwrap();
+
+        let bad: ConnectionPolicy = toml::from_str("allowed_statements = [\"selects\"]\n").unwrap();
+        let err = bad.check("prod").unwrap_err().to_string();
+        assert!(err.contains("\"selects\""), "{err}");
+    }
+}
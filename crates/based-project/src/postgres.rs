@@ -0,0 +1,165 @@
+//! PostgreSQL keys of a connection file beyond host and credentials: `sslmode` by name,
+//! a libpq `service`, certificate paths, session settings, timeouts, and failover hosts.
+//! Names follow libpq.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PostgresSslMode {
+    #[default]
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl PostgresSslMode {
+    /// The mode a legacy `ssl = true | false` means.
+    pub fn from_legacy_ssl(ssl: bool) -> Self {
+        if ssl { Self::Require } else { Self::Disable }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Disable => "disable",
+            Self::Prefer => "prefer",
+            Self::Require => "require",
+            Self::VerifyCa => "verify-ca",
+            Self::VerifyFull => "verify-full",
+        }
+    }
+}
+
+/// Which server of `host` and `failover_hosts` is acceptable (libpq `target_session_attrs`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TargetSessionAttrs {
+    #[default]
+    Any,
+    ReadWrite,
+    ReadOnly,
+    Primary,
+    Standby,
+    PreferStandby,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PostgresSettings {
+    /// `pg_service.conf` entry supplying host, port, database, user, password, and other
+    /// settings the file leaves out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    /// CA certificate for `verify-ca` / `verify-full`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sslrootcert: Option<String>,
+    /// Client certificate and key for certificate authentication.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sslcert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sslkey: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_path: Option<String>,
+    /// Server settings as libpq `options`, e.g. `-c work_mem=64MB -c geqo=off`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<String>,
+    /// Seconds to wait for each host before trying the next.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+    /// Session `statement_timeout` in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement_timeout: Option<u64>,
+    /// `host[:port]` entries tried in order after `host`; port defaults to 5432.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover_hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_session_attrs: Option<TargetSessionAttrs>,
+}
+
+impl PostgresSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `failover_hosts` split into host and port. IPv6 addresses with a port are written
+    /// `[::1]:5433`.
+    pub fn failover_endpoints(&self) -> Result<Vec<(String, u16)>> {
+        self.failover_hosts
+            .iter()
+            .map(|entry| split_host_port(entry))
+            .collect()
+    }
+
+    /// Reject malformed `failover_hosts` entries.
+    pub(crate) fn check(&self, connection_id: &str) -> Result<()> {
+        self.failover_endpoints()
+            .with_context(|| format!("connection {connection_id} `failover_hosts`"))?;
+        Ok(())
+    }
+}
+
+fn split_host_port(entry: &str) -> Result<(String, u16)> {
+    let entry = entry.trim();
+    let (host, port) = if let Some(rest) = entry.strip_prefix('[') {
+        let Some((host, after)) = rest.split_once(']') else {
+            bail!("{entry:?}: missing `]`");
+        };
+        match after.strip_prefix(':') {
+            Some(port) => (host, Some(port)),
+            None if after.is_empty() => (host, None),
+            None => bail!("{entry:?}: expected `:port` after `]`"),
+        }
+    } else if entry.matches(':').count() == 1 {
+        let (host, port) = entry.split_once(':').unwrap_or((entry, ""));
+        (host, Some(port))
+    } else {
+        (entry, None)
+    };
+    if host.is_empty() {
+        bail!("{entry:?}: empty host");
+    }
+    let port = match port {
+        Some(port) => port
+            .parse()
+            .with_context(|| format!("{entry:?}: invalid port"))?,
+        None => 5432,
+    };
+    Ok((host.to_string(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failover_hosts_default_port_and_bracketed_ipv6() {
+        let settings = PostgresSettings {
+            failover_hosts: vec![
+                "replica-1".into(),
+                "replica-2:6432".into(),
+                "[::1]:5433".into(),
+                "fe80::1".into(),
+            ],
+            ..PostgresSettings::default()
+        };
+        assert_eq!(
+            settings.failover_endpoints().unwrap(),
+            [
+                ("replica-1".to_string(), 5432),
+                ("replica-2".to_string(), 6432),
+                ("::1".to_string(), 5433),
+                ("fe80::1".to_string(), 5432),
+            ]
+        );
+        let bad = PostgresSettings {
+            failover_hosts: vec!["replica:abc".into()],
+            ..PostgresSettings::default()
+        };
+        let err = format!("{:#}", bad.check("prod").unwrap_err());
+        assert!(err.contains("replica:abc"), "{err}");
+    }
+}
@@ -1,11 +1,17 @@
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
+use crate::migrate::{FileKind, parse_versioned};
+
 pub const PROJECT_SCHEMA_VERSION: u64 = 1;
 
+/// Row cap for query results when `[settings] max_result_rows` is unset.
+pub const DEFAULT_MAX_RESULT_ROWS: u64 = 10_000;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ProjectManifest {
     pub schema_version: u64,
@@ -28,16 +34,29 @@ pub struct ProjectSettings {
     pub cache_ttl: Option<u64>,
 }
 
+impl ProjectManifest {
+    /// Effective result row cap: `[settings] max_result_rows` when positive,
+    /// else [`DEFAULT_MAX_RESULT_ROWS`].
+    pub fn max_result_rows(&self) -> u64 {
+        self.settings
+            .as_ref()
+            .and_then(|s| s.max_result_rows)
+            .filter(|&n| n > 0)
+            .unwrap_or(DEFAULT_MAX_RESULT_ROWS)
+    }
+
+    /// `[settings] query_timeout` (milliseconds) when positive; overrides the user preference.
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.settings
+            .as_ref()
+            .and_then(|s| s.query_timeout)
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis)
+    }
+}
+
 pub fn load_manifest(project_root: &Path) -> Result<ProjectManifest> {
     let path = project_root.join(".based").join("project.toml");
     let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
-    let manifest: ProjectManifest =
-        toml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
-    if manifest.schema_version != PROJECT_SCHEMA_VERSION {
-        anyhow::bail!(
-            "unsupported project.toml schema_version {} (expected {PROJECT_SCHEMA_VERSION})",
-            manifest.schema_version
-        );
-    }
-    Ok(manifest)
+    parse_versioned(FileKind::Project, &raw, &path)
 }
@@ -1,14 +1,20 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 use serde::Deserialize;
+use toml_edit::{Array, DocumentMut, Item, TableLike, Value, table, value};
 
-use crate::target::QueryTarget;
+use crate::migrate::{FileKind, parse_versioned, upgrade_document};
+use crate::target::{QueryTarget, TargetConnection};
 use crate::walk::{rel_id, walk_toml_files};
 
 pub const QUERY_SCHEMA_VERSION: u64 = 1;
 
+/// Types the `[params]` table may declare for a query's `{{name}}` variables.
+pub const PARAM_TYPE_NAMES: &[&str] = &["string", "integer", "float", "boolean", "date", "json"];
+
 #[derive(Debug, Clone)]
 pub struct ProjectQuery {
     /// Path relative to `.based/queries/` without the `.toml` suffix
@@ -18,7 +24,13 @@ pub struct ProjectQuery {
     pub description: Option<String>,
     pub tags: Vec<String>,
     pub target: QueryTarget,
+    /// Declared types of `{{name}}` variables (`[params]`), one of [`PARAM_TYPE_NAMES`].
+    /// Undeclared variables bind as text.
+    pub params: BTreeMap<String, String>,
     pub body: QueryBody,
+    /// External body file (`[sql] file` / `[aggregate] file`), resolved against the query
+    /// file's directory. `None` when the body is inline.
+    pub body_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -34,27 +46,34 @@ pub enum QueryBody {
 
 #[derive(Debug, Deserialize)]
 struct QueryFileRaw {
-    schema_version: u64,
     name: String,
     #[serde(default)]
     description: Option<String>,
     #[serde(default)]
     tags: Vec<String>,
     target: QueryTarget,
+    #[serde(default)]
+    params: BTreeMap<String, String>,
     sql: Option<SqlSection>,
     aggregate: Option<AggregateSection>,
 }
 
 #[derive(Debug, Deserialize)]
 struct SqlSection {
-    query: String,
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    file: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct AggregateSection {
     #[serde(default)]
     collection: Option<String>,
-    pipeline: String,
+    #[serde(default)]
+    pipeline: Option<String>,
+    #[serde(default)]
+    file: Option<String>,
 }
 
 pub fn load_queries(project_root: &Path) -> Result<Vec<ProjectQuery>> {
@@ -70,21 +89,13 @@ pub fn load_queries(project_root: &Path) -> Result<Vec<ProjectQuery>> {
     Ok(queries)
 }
 
-fn parse_query_file(queries_dir: &Path, path: &Path) -> Result<ProjectQuery> {
+pub(crate) fn parse_query_file(queries_dir: &Path, path: &Path) -> Result<ProjectQuery> {
     let rel = path
         .strip_prefix(queries_dir)
         .with_context(|| format!("query path not under {}", queries_dir.display()))?;
     let rel_path = rel_id(rel);
     let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
-    let file: QueryFileRaw =
-        toml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
-    if file.schema_version != QUERY_SCHEMA_VERSION {
-        bail!(
-            "unsupported schema_version {} in {} (expected {QUERY_SCHEMA_VERSION})",
-            file.schema_version,
-            path.display()
-        );
-    }
+    let file: QueryFileRaw = parse_versioned(FileKind::Query, &raw, path)?;
     let has_sql = file.sql.is_some();
     let has_agg = file.aggregate.is_some();
     if has_sql == has_agg {
@@ -94,13 +105,41 @@ fn parse_query_file(queries_dir: &Path, path: &Path) -> Result<ProjectQuery> {
         );
     }
     file.target.validate()?;
-    let body = if let Some(sql) = file.sql {
-        QueryBody::Sql { query: sql.query }
+    let params = file
+        .params
+        .into_iter()
+        .map(|(name, ty)| {
+            let ty = ty.to_ascii_lowercase();
+            if !PARAM_TYPE_NAMES.contains(&ty.as_str()) {
+                bail!(
+                    "[params] {name} in {}: unknown type {ty:?} (expected one of {})",
+                    path.display(),
+                    PARAM_TYPE_NAMES.join(", ")
+                );
+            }
+            Ok((name, ty))
+        })
+        .collect::<Result<_>>()?;
+    let root = project_root(queries_dir.parent().unwrap_or(Path::new("")));
+    let (body, body_file) = if let Some(sql) = file.sql {
+        let (query, body_file) = read_body(root, path, "[sql]", "query", sql.query, sql.file)?;
+        (QueryBody::Sql { query }, body_file)
     } else if let Some(agg) = file.aggregate {
-        QueryBody::Aggregate {
-            pipeline: agg.pipeline,
-            collection: agg.collection,
-        }
+        let (pipeline, body_file) = read_body(
+            root,
+            path,
+            "[aggregate]",
+            "pipeline",
+            agg.pipeline,
+            agg.file,
+        )?;
+        (
+            QueryBody::Aggregate {
+                pipeline,
+                collection: agg.collection,
+            },
+            body_file,
+        )
     } else {
         unreachable!()
     };
@@ -110,10 +149,343 @@ fn parse_query_file(queries_dir: &Path, path: &Path) -> Result<ProjectQuery> {
         description: file.description,
         tags: file.tags,
         target: file.target,
+        params,
         body,
+        body_file,
     })
 }
 
+/// Body text of a `[sql]` / `[aggregate]` section: the inline `key` or the contents of
+/// `file`, which is relative to the directory of `query_path` and must stay inside `root`.
+fn read_body(
+    root: &Path,
+    query_path: &Path,
+    section: &str,
+    key: &str,
+    inline: Option<String>,
+    file: Option<String>,
+) -> Result<(String, Option<PathBuf>)> {
+    match (inline, file) {
+        (Some(body), None) => Ok((body, None)),
+        (None, Some(file)) => {
+            if Path::new(&file).is_absolute() {
+                bail!(
+                    "body file {file} of {} must be a relative path",
+                    query_path.display()
+                );
+            }
+            let dir = query_path.parent().unwrap_or(Path::new(""));
+            let body_path = normalize_path(&dir.join(file));
+            ensure_in_project(root, &body_path)?;
+            let body = fs::read_to_string(&body_path).with_context(|| {
+                format!(
+                    "read {} (body of {})",
+                    body_path.display(),
+                    query_path.display()
+                )
+            })?;
+            Ok((body, Some(body_path)))
+        }
+        _ => bail!(
+            "{section} in {} must have exactly one of `{key}` or `file`",
+            query_path.display()
+        ),
+    }
+}
+
+/// Resolve `.` and `..` components without touching the file system, so watchers and
+/// writers see one spelling of each body path.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// The project a `.based` directory belongs to; body files may live anywhere in it.
+fn project_root(based_dir: &Path) -> &Path {
+    based_dir.parent().unwrap_or(Path::new(""))
+}
+
+/// Body files are read and written on the query's say-so, so a query from a cloned repo
+/// must not reach files outside that repo through `..`, an absolute path, or a symlink.
+fn ensure_in_project(root: &Path, path: &Path) -> Result<()> {
+    let path = normalize_path(path);
+    let escapes = path.components().any(|c| c == Component::ParentDir);
+    if escapes || !path.starts_with(normalize_path(root)) || !resolves_inside(root, &path) {
+        bail!(
+            "body file {} is outside the project {}",
+            path.display(),
+            root.display()
+        );
+    }
+    Ok(())
+}
+
+/// Whether `path` is still under `root` once symlinks are resolved. A body file that does
+/// not exist yet is resolved through its nearest existing ancestor.
+fn resolves_inside(root: &Path, path: &Path) -> bool {
+    let Ok(root) = fs::canonicalize(root) else {
+        return false;
+    };
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            return rest
+                .iter()
+                .rev()
+                .fold(resolved, |p, c| p.join(c))
+                .starts_with(&root);
+        }
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            return false;
+        };
+        rest.push(name);
+        existing = parent;
+    }
+}
+
+/// Write an edited body back to its external file, which must be inside the project of
+/// `based_dir`. Inline bodies live in the query file itself and are not written here.
+pub fn write_query_body(based_dir: &Path, query: &ProjectQuery, body: &str) -> Result<PathBuf> {
+    let Some(path) = &query.body_file else {
+        bail!("query {} has an inline body", query.path);
+    };
+    ensure_in_project(project_root(based_dir), path)?;
+    fs::write(path, body).with_context(|| format!("write {}", path.display()))?;
+    Ok(path.clone())
+}
+
+/// Write `query` to `queries/<path>.toml` as a schema_version 1 file. An existing file is
+/// edited in place: comments and keys Based doesn't know are kept, and only `name`,
+/// `description`, `tags`, `[target]`, and the body change. An external body is written
+/// to its `file`, which stays referenced instead of being inlined.
+pub fn write_query_file(based_dir: &Path, query: &ProjectQuery) -> Result<PathBuf> {
+    validate_query_path(&query.path)?;
+    let queries_dir = based_dir.join("queries");
+    let path = existing_query_file(&queries_dir, &query.path)
+        .unwrap_or_else(|| queries_dir.join(format!("{}.toml", query.path)));
+    let mut doc = read_document(&path)?;
+    if path.exists() {
+        upgrade_document(FileKind::Query, &mut doc, &path)?;
+    }
+    doc["schema_version"] = value(QUERY_SCHEMA_VERSION as i64);
+    doc["name"] = value(query.name.as_str());
+    let root = doc.as_table_mut();
+    set_key(root, "description", query.description.as_deref().map(value));
+    set_key(root, "tags", string_array(&query.tags));
+    write_target(&mut doc, &query.target)?;
+    write_params(&mut doc, &query.params)?;
+    write_body(&mut doc, based_dir, &path, query)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    fs::write(&path, doc.to_string()).with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Move the query at `from` to `to` (both relative to `queries/`, without `.toml`). A
+/// relative body `file` is rewritten so it still points at the same file.
+pub fn rename_query_file(based_dir: &Path, from: &str, to: &str) -> Result<PathBuf> {
+    validate_query_path(to)?;
+    let queries_dir = based_dir.join("queries");
+    let Some(src) = existing_query_file(&queries_dir, from) else {
+        bail!("query {from} not found in {}", queries_dir.display());
+    };
+    if existing_query_file(&queries_dir, to).is_some() {
+        bail!("query {to} already exists");
+    }
+    let dst = queries_dir.join(format!("{to}.toml"));
+    let mut doc = read_document(&src)?;
+    let (src_dir, dst_dir) = (parent_dir(&src), parent_dir(&dst));
+    for section in ["sql", "aggregate"] {
+        let Some(body) = doc.get_mut(section).and_then(Item::as_table_like_mut) else {
+            continue;
+        };
+        let Some(file) = body.get("file").and_then(Item::as_str) else {
+            continue;
+        };
+        let target = normalize_path(&src_dir.join(file));
+        ensure_in_project(project_root(based_dir), &target)?;
+        set_key(body, "file", Some(value(relative_path(&dst_dir, &target))));
+    }
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    fs::write(&dst, doc.to_string()).with_context(|| format!("write {}", dst.display()))?;
+    fs::remove_file(&src).with_context(|| format!("remove {}", src.display()))?;
+    Ok(dst)
+}
+
+/// Query ids are `/`-separated names under `queries/`; `_`-prefixed names are skipped by
+/// the loader, so they are refused here.
+fn validate_query_path(path: &str) -> Result<()> {
+    let valid = !path.is_empty()
+        && path
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != ".." && !part.contains('\\'));
+    if !valid {
+        bail!("invalid query path {path:?}");
+    }
+    if path
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.starts_with('_'))
+    {
+        bail!("query names starting with `_` are ignored by the loader: {path}");
+    }
+    Ok(())
+}
+
+fn existing_query_file(queries_dir: &Path, path: &str) -> Option<PathBuf> {
+    [".toml", ".query.toml"]
+        .iter()
+        .map(|suffix| queries_dir.join(format!("{path}{suffix}")))
+        .find(|candidate| candidate.is_file())
+}
+
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    if !path.exists() {
+        return Ok(DocumentMut::new());
+    }
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    raw.parse()
+        .with_context(|| format!("parse {}", path.display()))
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+/// Set or remove `key`. An existing key is updated in place so comments above it stay.
+fn set_key(table: &mut dyn TableLike, key: &str, item: Option<Item>) {
+    match (item, table.get_mut(key)) {
+        (Some(item), Some(slot)) => *slot = item,
+        (Some(item), None) => {
+            table.insert(key, item);
+        }
+        (None, _) => {
+            table.remove(key);
+        }
+    }
+}
+
+fn string_array(items: &[String]) -> Option<Item> {
+    (!items.is_empty()).then(|| value(items.iter().collect::<Array>()))
+}
+
+/// A section table of `doc`, created when missing; inline tables are edited as they are.
+fn section<'a>(doc: &'a mut DocumentMut, key: &str) -> Result<&'a mut dyn TableLike> {
+    doc.entry(key)
+        .or_insert(table())
+        .as_table_like_mut()
+        .with_context(|| format!("`{key}` is not a table"))
+}
+
+fn write_target(doc: &mut DocumentMut, target: &QueryTarget) -> Result<()> {
+    let connection = target.connection.as_ref().map(|c| match c {
+        TargetConnection::Exclusive(id) => value(id.as_str()),
+        TargetConnection::OneOf(ids) => value(ids.iter().collect::<Array>()),
+    });
+    let table = section(doc, "target")?;
+    set_key(table, "connection", connection);
+    set_key(table, "engine", target.engine.as_deref().map(value));
+    set_key(table, "tags", string_array(&target.tags));
+    set_key(table, "exclude_tags", string_array(&target.exclude_tags));
+    Ok(())
+}
+
+/// `[params]` as declared; the table is dropped when nothing is declared.
+fn write_params(doc: &mut DocumentMut, params: &BTreeMap<String, String>) -> Result<()> {
+    if params.is_empty() {
+        doc.remove("params");
+        return Ok(());
+    }
+    let table = section(doc, "params")?;
+    let stale: Vec<String> = table
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .filter(|name| !params.contains_key(name))
+        .collect();
+    for name in stale {
+        table.remove(&name);
+    }
+    for (name, ty) in params {
+        set_key(table, name, Some(value(ty.as_str())));
+    }
+    Ok(())
+}
+
+fn write_body(
+    doc: &mut DocumentMut,
+    based_dir: &Path,
+    path: &Path,
+    query: &ProjectQuery,
+) -> Result<()> {
+    let (name, other, key, text) = match &query.body {
+        QueryBody::Sql { query } => ("sql", "aggregate", "query", query),
+        QueryBody::Aggregate { pipeline, .. } => ("aggregate", "sql", "pipeline", pipeline),
+    };
+    doc.remove(other);
+    let dir = parent_dir(path);
+    let body = section(doc, name)?;
+    if let QueryBody::Aggregate { collection, .. } = &query.body {
+        set_key(body, "collection", collection.as_deref().map(value));
+    }
+    match &query.body_file {
+        Some(file) => {
+            write_query_body(based_dir, query, text)?;
+            let current = body
+                .get("file")
+                .and_then(Item::as_str)
+                .map(|f| normalize_path(&dir.join(f)));
+            if current.as_ref() != Some(file) {
+                set_key(body, "file", Some(value(relative_path(&dir, file))));
+            }
+            set_key(body, key, None);
+        }
+        None => {
+            set_key(body, key, Some(body_value(text)));
+            set_key(body, "file", None);
+        }
+    }
+    Ok(())
+}
+
+/// Multi-line bodies as `'''` literal strings when they allow it, so SQL stays readable
+/// and diffs line by line.
+fn body_value(text: &str) -> Item {
+    if text.contains('\n')
+        && !text.contains("'''")
+        && let Ok(literal) = format!("'''\n{text}'''").parse::<Value>()
+    {
+        return Item::Value(literal);
+    }
+    value(text)
+}
+
+/// `/`-separated path from `dir` to `target`; both are absolute and normalized.
+fn relative_path(dir: &Path, target: &Path) -> String {
+    let dir: Vec<Component> = dir.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    let common = dir.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let ups = (common..dir.len()).map(|_| "..".to_string());
+    let downs = target[common..]
+        .iter()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned());
+    ups.chain(downs).collect::<Vec<_>>().join("/")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,4 +531,255 @@ query = "SELECT 1"
         let query = parse_query_file(&queries, &path).unwrap();
         assert_eq!(query.path, "list-tables");
     }
+
+    #[test]
+    fn file_bodies_resolve_against_query_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let queries = dir.path().join("queries");
+        fs::create_dir_all(queries.join("reports")).unwrap();
+        fs::create_dir_all(dir.path().join("sql")).unwrap();
+        fs::write(dir.path().join("sql/revenue.sql"), "SELECT 42;\n").unwrap();
+        let path = queries.join("reports/revenue.toml");
+        fs::write(
+            &path,
+            r#"
+schema_version = 1
+name = "Revenue"
+
+[target]
+engine = "postgres"
+
+[sql]
+file = "../../sql/revenue.sql"
+"#,
+        )
+        .unwrap();
+        let query = parse_query_file(&queries, &path).unwrap();
+        let body_file = dir.path().join("sql/revenue.sql");
+        assert!(matches!(&query.body, QueryBody::Sql { query } if query == "SELECT 42;\n"));
+        assert_eq!(query.body_file.as_deref(), Some(body_file.as_path()));
+
+        write_query_body(dir.path(), &query, "SELECT 43;\n").unwrap();
+        assert_eq!(fs::read_to_string(&body_file).unwrap(), "SELECT 43;\n");
+    }
+
+    fn write_file_query(path: &Path, file: &str) {
+        fs::write(
+            path,
+            format!(
+                r#"
+schema_version = 1
+name = "Escape"
+
+[target]
+engine = "postgres"
+
+[sql]
+file = "{file}"
+"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn body_files_outside_the_project_are_refused() {
+        let outer = tempfile::tempdir().unwrap();
+        let based = outer.path().join("project/.based");
+        let queries = based.join("queries");
+        fs::create_dir_all(&queries).unwrap();
+        fs::write(outer.path().join("secret.sql"), "SELECT 'secret';\n").unwrap();
+        let path = queries.join("escape.toml");
+
+        write_file_query(&path, "../../../secret.sql");
+        let err = parse_query_file(&queries, &path).unwrap_err();
+        assert!(err.to_string().contains("outside the project"), "{err:#}");
+
+        let absolute = outer.path().join("secret.sql");
+        write_file_query(&path, &absolute.display().to_string());
+        let err = parse_query_file(&queries, &path).unwrap_err();
+        assert!(err.to_string().contains("relative path"), "{err:#}");
+
+        let mut query = sql_query("escape", "SELECT 1;\n");
+        query.body_file = Some(queries.join("../../../secret.sql"));
+        assert!(write_query_body(&based, &query, "DROP TABLE t;\n").is_err());
+        query.body_file = Some(absolute.clone());
+        assert!(write_query_file(&based, &query).is_err());
+        assert_eq!(fs::read_to_string(&absolute).unwrap(), "SELECT 'secret';\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn body_files_linked_outside_the_project_are_refused() {
+        use std::os::unix::fs::symlink;
+
+        let outer = tempfile::tempdir().unwrap();
+        let based = outer.path().join("project/.based");
+        let queries = based.join("queries");
+        fs::create_dir_all(&queries).unwrap();
+        let secret = outer.path().join("secret.sql");
+        fs::write(&secret, "SELECT 'secret';\n").unwrap();
+        symlink(&secret, queries.join("linked.sql")).unwrap();
+        symlink(outer.path(), queries.join("outside")).unwrap();
+        let path = queries.join("escape.toml");
+
+        for file in ["linked.sql", "outside/secret.sql", "outside/new.sql"] {
+            write_file_query(&path, file);
+            let err = parse_query_file(&queries, &path).unwrap_err();
+            assert!(
+                err.to_string().contains("outside the project"),
+                "{file}: {err:#}"
+            );
+
+            let mut query = sql_query("escape", "SELECT 1;\n");
+            query.body_file = Some(queries.join(file));
+            assert!(write_query_body(&based, &query, "DROP TABLE t;\n").is_err());
+        }
+        assert_eq!(fs::read_to_string(&secret).unwrap(), "SELECT 'secret';\n");
+        assert!(!outer.path().join("new.sql").exists());
+
+        fs::write(queries.join("real.sql"), "SELECT 1;\n").unwrap();
+        symlink(queries.join("real.sql"), queries.join("alias.sql")).unwrap();
+        write_file_query(&path, "alias.sql");
+        let query = parse_query_file(&queries, &path).unwrap();
+        assert!(matches!(&query.body, QueryBody::Sql { query } if query == "SELECT 1;\n"));
+    }
+
+    #[test]
+    fn body_needs_exactly_one_of_inline_or_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let queries = dir.path().join("queries");
+        fs::create_dir_all(&queries).unwrap();
+        let path = queries.join("both.toml");
+        fs::write(
+            &path,
+            r#"
+schema_version = 1
+name = "Both"
+
+[target]
+engine = "mongodb"
+
+[aggregate]
+pipeline = "[]"
+file = "both.pipeline.json"
+"#,
+        )
+        .unwrap();
+        let err = parse_query_file(&queries, &path).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("exactly one of `pipeline` or `file`")
+        );
+    }
+
+    fn sql_query(path: &str, sql: &str) -> ProjectQuery {
+        ProjectQuery {
+            path: path.into(),
+            name: "Revenue".into(),
+            description: None,
+            tags: vec!["finance".into()],
+            target: QueryTarget {
+                connection: Some(TargetConnection::Exclusive("local/northwind".into())),
+                engine: None,
+                tags: vec![],
+                exclude_tags: vec![],
+            },
+            params: BTreeMap::new(),
+            body: QueryBody::Sql { query: sql.into() },
+            body_file: None,
+        }
+    }
+
+    #[test]
+    fn written_query_loads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql = "SELECT *\nFROM orders\nWHERE total > 100;\n";
+        let path = write_query_file(dir.path(), &sql_query("reports/revenue", sql)).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("query = '''\nSELECT *\nFROM orders"), "{raw}");
+        let loaded = parse_query_file(&dir.path().join("queries"), &path).unwrap();
+        assert_eq!(loaded.path, "reports/revenue");
+        assert_eq!(loaded.tags, vec!["finance".to_string()]);
+        assert!(matches!(loaded.body, QueryBody::Sql { query } if query == sql));
+    }
+
+    #[test]
+    fn params_declare_variable_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut query = sql_query("orders", "SELECT * FROM orders WHERE id = {{id}}");
+        query.params.insert("id".into(), "integer".into());
+        let path = write_query_file(dir.path(), &query).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("[params]\nid = \"integer\""), "{raw}");
+        let queries = dir.path().join("queries");
+        let loaded = parse_query_file(&queries, &path).unwrap();
+        assert_eq!(loaded.params, query.params);
+
+        query.params.clear();
+        write_query_file(dir.path(), &query).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("[params]"));
+
+        fs::write(&path, raw.replace("\"integer\"", "\"int\"")).unwrap();
+        let err = parse_query_file(&queries, &path).unwrap_err().to_string();
+        assert!(err.contains("unknown type \"int\""), "{err}");
+    }
+
+    #[test]
+    fn rewrite_keeps_comments_and_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queries/revenue.toml");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            r#"# Owned by the finance team.
+schema_version = 1
+name = "Old name"
+owner = "finance"
+
+[target]
+connection = "local/northwind"
+
+[sql]
+# Reviewed 2024-01.
+query = "SELECT 1"
+"#,
+        )
+        .unwrap();
+        write_query_file(dir.path(), &sql_query("revenue", "SELECT 2")).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.starts_with("# Owned by the finance team.\n"), "{raw}");
+        assert!(raw.contains("owner = \"finance\""), "{raw}");
+        assert!(
+            raw.contains("# Reviewed 2024-01.\nquery = \"SELECT 2\""),
+            "{raw}"
+        );
+        assert!(raw.contains("name = \"Revenue\""), "{raw}");
+    }
+
+    #[test]
+    fn rename_moves_file_and_rebases_body_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let queries = dir.path().join("queries");
+        fs::create_dir_all(&queries).unwrap();
+        fs::write(queries.join("revenue.sql"), "SELECT 1").unwrap();
+        fs::write(
+            queries.join("revenue.toml"),
+            "schema_version = 1\nname = \"Revenue\"\n\n[target]\nengine = \"postgres\"\n\n[sql]\nfile = \"revenue.sql\"\n",
+        )
+        .unwrap();
+        let moved = rename_query_file(dir.path(), "revenue", "finance/monthly/revenue").unwrap();
+        assert!(!queries.join("revenue.toml").exists());
+        assert!(
+            fs::read_to_string(&moved)
+                .unwrap()
+                .contains("file = \"../../revenue.sql\"")
+        );
+        let loaded = parse_query_file(&queries, &moved).unwrap();
+        assert!(matches!(loaded.body, QueryBody::Sql { query } if query == "SELECT 1"));
+
+        let err =
+            rename_query_file(dir.path(), "finance/monthly/revenue", "../escape").unwrap_err();
+        assert!(err.to_string().contains("invalid query path"));
+    }
 }
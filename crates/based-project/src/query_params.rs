@@ -0,0 +1,91 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::migrate::{FileKind, parse_versioned};
+
+pub const QUERY_PARAMS_SCHEMA_VERSION: u64 = 1;
+
+/// Last values entered in the run-time parameter form, per query.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct QueryParamsFile {
+    pub schema_version: u64,
+    /// Query key (saved query path, or an editor-chosen key for ad-hoc tabs) → name → value.
+    #[serde(default)]
+    pub queries: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+fn query_params_path(project_root: &Path) -> PathBuf {
+    project_root
+        .join(".based")
+        .join("state")
+        .join("query_params.toml")
+}
+
+fn read_query_params(path: &Path) -> Result<QueryParamsFile> {
+    if !path.exists() {
+        return Ok(QueryParamsFile {
+            schema_version: QUERY_PARAMS_SCHEMA_VERSION,
+            queries: BTreeMap::new(),
+        });
+    }
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    parse_versioned(FileKind::QueryParams, &raw, path)
+}
+
+/// Remembered parameter values for `query_key`; empty when none were saved.
+pub fn load_query_params(project_root: &Path, query_key: &str) -> Result<HashMap<String, String>> {
+    let mut file = read_query_params(&query_params_path(project_root))?;
+    Ok(file
+        .queries
+        .remove(query_key)
+        .map(|values| values.into_iter().collect())
+        .unwrap_or_default())
+}
+
+/// Remember `values` for `query_key`, keeping values of names not in this run.
+pub fn persist_query_params(
+    project_root: &Path,
+    query_key: &str,
+    values: &HashMap<String, String>,
+) -> Result<()> {
+    let path = query_params_path(project_root);
+    let mut file = read_query_params(&path)?;
+    file.queries
+        .entry(query_key.to_string())
+        .or_default()
+        .extend(values.clone());
+    fs::create_dir_all(project_root.join(".based").join("state"))?;
+    let content = toml::to_string_pretty(&file)?;
+    fs::write(&path, content).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_round_trip_per_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = "local/northwind/monthly-sales";
+        assert!(load_query_params(dir.path(), report).unwrap().is_empty());
+
+        let first = HashMap::from([
+            ("start".to_string(), "2024-01-01".to_string()),
+            ("end".to_string(), "2024-01-31".to_string()),
+        ]);
+        persist_query_params(dir.path(), report, &first).unwrap();
+        let second = HashMap::from([("end".to_string(), "2024-02-29".to_string())]);
+        persist_query_params(dir.path(), report, &second).unwrap();
+        persist_query_params(dir.path(), "other", &second).unwrap();
+
+        let loaded = load_query_params(dir.path(), report).unwrap();
+        assert_eq!(loaded["start"], "2024-01-01");
+        assert_eq!(loaded["end"], "2024-02-29");
+        assert_eq!(load_query_params(dir.path(), "other").unwrap().len(), 1);
+    }
+}
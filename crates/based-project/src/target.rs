@@ -1,7 +1,7 @@
 use based_core::EngineKind;
 use serde::Deserialize;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct QueryTarget {
     #[serde(default)]
     pub connection: Option<TargetConnection>,
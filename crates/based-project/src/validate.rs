@@ -0,0 +1,594 @@
+//! Lenient project loading with per-file diagnostics.
+//!
+//! [`load_project`](crate::load_project) stops at the first bad file. Validation loads
+//! what it can, skips files that fail, and reports every problem with a location, so the
+//! app can open a half-broken project and list what to fix.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs, slice};
+
+use anyhow::Result;
+use toml::de::Error as DeError;
+use toml_edit::{ImDocument, TableLike, TomlError};
+
+use crate::connection::{ProjectConnection, parse_connection_file};
+use crate::dotenv::{env_keys, load_env_file};
+use crate::environment::{load_active_environment, parse_environment_file};
+use crate::favorites::load_favorites;
+use crate::load::ProjectSnapshot;
+use crate::migrate::FileKind;
+use crate::project::{PROJECT_SCHEMA_VERSION, ProjectManifest, load_manifest};
+use crate::query::parse_query_file;
+use crate::target::TargetConnection;
+use crate::walk::walk_toml_files;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// One problem in a `.based/` file. `line` and `column` are 1-based and `None` when the
+/// problem is not tied to a spot in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        write!(f, ": {}: {}", self.severity.label(), self.message)
+    }
+}
+
+/// Everything that loaded, plus what did not.
+#[derive(Debug, Clone)]
+pub struct ProjectValidation {
+    pub snapshot: ProjectSnapshot,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ProjectValidation {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+}
+
+/// Load the project at `project_root` leniently. Files that fail to parse are left out of
+/// the snapshot and reported; a broken `project.toml` falls back to the directory name.
+pub fn validate_project(project_root: &Path) -> ProjectValidation {
+    let based = project_root.join(".based");
+    let mut diags = Diagnostics::default();
+
+    let manifest_path = based.join("project.toml");
+    if let Ok(raw) = fs::read_to_string(&manifest_path) {
+        diags.unknown_keys(FileKind::Project, &manifest_path, &raw);
+    }
+    let manifest = load_manifest(project_root).unwrap_or_else(|err| {
+        diags.load_error(&manifest_path, &err);
+        fallback_manifest(project_root)
+    });
+
+    let connections = diags.load_dir(
+        FileKind::Connection,
+        &based.join("connections"),
+        parse_connection_file,
+        |c| &c.id,
+    );
+    let queries = diags.load_dir(
+        FileKind::Query,
+        &based.join("queries"),
+        parse_query_file,
+        |q| &q.path,
+    );
+    let environments = diags.load_dir(
+        FileKind::Environment,
+        &based.join("environments"),
+        parse_environment_file,
+        |e| &e.name,
+    );
+
+    let state = based.join("state");
+    let favorites = load_favorites(project_root).unwrap_or_else(|err| {
+        diags.load_error(&state.join("favorites.toml"), &err);
+        vec![]
+    });
+    let active_environment = load_active_environment(project_root)
+        .unwrap_or_else(|err| {
+            diags.load_error(&state.join("active_environment.toml"), &err);
+            Default::default()
+        })
+        .name;
+
+    let connection_ids: HashSet<&str> = connections.iter().map(|c| c.id.as_str()).collect();
+    let queries_dir = based.join("queries");
+    for query in &queries {
+        let ids = match &query.target.connection {
+            Some(TargetConnection::Exclusive(id)) => slice::from_ref(id),
+            Some(TargetConnection::OneOf(ids)) => ids.as_slice(),
+            None => &[],
+        };
+        for id in ids
+            .iter()
+            .filter(|id| !connection_ids.contains(id.as_str()))
+        {
+            let Some(file) = source_file(&queries_dir, &query.path) else {
+                continue;
+            };
+            let at = fs::read_to_string(&file)
+                .ok()
+                .and_then(|raw| key_position(&raw, &["target", "connection"]));
+            diags.push(
+                Severity::Warning,
+                &file,
+                at,
+                format!("[target] connection `{id}` is not defined in connections/"),
+            );
+        }
+    }
+
+    diags.missing_env_keys(&based);
+
+    ProjectValidation {
+        snapshot: ProjectSnapshot {
+            manifest,
+            connections,
+            queries,
+            favorites,
+            environments,
+            active_environment,
+        },
+        diagnostics: diags.0,
+    }
+}
+
+/// Load `connections/**/*.toml` from a based-dir, skipping files that fail to parse.
+pub fn load_connections_lenient(based_dir: &Path) -> (Vec<ProjectConnection>, Vec<Diagnostic>) {
+    let mut diags = Diagnostics::default();
+    let connections = diags.load_dir(
+        FileKind::Connection,
+        &based_dir.join("connections"),
+        parse_connection_file,
+        |c| &c.id,
+    );
+    (connections, diags.0)
+}
+
+fn fallback_manifest(project_root: &Path) -> ProjectManifest {
+    ProjectManifest {
+        schema_version: PROJECT_SCHEMA_VERSION,
+        name: project_root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "project".to_string()),
+        description: None,
+        settings: None,
+    }
+}
+
+/// The file a loaded id came from: `<id>.toml`, else a legacy `.conn.toml` / `.query.toml`.
+fn source_file(dir: &Path, id: &str) -> Option<PathBuf> {
+    ["toml", "query.toml", "conn.toml"]
+        .iter()
+        .map(|ext| dir.join(format!("{id}.{ext}")))
+        .find(|path| path.is_file())
+}
+
+/// Keys each file kind reads, with the keys of each table among them. A key listed with
+/// no sub-keys holds a plain value, an `{ env = … }` reference, or free-form entries.
+type KeySchema = &'static [(&'static str, &'static [&'static str])];
+
+const PROJECT_KEYS: KeySchema = &[
+    ("schema_version", &[]),
+    ("name", &[]),
+    ("description", &[]),
+    (
+        "settings",
+        &[
+            "query_timeout",
+            "max_result_rows",
+            "enable_query_cache",
+            "cache_ttl",
+        ],
+    ),
+];
+
+const CONNECTION_KEYS: KeySchema = &[
+    ("schema_version", &[]),
+    ("label", &[]),
+    ("extends", &[]),
+    ("engine", &[]),
+    ("tags", &[]),
+    ("read_only", &[]),
+    ("file", &[]),
+    ("pragma", &["journal_mode", "synchronous", "foreign_keys"]),
+    ("host", &[]),
+    ("port", &[]),
+    ("database", &[]),
+    ("username", &[]),
+    ("password", &[]),
+    ("ssl", &[]),
+    ("service", &[]),
+    ("sslmode", &[]),
+    ("sslrootcert", &[]),
+    ("sslcert", &[]),
+    ("sslkey", &[]),
+    ("application_name", &[]),
+    ("search_path", &[]),
+    ("options", &[]),
+    ("connect_timeout", &[]),
+    ("statement_timeout", &[]),
+    ("failover_hosts", &[]),
+    ("target_session_attrs", &[]),
+    ("url", &[]),
+    (
+        "ssh",
+        &[
+            "alias",
+            "host",
+            "port",
+            "user",
+            "key_path",
+            "key_passphrase",
+            "keepalive_interval",
+            "keepalive_count_max",
+            "jump_hosts",
+        ],
+    ),
+    (
+        "policy",
+        &[
+            "confirm_writes",
+            "forbid_ddl",
+            "allowed_statements",
+            "max_rows",
+            "require_where_for_update_delete",
+        ],
+    ),
+];
+
+const QUERY_KEYS: KeySchema = &[
+    ("schema_version", &[]),
+    ("name", &[]),
+    ("description", &[]),
+    ("tags", &[]),
+    ("target", &["connection", "engine", "tags", "exclude_tags"]),
+    ("params", &[]),
+    ("sql", &["query", "file"]),
+    ("aggregate", &["collection", "pipeline", "file"]),
+];
+
+const ENVIRONMENT_KEYS: KeySchema = &[("schema_version", &[]), ("description", &[]), ("vars", &[])];
+
+fn key_schema(kind: FileKind) -> Option<KeySchema> {
+    match kind {
+        FileKind::Project => Some(PROJECT_KEYS),
+        FileKind::Connection => Some(CONNECTION_KEYS),
+        FileKind::Query => Some(QUERY_KEYS),
+        FileKind::Environment => Some(ENVIRONMENT_KEYS),
+        FileKind::Favorites | FileKind::ActiveEnvironment | FileKind::QueryParams => None,
+    }
+}
+
+#[derive(Default)]
+struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    fn push(
+        &mut self,
+        severity: Severity,
+        file: &Path,
+        at: Option<(usize, usize)>,
+        message: impl Into<String>,
+    ) {
+        self.0.push(Diagnostic {
+            file: file.to_path_buf(),
+            line: at.map(|(line, _)| line),
+            column: at.map(|(_, column)| column),
+            severity,
+            message: message.into(),
+        });
+    }
+
+    /// Report a file that failed to load, located by the TOML error span when there is
+    /// one, else by the first `[section]` the message names.
+    fn load_error(&mut self, file: &Path, err: &anyhow::Error) {
+        let raw = fs::read_to_string(file).unwrap_or_default();
+        let (message, at) = match toml_error(err) {
+            Some((message, span)) => (message, span.map(|s| position(&raw, s.start))),
+            None => {
+                let message = format!("{err:#}");
+                let at = named_section(&raw, &message);
+                (message, at)
+            }
+        };
+        self.push(Severity::Error, file, at, message);
+    }
+
+    /// Parse every `*.toml` under `dir`. Failures and repeated ids (`x.toml` next to a
+    /// legacy `x.conn.toml`) are reported and left out; the first file with an id wins.
+    fn load_dir<T>(
+        &mut self,
+        kind: FileKind,
+        dir: &Path,
+        parse: impl Fn(&Path, &Path) -> Result<T>,
+        id: impl Fn(&T) -> &str,
+    ) -> Vec<T> {
+        let files = match walk_toml_files(dir) {
+            Ok(files) => files,
+            Err(err) => {
+                self.push(Severity::Error, dir, None, format!("{err:#}"));
+                return vec![];
+            }
+        };
+        let mut seen: HashMap<String, PathBuf> = HashMap::new();
+        let mut loaded = Vec::with_capacity(files.len());
+        for path in files {
+            if let Ok(raw) = fs::read_to_string(&path) {
+                self.unknown_keys(kind, &path, &raw);
+            }
+            let item = match parse(dir, &path) {
+                Ok(item) => item,
+                Err(err) => {
+                    self.load_error(&path, &err);
+                    continue;
+                }
+            };
+            if let Some(first) = seen.get(id(&item)) {
+                self.push(
+                    Severity::Error,
+                    &path,
+                    None,
+                    format!(
+                        "duplicate {} id `{}`; {} already defines it",
+                        kind.label(),
+                        id(&item),
+                        first.display()
+                    ),
+                );
+                continue;
+            }
+            seen.insert(id(&item).to_string(), path);
+            loaded.push(item);
+        }
+        loaded
+    }
+
+    /// Warn about keys Based does not read (usually typos), which loading ignores.
+    fn unknown_keys(&mut self, kind: FileKind, path: &Path, raw: &str) {
+        let Some(schema) = key_schema(kind) else {
+            return;
+        };
+        // Parse errors are reported by the loader.
+        let Ok(doc) = ImDocument::parse(raw) else {
+            return;
+        };
+        let mut unknown = Vec::new();
+        for (name, item) in doc.iter() {
+            match schema.iter().find(|(key, _)| *key == name) {
+                None => unknown.push(vec![name]),
+                Some((_, sub_keys)) if !sub_keys.is_empty() => {
+                    if let Some(table) = item.as_table_like() {
+                        for (sub, _) in table.iter() {
+                            if !sub_keys.contains(&sub) {
+                                unknown.push(vec![name, sub]);
+                            }
+                        }
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        for key in unknown {
+            let at = table_key_span(doc.as_table(), &key).map(|span| position(raw, span.start));
+            self.push(
+                Severity::Warning,
+                path,
+                at,
+                format!(
+                    "unknown {} key `{}` is ignored",
+                    kind.label(),
+                    key.join(".")
+                ),
+            );
+        }
+    }
+
+    /// Warn about `.env.example` keys set neither in `.env` nor the process environment.
+    fn missing_env_keys(&mut self, based_dir: &Path) {
+        let example = based_dir.join(".env.example");
+        if !example.is_file() {
+            return;
+        }
+        let keys = match fs::read_to_string(&example) {
+            Ok(raw) => env_keys(&raw),
+            Err(err) => {
+                self.push(Severity::Error, &example, None, format!("read: {err}"));
+                return;
+            }
+        };
+        let env_path = based_dir.join(".env");
+        let file_vars = load_env_file(&env_path).unwrap_or_else(|err| {
+            self.push(Severity::Error, &env_path, None, format!("{err:#}"));
+            HashMap::new()
+        });
+        for (line, key) in keys {
+            if !file_vars.contains_key(&key) && env::var_os(&key).is_none() {
+                self.push(
+                    Severity::Warning,
+                    &example,
+                    Some((line, 1)),
+                    format!("`{key}` is not set in .env or the environment"),
+                );
+            }
+        }
+    }
+}
+
+/// Message and span of the TOML error behind `err`, if any.
+fn toml_error(err: &anyhow::Error) -> Option<(String, Option<Range<usize>>)> {
+    err.chain().find_map(|cause| {
+        if let Some(e) = cause.downcast_ref::<DeError>() {
+            return Some((e.message().to_string(), e.span()));
+        }
+        cause
+            .downcast_ref::<TomlError>()
+            .map(|e| (e.message().to_string(), e.span()))
+    })
+}
+
+/// Header of the first `[section]` mentioned in `message` that `raw` defines.
+fn named_section(raw: &str, message: &str) -> Option<(usize, usize)> {
+    message
+        .split('[')
+        .skip(1)
+        .filter_map(|rest| rest.split_once(']').map(|(name, _)| name))
+        .find_map(|name| key_position(raw, &[name]))
+}
+
+fn key_position(raw: &str, path: &[&str]) -> Option<(usize, usize)> {
+    let doc = ImDocument::parse(raw).ok()?;
+    let span = table_key_span(doc.as_table(), path)?;
+    Some(position(raw, span.start))
+}
+
+fn table_key_span(table: &dyn TableLike, path: &[&str]) -> Option<Range<usize>> {
+    let (first, rest) = path.split_first()?;
+    let (key, item) = table.get_key_value(first)?;
+    if rest.is_empty() {
+        return key.span().or_else(|| item.span());
+    }
+    table_key_span(item.as_table_like()?, rest)
+}
+
+/// 1-based line and column (in characters) of byte `offset` in `raw`.
+fn position(raw: &str, offset: usize) -> (usize, usize) {
+    let before = &raw[..offset.min(raw.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (rel, raw) in files {
+            let path = dir.path().join(".based").join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, raw).unwrap();
+        }
+        dir
+    }
+
+    const MANIFEST: &str = "schema_version = 1\nname = \"demo\"\n";
+    const SQLITE: &str =
+        "schema_version = 1\nlabel = \"App\"\nengine = \"sqlite\"\nfile = \"app.db\"\n";
+
+    fn find<'a>(v: &'a ProjectValidation, needle: &str) -> &'a Diagnostic {
+        v.diagnostics
+            .iter()
+            .find(|d| d.message.contains(needle))
+            .unwrap_or_else(|| panic!("no diagnostic with {needle:?} in {:#?}", v.diagnostics))
+    }
+
+    #[test]
+    fn bad_files_are_reported_with_locations_and_skipped() {
+        let dir = project(&[
+            ("project.toml", MANIFEST),
+            ("connections/app.toml", SQLITE),
+            (
+                "connections/broken.toml",
+                "schema_version = 1\nlabel = \"Broken\"\nengine = \n",
+            ),
+            (
+                "queries/orders.toml",
+                "schema_version = 1\nname = \"Orders\"\n\n[target]\nconnection = []\n\n[sql]\nquery = \"SELECT 1\"\n",
+            ),
+            (
+                "queries/users.toml",
+                "schema_version = 1\nname = \"Users\"\ncolour = \"red\"\n\n[target]\nconnection = \"gone\"\n\n[sql]\nquery = \"SELECT 1\"\n",
+            ),
+        ]);
+        let v = validate_project(dir.path());
+        assert_eq!(v.snapshot.manifest.name, "demo");
+        assert_eq!(v.snapshot.connections.len(), 1);
+        assert_eq!(v.snapshot.queries.len(), 1);
+        assert!(v.has_errors());
+
+        let parse = v
+            .diagnostics
+            .iter()
+            .find(|d| d.file.ends_with("broken.toml"))
+            .unwrap();
+        assert_eq!((parse.severity, parse.line), (Severity::Error, Some(3)));
+
+        let target = find(&v, "must not be empty");
+        assert_eq!((target.line, target.column), (Some(4), Some(2)));
+
+        let unknown = find(&v, "`colour`");
+        assert_eq!(unknown.severity, Severity::Warning);
+        assert_eq!((unknown.line, unknown.column), (Some(3), Some(1)));
+
+        let dangling = find(&v, "`gone`");
+        assert_eq!((dangling.line, dangling.column), (Some(6), Some(1)));
+    }
+
+    #[test]
+    fn duplicate_ids_and_missing_env_keys() {
+        let dir = project(&[
+            ("project.toml", "schema_version = 1\nname = \n"),
+            ("connections/app.toml", SQLITE),
+            ("connections/app.conn.toml", SQLITE),
+            (
+                ".env.example",
+                "# secrets\nBASED_VALIDATE_SET=\nBASED_VALIDATE_UNSET=\n",
+            ),
+            (".env", "BASED_VALIDATE_SET=x\n"),
+        ]);
+        let v = validate_project(dir.path());
+        assert_eq!(v.snapshot.connections.len(), 1);
+        assert_eq!(
+            v.snapshot.manifest.name,
+            dir.path().file_name().unwrap().to_string_lossy()
+        );
+        let dup = find(&v, "duplicate connection id `app`");
+        assert!(dup.file.ends_with("app.toml"), "{dup}");
+
+        let env = find(&v, "BASED_VALIDATE_UNSET");
+        assert_eq!((env.severity, env.line), (Severity::Warning, Some(3)));
+        assert!(
+            !v.diagnostics
+                .iter()
+                .any(|d| d.message.contains("BASED_VALIDATE_SET"))
+        );
+    }
+}
@@ -33,6 +33,30 @@ fn walk_toml_files_inner(dir: &Path, out: &mut Vec<PathBuf>) -> anyhow::Result<(
     Ok(())
 }
 
+/// Recursively collect files called `name` (e.g. `_defaults.toml`, which
+/// [`walk_toml_files`] skips).
+pub fn walk_named_files(root: &Path, name: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    walk_named_files_inner(root, name, &mut out)?;
+    out.sort();
+    Ok(out)
+}
+
+fn walk_named_files_inner(dir: &Path, name: &str, out: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            walk_named_files_inner(&path, name, out)?;
+        } else if path.file_name().is_some_and(|n| n == name) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
 /// Path relative to `connections/` or `queries/`, without the TOML suffix.
 ///
 /// Accepts plain `*.toml` and the legacy `*.conn.toml` / `*.query.toml` midfixes.
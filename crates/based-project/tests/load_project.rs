@@ -1,9 +1,11 @@
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 use based_core::EngineKind;
 use based_project::{
-    ConnectionRef, QueryTarget, ResolveError, TargetConnection, load_project, resolve_target,
+    ConnectionRef, QueryTarget, ResolveError, Severity, TargetConnection, load_project,
+    plan_project_migration, resolve_target, validate_project,
 };
 
 #[test]
@@ -11,6 +13,8 @@ fn loads_repo_based_project() {
     let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
     let snap = load_project(&root).expect("load project");
     assert_eq!(snap.manifest.name, "based");
+    assert_eq!(snap.manifest.max_result_rows(), 1000);
+    assert_eq!(snap.manifest.query_timeout(), Some(Duration::from_secs(30)));
     assert!(!snap.connections.is_empty());
     assert!(!snap.queries.is_empty());
     assert!(snap.connections.iter().any(|c| c.id == "local/northwind"));
@@ -21,6 +25,23 @@ fn loads_repo_based_project() {
     );
 }
 
+#[test]
+fn repo_based_project_validates_without_errors() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let validation = validate_project(&root);
+    let errors: Vec<String> = validation
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(ToString::to_string)
+        .collect();
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert_eq!(
+        validation.snapshot.queries.len(),
+        load_project(&root).unwrap().queries.len()
+    );
+}
+
 #[test]
 fn resolve_exclusive_target() {
     let target = QueryTarget {
@@ -157,3 +178,28 @@ query = "SELECT 1"
     assert!(snap.connections.is_empty());
     assert!(snap.queries.is_empty());
 }
+
+#[test]
+fn newer_schema_asks_to_upgrade_based() {
+    let dir = tempfile::tempdir().unwrap();
+    let based = dir.path().join(".based");
+    fs::create_dir_all(based.join("connections")).unwrap();
+    fs::write(
+        based.join("project.toml"),
+        "schema_version = 1\nname = \"t\"\n",
+    )
+    .unwrap();
+    fs::write(
+        based.join("connections/next.toml"),
+        r#"
+schema_version = 2
+label = "Next"
+engine = "sqlite"
+file = "next.db"
+"#,
+    )
+    .unwrap();
+    let err = format!("{:#}", load_project(dir.path()).unwrap_err());
+    assert!(err.contains("upgrade Based"), "{err}");
+    assert!(plan_project_migration(dir.path()).is_err());
+}
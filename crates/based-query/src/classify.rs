@@ -0,0 +1,493 @@
+//! Statement classification from tokens rather than keyword-prefix sniffing.
+//!
+//! Comments are skipped, `WITH` clauses are walked to the main statement (and their
+//! bodies checked for data-modifying CTEs), and a top-level `RETURNING` marks
+//! `INSERT`/`UPDATE`/`DELETE`/`MERGE` as row-returning.
+
+use std::cell::RefCell;
+
+use crate::lexer::{Dialect, Token, TokenKind, tokenize};
+
+/// What a single statement does, by its leading (post-CTE) keyword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    Select,
+    Values,
+    Table,
+    Insert,
+    Update,
+    Delete,
+    Merge,
+    Copy,
+    /// `CREATE`, `ALTER`, `DROP`, `TRUNCATE`, `COMMENT`, `RENAME`, `SECURITY LABEL`,
+    /// `IMPORT FOREIGN SCHEMA`, SQLite `ATTACH` and `DETACH`.
+    Ddl,
+    /// `GRANT`, `REVOKE`, `REASSIGN OWNED`.
+    Dcl,
+    /// `BEGIN`, `COMMIT`, `ROLLBACK`, `SAVEPOINT`, …
+    Transaction,
+    Explain,
+    Show,
+    /// `SET`, `RESET`.
+    Set,
+    Pragma,
+    /// `CALL`, `DO`, `EXECUTE`.
+    Call,
+    /// `VACUUM`, `ANALYZE`, `REINDEX`, `CHECKPOINT`, …
+    Maintenance,
+    /// Cursors, `LISTEN`, `PREPARE`, and anything unrecognised, which counts as a write.
+    Other,
+    /// Only whitespace and comments.
+    Empty,
+}
+
+impl StatementKind {
+    /// Upper-case label for status lines and result tabs.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Select => "SELECT",
+            Self::Values => "VALUES",
+            Self::Table => "TABLE",
+            Self::Insert => "INSERT",
+            Self::Update => "UPDATE",
+            Self::Delete => "DELETE",
+            Self::Merge => "MERGE",
+            Self::Copy => "COPY",
+            Self::Ddl => "DDL",
+            Self::Dcl => "DCL",
+            Self::Transaction => "TRANSACTION",
+            Self::Explain => "EXPLAIN",
+            Self::Show => "SHOW",
+            Self::Set => "SET",
+            Self::Pragma => "PRAGMA",
+            Self::Call => "CALL",
+            Self::Maintenance => "MAINTENANCE",
+            Self::Other => "OTHER",
+            Self::Empty => "EMPTY",
+        }
+    }
+}
+
+/// Classification of one statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatementClass {
+    pub kind: StatementKind,
+    /// Expected to produce a result set (a hint; execution reads the actual columns).
+    pub returns_rows: bool,
+    /// Changes data, schema, or privileges (including data-modifying CTEs and
+    /// `EXPLAIN ANALYZE` of a write).
+    pub is_write: bool,
+    /// `UPDATE`/`DELETE` with a top-level `WHERE`.
+    pub has_where: bool,
+}
+
+/// Classify a single statement (no splitting; see [`crate::statements_in_script`]).
+pub fn classify_statement(sql: &str, dialect: Dialect) -> StatementClass {
+    let tokens = tokenize(sql, dialect);
+    Classifier::new(sql, &tokens).classify(0)
+}
+
+/// Writes a single statement runs besides its main one: data-modifying CTE bodies and
+/// the statement under `EXPLAIN ANALYZE`, innermost first. Policies check these like
+/// top-level statements, since the main statement's kind hides them.
+pub fn nested_writes(sql: &str, dialect: Dialect) -> Vec<StatementClass> {
+    let tokens = tokenize(sql, dialect);
+    let classifier = Classifier::new(sql, &tokens);
+    classifier.classify(0);
+    classifier.nested_writes.into_inner()
+}
+
+struct Classifier<'a> {
+    sql: &'a str,
+    tokens: &'a [Token],
+    /// Executed inner writes found so far, for [`nested_writes`].
+    nested_writes: RefCell<Vec<StatementClass>>,
+}
+
+impl<'a> Classifier<'a> {
+    fn new(sql: &'a str, tokens: &'a [Token]) -> Self {
+        Self {
+            sql,
+            tokens,
+            nested_writes: RefCell::default(),
+        }
+    }
+
+    fn classify(&self, start: usize) -> StatementClass {
+        let mut pos = start;
+        // `(SELECT …) UNION …`
+        while self.tokens.get(pos).is_some_and(|t| t.is_punct('(')) {
+            pos += 1;
+        }
+        let Some(first) = self.tokens.get(pos) else {
+            return class(StatementKind::Empty, false, false);
+        };
+        if first.kind != TokenKind::Word {
+            return class(StatementKind::Other, false, false);
+        }
+        let word = first.text(self.sql).to_ascii_uppercase();
+        match word.as_str() {
+            "WITH" => self.with_clause(pos + 1),
+            "SELECT" => {
+                // `SELECT … INTO new_table` creates a table instead of returning rows.
+                let into = self.top_level_keyword(pos + 1, "INTO").is_some();
+                class(StatementKind::Select, !into, into)
+            }
+            "VALUES" => class(StatementKind::Values, true, false),
+            "TABLE" => class(StatementKind::Table, true, false),
+            "INSERT" | "REPLACE" | "UPSERT" => self.dml(StatementKind::Insert, pos + 1),
+            "UPDATE" => self.dml(StatementKind::Update, pos + 1),
+            "DELETE" => self.dml(StatementKind::Delete, pos + 1),
+            "MERGE" => self.dml(StatementKind::Merge, pos + 1),
+            "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "COMMENT" | "RENAME" | "SECURITY"
+            | "IMPORT" | "ATTACH" | "DETACH" => class(StatementKind::Ddl, false, true),
+            "GRANT" | "REVOKE" | "REASSIGN" => class(StatementKind::Dcl, false, true),
+            "BEGIN" | "START" | "COMMIT" | "END" | "ROLLBACK" | "ABORT" | "SAVEPOINT"
+            | "RELEASE" => class(StatementKind::Transaction, false, false),
+            "EXPLAIN" => self.explain(pos + 1),
+            "SHOW" => class(StatementKind::Show, true, false),
+            "SET" | "RESET" => class(StatementKind::Set, false, false),
+            "PRAGMA" => {
+                let assigns = self.tokens[pos + 1..].iter().any(|t| t.is_punct('='));
+                class(StatementKind::Pragma, true, assigns)
+            }
+            "COPY" => {
+                let from = self.top_level_keyword(pos + 1, "FROM").is_some();
+                class(StatementKind::Copy, false, from)
+            }
+            "CALL" | "DO" | "EXECUTE" => class(StatementKind::Call, false, true),
+            // These rewrite tables, indexes, statistics, or materialized views.
+            "VACUUM" | "ANALYZE" | "ANALYSE" | "REINDEX" | "CLUSTER" | "REFRESH" => {
+                class(StatementKind::Maintenance, false, true)
+            }
+            "CHECKPOINT" | "DISCARD" => class(StatementKind::Maintenance, false, false),
+            "FETCH" | "MOVE" => class(StatementKind::Other, true, false),
+            "DECLARE" | "CLOSE" | "LISTEN" | "UNLISTEN" | "PREPARE" | "DEALLOCATE" => {
+                class(StatementKind::Other, false, false)
+            }
+            // `LOCK`, `NOTIFY`, `LOAD`, and statements the classifier does not know may
+            // change something, so policies treat them as writes.
+            _ => class(StatementKind::Other, false, true),
+        }
+    }
+
+    /// `WITH [RECURSIVE] name [(cols)] AS [[NOT] MATERIALIZED] ( body ) [, …] main`.
+    fn with_clause(&self, mut pos: usize) -> StatementClass {
+        let mut cte_writes = false;
+        if self.keyword_at(pos, "RECURSIVE") {
+            pos += 1;
+        }
+        loop {
+            // CTE name and optional column list.
+            pos += 1;
+            if self.tokens.get(pos).is_some_and(|t| t.is_punct('(')) {
+                pos = self.skip_parens(pos);
+            }
+            if !self.keyword_at(pos, "AS") {
+                break;
+            }
+            pos += 1;
+            if self.keyword_at(pos, "NOT") {
+                pos += 1;
+            }
+            if self.keyword_at(pos, "MATERIALIZED") {
+                pos += 1;
+            }
+            if !self.tokens.get(pos).is_some_and(|t| t.is_punct('(')) {
+                break;
+            }
+            let cte = self.classify(pos + 1);
+            if cte.is_write {
+                cte_writes = true;
+                self.nested_writes.borrow_mut().push(cte);
+            }
+            pos = self.skip_parens(pos);
+            if self.tokens.get(pos).is_some_and(|t| t.is_punct(',')) {
+                pos += 1;
+                continue;
+            }
+            break;
+        }
+        let mut main = self.classify(pos);
+        main.is_write |= cte_writes;
+        main
+    }
+
+    fn dml(&self, kind: StatementKind, body: usize) -> StatementClass {
+        let returning = self.top_level_keyword(body, "RETURNING").is_some();
+        let has_where = matches!(kind, StatementKind::Update | StatementKind::Delete)
+            && self.top_level_keyword(body, "WHERE").is_some();
+        StatementClass {
+            kind,
+            returns_rows: returning,
+            is_write: true,
+            has_where,
+        }
+    }
+
+    /// `EXPLAIN [ANALYZE] [VERBOSE] [QUERY PLAN] [( options )] statement`. Only
+    /// `ANALYZE` executes the inner statement, so only then can it write.
+    fn explain(&self, mut pos: usize) -> StatementClass {
+        let mut analyze = false;
+        while let Some(tok) = self.tokens.get(pos) {
+            if tok.is_punct('(') {
+                let end = self.skip_parens(pos);
+                analyze |= self.tokens[pos..end].iter().any(|t| {
+                    t.is_keyword(self.sql, "ANALYZE") || t.is_keyword(self.sql, "ANALYSE")
+                });
+                pos = end;
+            } else if tok.is_keyword(self.sql, "ANALYZE") || tok.is_keyword(self.sql, "ANALYSE") {
+                analyze = true;
+                pos += 1;
+            } else if ["VERBOSE", "QUERY", "PLAN"]
+                .iter()
+                .any(|k| tok.is_keyword(self.sql, k))
+            {
+                pos += 1;
+            } else {
+                break;
+            }
+        }
+        let found = self.nested_writes.borrow().len();
+        let inner = self.classify(pos);
+        let writes = analyze && inner.is_write;
+        if writes {
+            self.nested_writes.borrow_mut().push(inner);
+        } else {
+            // A plain EXPLAIN only plans, so writes inside it never run.
+            self.nested_writes.borrow_mut().truncate(found);
+        }
+        class(StatementKind::Explain, true, writes)
+    }
+
+    fn keyword_at(&self, pos: usize, keyword: &str) -> bool {
+        self.tokens
+            .get(pos)
+            .is_some_and(|t| t.is_keyword(self.sql, keyword))
+    }
+
+    /// Index just past the `)` matching the `(` at `open`.
+    fn skip_parens(&self, open: usize) -> usize {
+        let mut depth = 0usize;
+        for (i, tok) in self.tokens.iter().enumerate().skip(open) {
+            if tok.is_punct('(') {
+                depth += 1;
+            } else if tok.is_punct(')') {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+        }
+        self.tokens.len()
+    }
+
+    /// First `keyword` at parenthesis depth zero at or after `from`.
+    fn top_level_keyword(&self, from: usize, keyword: &str) -> Option<usize> {
+        let mut depth = 0usize;
+        for (i, tok) in self.tokens.iter().enumerate().skip(from) {
+            if tok.is_punct('(') {
+                depth += 1;
+            } else if tok.is_punct(')') {
+                depth = depth.saturating_sub(1);
+            } else if depth == 0 && tok.is_keyword(self.sql, keyword) {
+                return Some(i);
+            }
+        }
+        None
+    }
+}
+
+fn class(kind: StatementKind, returns_rows: bool, is_write: bool) -> StatementClass {
+    StatementClass {
+        kind,
+        returns_rows,
+        is_write,
+        has_where: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(sql: &str) -> StatementKind {
+        classify_statement(sql, Dialect::Postgres).kind
+    }
+
+    #[test]
+    fn leading_comments_are_skipped() {
+        let c = classify_statement("-- report\n/* v2 */ SELECT * FROM t", Dialect::Postgres);
+        assert_eq!(c.kind, StatementKind::Select);
+        assert!(c.returns_rows);
+        assert!(!c.is_write);
+    }
+
+    #[test]
+    fn values_table_and_pragma_return_rows() {
+        for sql in [
+            "VALUES (1), (2)",
+            "TABLE users",
+            "PRAGMA table_info(t)",
+            "SHOW search_path",
+        ] {
+            assert!(
+                classify_statement(sql, Dialect::Postgres).returns_rows,
+                "{sql}"
+            );
+        }
+        assert!(classify_statement("PRAGMA journal_mode = WAL", Dialect::Postgres).is_write);
+    }
+
+    #[test]
+    fn returning_makes_dml_return_rows() {
+        let c = classify_statement(
+            "INSERT INTO t (a) VALUES (1) RETURNING id",
+            Dialect::Postgres,
+        );
+        assert_eq!(c.kind, StatementKind::Insert);
+        assert!(c.returns_rows && c.is_write);
+        let c = classify_statement(
+            "update t set a = 1 where id = 2 returning *",
+            Dialect::Postgres,
+        );
+        assert!(c.returns_rows && c.has_where);
+        assert!(!classify_statement("DELETE FROM t", Dialect::Postgres).returns_rows);
+    }
+
+    #[test]
+    fn returning_inside_a_string_does_not_count() {
+        let c = classify_statement(
+            "INSERT INTO log (msg) VALUES ('returning soon')",
+            Dialect::Postgres,
+        );
+        assert!(!c.returns_rows);
+    }
+
+    #[test]
+    fn cte_main_statement_decides_kind() {
+        let c = classify_statement(
+            "WITH RECURSIVE old (id) AS (SELECT id FROM t WHERE stale) DELETE FROM t WHERE id IN (SELECT id FROM old)",
+            Dialect::Postgres,
+        );
+        assert_eq!(c.kind, StatementKind::Delete);
+        assert!(c.is_write && c.has_where);
+
+        let c = classify_statement(
+            "WITH a AS MATERIALIZED (SELECT 1), b AS (SELECT 2) SELECT * FROM a, b",
+            Dialect::Postgres,
+        );
+        assert_eq!(c.kind, StatementKind::Select);
+        assert!(!c.is_write);
+    }
+
+    #[test]
+    fn data_modifying_cte_is_a_write() {
+        let c = classify_statement(
+            "WITH gone AS (DELETE FROM t RETURNING *) SELECT count(*) FROM gone",
+            Dialect::Postgres,
+        );
+        assert_eq!(c.kind, StatementKind::Select);
+        assert!(c.returns_rows && c.is_write);
+    }
+
+    #[test]
+    fn nested_writes_are_listed() {
+        let nested = nested_writes(
+            "WITH gone AS (DELETE FROM t RETURNING *), kept AS (SELECT 1) SELECT * FROM gone",
+            Dialect::Postgres,
+        );
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].kind, StatementKind::Delete);
+        assert!(!nested[0].has_where);
+
+        let nested = nested_writes(
+            "EXPLAIN ANALYZE WITH n AS (INSERT INTO t VALUES (1) RETURNING id) UPDATE u SET a = 1 WHERE id IN (SELECT id FROM n)",
+            Dialect::Postgres,
+        );
+        let kinds: Vec<_> = nested.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [StatementKind::Insert, StatementKind::Update]);
+
+        assert!(nested_writes("EXPLAIN DELETE FROM t", Dialect::Postgres).is_empty());
+        assert!(
+            nested_writes(
+                "EXPLAIN WITH g AS (DELETE FROM t RETURNING *) SELECT * FROM g",
+                Dialect::Postgres
+            )
+            .is_empty()
+        );
+        assert!(nested_writes("DELETE FROM t", Dialect::Postgres).is_empty());
+    }
+
+    #[test]
+    fn where_in_subquery_is_not_top_level() {
+        let c = classify_statement(
+            "DELETE FROM t USING (SELECT id FROM u WHERE x) s",
+            Dialect::Postgres,
+        );
+        assert!(!c.has_where);
+    }
+
+    #[test]
+    fn explain_analyze_of_a_write_is_a_write() {
+        assert!(!classify_statement("EXPLAIN DELETE FROM t", Dialect::Postgres).is_write);
+        assert!(classify_statement("EXPLAIN ANALYZE DELETE FROM t", Dialect::Postgres).is_write);
+        assert!(
+            classify_statement(
+                "EXPLAIN (ANALYZE, BUFFERS) UPDATE t SET a = 1",
+                Dialect::Postgres
+            )
+            .is_write
+        );
+        assert_eq!(kind("EXPLAIN QUERY PLAN SELECT 1"), StatementKind::Explain);
+    }
+
+    #[test]
+    fn select_into_creates_a_table() {
+        let c = classify_statement("SELECT * INTO archive FROM t", Dialect::Postgres);
+        assert!(c.is_write && !c.returns_rows);
+    }
+
+    #[test]
+    fn other_kinds() {
+        assert_eq!(kind("create table t (a int)"), StatementKind::Ddl);
+        assert_eq!(kind("GRANT SELECT ON t TO r"), StatementKind::Dcl);
+        assert_eq!(kind("BEGIN"), StatementKind::Transaction);
+        assert_eq!(kind("(SELECT 1) UNION (SELECT 2)"), StatementKind::Select);
+        assert_eq!(kind("VACUUM"), StatementKind::Maintenance);
+        assert_eq!(kind("  -- nothing\n"), StatementKind::Empty);
+    }
+
+    #[test]
+    fn maintenance_and_unknown_statements_write() {
+        let writes = |sql| classify_statement(sql, Dialect::Postgres).is_write;
+        for sql in [
+            "VACUUM FULL t",
+            "CLUSTER t USING t_pkey",
+            "REINDEX TABLE t",
+            "REFRESH MATERIALIZED VIEW mv",
+            "ANALYZE t",
+            "LOCK TABLE t IN ACCESS EXCLUSIVE MODE",
+            "NOTIFY jobs",
+        ] {
+            assert!(writes(sql), "{sql}");
+        }
+        for sql in [
+            "CHECKPOINT",
+            "DISCARD ALL",
+            "FETCH 10 FROM c",
+            "LISTEN jobs",
+        ] {
+            assert!(!writes(sql), "{sql}");
+        }
+        assert_eq!(
+            kind("IMPORT FOREIGN SCHEMA s FROM SERVER f INTO t"),
+            StatementKind::Ddl
+        );
+        assert_eq!(kind("SECURITY LABEL ON TABLE t IS 'x'"), StatementKind::Ddl);
+        assert_eq!(kind("REASSIGN OWNED BY a TO b"), StatementKind::Dcl);
+        let sqlite = |sql| classify_statement(sql, Dialect::Sqlite);
+        assert_eq!(sqlite("ATTACH 'x.db' AS x").kind, StatementKind::Ddl);
+        assert!(sqlite("DETACH x").is_write);
+    }
+}
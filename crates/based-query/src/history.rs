@@ -15,6 +15,8 @@ pub enum RunStatus {
     #[default]
     Ok,
     Error,
+    /// Stopped by the user before it finished.
+    Cancelled,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -0,0 +1,328 @@
+//! SQL tokenizer shared by the statement classifier and script splitter.
+//!
+//! Produces significant tokens only: whitespace and comments are skipped. Strings
+//! (including `E'…'` escapes and `$tag$…$tag$` bodies) and quoted identifiers are
+//! single tokens, so keywords and `;` inside them are never seen.
+
+/// Lexical rules that differ between engines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// Nested block comments, `$tag$` strings, `E'…'` backslash escapes.
+    #[default]
+    Postgres,
+    /// `[bracket]` and `` `backtick` `` identifiers, `$name` parameters, flat block comments.
+    Sqlite,
+}
+
+/// Token category; the text is recovered from the source with [`Token::text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Bare word: keyword or unquoted identifier.
+    Word,
+    /// `"quoted identifier"`.
+    QuotedIdent,
+    /// `'…'`, `E'…'`, or `$tag$…$tag$` literal.
+    String,
+    Number,
+    /// Positional parameter such as `$1`.
+    Param,
+    /// Any other single character (`;`, `(`, `,`, `=`, …).
+    Punct(char),
+}
+
+/// One significant token and its byte range in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    pub fn text<'a>(&self, sql: &'a str) -> &'a str {
+        &sql[self.start..self.end]
+    }
+
+    /// Whether this is a bare word equal to `keyword` (ASCII case-insensitive).
+    pub fn is_keyword(&self, sql: &str, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text(sql).eq_ignore_ascii_case(keyword)
+    }
+
+    pub fn is_punct(&self, c: char) -> bool {
+        self.kind == TokenKind::Punct(c)
+    }
+}
+
+/// Tokenize `sql` under `dialect`, skipping whitespace and `--` / `/* */` comments.
+/// Unterminated strings and comments run to the end of the input.
+pub fn tokenize(sql: &str, dialect: Dialect) -> Vec<Token> {
+    let pg = dialect == Dialect::Postgres;
+    let bytes = sql.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+        let kind = match b {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = line_comment_end(bytes, i);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = block_comment_end(bytes, i, pg);
+                continue;
+            }
+            b'\'' => {
+                i = quoted_end(bytes, i, b'\'', false);
+                TokenKind::String
+            }
+            b'e' | b'E' if pg && bytes.get(i + 1) == Some(&b'\'') => {
+                i = quoted_end(bytes, i + 1, b'\'', true);
+                TokenKind::String
+            }
+            b'"' => {
+                i = quoted_end(bytes, i, b'"', false);
+                TokenKind::QuotedIdent
+            }
+            b'`' if !pg => {
+                i = quoted_end(bytes, i, b'`', false);
+                TokenKind::QuotedIdent
+            }
+            b'[' if !pg => {
+                i = bytes[i..]
+                    .iter()
+                    .position(|&b| b == b']')
+                    .map_or(bytes.len(), |n| i + n + 1);
+                TokenKind::QuotedIdent
+            }
+            b'$' if !pg && bytes.get(i + 1).is_some_and(|&b| is_word_continue(b)) => {
+                i += 1;
+                while i < bytes.len() && is_word_continue(bytes[i]) {
+                    i += 1;
+                }
+                TokenKind::Param
+            }
+            b'$' => match dollar_tag_len(bytes, i) {
+                Some(tag_len) => {
+                    i = dollar_quoted_end(bytes, i, tag_len);
+                    TokenKind::String
+                }
+                None if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                    i += 1;
+                    while i < bytes.len() && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                    TokenKind::Param
+                }
+                None => {
+                    i += 1;
+                    TokenKind::Punct('$')
+                }
+            },
+            b if b.is_ascii_digit()
+                || (b == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) =>
+            {
+                i = number_end(bytes, i);
+                TokenKind::Number
+            }
+            b if is_word_start(b) => {
+                while i < bytes.len() && is_word_continue(bytes[i]) {
+                    i += 1;
+                }
+                TokenKind::Word
+            }
+            _ => {
+                let c = sql[i..].chars().next().unwrap_or('\0');
+                i += c.len_utf8();
+                TokenKind::Punct(c)
+            }
+        };
+        out.push(Token {
+            kind,
+            start,
+            end: i,
+        });
+    }
+    out
+}
+
+fn is_word_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b >= 0x80
+}
+
+fn is_word_continue(b: u8) -> bool {
+    is_word_start(b) || b.is_ascii_digit() || b == b'$'
+}
+
+fn line_comment_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |n| start + n + 1)
+}
+
+/// End of a `/* … */` comment starting at `start`; with `nested`, inner comments are
+/// balanced (Postgres), otherwise the first `*/` closes it (SQLite).
+fn block_comment_end(bytes: &[u8], start: usize, nested: bool) -> usize {
+    let mut depth = 0usize;
+    let mut i = start;
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'*') if nested || depth == 0 => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', b'/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// End of a literal opened by `quote` at `open`. A doubled quote is an escaped quote;
+/// with `backslash`, `\` escapes the next byte (`E'…'` strings).
+fn quoted_end(bytes: &[u8], open: usize, quote: u8, backslash: bool) -> usize {
+    let mut i = open + 1;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if backslash && b == b'\\' {
+            i += 2;
+            continue;
+        }
+        if b == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Length of a `$tag$` opener at `start` (`$$` has length 2), if one is there.
+fn dollar_tag_len(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if bytes.get(i).is_some_and(u8::is_ascii_digit) {
+        return None;
+    }
+    while i < bytes.len() {
+        match bytes[i] {
+            b'$' => return Some(i - start + 1),
+            b if b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80 => i += 1,
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn dollar_quoted_end(bytes: &[u8], start: usize, tag_len: usize) -> usize {
+    let tag = &bytes[start..start + tag_len];
+    let body = start + tag_len;
+    bytes[body..]
+        .windows(tag_len)
+        .position(|w| w == tag)
+        .map_or(bytes.len(), |n| body + n + tag_len)
+}
+
+fn number_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let exponent_sign =
+            (b == b'+' || b == b'-') && i > start && matches!(bytes[i - 1], b'e' | b'E');
+        if b.is_ascii_alphanumeric() || b == b'.' || b == b'_' || exponent_sign {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_and_text(sql: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(sql, Dialect::Postgres)
+            .iter()
+            .map(|t| (t.kind, t.text(sql)))
+            .collect()
+    }
+
+    #[test]
+    fn skips_comments_and_whitespace() {
+        let sql = "-- lead\n/* block /* nested */ still */ SELECT 1";
+        assert_eq!(
+            kinds_and_text(sql),
+            vec![(TokenKind::Word, "SELECT"), (TokenKind::Number, "1")]
+        );
+    }
+
+    #[test]
+    fn strings_and_identifiers_are_single_tokens() {
+        let sql = r#"SELECT 'a;''b', E'x\';y', "semi;colon", $fn$ body; $$ $fn$"#;
+        let toks = kinds_and_text(sql);
+        assert_eq!(
+            toks,
+            vec![
+                (TokenKind::Word, "SELECT"),
+                (TokenKind::String, "'a;''b'"),
+                (TokenKind::Punct(','), ","),
+                (TokenKind::String, r"E'x\';y'"),
+                (TokenKind::Punct(','), ","),
+                (TokenKind::QuotedIdent, "\"semi;colon\""),
+                (TokenKind::Punct(','), ","),
+                (TokenKind::String, "$fn$ body; $$ $fn$"),
+            ]
+        );
+    }
+
+    #[test]
+    fn positional_params_are_not_dollar_quotes() {
+        let sql = "SELECT $1, $2::int";
+        let toks = kinds_and_text(sql);
+        assert_eq!(toks[1], (TokenKind::Param, "$1"));
+        assert_eq!(toks[3], (TokenKind::Param, "$2"));
+    }
+
+    #[test]
+    fn unterminated_literal_runs_to_end() {
+        let sql = "SELECT 'open; SELECT 2";
+        assert_eq!(tokenize(sql, Dialect::Postgres).len(), 2);
+    }
+
+    #[test]
+    fn sqlite_identifiers_and_params() {
+        let sql = "SELECT [a;b], `c;d`, $name, E FROM t /* /* */ x";
+        let toks: Vec<_> = tokenize(sql, Dialect::Sqlite)
+            .iter()
+            .map(|t| (t.kind, t.text(sql)))
+            .collect();
+        assert_eq!(toks[1], (TokenKind::QuotedIdent, "[a;b]"));
+        assert_eq!(toks[3], (TokenKind::QuotedIdent, "`c;d`"));
+        assert_eq!(toks[5], (TokenKind::Param, "$name"));
+        assert_eq!(toks[7], (TokenKind::Word, "E"));
+        // Block comments don't nest, so `x` is a token.
+        assert_eq!(toks.last(), Some(&(TokenKind::Word, "x")));
+    }
+
+    #[test]
+    fn postgres_comments_nest() {
+        let sql = "/* a /* b */ c */ SELECT";
+        assert_eq!(tokenize(sql, Dialect::Postgres).len(), 1);
+        assert_eq!(tokenize(sql, Dialect::Sqlite).len(), 4);
+    }
+}
@@ -1,13 +1,22 @@
-//! Query persistence, variable substitution, and SQL utilities (no UI).
+//! Query persistence, variable substitution and parameter binding, and SQL utilities:
+//! tokenizer, statement classifier, and script splitting (no UI).
 
+pub mod classify;
 pub mod history;
+pub mod lexer;
+pub mod params;
 pub mod resolve;
 pub mod saved;
 pub mod sql;
 pub mod variables;
 
+pub use classify::{StatementClass, StatementKind, classify_statement, nested_writes};
 pub use history::{HistoryEntry, MAX_HISTORY_PER_CONNECTION, QueryHistory, RunStatus};
-pub use resolve::{ResolveError, VariableContext, resolve_query};
+pub use lexer::{Dialect, Token, TokenKind, tokenize};
+pub use params::{BoundSql, SqlParam, bind_json, bind_sql};
+pub use resolve::{
+    RAW_MARKER, ResolveError, VariableContext, find_missing_variables, resolve_query,
+};
 pub use saved::{SavedQueries, SavedQuery};
 pub use sql::{SqlStatement, statement_at_offset, statements_in_script};
 pub use variables::{Variables, load_variables, save_variables, substitute_dollar_vars};
@@ -0,0 +1,283 @@
+//! Compile `{{name}}` placeholders to engine-native bind parameters.
+//!
+//! In SQL, a placeholder in code position becomes `$n` (Postgres) or `?n` (SQLite) and
+//! its value travels separately, as does a string literal that is only a placeholder
+//! (`'{{name}}'`). Only the [`RAW_MARKER`] (`{{raw:table}}`, for identifiers) splices a
+//! value as text; any other placeholder inside a literal or comment is an error. In a JSON
+//! document (Mongo pipelines) a string that is exactly one placeholder is replaced by the
+//! typed value; placeholders inside longer strings are spliced into the string.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value as JsonValue;
+
+use crate::lexer::{Dialect, TokenKind, tokenize};
+use crate::resolve::{
+    RAW_MARKER, ResolveError, VariableContext, eval_token, resolve_query, token_name,
+};
+
+/// One bound placeholder: the variable name and its resolved text, which the caller
+/// coerces to the variable's type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlParam {
+    pub name: String,
+    pub value: String,
+}
+
+/// SQL with engine-native placeholders; `params[i]` binds to index `i + 1`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoundSql {
+    pub sql: String,
+    pub params: Vec<SqlParam>,
+}
+
+/// Replace `{{…}}` placeholders in `query` with bind parameters for `dialect`. A name
+/// used more than once shares one parameter; builtins such as `{{$randomUUID}}` get a
+/// fresh one per occurrence.
+pub fn bind_sql(
+    query: &str,
+    ctx: &VariableContext,
+    dialect: Dialect,
+) -> Result<BoundSql, ResolveError> {
+    let tokens = tokenize(query, dialect);
+    // `{` outside literals and comments is a token of its own.
+    let code_braces: HashSet<usize> = tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::Punct('{'))
+        .map(|t| t.start)
+        .collect();
+    // `'{{name}}'`: the whole literal, quotes included, becomes the parameter.
+    let quoted: HashMap<usize, usize> = tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::String)
+        .filter(|t| {
+            let text = t.text(query);
+            text.len() > 2
+                && text.starts_with('\'')
+                && text.ends_with('\'')
+                && sole_placeholder(&text[1..text.len() - 1])
+                    .is_some_and(|token| !token.starts_with(RAW_MARKER))
+        })
+        .map(|t| (t.start + 1, t.end))
+        .collect();
+    let mut bound = BoundSql::default();
+    let mut by_name: HashMap<&str, usize> = HashMap::new();
+    let mut pos = 0;
+    while let Some(found) = query[pos..].find("{{") {
+        let start = pos + found;
+        let after = start + 2;
+        let Some(len) = query[after..].find("}}") else {
+            break;
+        };
+        let token = query[after..after + len].trim();
+        match quoted.get(&start) {
+            Some(&end) => {
+                bound.sql.push_str(&query[pos..start - 1]);
+                pos = end;
+            }
+            None => {
+                bound.sql.push_str(&query[pos..start]);
+                pos = after + len + 2;
+            }
+        }
+        let raw = token.starts_with(RAW_MARKER);
+        if !raw && !code_braces.contains(&start) && !quoted.contains_key(&start) {
+            return Err(ResolveError::PlaceholderInText(token.to_string()));
+        }
+        let value = eval_token(token, ctx)?;
+        if raw {
+            bound.sql.push_str(&value);
+            continue;
+        }
+        let name = token_name(token);
+        let index = match by_name.get(name) {
+            Some(&index) => index,
+            None => {
+                bound.params.push(SqlParam {
+                    name: name.to_string(),
+                    value,
+                });
+                let index = bound.params.len();
+                if !name.starts_with('$') {
+                    by_name.insert(name, index);
+                }
+                index
+            }
+        };
+        bound.sql.push_str(&placeholder(dialect, index));
+    }
+    bound.sql.push_str(&query[pos..]);
+    Ok(bound)
+}
+
+fn placeholder(dialect: Dialect, index: usize) -> String {
+    match dialect {
+        Dialect::Postgres => format!("${index}"),
+        Dialect::Sqlite => format!("?{index}"),
+    }
+}
+
+/// Bind placeholders in a JSON document in place: a string that is exactly `{{name}}`
+/// becomes `coerce(param)`, any other placeholder is spliced into its string.
+pub fn bind_json<F>(
+    value: &mut JsonValue,
+    ctx: &VariableContext,
+    coerce: &mut F,
+) -> Result<(), ResolveError>
+where
+    F: FnMut(&SqlParam) -> Result<JsonValue, ResolveError>,
+{
+    match value {
+        JsonValue::String(text) => match sole_placeholder(text) {
+            Some(token) if !token.starts_with(RAW_MARKER) => {
+                let param = SqlParam {
+                    name: token_name(token).to_string(),
+                    value: eval_token(token, ctx)?,
+                };
+                *value = coerce(&param)?;
+            }
+            _ if text.contains("{{") => *text = resolve_query(text, ctx)?,
+            _ => {}
+        },
+        JsonValue::Array(items) => {
+            for item in items {
+                bind_json(item, ctx, coerce)?;
+            }
+        }
+        JsonValue::Object(fields) => {
+            for field in fields.values_mut() {
+                bind_json(field, ctx, coerce)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Token of a string that consists of a single `{{…}}` placeholder.
+fn sole_placeholder(text: &str) -> Option<&str> {
+    let inner = text.trim().strip_prefix("{{")?.strip_suffix("}}")?;
+    (!inner.contains("{{") && !inner.contains("}}")).then(|| inner.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn ctx() -> VariableContext {
+        let mut ctx = VariableContext::default();
+        ctx.session.insert("id".into(), "42".into());
+        ctx.session.insert("name".into(), "O'Brien".into());
+        ctx.session.insert("table".into(), "users".into());
+        ctx
+    }
+
+    fn param(name: &str, value: &str) -> SqlParam {
+        SqlParam {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn postgres_numbers_params_and_reuses_names() {
+        let bound = bind_sql(
+            "SELECT * FROM t WHERE id = {{id}} OR parent = {{ id }} OR name = {{name}}",
+            &ctx(),
+            Dialect::Postgres,
+        )
+        .unwrap();
+        assert_eq!(
+            bound.sql,
+            "SELECT * FROM t WHERE id = $1 OR parent = $1 OR name = $2"
+        );
+        assert_eq!(
+            bound.params,
+            vec![param("id", "42"), param("name", "O'Brien")]
+        );
+    }
+
+    #[test]
+    fn sqlite_uses_numbered_question_marks() {
+        let bound = bind_sql("DELETE FROM t WHERE id = {{id}}", &ctx(), Dialect::Sqlite).unwrap();
+        assert_eq!(bound.sql, "DELETE FROM t WHERE id = ?1");
+    }
+
+    #[test]
+    fn only_the_raw_marker_splices_text() {
+        let bound = bind_sql(
+            "SELECT * FROM {{raw:table}} -- all\nWHERE id = {{id}} AND tag = 'v{{raw:id}}'",
+            &ctx(),
+            Dialect::Postgres,
+        )
+        .unwrap();
+        assert_eq!(
+            bound.sql,
+            "SELECT * FROM users -- all\nWHERE id = $1 AND tag = 'v42'"
+        );
+        assert_eq!(bound.params, vec![param("id", "42")]);
+    }
+
+    #[test]
+    fn quoted_placeholder_binds_the_whole_literal() {
+        let bound = bind_sql(
+            "SELECT * FROM t WHERE name = '{{name}}' OR alias = '{{ name }}'",
+            &ctx(),
+            Dialect::Sqlite,
+        )
+        .unwrap();
+        assert_eq!(bound.sql, "SELECT * FROM t WHERE name = ?1 OR alias = ?1");
+        assert_eq!(bound.params, vec![param("name", "O'Brien")]);
+    }
+
+    #[test]
+    fn placeholders_inside_text_are_rejected() {
+        for sql in [
+            "SELECT 'hello {{name}}'",
+            "SELECT 1 -- {{name}}",
+            "SELECT 1 /* {{name}} */",
+            "SELECT $$ {{name}} $$",
+        ] {
+            assert!(
+                matches!(
+                    bind_sql(sql, &ctx(), Dialect::Postgres),
+                    Err(ResolveError::PlaceholderInText(name)) if name == "name"
+                ),
+                "{sql}"
+            );
+        }
+    }
+
+    #[test]
+    fn builtins_bind_once_per_occurrence() {
+        let bound = bind_sql(
+            "VALUES ({{$randomUUID}}, {{$randomUUID}})",
+            &ctx(),
+            Dialect::Postgres,
+        )
+        .unwrap();
+        assert_eq!(bound.sql, "VALUES ($1, $2)");
+        assert_ne!(bound.params[0].value, bound.params[1].value);
+    }
+
+    #[test]
+    fn json_placeholders_become_typed_values() {
+        let mut pipeline = json!([
+            { "$match": { "age": "{{id}}", "label": "user {{name}}" } },
+            { "$limit": "{{raw:id}}" }
+        ]);
+        bind_json(&mut pipeline, &ctx(), &mut |p| {
+            Ok(json!(p.value.parse::<i64>().unwrap()))
+        })
+        .unwrap();
+        assert_eq!(
+            pipeline,
+            json!([
+                { "$match": { "age": 42, "label": "user O'Brien" } },
+                { "$limit": "42" }
+            ])
+        );
+    }
+}
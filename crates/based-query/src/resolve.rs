@@ -1,4 +1,7 @@
 //! Postman-style `{{$fn}}` and `{{name}}` variable resolution (sandboxed).
+//!
+//! [`resolve_query`] splices every value into the text; editors bind `{{name}}` as a
+//! parameter instead (see [`crate::params`]) and keep splicing for `{{raw:name}}`.
 
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FmtResult};
@@ -27,6 +30,13 @@ pub struct VariableContext {
 pub enum ResolveError {
     MissingVariable(String),
     InvalidRandomInt(String),
+    /// A value that can't be coerced to its variable's declared type.
+    InvalidValue {
+        name: String,
+        reason: String,
+    },
+    /// A `{{name}}` inside a SQL string literal or comment, where no parameter can bind.
+    PlaceholderInText(String),
 }
 
 impl Display for ResolveError {
@@ -34,6 +44,13 @@ impl Display for ResolveError {
         match self {
             Self::MissingVariable(name) => write!(f, "Missing variable: {{{{{name}}}}}"),
             Self::InvalidRandomInt(msg) => write!(f, "Invalid {{$randomInt}}: {msg}"),
+            Self::InvalidValue { name, reason } => {
+                write!(f, "Invalid value for {{{{{name}}}}}: {reason}")
+            }
+            Self::PlaceholderInText(name) => write!(
+                f,
+                "{{{{{name}}}}} is inside a string or comment; write {{{{raw:{name}}}}} to splice it as text"
+            ),
         }
     }
 }
@@ -60,7 +77,17 @@ pub fn resolve_query(query: &str, ctx: &VariableContext) -> Result<String, Resol
     Ok(out)
 }
 
-fn eval_token(token: &str, ctx: &VariableContext) -> Result<String, ResolveError> {
+/// Marker for a placeholder spliced as text even where a parameter is bound, e.g.
+/// `{{raw:table}}` for an identifier.
+pub const RAW_MARKER: &str = "raw:";
+
+/// Variable name of a placeholder token, without the raw marker.
+pub(crate) fn token_name(token: &str) -> &str {
+    token.strip_prefix(RAW_MARKER).map_or(token, str::trim)
+}
+
+pub(crate) fn eval_token(token: &str, ctx: &VariableContext) -> Result<String, ResolveError> {
+    let token = token_name(token);
     if let Some(builtin) = token.strip_prefix('$') {
         return eval_builtin(builtin);
     }
@@ -125,14 +152,15 @@ fn bail_resolve(e: ResolveError) -> Result<String, ResolveError> {
     Err(e)
 }
 
-/// List unresolved `{{name}}` tokens (non-builtin) for preview UI.
+/// List unresolved `{{name}}` tokens (non-builtin), once each in order of first use, for
+/// preview UI and the run-time parameter form.
 pub fn find_missing_variables(query: &str, ctx: &VariableContext) -> Vec<String> {
     let mut missing = Vec::new();
     let mut rest = query;
     while let Some(start) = rest.find("{{") {
         let after = &rest[start + 2..];
         let Some(end) = after.find("}}") else { break };
-        let token = after[..end].trim();
+        let token = token_name(after[..end].trim());
         if !token.starts_with('$')
             && !ctx.session.contains_key(token)
             && !ctx.query.contains_key(token)
@@ -143,6 +171,7 @@ pub fn find_missing_variables(query: &str, ctx: &VariableContext) -> Vec<String>
                 .is_some_and(|vars| vars.contains_key(token))
             && !ctx.workspace.contains_key(token)
             && !ctx.connection.contains_key(token)
+            && !missing.iter().any(|name| name == token)
         {
             missing.push(token.to_string());
         }
@@ -195,10 +224,30 @@ mod tests {
         assert_eq!(out, "workspace");
     }
 
+    #[test]
+    fn raw_marker_resolves_like_plain() {
+        let mut ctx = VariableContext::default();
+        ctx.session.insert("t".into(), "users".into());
+        let out = resolve_query("SELECT * FROM {{raw:t}}", &ctx).unwrap();
+        assert_eq!(out, "SELECT * FROM users");
+    }
+
     #[test]
     fn missing_blocks() {
         let ctx = VariableContext::default();
         let err = resolve_query("{{missing}}", &ctx).unwrap_err();
         assert!(matches!(err, ResolveError::MissingVariable(_)));
     }
+
+    #[test]
+    fn missing_lists_each_name_once() {
+        let mut ctx = VariableContext::default();
+        ctx.workspace.insert("schema".into(), "public".into());
+        let missing = find_missing_variables(
+            "SELECT * FROM {{raw:schema}}.t WHERE d >= {{start}} AND d < {{end}} \
+             AND d <> {{ start }} AND id = {{$randomInt}}",
+            &ctx,
+        );
+        assert_eq!(missing, vec!["start".to_string(), "end".to_string()]);
+    }
 }
@@ -1,4 +1,10 @@
-//! SQL statement boundaries for run-current / run-script (P0 baseline).
+//! SQL statement boundaries for run-current / run-script.
+//!
+//! Splitting works on [`tokenize`] output, so `;` inside strings, quoted identifiers,
+//! `$$` bodies, and comments never ends a statement. Compound bodies (`CREATE TRIGGER
+//! … BEGIN … END` in SQLite, `BEGIN ATOMIC … END` in Postgres) are kept whole.
+
+use crate::lexer::{Dialect, Token, tokenize};
 
 /// Byte range of a single SQL statement in the source script.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,32 +19,41 @@ impl SqlStatement {
     }
 }
 
-/// Split `script` into statements delimited by `;` outside single-quoted strings.
-pub fn statements_in_script(script: &str) -> Vec<SqlStatement> {
+/// Split `script` into `;`-delimited statements. A range starts just after the previous
+/// `;` and ends at its own `;` (or the end of input); chunks holding only whitespace or
+/// comments are dropped.
+pub fn statements_in_script(script: &str, dialect: Dialect) -> Vec<SqlStatement> {
+    let tokens = tokenize(script, dialect);
     let mut out = Vec::new();
     let mut start = 0usize;
-    let mut in_string = false;
-    let bytes = script.as_bytes();
-    let mut i = 0usize;
-    while i < bytes.len() {
-        let b = bytes[i];
-        if b == b'\'' {
-            in_string = !in_string;
-            i += 1;
+    // Index of the current statement's first token, if it has one yet.
+    let mut first: Option<usize> = None;
+    let mut depth = 0usize;
+    for (i, tok) in tokens.iter().enumerate() {
+        if tok.is_punct(';') && depth == 0 {
+            if first.is_some() {
+                out.push(SqlStatement {
+                    start,
+                    end: tok.start,
+                });
+            }
+            start = tok.end;
+            first = None;
             continue;
         }
-        if !in_string && b == b';' {
-            let end = i;
-            let slice = script[start..end].trim();
-            if !slice.is_empty() {
-                out.push(SqlStatement { start, end });
+        let first = *first.get_or_insert(i);
+        if depth > 0 {
+            if tok.is_keyword(script, "BEGIN") || tok.is_keyword(script, "CASE") {
+                depth += 1;
+            } else if tok.is_keyword(script, "END") {
+                depth -= 1;
             }
-            start = i + 1;
+        } else if tok.is_keyword(script, "BEGIN") && opens_body(script, &tokens, first, i, dialect)
+        {
+            depth = 1;
         }
-        i += 1;
     }
-    let tail = script[start..].trim();
-    if !tail.is_empty() {
+    if first.is_some() {
         out.push(SqlStatement {
             start,
             end: script.len(),
@@ -47,9 +62,31 @@ pub fn statements_in_script(script: &str) -> Vec<SqlStatement> {
     out
 }
 
+/// Whether the `BEGIN` at `at` opens a compound body rather than a transaction.
+fn opens_body(script: &str, tokens: &[Token], first: usize, at: usize, dialect: Dialect) -> bool {
+    match dialect {
+        Dialect::Postgres => tokens
+            .get(at + 1)
+            .is_some_and(|t| t.is_keyword(script, "ATOMIC")),
+        Dialect::Sqlite => {
+            let mut words = tokens[first..at].iter();
+            if !words.next().is_some_and(|t| t.is_keyword(script, "CREATE")) {
+                return false;
+            }
+            let mut next = words.next();
+            if next
+                .is_some_and(|t| t.is_keyword(script, "TEMP") || t.is_keyword(script, "TEMPORARY"))
+            {
+                next = words.next();
+            }
+            next.is_some_and(|t| t.is_keyword(script, "TRIGGER"))
+        }
+    }
+}
+
 /// Statement containing `offset`, or the next non-empty statement after `offset`.
-pub fn statement_at_offset(script: &str, offset: usize) -> Option<SqlStatement> {
-    let stmts = statements_in_script(script);
+pub fn statement_at_offset(script: &str, offset: usize, dialect: Dialect) -> Option<SqlStatement> {
+    let stmts = statements_in_script(script, dialect);
     if stmts.is_empty() {
         return None;
     }
@@ -69,10 +106,17 @@ pub fn statement_at_offset(script: &str, offset: usize) -> Option<SqlStatement>
 mod tests {
     use super::*;
 
+    fn texts(script: &str, dialect: Dialect) -> Vec<&str> {
+        statements_in_script(script, dialect)
+            .iter()
+            .map(|s| s.text(script))
+            .collect()
+    }
+
     #[test]
     fn splits_two_statements() {
         let script = "SELECT 1;\nSELECT 2;";
-        let stmts = statements_in_script(script);
+        let stmts = statements_in_script(script, Dialect::Postgres);
         assert_eq!(stmts.len(), 2);
         assert_eq!(stmts[0].text(script), "SELECT 1");
         assert_eq!(stmts[1].text(script), "SELECT 2");
@@ -81,7 +125,76 @@ mod tests {
     #[test]
     fn semicolon_in_string_ignored() {
         let script = "SELECT ';' AS x; SELECT 2;";
-        let stmts = statements_in_script(script);
+        let stmts = statements_in_script(script, Dialect::Postgres);
         assert_eq!(stmts.len(), 2);
     }
+
+    #[test]
+    fn dollar_quoted_function_body_is_one_statement() {
+        let script = "CREATE FUNCTION f() RETURNS int AS $body$\n\
+                      BEGIN\n  PERFORM 1; RETURN 2;\nEND;\n$body$ LANGUAGE plpgsql;\n\
+                      SELECT f();";
+        let stmts = texts(script, Dialect::Postgres);
+        assert_eq!(stmts.len(), 2);
+        assert!(stmts[0].ends_with("LANGUAGE plpgsql"));
+        assert_eq!(stmts[1], "SELECT f()");
+    }
+
+    #[test]
+    fn comments_and_quoted_identifiers_do_not_split() {
+        let script = "-- step 1; prepare\nSELECT \"a;b\" FROM t /* ; */;\n-- trailing; note\n";
+        assert_eq!(
+            texts(script, Dialect::Postgres),
+            vec!["-- step 1; prepare\nSELECT \"a;b\" FROM t /* ; */"]
+        );
+    }
+
+    #[test]
+    fn escape_strings_keep_backslash_quotes() {
+        let script = r"SELECT E'it\'s; fine'; SELECT 2";
+        assert_eq!(
+            texts(script, Dialect::Postgres),
+            vec![r"SELECT E'it\'s; fine'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn postgres_begin_atomic_body_and_plain_begin() {
+        let script = "BEGIN;\nCREATE PROCEDURE p() BEGIN ATOMIC\n  INSERT INTO t VALUES (1);\n  \
+                      SELECT CASE WHEN true THEN 1 END;\nEND;\nCOMMIT;";
+        let stmts = texts(script, Dialect::Postgres);
+        assert_eq!(stmts.len(), 3);
+        assert_eq!(stmts[0], "BEGIN");
+        assert!(stmts[1].starts_with("CREATE PROCEDURE") && stmts[1].ends_with("END"));
+        assert_eq!(stmts[2], "COMMIT");
+    }
+
+    #[test]
+    fn sqlite_trigger_body_is_one_statement() {
+        let script = "CREATE TEMP TRIGGER trg AFTER INSERT ON t BEGIN\n  \
+                      UPDATE t SET n = CASE WHEN n > 0 THEN n END;\n  DELETE FROM u;\nEND;\n\
+                      BEGIN; INSERT INTO [odd;name] VALUES (1); END;";
+        let stmts = texts(script, Dialect::Sqlite);
+        assert_eq!(stmts.len(), 4);
+        assert!(stmts[0].starts_with("CREATE TEMP TRIGGER") && stmts[0].ends_with("END"));
+        assert_eq!(stmts[1], "BEGIN");
+        assert_eq!(stmts[2], "INSERT INTO [odd;name] VALUES (1)");
+        assert_eq!(stmts[3], "END");
+    }
+
+    #[test]
+    fn statement_at_offset_uses_byte_ranges() {
+        let script = "SELECT 1; SELECT $$;$$; SELECT 3";
+        let at = |offset| {
+            statement_at_offset(script, offset, Dialect::Postgres)
+                .map(|s| s.text(script).to_string())
+        };
+        assert_eq!(at(0).as_deref(), Some("SELECT 1"));
+        assert_eq!(at(19).as_deref(), Some("SELECT $$;$$"));
+        assert_eq!(at(script.len()).as_deref(), Some("SELECT 3"));
+        assert_eq!(
+            statement_at_offset("-- only a comment", 0, Dialect::Postgres),
+            None
+        );
+    }
 }
@@ -0,0 +1,146 @@
+//! Interrupt a running statement from another task.
+//!
+//! The executing pooled connection publishes its raw `sqlite3*` in a [`SqliteInterrupt`];
+//! [`SqliteInterrupt::interrupt`] calls `sqlite3_interrupt`, which is safe from any thread
+//! and makes the running step fail with `SQLITE_INTERRUPT`.
+
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use based_core::Value;
+use libsqlite3_sys::{sqlite3, sqlite3_interrupt};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::stream::QueryResult;
+use crate::timeout::execute_sql_with_timeout;
+
+/// `SQLITE_INTERRUPT` primary result code.
+const SQLITE_INTERRUPT: &str = "9";
+
+struct RawDb(NonNull<sqlite3>);
+
+// SAFETY: `sqlite3_interrupt` is documented as callable from any thread, and the pointer
+// is only dereferenced under the mutex while the owning connection is attached.
+unsafe impl Send for RawDb {}
+
+/// Shared slot holding the connection handle of the statement that may be interrupted.
+#[derive(Clone, Default)]
+pub struct SqliteInterrupt {
+    handle: Arc<Mutex<Option<RawDb>>>,
+}
+
+impl SqliteInterrupt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a connection is attached (a statement is running).
+    pub fn is_attached(&self) -> bool {
+        self.handle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Publish `conn`'s raw handle before it starts executing.
+    pub async fn attach(&self, conn: &mut SqliteConnection) -> Result<()> {
+        let raw = conn
+            .lock_handle()
+            .await
+            .context("lock sqlite handle")?
+            .as_raw_handle();
+        *self.handle.lock().unwrap_or_else(PoisonError::into_inner) = Some(RawDb(raw));
+        Ok(())
+    }
+
+    /// Forget the handle once the statement has finished. Must run before the
+    /// connection is returned to the pool.
+    pub fn detach(&self) {
+        *self.handle.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Interrupt the attached connection. Returns `false` when nothing is running.
+    pub fn interrupt(&self) -> bool {
+        let guard = self.handle.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(raw) = guard.as_ref() else {
+            return false;
+        };
+        // SAFETY: the connection stays open while attached; `detach` takes the same lock.
+        unsafe { sqlite3_interrupt(raw.0.as_ptr()) };
+        true
+    }
+}
+
+/// [`execute_sql_with_timeout`] on a dedicated pooled connection exposed via `interrupt`.
+pub async fn execute_sql_interruptible(
+    pool: &SqlitePool,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+    interrupt: &SqliteInterrupt,
+) -> Result<QueryResult> {
+    let mut conn = pool.acquire().await.context("acquire connection")?;
+    interrupt.attach(&mut conn).await?;
+    let result =
+        execute_sql_with_timeout(&mut conn, sql, params, max_rows, timeout, interrupt).await;
+    interrupt.detach();
+    result
+}
+
+/// Whether `err` came from [`SqliteInterrupt::interrupt`].
+pub fn is_interrupted(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<sqlx::Error>()
+            .and_then(|e| e.as_database_error())
+            .is_some_and(|db| {
+                db.code().as_deref() == Some(SQLITE_INTERRUPT) || db.message() == "interrupted"
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tokio::time::sleep;
+
+    use super::*;
+    use crate::stream::execute_sql;
+
+    #[tokio::test]
+    async fn interrupt_stops_long_running_statement() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let interrupt = SqliteInterrupt::new();
+        assert!(!interrupt.interrupt());
+
+        let canceller = interrupt.clone();
+        tokio::spawn(async move {
+            while !canceller.is_attached() {
+                sleep(Duration::from_millis(5)).await;
+            }
+            sleep(Duration::from_millis(50)).await;
+            canceller.interrupt();
+        });
+
+        let sql = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) \
+                   SELECT count(*) FROM c";
+        let err = execute_sql_interruptible(&pool, sql, &[], None, None, &interrupt)
+            .await
+            .unwrap_err();
+        assert!(is_interrupted(&err), "{err:#}");
+        assert!(!interrupt.is_attached());
+
+        // The connection is still usable after the interrupt.
+        let ok = execute_sql(&pool, "SELECT 1", &[], None).await.unwrap();
+        assert_eq!(ok.rows.len(), 1);
+    }
+}
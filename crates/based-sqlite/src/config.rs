@@ -32,6 +32,10 @@ pub struct SqliteConfig {
     pub path: PathBuf,
     #[serde(default)]
     pub read_only: bool,
+    /// Open with `PRAGMA query_only`, so SQLite refuses writes while the file stays
+    /// writable by other tools.
+    #[serde(default)]
+    pub query_only: bool,
     #[serde(default)]
     pub pragma: Option<SqlitePragma>,
 }
@@ -65,6 +69,8 @@ pub fn resolve_sqlite_path(path: &Path, ctx: &SqlitePathContext) -> PathBuf {
 pub struct SqliteOpenOptions<'a> {
     pub path: &'a Path,
     pub read_only: bool,
+    /// `PRAGMA query_only` on every connection.
+    pub query_only: bool,
 }
 
 /// SQLite URI (`file:…`). `read_only` adds `?mode=ro`.
@@ -117,6 +123,9 @@ pub fn sqlite_connect_options(opts: &SqliteOpenOptions<'_>) -> SqliteConnectOpti
     } else if !opts.path.exists() {
         o = o.create_if_missing(true);
     }
+    if opts.query_only {
+        o = o.pragma("query_only", "ON");
+    }
     o
 }
 
@@ -148,6 +157,7 @@ mod tests {
         let _opts = sqlite_connect_options(&SqliteOpenOptions {
             path: &missing,
             read_only: true,
+            query_only: false,
         });
     }
 
@@ -207,6 +217,7 @@ mod tests {
             let pool = sqlx::SqlitePool::connect_with(sqlite_connect_options(&SqliteOpenOptions {
                 path: &path,
                 read_only: false,
+                query_only: false,
             }))
             .await
             .unwrap();
@@ -219,6 +230,7 @@ mod tests {
         let pool = sqlx::SqlitePool::connect_with(sqlite_connect_options(&SqliteOpenOptions {
             path: &path,
             read_only: true,
+            query_only: false,
         }))
         .await
         .unwrap();
@@ -231,4 +243,27 @@ mod tests {
             "expected read-only error, got: {err}"
         );
     }
+
+    #[tokio::test]
+    async fn query_only_rejects_writes_from_any_statement() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("q.db");
+        let pool = sqlx::SqlitePool::connect_with(sqlite_connect_options(&SqliteOpenOptions {
+            path: &path,
+            read_only: false,
+            query_only: true,
+        }))
+        .await
+        .unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT count(*) FROM sqlite_master")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+        let err = sqlx::query("CREATE TABLE t (id INTEGER)")
+            .execute(&pool)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("readonly"), "{err}");
+    }
 }
@@ -0,0 +1,107 @@
+//! Decode SQLite result cells into [`based_core::Value`].
+//!
+//! SQLite is dynamically typed, so the storage class of each value decides the
+//! variant. The declared column type only refines it: `BOOLEAN` integers become
+//! booleans. Dates and JSON stay text — that is how SQLite stores them.
+
+use based_core::Value;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, Row, TypeInfo, ValueRef};
+
+/// Decode every column of `row`.
+pub fn sqlite_row_values(row: &SqliteRow) -> Vec<Value> {
+    (0..row.len()).map(|i| sqlite_value(row, i)).collect()
+}
+
+/// Decode column `index` of `row`.
+pub fn sqlite_value(row: &SqliteRow, index: usize) -> Value {
+    let Ok(raw) = row.try_get_raw(index) else {
+        return Value::Null;
+    };
+    if raw.is_null() {
+        return Value::Null;
+    }
+    let storage = raw.type_info().name().to_string();
+    let declared = row
+        .columns()
+        .get(index)
+        .map(|c| c.type_info().name().to_ascii_uppercase())
+        .unwrap_or_default();
+    decode_storage(row, index, &storage, &declared).unwrap_or(Value::Null)
+}
+
+fn decode_storage(row: &SqliteRow, i: usize, storage: &str, declared: &str) -> Option<Value> {
+    // Storage class already checked, so skip sqlx's declared-type compatibility check.
+    let value = match storage {
+        "INTEGER" | "BOOLEAN" => {
+            let n: i64 = row.try_get_unchecked(i).ok()?;
+            if declared == "BOOLEAN" {
+                Value::Bool(n != 0)
+            } else {
+                Value::Int(n)
+            }
+        }
+        "REAL" => Value::Float(row.try_get_unchecked(i).ok()?),
+        "BLOB" => Value::Bytes(row.try_get_unchecked(i).ok()?),
+        _ => Value::Text(row.try_get_unchecked(i).ok()?),
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn decodes_by_storage_class_and_declared_type() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE t (i INTEGER, r REAL, s TEXT, b BLOB, f BOOLEAN, n TEXT, d DATE)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO t VALUES (7, 1.5, 'hi', x'dead', 1, NULL, '2024-01-02')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let row = sqlx::query("SELECT * FROM t")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            sqlite_row_values(&row),
+            vec![
+                Value::Int(7),
+                Value::Float(1.5),
+                Value::Text("hi".into()),
+                Value::Bytes(vec![0xde, 0xad]),
+                Value::Bool(true),
+                Value::Null,
+                Value::Text("2024-01-02".into()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn expression_columns_use_storage_class() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let row = sqlx::query("SELECT 1 + 1, 'x' || 'y', 2.5 * 2")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            sqlite_row_values(&row),
+            vec![Value::Int(2), Value::Text("xy".into()), Value::Float(5.0)]
+        );
+    }
+}
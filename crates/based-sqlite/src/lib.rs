@@ -1,10 +1,23 @@
-//! SQLite configuration, path resolution, and sqlx execution (no UI).
+//! SQLite configuration, path resolution, streaming sqlx execution, script runs, manual
+//! transactions, interrupts, and query timeouts (no UI).
 
+pub mod cancel;
 pub mod config;
+pub mod decode;
 pub mod mutations;
+pub mod script;
+pub mod stream;
+pub mod timeout;
+pub mod transaction;
 
+pub use cancel::{SqliteInterrupt, execute_sql_interruptible, is_interrupted};
 pub use config::{
     SqliteConfig, SqliteOpenOptions, SqlitePathContext, SqlitePragma, resolve_sqlite_path,
     sqlite_connect_options, sqlite_uri, sqlite3_command,
 };
-pub use mutations::{QueryColumn, delete_row, execute_sql, insert_row, update_row};
+pub use decode::{sqlite_row_values, sqlite_value};
+pub use mutations::{QueryColumn, delete_row, insert_row, update_row};
+pub use script::{BoundStatement, ScriptStep, SqliteScriptSession, execute_script};
+pub use stream::{QueryResult, STREAM_BATCH_ROWS, StreamSummary, execute_sql, stream_sql};
+pub use timeout::execute_sql_with_timeout;
+pub use transaction::SqliteManualTx;
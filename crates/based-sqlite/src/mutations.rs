@@ -1,5 +1,5 @@
 use anyhow::Result;
-use sqlx::{AssertSqlSafe, Column as SqlxColumn, Row, SqlitePool, TypeInfo};
+use sqlx::{AssertSqlSafe, SqlitePool};
 
 #[derive(Debug, Clone)]
 pub struct QueryColumn {
@@ -65,42 +65,3 @@ pub async fn delete_row(pool: &SqlitePool, table: &str, pk_col: &str, pk_val: &s
         .await?;
     Ok(result.rows_affected())
 }
-
-/// Execute arbitrary SQL.
-/// Returns `(column_names, rows, rows_affected)`.
-pub async fn execute_sql(
-    pool: &SqlitePool,
-    sql: &str,
-) -> Result<(Vec<QueryColumn>, Vec<Vec<String>>, u64)> {
-    let trimmed = sql.trim_start().to_ascii_uppercase();
-    if trimmed.starts_with("SELECT")
-        || trimmed.starts_with("WITH")
-        || trimmed.starts_with("EXPLAIN")
-    {
-        let rows = sqlx::query(AssertSqlSafe(sql)).fetch_all(pool).await?;
-        let columns: Vec<QueryColumn> = if let Some(first) = rows.first() {
-            first
-                .columns()
-                .iter()
-                .map(|c| QueryColumn {
-                    name: c.name().to_string(),
-                    type_name: c.type_info().name().to_string(),
-                })
-                .collect()
-        } else {
-            vec![]
-        };
-        let data: Vec<Vec<String>> = rows
-            .iter()
-            .map(|row| {
-                (0..row.len())
-                    .map(|i| row.try_get::<String, _>(i).unwrap_or_default())
-                    .collect()
-            })
-            .collect();
-        Ok((columns, data, 0))
-    } else {
-        let result = sqlx::query(AssertSqlSafe(sql)).execute(pool).await?;
-        Ok((vec![], vec![], result.rows_affected()))
-    }
-}
@@ -0,0 +1,286 @@
+//! Run a script statement by statement on one connection ("Run script").
+//!
+//! Temp tables, pragmas, and an explicit `BEGIN` carry from one statement to the next,
+//! and on to the statements "Continue" runs after a failure: a [`SqliteScriptSession`]
+//! keeps its connection until the script is finished. Each statement gets its own
+//! [`execute_sql_with_timeout`] budget. While a transaction is open, each statement runs
+//! under a savepoint, so a failure rolls back only that statement and the transaction
+//! stays usable.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use based_core::Value;
+use based_query::{Dialect, StatementKind, classify_statement, statements_in_script};
+use libsqlite3_sys::sqlite3_get_autocommit;
+use sqlx::pool::PoolConnection;
+use sqlx::{Executor, Sqlite, SqliteConnection, SqlitePool};
+use tokio::sync::Mutex;
+
+use crate::cancel::SqliteInterrupt;
+use crate::stream::QueryResult;
+use crate::timeout::execute_sql_with_timeout;
+
+/// One statement of a script with the values bound to its `?1`, `?2`, ….
+#[derive(Debug, Clone, Default)]
+pub struct BoundStatement {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+/// Outcome of one statement of [`execute_script`].
+#[derive(Debug)]
+pub struct ScriptStep {
+    pub elapsed: Duration,
+    pub result: Result<QueryResult>,
+}
+
+/// Pooled connection a script runs on; clones share it. It stays checked out between
+/// batches, so "Continue" after a failure sees the session the script built up.
+#[derive(Clone)]
+pub struct SqliteScriptSession {
+    conn: Arc<Mutex<Option<PoolConnection<Sqlite>>>>,
+}
+
+/// Run `statements` in order on a single pooled connection exposed via `interrupt`,
+/// stopping after the first failure, then [`finish`](SqliteScriptSession::finish) the
+/// session.
+///
+/// `Err` means the script never started; statement errors are in the returned steps.
+pub async fn execute_script(
+    pool: &SqlitePool,
+    statements: &[BoundStatement],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+    interrupt: &SqliteInterrupt,
+) -> Result<Vec<ScriptStep>> {
+    let session = SqliteScriptSession::start(pool).await?;
+    let steps = session
+        .execute(statements, max_rows, timeout, interrupt)
+        .await?;
+    session.finish().await?;
+    Ok(steps)
+}
+
+impl SqliteScriptSession {
+    /// Check out a connection from `pool` for a script.
+    pub async fn start(pool: &SqlitePool) -> Result<Self> {
+        let conn = pool.acquire().await.context("acquire connection")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(Some(conn))),
+        })
+    }
+
+    /// Run `statements` in order, stopping after the first failure; see
+    /// [`execute_script`]. The next call continues on the same connection.
+    ///
+    /// A session that stopped on a failure closes its connection when the last clone is
+    /// dropped without [`finish`](Self::finish), rather than return its state to the
+    /// pool.
+    pub async fn execute(
+        &self,
+        statements: &[BoundStatement],
+        max_rows: Option<u64>,
+        timeout: Option<Duration>,
+        interrupt: &SqliteInterrupt,
+    ) -> Result<Vec<ScriptStep>> {
+        let mut guard = self.conn.lock().await;
+        let Some(conn) = guard.as_mut() else {
+            bail!("script has already finished");
+        };
+        let steps = run_steps(conn, statements, max_rows, timeout, interrupt).await?;
+        if steps.len() < statements.len() {
+            conn.close_on_drop();
+        }
+        Ok(steps)
+    }
+
+    /// Roll back a transaction the script left open and return the connection to the
+    /// pool.
+    pub async fn finish(&self) -> Result<()> {
+        let Some(mut conn) = self.conn.lock().await.take() else {
+            return Ok(());
+        };
+        if in_transaction(&mut conn).await? {
+            conn.execute("ROLLBACK")
+                .await
+                .context("roll back the script's transaction")?;
+        }
+        Ok(())
+    }
+}
+
+/// Statement loop of [`execute_script`] on an existing connection.
+pub(crate) async fn run_steps(
+    conn: &mut SqliteConnection,
+    statements: &[BoundStatement],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+    interrupt: &SqliteInterrupt,
+) -> Result<Vec<ScriptStep>> {
+    interrupt.attach(conn).await?;
+    let mut steps = Vec::with_capacity(statements.len());
+    for stmt in statements {
+        let start = Instant::now();
+        let result = run_statement(conn, stmt, max_rows, timeout, interrupt).await;
+        let failed = result.is_err();
+        steps.push(ScriptStep {
+            elapsed: start.elapsed(),
+            result,
+        });
+        if failed {
+            break;
+        }
+    }
+    interrupt.detach();
+    Ok(steps)
+}
+
+/// Run `stmt`, under a savepoint while a transaction is open.
+async fn run_statement(
+    conn: &mut SqliteConnection,
+    stmt: &BoundStatement,
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+    interrupt: &SqliteInterrupt,
+) -> Result<QueryResult> {
+    // `SAVEPOINT`, `RELEASE`, and `ROLLBACK TO` would interleave with ours.
+    let savepoint = !controls_transaction(&stmt.sql) && in_transaction(conn).await?;
+    if savepoint {
+        conn.execute("SAVEPOINT based_script_statement").await?;
+    }
+    let result =
+        execute_sql_with_timeout(conn, &stmt.sql, &stmt.params, max_rows, timeout, interrupt).await;
+    // An interrupted write rolls back the whole transaction, savepoint included.
+    if savepoint && in_transaction(conn).await? {
+        if result.is_err() {
+            conn.execute("ROLLBACK TO based_script_statement").await?;
+        }
+        conn.execute("RELEASE based_script_statement").await?;
+    }
+    result
+}
+
+/// Whether `sql` begins, ends, or marks a transaction (`BEGIN`, `COMMIT`, `SAVEPOINT`, …).
+pub(crate) fn controls_transaction(sql: &str) -> bool {
+    statements_in_script(sql, Dialect::Sqlite)
+        .iter()
+        .any(|stmt| {
+            classify_statement(stmt.text(sql), Dialect::Sqlite).kind == StatementKind::Transaction
+        })
+}
+
+/// Whether a transaction is open on `conn`, explicit or left by a failed statement.
+async fn in_transaction(conn: &mut SqliteConnection) -> Result<bool> {
+    let mut handle = conn.lock_handle().await.context("lock sqlite handle")?;
+    // SAFETY: the handle is locked, so the connection is open and not in use elsewhere.
+    Ok(unsafe { sqlite3_get_autocommit(handle.as_raw_handle().as_ptr()) } == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::stream::execute_sql;
+
+    fn script(stmts: &[&str]) -> Vec<BoundStatement> {
+        stmts
+            .iter()
+            .map(|s| BoundStatement {
+                sql: s.to_string(),
+                params: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn runs_on_one_connection_and_stops_at_first_error() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(2)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let stmts = script(&[
+            "CREATE TEMP TABLE t (id INTEGER PRIMARY KEY)",
+            "INSERT INTO t VALUES (1), (2)",
+            "SELECT id FROM t ORDER BY id",
+            "SELECT nope FROM t",
+            "DROP TABLE t",
+        ]);
+        let steps = execute_script(&pool, &stmts, None, None, &SqliteInterrupt::new())
+            .await
+            .unwrap();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[1].result.as_ref().unwrap().rows_affected, 2);
+        assert_eq!(steps[2].result.as_ref().unwrap().rows.len(), 2);
+        assert!(steps[3].result.is_err());
+    }
+
+    #[tokio::test]
+    async fn open_transaction_is_rolled_back() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        execute_sql(&pool, "CREATE TABLE t (id INTEGER)", &[], None)
+            .await
+            .unwrap();
+        let stmts = script(&["BEGIN", "INSERT INTO t VALUES (1)"]);
+        let steps = execute_script(&pool, &stmts, None, None, &SqliteInterrupt::new())
+            .await
+            .unwrap();
+        assert!(steps.iter().all(|s| s.result.is_ok()));
+
+        let left = execute_sql(&pool, "SELECT count(*) FROM t", &[], None)
+            .await
+            .unwrap();
+        assert_eq!(left.rows[0][0], Value::Int(0));
+    }
+
+    #[tokio::test]
+    async fn continue_keeps_the_session_and_its_transaction() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(2)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let interrupt = SqliteInterrupt::new();
+        let session = SqliteScriptSession::start(&pool).await.unwrap();
+        let steps = session
+            .execute(
+                &script(&[
+                    "CREATE TEMP TABLE t (id INTEGER PRIMARY KEY)",
+                    "BEGIN",
+                    "INSERT INTO t VALUES (1)",
+                    "INSERT INTO t VALUES (1)",
+                ]),
+                None,
+                None,
+                &interrupt,
+            )
+            .await
+            .unwrap();
+        assert_eq!(steps.len(), 4);
+        assert!(steps[3].result.is_err());
+
+        let steps = session
+            .execute(
+                &script(&["INSERT INTO t VALUES (2)", "COMMIT", "SELECT id FROM t"]),
+                None,
+                None,
+                &interrupt,
+            )
+            .await
+            .unwrap();
+        assert!(steps.iter().all(|s| s.result.is_ok()));
+        assert_eq!(
+            steps[2].result.as_ref().unwrap().rows,
+            vec![vec![Value::Int(1)], vec![Value::Int(2)]]
+        );
+        session.finish().await.unwrap();
+        assert!(session.execute(&[], None, None, &interrupt).await.is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! Streaming query execution with a row cap.
+//!
+//! Rows are pulled through a sqlx `fetch_many` stream and handed out in batches, so a
+//! `SELECT *` on a large table never materialises more than the cap in memory.
+
+use std::mem;
+
+use anyhow::Result;
+use based_core::Value;
+use futures::TryStreamExt;
+use sqlx::query::Query;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::{AssertSqlSafe, Column as SqlxColumn, Either, Executor, Row, Sqlite, TypeInfo};
+
+use crate::decode::sqlite_row_values;
+use crate::mutations::QueryColumn;
+
+/// Rows per batch handed to the [`stream_sql`] callback.
+pub const STREAM_BATCH_ROWS: usize = 500;
+
+/// Outcome of [`stream_sql`]; rows themselves went to the batch callback.
+#[derive(Debug, Clone, Default)]
+pub struct StreamSummary {
+    pub columns: Vec<QueryColumn>,
+    pub row_count: u64,
+    /// Rows changed by a statement without a result set.
+    pub rows_affected: u64,
+    /// `Some(cap)` when the stream was stopped at the cap with rows remaining.
+    pub truncated_at: Option<u64>,
+}
+
+/// Collected result of [`execute_sql`].
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    pub columns: Vec<QueryColumn>,
+    pub rows: Vec<Vec<Value>>,
+    /// Rows changed by a statement without a result set.
+    pub rows_affected: u64,
+    /// `Some(cap)` when more rows were available than were fetched.
+    pub truncated_at: Option<u64>,
+}
+
+impl QueryResult {
+    /// `"truncated at N rows"` when the row cap stopped the fetch.
+    pub fn truncation_notice(&self) -> Option<String> {
+        self.truncated_at.map(|n| format!("truncated at {n} rows"))
+    }
+}
+
+/// Run `sql` with `params` bound to `?1`, `?2`, … and stream result rows to `on_batch`
+/// in chunks of [`STREAM_BATCH_ROWS`]. Stops after `max_rows` rows (`None` = unbounded).
+pub async fn stream_sql<'c, E, F>(
+    executor: E,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    mut on_batch: F,
+) -> Result<StreamSummary>
+where
+    E: Executor<'c, Database = Sqlite>,
+    F: FnMut(&[QueryColumn], Vec<Vec<Value>>),
+{
+    let query = params
+        .iter()
+        .fold(sqlx::query(AssertSqlSafe(sql)), bind_value);
+    let mut stream = executor.fetch_many(query);
+    let mut summary = StreamSummary::default();
+    let mut batch = Vec::with_capacity(STREAM_BATCH_ROWS);
+    while let Some(step) = stream.try_next().await? {
+        let row = match step {
+            Either::Left(done) => {
+                if summary.columns.is_empty() {
+                    summary.rows_affected += done.rows_affected();
+                }
+                continue;
+            }
+            Either::Right(row) => row,
+        };
+        if max_rows.is_some_and(|cap| summary.row_count >= cap) {
+            summary.truncated_at = max_rows;
+            break;
+        }
+        if summary.columns.is_empty() {
+            summary.columns = row_columns(&row);
+        }
+        batch.push(sqlite_row_values(&row));
+        summary.row_count += 1;
+        if batch.len() == STREAM_BATCH_ROWS {
+            on_batch(&summary.columns, mem::take(&mut batch));
+        }
+    }
+    if !batch.is_empty() {
+        on_batch(&summary.columns, batch);
+    }
+    Ok(summary)
+}
+
+/// Run `sql` with `params` and collect up to `max_rows` rows.
+pub async fn execute_sql<'c, E>(
+    executor: E,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+) -> Result<QueryResult>
+where
+    E: Executor<'c, Database = Sqlite>,
+{
+    let mut rows = Vec::new();
+    let summary = stream_sql(executor, sql, params, max_rows, |_, batch| {
+        rows.extend(batch)
+    })
+    .await?;
+    Ok(QueryResult {
+        columns: summary.columns,
+        rows,
+        rows_affected: summary.rows_affected,
+        truncated_at: summary.truncated_at,
+    })
+}
+
+/// Bind one parameter with its SQLite storage class; temporal values bind as ISO text
+/// and kinds without a mapping (decimal, JSON, UUID, arrays) as their display text.
+fn bind_value<'q>(
+    query: Query<'q, Sqlite, SqliteArguments>,
+    value: &Value,
+) -> Query<'q, Sqlite, SqliteArguments> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Int(n) => query.bind(*n),
+        Value::Float(f) => query.bind(*f),
+        Value::Text(s) => query.bind(s.clone()),
+        Value::Bytes(b) => query.bind(b.clone()),
+        Value::Date(d) => query.bind(*d),
+        Value::Time(t) => query.bind(*t),
+        Value::Timestamp(ts) => query.bind(*ts),
+        Value::TimestampTz(ts) => query.bind(*ts),
+        Value::Decimal(_) | Value::Json(_) | Value::Uuid(_) | Value::Array(_) => {
+            query.bind(value.to_string())
+        }
+    }
+}
+
+fn row_columns(row: &SqliteRow) -> Vec<QueryColumn> {
+    row.columns()
+        .iter()
+        .map(|c| QueryColumn {
+            name: c.name().to_string(),
+            type_name: c.type_info().name().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool_with_rows(n: usize) -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(AssertSqlSafe(format!(
+            "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < {n}) \
+             INSERT INTO t SELECT x FROM c"
+        )))
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn stops_at_cap_and_reports_truncation() {
+        let pool = pool_with_rows(1200).await;
+        let result = execute_sql(&pool, "SELECT id FROM t ORDER BY id", &[], Some(1000))
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 1000);
+        assert_eq!(result.truncated_at, Some(1000));
+        assert_eq!(
+            result.truncation_notice().as_deref(),
+            Some("truncated at 1000 rows")
+        );
+        assert_eq!(result.columns[0].name, "id");
+    }
+
+    #[tokio::test]
+    async fn exact_cap_is_not_truncated() {
+        let pool = pool_with_rows(10).await;
+        let result = execute_sql(&pool, "SELECT id FROM t", &[], Some(10))
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 10);
+        assert!(result.truncated_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn streams_in_batches() {
+        let pool = pool_with_rows(STREAM_BATCH_ROWS * 2 + 3).await;
+        let mut sizes = Vec::new();
+        let summary = stream_sql(&pool, "SELECT id FROM t", &[], None, |_, batch| {
+            sizes.push(batch.len())
+        })
+        .await
+        .unwrap();
+        assert_eq!(sizes, vec![STREAM_BATCH_ROWS, STREAM_BATCH_ROWS, 3]);
+        assert_eq!(summary.row_count, (STREAM_BATCH_ROWS * 2 + 3) as u64);
+    }
+
+    #[tokio::test]
+    async fn binds_params_by_index() {
+        let pool = pool_with_rows(5).await;
+        let params = [Value::Int(2), Value::Text("it's".into())];
+        let result = execute_sql(&pool, "SELECT id, ?2 FROM t WHERE id > ?1", &params, None)
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 3);
+        assert_eq!(result.rows[0][1], Value::Text("it's".into()));
+    }
+
+    #[tokio::test]
+    async fn statements_report_rows_affected() {
+        let pool = pool_with_rows(5).await;
+        let result = execute_sql(&pool, "DELETE FROM t WHERE id > 2", &[], Some(1))
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected, 3);
+        assert!(result.rows.is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+//! Query timeout for SQLite: a busy handler for lock waits plus an interrupt timer.
+//!
+//! `PRAGMA busy_timeout` bounds how long a statement waits on another writer's lock;
+//! the timer calls `sqlite3_interrupt` once the budget is spent so long-running reads
+//! stop too. Both are scoped to one run and the previous busy timeout is restored.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::Result;
+use based_core::{QueryTimedOut, Value};
+use sqlx::{AssertSqlSafe, SqliteConnection};
+use tokio::time::sleep;
+
+use crate::cancel::{SqliteInterrupt, is_interrupted};
+use crate::stream::{QueryResult, execute_sql};
+
+/// [`execute_sql`] on `conn` bounded by `timeout` (`None` = unbounded). `interrupt`
+/// must already be attached to `conn`. A timed-out statement fails with [`QueryTimedOut`].
+pub async fn execute_sql_with_timeout(
+    conn: &mut SqliteConnection,
+    sql: &str,
+    params: &[Value],
+    max_rows: Option<u64>,
+    timeout: Option<Duration>,
+    interrupt: &SqliteInterrupt,
+) -> Result<QueryResult> {
+    let Some(timeout) = timeout else {
+        return execute_sql(conn, sql, params, max_rows).await;
+    };
+    let previous_busy: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+        .fetch_one(&mut *conn)
+        .await?;
+    set_busy_timeout(conn, busy_timeout_ms(timeout)).await?;
+
+    let fired = Arc::new(AtomicBool::new(false));
+    let timer = tokio::spawn({
+        let fired = fired.clone();
+        let interrupt = interrupt.clone();
+        async move {
+            sleep(timeout).await;
+            fired.store(true, Ordering::Relaxed);
+            interrupt.interrupt();
+        }
+    });
+    let result = execute_sql(&mut *conn, sql, params, max_rows).await;
+    timer.abort();
+    set_busy_timeout(conn, previous_busy).await?;
+
+    result.map_err(|e| {
+        if fired.load(Ordering::Relaxed) && is_interrupted(&e) {
+            anyhow::Error::new(QueryTimedOut { after: timeout })
+        } else {
+            e
+        }
+    })
+}
+
+fn busy_timeout_ms(timeout: Duration) -> i64 {
+    i64::try_from(timeout.as_millis())
+        .unwrap_or(i64::MAX)
+        .max(1)
+}
+
+async fn set_busy_timeout(conn: &mut SqliteConnection, ms: i64) -> Result<()> {
+    sqlx::query(AssertSqlSafe(format!("PRAGMA busy_timeout = {ms}")))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Connection;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn long_statement_times_out_and_restores_busy_timeout() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        set_busy_timeout(&mut conn, 1234).await.unwrap();
+        let interrupt = SqliteInterrupt::new();
+        interrupt.attach(&mut conn).await.unwrap();
+
+        let sql = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) \
+                   SELECT count(*) FROM c";
+        let err = execute_sql_with_timeout(
+            &mut conn,
+            sql,
+            &[],
+            None,
+            Some(Duration::from_millis(100)),
+            &interrupt,
+        )
+        .await
+        .unwrap_err();
+        interrupt.detach();
+        assert_eq!(
+            err.downcast_ref::<QueryTimedOut>(),
+            Some(&QueryTimedOut {
+                after: Duration::from_millis(100)
+            })
+        );
+
+        let busy: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(busy, 1234);
+    }
+
+    #[tokio::test]
+    async fn fast_statement_is_unaffected() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        let interrupt = SqliteInterrupt::new();
+        let result = execute_sql_with_timeout(
+            &mut conn,
+            "SELECT 1",
+            &[],
+            None,
+            Some(Duration::from_secs(5)),
+            &interrupt,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.rows.len(), 1);
+    }
+}
@@ -0,0 +1,227 @@
+//! Manual transaction mode: one pooled connection pinned to an editor tab.
+//!
+//! SQL that would begin or end the transaction itself (`COMMIT`, `ROLLBACK`, `END`, …) is
+//! refused, so the tab never shows a transaction SQLite has already closed; savepoints
+//! stay usable.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use based_core::Value;
+use based_query::{Dialect, TokenKind, statements_in_script, tokenize};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use tokio::sync::Mutex;
+
+use crate::cancel::SqliteInterrupt;
+use crate::script::{BoundStatement, ScriptStep, run_steps};
+use crate::stream::QueryResult;
+use crate::timeout::execute_sql_with_timeout;
+
+/// Open transaction on a pinned connection; clones share it. Dropping the last clone
+/// without [`commit`](Self::commit) rolls back.
+#[derive(Clone)]
+pub struct SqliteManualTx {
+    tx: Arc<Mutex<Option<Transaction<'static, Sqlite>>>>,
+}
+
+impl SqliteManualTx {
+    /// Pin a connection from `pool` and issue `BEGIN`.
+    pub async fn begin(pool: &SqlitePool) -> Result<Self> {
+        let tx = pool.begin().await.context("begin transaction")?;
+        Ok(Self {
+            tx: Arc::new(Mutex::new(Some(tx))),
+        })
+    }
+
+    /// Run `sql` inside the transaction; see [`crate::execute_sql_interruptible`].
+    pub async fn execute(
+        &self,
+        sql: &str,
+        params: &[Value],
+        max_rows: Option<u64>,
+        timeout: Option<Duration>,
+        interrupt: &SqliteInterrupt,
+    ) -> Result<QueryResult> {
+        let mut guard = self.tx.lock().await;
+        let Some(tx) = guard.as_mut() else {
+            bail!("transaction is already closed");
+        };
+        ensure_keeps_transaction(sql)?;
+        interrupt.attach(tx).await?;
+        let result = execute_sql_with_timeout(tx, sql, params, max_rows, timeout, interrupt).await;
+        interrupt.detach();
+        result
+    }
+
+    /// Run `statements` in order inside the transaction, stopping after the first
+    /// failure; see [`crate::execute_script`]. A script with transaction control is
+    /// refused before any of it runs.
+    pub async fn execute_script(
+        &self,
+        statements: &[BoundStatement],
+        max_rows: Option<u64>,
+        timeout: Option<Duration>,
+        interrupt: &SqliteInterrupt,
+    ) -> Result<Vec<ScriptStep>> {
+        let mut guard = self.tx.lock().await;
+        let Some(tx) = guard.as_mut() else {
+            bail!("transaction is already closed");
+        };
+        for stmt in statements {
+            ensure_keeps_transaction(&stmt.sql)?;
+        }
+        run_steps(tx, statements, max_rows, timeout, interrupt).await
+    }
+
+    pub async fn commit(&self) -> Result<()> {
+        let tx = self.take().await?;
+        tx.commit().await.context("commit transaction")
+    }
+
+    pub async fn rollback(&self) -> Result<()> {
+        let tx = self.take().await?;
+        tx.rollback().await.context("roll back transaction")
+    }
+
+    async fn take(&self) -> Result<Transaction<'static, Sqlite>> {
+        match self.tx.lock().await.take() {
+            Some(tx) => Ok(tx),
+            None => bail!("transaction is already closed"),
+        }
+    }
+}
+
+/// Refuse `sql` when it would begin or end the pinned transaction behind the tab's back.
+fn ensure_keeps_transaction(sql: &str) -> Result<()> {
+    for stmt in statements_in_script(sql, Dialect::Sqlite) {
+        let text = stmt.text(sql);
+        let words: Vec<String> = tokenize(text, Dialect::Sqlite)
+            .iter()
+            .filter(|t| t.kind == TokenKind::Word)
+            .map(|t| t.text(text).to_ascii_uppercase())
+            .collect();
+        let controls = match words.first().map(String::as_str) {
+            Some("BEGIN" | "COMMIT" | "END") => true,
+            Some("ROLLBACK") => !words.iter().any(|w| w == "TO"),
+            _ => false,
+        };
+        if controls {
+            bail!(
+                "`{}` is not allowed in a manual transaction; end it with Commit or Rollback",
+                text.trim()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::stream::execute_sql;
+
+    async fn pool_with_table() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        execute_sql(&pool, "CREATE TABLE t (id INTEGER)", &[], None)
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn count(pool: &SqlitePool) -> Value {
+        let result = execute_sql(pool, "SELECT count(*) FROM t", &[], None)
+            .await
+            .unwrap();
+        result.rows[0][0].clone()
+    }
+
+    #[tokio::test]
+    async fn rollback_discards_and_commit_keeps() {
+        let pool = pool_with_table().await;
+        let interrupt = SqliteInterrupt::new();
+
+        let tx = SqliteManualTx::begin(&pool).await.unwrap();
+        tx.execute(
+            "INSERT INTO t VALUES (?1)",
+            &[Value::Int(1)],
+            None,
+            None,
+            &interrupt,
+        )
+        .await
+        .unwrap();
+        tx.rollback().await.unwrap();
+        assert_eq!(count(&pool).await, Value::Int(0));
+        assert!(tx.commit().await.is_err());
+
+        let tx = SqliteManualTx::begin(&pool).await.unwrap();
+        let steps = tx
+            .execute_script(
+                &[
+                    BoundStatement {
+                        sql: "INSERT INTO t VALUES (1)".into(),
+                        params: Vec::new(),
+                    },
+                    BoundStatement {
+                        sql: "INSERT INTO t VALUES (?1)".into(),
+                        params: vec![Value::Int(2)],
+                    },
+                ],
+                None,
+                None,
+                &interrupt,
+            )
+            .await
+            .unwrap();
+        assert_eq!(steps.len(), 2);
+        tx.commit().await.unwrap();
+        assert_eq!(count(&pool).await, Value::Int(2));
+    }
+
+    #[tokio::test]
+    async fn transaction_control_is_refused() {
+        let pool = pool_with_table().await;
+        let interrupt = SqliteInterrupt::new();
+        let tx = SqliteManualTx::begin(&pool).await.unwrap();
+        tx.execute("INSERT INTO t VALUES (1)", &[], None, None, &interrupt)
+            .await
+            .unwrap();
+        for sql in ["COMMIT", "end transaction", "ROLLBACK", "SELECT 1; BEGIN"] {
+            let err = tx
+                .execute(sql, &[], None, None, &interrupt)
+                .await
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("Commit or Rollback"),
+                "{sql}: {err}"
+            );
+        }
+        let script =
+            ["SAVEPOINT a", "INSERT INTO t VALUES (2)", "COMMIT"].map(|sql| BoundStatement {
+                sql: sql.into(),
+                params: Vec::new(),
+            });
+        assert!(
+            tx.execute_script(&script, None, None, &interrupt)
+                .await
+                .is_err()
+        );
+        for sql in [
+            "SAVEPOINT a",
+            "INSERT INTO t VALUES (2)",
+            "ROLLBACK TO a",
+            "RELEASE a",
+        ] {
+            tx.execute(sql, &[], None, None, &interrupt).await.unwrap();
+        }
+        tx.rollback().await.unwrap();
+        assert_eq!(count(&pool).await, Value::Int(0));
+    }
+}
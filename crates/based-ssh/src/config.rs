@@ -0,0 +1,560 @@
+//! Resolve tunnel settings through the OpenSSH client config (`~/.ssh/config`).
+//!
+//! Only what a tunnel needs is read: `Host` blocks (wildcards and `!` negation),
+//! `Include`, `HostName`, `User`, `Port`, `IdentityFile`, `ProxyJump`, and the
+//! `ServerAlive*` keepalive settings. As in `ssh`,
+//! the first value of a keyword wins and `IdentityFile`s accumulate. `Match` blocks
+//! other than `Match all` are skipped.
+
+use std::env;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, bail};
+use based_core::{SshJumpHost, SshTunnelConfig};
+
+use crate::path::expand_tilde;
+
+const MAX_INCLUDE_DEPTH: usize = 16;
+const MAX_JUMP_DEPTH: usize = 8;
+
+/// What the client config says about one host name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SshConfigHost {
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_files: Vec<String>,
+    pub proxy_jump: Option<String>,
+    pub server_alive_interval: Option<u64>,
+    pub server_alive_count_max: Option<u32>,
+}
+
+impl SshConfigHost {
+    fn jump_spec(&self) -> Option<&str> {
+        self.proxy_jump
+            .as_deref()
+            .filter(|spec| !spec.eq_ignore_ascii_case("none"))
+    }
+}
+
+/// Settings for `alias` from `~/.ssh/config`, then (on Unix) `/etc/ssh/ssh_config`.
+pub fn lookup_ssh_config(alias: &str) -> Result<SshConfigHost> {
+    let mut files = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        let dir = home.join(".ssh");
+        files.push((dir.join("config"), dir));
+    }
+    #[cfg(unix)]
+    files.push((
+        PathBuf::from("/etc/ssh/ssh_config"),
+        PathBuf::from("/etc/ssh"),
+    ));
+    let mut host = SshConfigHost::default();
+    for (file, include_root) in files {
+        if file.is_file() {
+            read_config(&file, &include_root, alias, &mut host, 0)?;
+        }
+    }
+    Ok(host)
+}
+
+/// Settings for `alias` from one config file. Relative `Include`s resolve against the
+/// file's folder.
+pub fn parse_ssh_config(path: &Path, alias: &str) -> Result<SshConfigHost> {
+    let include_root = path.parent().unwrap_or(Path::new("."));
+    let mut host = SshConfigHost::default();
+    read_config(path, include_root, alias, &mut host, 0)?;
+    Ok(host)
+}
+
+/// Fill `ssh` from the client config. With `alias`, the alias's `HostName`, `User`,
+/// `Port`, first existing `IdentityFile`, `ProxyJump`, and keepalive settings apply where
+/// `ssh` leaves them unset (port 22 counts as unset). Jump hosts are looked up by name too, and their own
+/// `ProxyJump`s come first, as with `ssh -J`.
+pub fn resolve_ssh_config(ssh: &SshTunnelConfig) -> Result<SshTunnelConfig> {
+    resolve_with(ssh, &lookup_ssh_config)
+}
+
+type Lookup<'a> = &'a dyn Fn(&str) -> Result<SshConfigHost>;
+
+fn resolve_with(ssh: &SshTunnelConfig, lookup: Lookup<'_>) -> Result<SshTunnelConfig> {
+    let mut out = ssh.clone();
+    let mut jumps = ssh.jump_hosts.clone();
+    if let Some(alias) = ssh.alias() {
+        let config =
+            lookup(alias).with_context(|| format!("SSH config: could not read Host {alias}"))?;
+        let endpoint = fill(
+            alias,
+            Endpoint {
+                host: ssh.host.clone(),
+                port: ssh.port,
+                user: ssh.user.clone(),
+                key_path: ssh.key_path.clone(),
+            },
+            &config,
+        );
+        out.host = endpoint.host;
+        out.port = endpoint.port;
+        out.user = endpoint.user;
+        out.key_path = endpoint.key_path;
+        out.keepalive_interval = ssh.keepalive_interval.or(config.server_alive_interval);
+        out.keepalive_count_max = ssh.keepalive_count_max.or(config.server_alive_count_max);
+        if jumps.is_empty()
+            && let Some(spec) = config.jump_spec()
+        {
+            jumps = SshJumpHost::parse_list(spec, "")
+                .with_context(|| format!("SSH config: ProxyJump of Host {alias}"))?;
+        }
+    }
+    if !jumps.is_empty() {
+        out.jump_hosts = expand_jumps(jumps, lookup, 0)?;
+    }
+    Ok(out)
+}
+
+fn expand_jumps(
+    hops: Vec<SshJumpHost>,
+    lookup: Lookup<'_>,
+    depth: usize,
+) -> Result<Vec<SshJumpHost>> {
+    if depth > MAX_JUMP_DEPTH {
+        bail!("SSH config: ProxyJump chain is nested too deeply (loop?)");
+    }
+    let mut out = Vec::with_capacity(hops.len());
+    for hop in hops {
+        let config = lookup(&hop.host)
+            .with_context(|| format!("SSH config: could not read Host {}", hop.host))?;
+        if let Some(spec) = config.jump_spec() {
+            let before = SshJumpHost::parse_list(spec, "")
+                .with_context(|| format!("SSH config: ProxyJump of Host {}", hop.host))?;
+            out.extend(expand_jumps(before, lookup, depth + 1)?);
+        }
+        let endpoint = fill(
+            &hop.host,
+            Endpoint {
+                host: String::new(),
+                port: hop.port,
+                user: hop.user,
+                key_path: hop.key_path,
+            },
+            &config,
+        );
+        out.push(SshJumpHost {
+            host: endpoint.host,
+            port: endpoint.port,
+            user: endpoint.user,
+            key_path: endpoint.key_path,
+            key_passphrase: hop.key_passphrase,
+        });
+    }
+    Ok(out)
+}
+
+struct Endpoint {
+    host: String,
+    port: u16,
+    user: String,
+    key_path: Option<String>,
+}
+
+/// `explicit` with its blank fields taken from `config`, looked up as `name`.
+fn fill(name: &str, explicit: Endpoint, config: &SshConfigHost) -> Endpoint {
+    let host = if explicit.host.trim().is_empty() {
+        config
+            .host_name
+            .as_deref()
+            .map(|h| h.replace("%h", name))
+            .unwrap_or_else(|| name.to_string())
+    } else {
+        explicit.host
+    };
+    let user = Some(explicit.user)
+        .filter(|u| !u.trim().is_empty())
+        .or_else(|| config.user.clone())
+        .unwrap_or_else(local_user);
+    let port = match explicit.port {
+        22 => config.port.unwrap_or(22),
+        port => port,
+    };
+    let key_path = explicit
+        .key_path
+        .filter(|p| !p.trim().is_empty())
+        .or_else(|| {
+            config
+                .identity_files
+                .iter()
+                .map(|file| expand_tokens(file, &host, port, &user))
+                .find(|file| expand_tilde(file).is_file())
+        });
+    Endpoint {
+        host,
+        port,
+        user,
+        key_path,
+    }
+}
+
+fn local_user() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+/// Expand `%h`, `%p`, `%r`, `%u`, `%d`, and `%%` in an `IdentityFile`.
+fn expand_tokens(value: &str, host: &str, port: u16, user: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => out.push_str(host),
+            Some('p') => out.push_str(&port.to_string()),
+            Some('r') => out.push_str(user),
+            Some('u') => out.push_str(&local_user()),
+            Some('d') => out.push_str(
+                &dirs::home_dir()
+                    .map(|home| home.display().to_string())
+                    .unwrap_or_default(),
+            ),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+fn read_config(
+    path: &Path,
+    include_root: &Path,
+    alias: &str,
+    out: &mut SshConfigHost,
+    depth: usize,
+) -> Result<()> {
+    if depth > MAX_INCLUDE_DEPTH {
+        bail!("{}: Include is nested too deeply", path.display());
+    }
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let mut active = true;
+    for (index, line) in raw.lines().enumerate() {
+        let Some((keyword, args)) = split_line(line) else {
+            continue;
+        };
+        let first = args.first().cloned();
+        match keyword.to_ascii_lowercase().as_str() {
+            "host" => active = host_matches(&args, alias),
+            "match" => active = args.len() == 1 && args[0].eq_ignore_ascii_case("all"),
+            _ if !active => {}
+            "include" => {
+                for pattern in &args {
+                    for file in include_files(pattern, include_root)? {
+                        read_config(&file, include_root, alias, out, depth + 1)?;
+                    }
+                }
+            }
+            "hostname" => set_once(&mut out.host_name, first),
+            "user" => set_once(&mut out.user, first),
+            "port" => parse_once(&mut out.port, first, "Port", path, index)?,
+            "serveraliveinterval" => parse_once(
+                &mut out.server_alive_interval,
+                first,
+                "ServerAliveInterval",
+                path,
+                index,
+            )?,
+            "serveralivecountmax" => parse_once(
+                &mut out.server_alive_count_max,
+                first,
+                "ServerAliveCountMax",
+                path,
+                index,
+            )?,
+            "identityfile" => out.identity_files.extend(first),
+            "proxyjump" => set_once(&mut out.proxy_jump, first),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn set_once(slot: &mut Option<String>, value: Option<String>) {
+    if slot.is_none() {
+        *slot = value;
+    }
+}
+
+/// [`set_once`] for a number, naming the file and line when it does not parse.
+fn parse_once<T: FromStr>(
+    slot: &mut Option<T>,
+    value: Option<String>,
+    keyword: &str,
+    path: &Path,
+    index: usize,
+) -> Result<()> {
+    if slot.is_none()
+        && let Some(value) = value
+    {
+        let parsed = value.parse().map_err(|_| {
+            anyhow!(
+                "{}:{}: invalid {keyword} {value:?}",
+                path.display(),
+                index + 1
+            )
+        })?;
+        *slot = Some(parsed);
+    }
+    Ok(())
+}
+
+/// Keyword and arguments of a config line; `Keyword=value` and quoted arguments are
+/// accepted. `None` for blank lines and comments.
+fn split_line(line: &str) -> Option<(&str, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let (keyword, rest) = line.split_at(end);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in rest.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    args.push(mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        args.push(current);
+    }
+    Some((keyword, args))
+}
+
+/// A `Host` line matches when any pattern matches and no `!pattern` does.
+fn host_matches(patterns: &[String], alias: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if wildcard_match(negated, alias) {
+                return false;
+            }
+        } else if wildcard_match(pattern, alias) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// `*` and `?` glob match, ignoring ASCII case.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_ascii_lowercase().chars().collect();
+    let text: Vec<char> = text.to_ascii_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Files an `Include` argument names: `~` expands, relative paths start at
+/// `include_root`, and wildcards in the file name match sorted directory entries.
+fn include_files(pattern: &str, include_root: &Path) -> Result<Vec<PathBuf>> {
+    let path = expand_tilde(pattern);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        include_root.join(path)
+    };
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return Ok(if path.is_file() { vec![path] } else { vec![] });
+    }
+    let Some(dir) = path.parent().filter(|d| d.is_dir()) else {
+        return Ok(vec![]);
+    };
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .is_some_and(|n| wildcard_match(&name, &n.to_string_lossy()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_patterns_includes_and_first_value_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let conf_d = dir.path().join("conf.d");
+        fs::create_dir_all(&conf_d).unwrap();
+        fs::write(
+            conf_d.join("10-legacy.conf"),
+            "Host prod-legacy\n  User deploy\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("config"),
+            "\
+# team settings
+Include conf.d/*.conf
+
+Host prod-bastion
+    HostName 10.0.0.5
+    Port=2222
+    ServerAliveInterval 30
+    IdentityFile \"~/.ssh/prod key\"
+
+Host prod-* !prod-legacy
+    User ops
+    IdentityFile ~/.ssh/id_%h
+    ProxyJump edge
+
+Host *
+    User nobody
+    Port 22
+",
+        )
+        .unwrap();
+        let host = parse_ssh_config(&dir.path().join("config"), "prod-bastion").unwrap();
+        assert_eq!(host.host_name.as_deref(), Some("10.0.0.5"));
+        assert_eq!(host.port, Some(2222));
+        assert_eq!(host.user.as_deref(), Some("ops"));
+        assert_eq!(host.identity_files, ["~/.ssh/prod key", "~/.ssh/id_%h"]);
+        assert_eq!(host.proxy_jump.as_deref(), Some("edge"));
+        assert_eq!(host.server_alive_interval, Some(30));
+        assert_eq!(host.server_alive_count_max, None);
+
+        let legacy = parse_ssh_config(&dir.path().join("config"), "prod-legacy").unwrap();
+        assert_eq!(legacy.user.as_deref(), Some("deploy"));
+        assert_eq!(legacy.port, Some(22));
+        assert!(legacy.proxy_jump.is_none());
+    }
+
+    #[test]
+    fn resolve_fills_unset_fields_and_expands_proxy_jump() {
+        let lookup = |name: &str| -> Result<SshConfigHost> {
+            Ok(match name {
+                "prod-bastion" => SshConfigHost {
+                    host_name: Some("10.0.0.5".into()),
+                    user: Some("ops".into()),
+                    port: Some(2222),
+                    proxy_jump: Some("edge".into()),
+                    server_alive_interval: Some(30),
+                    ..SshConfigHost::default()
+                },
+                "edge" => SshConfigHost {
+                    host_name: Some("edge.example.com".into()),
+                    user: Some("jump".into()),
+                    proxy_jump: Some("gw@outer:2200".into()),
+                    ..SshConfigHost::default()
+                },
+                _ => SshConfigHost::default(),
+            })
+        };
+        let ssh = SshTunnelConfig {
+            host: String::new(),
+            port: 22,
+            user: String::new(),
+            key_path: None,
+            key_passphrase: None,
+            alias: Some("prod-bastion".into()),
+            keepalive_interval: None,
+            keepalive_count_max: None,
+            jump_hosts: vec![],
+        };
+        let resolved = resolve_with(&ssh, &lookup).unwrap();
+        assert_eq!(
+            (
+                resolved.host.as_str(),
+                resolved.port,
+                resolved.user.as_str()
+            ),
+            ("10.0.0.5", 2222, "ops")
+        );
+        assert_eq!(resolved.keepalive_interval, Some(30));
+        let hops: Vec<_> = resolved
+            .jump_hosts
+            .iter()
+            .map(SshJumpHost::display_name)
+            .collect();
+        assert_eq!(hops, ["gw@outer:2200", "jump@edge.example.com:22"]);
+
+        let explicit = SshTunnelConfig {
+            user: "me".into(),
+            ..ssh
+        };
+        assert_eq!(resolve_with(&explicit, &lookup).unwrap().user, "me");
+    }
+
+    #[test]
+    fn proxy_jump_loops_are_an_error() {
+        let lookup = |_: &str| -> Result<SshConfigHost> {
+            Ok(SshConfigHost {
+                proxy_jump: Some("loop".into()),
+                ..SshConfigHost::default()
+            })
+        };
+        let ssh = SshTunnelConfig {
+            host: String::new(),
+            port: 22,
+            user: "me".into(),
+            key_path: None,
+            key_passphrase: None,
+            alias: Some("loop".into()),
+            keepalive_interval: None,
+            keepalive_count_max: None,
+            jump_hosts: vec![],
+        };
+        let err = resolve_with(&ssh, &lookup).unwrap_err();
+        assert!(err.to_string().contains("nested too deeply"), "{err}");
+    }
+}
@@ -0,0 +1,136 @@
+//! Host key verification against known_hosts files, with trust-on-first-use.
+
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use russh::keys::known_hosts::{
+    check_known_hosts, check_known_hosts_path, learn_known_hosts, learn_known_hosts_path,
+};
+use russh::keys::{Error as KeyError, HashAlg, PublicKey};
+
+/// A server key no known_hosts file lists, as shown to the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownHostKey {
+    pub host: String,
+    pub port: u16,
+    /// Key type, e.g. `ssh-ed25519`.
+    pub algorithm: String,
+    /// `SHA256:…`, as `ssh` prints it.
+    pub fingerprint: String,
+}
+
+/// Asks whether to trust an [`UnknownHostKey`]. `true` trusts it and saves it, so later
+/// connections do not ask again.
+pub type HostKeyCallback =
+    Arc<dyn Fn(UnknownHostKey) -> Pin<Box<dyn Future<Output = bool> + Send>> + Send + Sync>;
+
+/// Where host keys are checked and trusted keys are saved.
+#[derive(Clone, Default)]
+pub struct HostKeyPolicy {
+    /// The user's known_hosts; `~/.ssh/known_hosts` when unset.
+    pub known_hosts: Option<PathBuf>,
+    /// Checked after `known_hosts`. Keys the user trusts are appended here when set,
+    /// otherwise to `known_hosts`.
+    pub managed_known_hosts: Option<PathBuf>,
+    /// Without it, unknown hosts fail.
+    pub confirm: Option<HostKeyCallback>,
+}
+
+impl HostKeyPolicy {
+    /// Accept `key` for `host:port` if a known_hosts file lists it, or the user trusts it
+    /// now. A listed host whose key differs always fails, naming the file and line.
+    pub(crate) async fn verify(&self, host: &str, port: u16, key: &PublicKey) -> Result<()> {
+        let user_file = KnownHostsFile::new(self.known_hosts.clone());
+        let managed = self.managed_known_hosts.clone().map(KnownHostsFile::Path);
+        for file in [Some(&user_file), managed.as_ref()].into_iter().flatten() {
+            match file.check(host, port, key) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(KeyError::KeyChanged { line }) => bail!(
+                    "SSH tunnel: WARNING: the host key has changed for {host}:{port}. \
+                     It now presents {} {}, which does not match line {line} of {}. \
+                     Someone could be intercepting the connection, or the server was \
+                     reinstalled. Confirm the new fingerprint with its administrator, then \
+                     remove the old entry (`ssh-keygen -R {host}`) to connect.",
+                    key.algorithm().as_str(),
+                    fingerprint(key),
+                    file.display(),
+                ),
+                Err(err) => bail!("SSH tunnel: host key verification failed: {err}"),
+            }
+        }
+
+        let unknown = UnknownHostKey {
+            host: host.to_string(),
+            port,
+            algorithm: key.algorithm().as_str().to_string(),
+            fingerprint: fingerprint(key),
+        };
+        let Some(confirm) = &self.confirm else {
+            bail!(
+                "SSH tunnel: host {host}:{port} is not in known_hosts (key {}). \
+                 Add it with `ssh -p {port} {host}` once.",
+                unknown.fingerprint
+            );
+        };
+        if !confirm(unknown).await {
+            bail!("SSH tunnel: host key for {host}:{port} was not trusted");
+        }
+        let target = managed.as_ref().unwrap_or(&user_file);
+        target.learn(host, port, key).with_context(|| {
+            format!(
+                "SSH tunnel: could not save host key to {}",
+                target.display()
+            )
+        })
+    }
+}
+
+/// `SHA256:…` fingerprint of `key`.
+pub fn fingerprint(key: &PublicKey) -> String {
+    key.fingerprint(HashAlg::Sha256).to_string()
+}
+
+enum KnownHostsFile {
+    /// `~/.ssh/known_hosts`, located by russh.
+    Default,
+    Path(PathBuf),
+}
+
+impl KnownHostsFile {
+    fn new(path: Option<PathBuf>) -> Self {
+        path.map_or(Self::Default, Self::Path)
+    }
+
+    fn check(&self, host: &str, port: u16, key: &PublicKey) -> Result<bool, KeyError> {
+        match self {
+            Self::Default => check_known_hosts(host, port, key),
+            Self::Path(path) => check_known_hosts_path(host, port, key, path),
+        }
+    }
+
+    fn learn(&self, host: &str, port: u16, key: &PublicKey) -> Result<()> {
+        match self {
+            Self::Default => learn_known_hosts(host, port, key)?,
+            Self::Path(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("create {}", parent.display()))?;
+                }
+                learn_known_hosts_path(host, port, key, path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn display(&self) -> String {
+        match self {
+            Self::Default => "~/.ssh/known_hosts".into(),
+            Self::Path(path) => path.display().to_string(),
+        }
+    }
+}
@@ -1,7 +1,15 @@
 //! SSH local-forward used as a transport hop in front of a database engine.
 
+mod config;
+mod known_hosts;
 mod path;
+mod pool;
 mod tunnel;
 
+pub use config::{SshConfigHost, lookup_ssh_config, parse_ssh_config, resolve_ssh_config};
+pub use known_hosts::{HostKeyCallback, HostKeyPolicy, UnknownHostKey, fingerprint};
 pub use path::{expand_key_path, expand_tilde};
-pub use tunnel::{SshTunnel, open_tunnel, open_tunnel_with_known_hosts};
+pub use pool::TunnelPool;
+pub use tunnel::{
+    SshTunnel, TunnelHealth, open_tunnel, open_tunnel_with, open_tunnel_with_known_hosts,
+};
@@ -0,0 +1,100 @@
+//! Shared SSH sessions: tunnels behind the same hops reuse one login.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex as StdMutex, PoisonError, Weak};
+
+use anyhow::{Result, bail};
+use based_core::SshTunnelConfig;
+use tokio::sync::Mutex;
+
+use crate::config::resolve_ssh_config;
+use crate::known_hosts::HostKeyPolicy;
+use crate::tunnel::{SharedSession, SshTunnel, hops};
+
+/// Each hop as (host, port, user, key path), outermost first. The passphrase is not
+/// part of it: the same key reaches the same session.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SessionKey(Vec<(String, u16, String, Option<String>)>);
+
+impl SessionKey {
+    fn new(ssh: &SshTunnelConfig) -> Self {
+        Self(
+            hops(ssh)
+                .iter()
+                .map(|hop| {
+                    (
+                        hop.host.to_ascii_lowercase(),
+                        hop.port,
+                        hop.user.to_string(),
+                        hop.key_path.map(str::to_string),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Locked while its session is looked up or connected, so concurrent opens log in once.
+type Slot = Arc<Mutex<Weak<SharedSession>>>;
+
+/// SSH sessions keyed by their hops. Each session multiplexes `direct-tcpip` channels
+/// for every remote endpoint tunnelled through it, reconnects when it drops, and closes
+/// when its last [`SshTunnel`] drops.
+#[derive(Default)]
+pub struct TunnelPool {
+    sessions: StdMutex<HashMap<SessionKey, Slot>>,
+}
+
+static SHARED: LazyLock<TunnelPool> = LazyLock::new(TunnelPool::default);
+
+impl TunnelPool {
+    /// The pool behind [`open_tunnel`](crate::open_tunnel) and friends.
+    pub fn shared() -> &'static Self {
+        &SHARED
+    }
+
+    /// Forward a local port to `remote_host:remote_port` through `ssh`, reusing an open
+    /// (or reconnecting) session to the same hops. `host_keys` and the keepalive
+    /// settings only apply when a new session logs in.
+    pub async fn open(
+        &self,
+        ssh: &SshTunnelConfig,
+        remote_host: &str,
+        remote_port: u16,
+        host_keys: HostKeyPolicy,
+    ) -> Result<SshTunnel> {
+        let ssh = resolve_ssh_config(ssh)?;
+        if ssh.host.trim().is_empty() || ssh.user.trim().is_empty() {
+            bail!("SSH tunnel: host and user are required");
+        }
+
+        let slot = self.slot(SessionKey::new(&ssh));
+        let mut shared = slot.lock().await;
+        let session = match shared.upgrade() {
+            Some(session) if !session.is_failed() => session,
+            _ => {
+                let session = SharedSession::connect(ssh, host_keys).await?;
+                *shared = Arc::downgrade(&session);
+                session
+            }
+        };
+        session.forward(remote_host, remote_port).await
+    }
+
+    /// Sessions still held by a tunnel, or logging in.
+    pub fn session_count(&self) -> usize {
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        sessions.retain(|_, slot| slot_in_use(slot));
+        sessions.len()
+    }
+
+    fn slot(&self, key: SessionKey) -> Slot {
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        sessions.retain(|_, slot| slot_in_use(slot));
+        sessions.entry(key).or_default().clone()
+    }
+}
+
+fn slot_in_use(slot: &Slot) -> bool {
+    Arc::strong_count(slot) > 1 || slot.try_lock().is_ok_and(|s| s.strong_count() > 0)
+}
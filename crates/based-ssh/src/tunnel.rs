@@ -1,27 +1,37 @@
 //! Local-forward an SSH hop to a remote TCP endpoint.
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard, PoisonError, Weak};
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
-use based_core::SshTunnelConfig;
+use based_core::{SshJumpHost, SshTunnelConfig};
 use russh::keys::{HashAlg, PrivateKeyWithHashAlg, PublicKey, load_secret_key};
 use russh::{
     Channel, client,
     keys::agent::client::{AgentClient, AgentStream},
-    keys::known_hosts::{check_known_hosts, check_known_hosts_path},
 };
 use tokio::io::{AsyncWriteExt, copy_bidirectional};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{Mutex, oneshot};
+use tokio::sync::{Mutex, oneshot, watch};
+use tokio::time::sleep;
 
+use crate::known_hosts::HostKeyPolicy;
 use crate::path::expand_key_path;
+use crate::pool::TunnelPool;
+
+/// How often a session is checked for having dropped.
+const HEALTH_POLL: Duration = Duration::from_secs(1);
+/// Logins tried after a session drops before the tunnel counts as failed.
+const RECONNECT_ATTEMPTS: u32 = 8;
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(30);
 
 struct ClientHandler {
     host: String,
     port: u16,
-    known_hosts: Option<PathBuf>,
+    host_keys: HostKeyPolicy,
 }
 
 impl client::Handler for ClientHandler {
@@ -31,50 +41,58 @@ impl client::Handler for ClientHandler {
         &mut self,
         server_public_key: &PublicKey,
     ) -> Result<bool, Self::Error> {
-        let verified = match &self.known_hosts {
-            Some(path) => check_known_hosts_path(&self.host, self.port, server_public_key, path),
-            None => check_known_hosts(&self.host, self.port, server_public_key),
-        };
-        match verified {
-            Ok(true) => Ok(true),
-            Ok(false) => bail!(
-                "SSH tunnel: host {} is not in known_hosts. Add it with `ssh {}@{}` once.",
-                self.host,
-                self.host,
-                self.port
-            ),
-            Err(err) => bail!("SSH tunnel: host key verification failed: {err}"),
+        self.host_keys
+            .verify(&self.host, self.port, server_public_key)
+            .await?;
+        Ok(true)
+    }
+}
+
+/// Whether the SSH session behind a tunnel is up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunnelHealth {
+    Connected,
+    /// The session dropped and is logging in again; new connections wait for it.
+    Reconnecting {
+        attempt: u32,
+    },
+    /// Reconnecting gave up, with the last error.
+    Failed(String),
+}
+
+impl TunnelHealth {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Connected => "connected",
+            Self::Reconnecting { .. } => "reconnecting",
+            Self::Failed(_) => "failed",
         }
     }
 }
 
-/// Live local-forward. Dropping it closes the listen port and SSH session.
+/// Live local-forward. Tunnels to the same endpoint through the same SSH session share
+/// one listen port; dropping the last of them closes it. When the session drops, it
+/// logs in again behind the same port.
 pub struct SshTunnel {
-    local_addr: SocketAddr,
-    shutdown: Option<oneshot::Sender<()>>,
-    handle: Option<Arc<Mutex<client::Handle<ClientHandler>>>>,
+    forward: Arc<LocalForward>,
 }
 
 impl SshTunnel {
     pub fn local_addr(&self) -> SocketAddr {
-        self.local_addr
+        self.forward.local_addr
     }
 
     pub fn local_port(&self) -> u16 {
-        self.local_addr.port()
+        self.forward.local_addr.port()
     }
-}
 
-impl Drop for SshTunnel {
-    fn drop(&mut self) {
-        if let Some(tx) = self.shutdown.take() {
-            let _ = tx.send(());
-        }
-        if let Some(handle) = self.handle.take() {
-            tokio::spawn(async move {
-                drop(handle);
-            });
-        }
+    pub fn health(&self) -> TunnelHealth {
+        self.forward.session.health.borrow().clone()
+    }
+
+    /// Follows [`SshTunnel::health`], e.g. to refresh a status display.
+    pub fn watch_health(&self) -> watch::Receiver<TunnelHealth> {
+        self.forward.session.health.subscribe()
     }
 }
 
@@ -84,72 +102,341 @@ pub async fn open_tunnel(
     remote_host: &str,
     remote_port: u16,
 ) -> Result<SshTunnel> {
-    open_tunnel_with_known_hosts(ssh, remote_host, remote_port, None).await
+    open_tunnel_with(ssh, remote_host, remote_port, HostKeyPolicy::default()).await
 }
 
+/// [`open_tunnel`] checking host keys against `known_hosts` instead of
+/// `~/.ssh/known_hosts`.
 pub async fn open_tunnel_with_known_hosts(
     ssh: &SshTunnelConfig,
     remote_host: &str,
     remote_port: u16,
     known_hosts: Option<PathBuf>,
 ) -> Result<SshTunnel> {
-    if !ssh.is_configured() {
-        bail!("SSH tunnel: host and user are required");
-    }
-
-    let handler = ClientHandler {
-        host: ssh.host.clone(),
-        port: ssh.port,
+    let host_keys = HostKeyPolicy {
         known_hosts,
+        ..HostKeyPolicy::default()
     };
-    let config = Arc::new(client::Config::default());
-    let mut session = client::connect(config, (ssh.host.as_str(), ssh.port), handler)
+    open_tunnel_with(ssh, remote_host, remote_port, host_keys).await
+}
+
+/// [`open_tunnel`] verifying every hop's host key with `host_keys`, which may ask the
+/// user to trust keys seen for the first time. Goes through [`TunnelPool::shared`], so
+/// an SSH session already open to the same hops is reused.
+pub async fn open_tunnel_with(
+    ssh: &SshTunnelConfig,
+    remote_host: &str,
+    remote_port: u16,
+    host_keys: HostKeyPolicy,
+) -> Result<SshTunnel> {
+    TunnelPool::shared()
+        .open(ssh, remote_host, remote_port, host_keys)
         .await
-        .map_err(|err| anyhow::anyhow!("SSH tunnel: {err}"))?;
+}
 
-    authenticate(&mut session, ssh).await?;
+/// Sessions of one login: the tunnel host, then the jump hosts that carry it.
+pub(crate) struct SessionChain {
+    session: client::Handle<ClientHandler>,
+    /// Outermost first; each carries the next hop.
+    jumps: Vec<client::Handle<ClientHandler>>,
+}
 
-    let listener = TcpListener::bind("127.0.0.1:0")
-        .await
-        .context("SSH tunnel: bind local port")?;
-    let local_addr = listener.local_addr().context("SSH tunnel: local address")?;
-    let remote_host = remote_host.to_string();
-    let session = Arc::new(Mutex::new(session));
-    let handle = session.clone();
-    let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
-
-    tokio::spawn(async move {
-        loop {
-            tokio::select! {
-                _ = &mut shutdown_rx => break,
-                accepted = listener.accept() => {
-                    let Ok((socket, _)) = accepted else { break };
-                    let handle = handle.clone();
-                    let remote_host = remote_host.clone();
-                    tokio::spawn(async move {
-                        if let Err(err) = forward_one(handle, socket, &remote_host, remote_port).await {
-                            log::debug!("SSH tunnel forward ended: {err:#}");
-                        }
-                    });
+/// One authenticated SSH session, shared by every local forward opened through it.
+/// Dropping the last forward closes the session.
+pub(crate) struct SharedSession {
+    /// Resolved settings, kept to log in again.
+    ssh: SshTunnelConfig,
+    host_keys: HostKeyPolicy,
+    chain: Mutex<Option<SessionChain>>,
+    health: watch::Sender<TunnelHealth>,
+    forwards: StdMutex<HashMap<(String, u16), Weak<LocalForward>>>,
+}
+
+impl SharedSession {
+    /// Log in through every hop of `ssh` and start watching the session.
+    pub(crate) async fn connect(
+        ssh: SshTunnelConfig,
+        host_keys: HostKeyPolicy,
+    ) -> Result<Arc<Self>> {
+        let chain = connect_chain(&ssh, &host_keys).await?;
+        let session = Arc::new(Self {
+            ssh,
+            host_keys,
+            chain: Mutex::new(Some(chain)),
+            health: watch::Sender::new(TunnelHealth::Connected),
+            forwards: StdMutex::default(),
+        });
+        tokio::spawn(supervise(Arc::downgrade(&session)));
+        Ok(session)
+    }
+
+    /// Reconnecting gave up; the pool opens a new session instead.
+    pub(crate) fn is_failed(&self) -> bool {
+        matches!(*self.health.borrow(), TunnelHealth::Failed(_))
+    }
+
+    async fn is_closed(&self) -> bool {
+        self.chain
+            .lock()
+            .await
+            .as_ref()
+            .is_none_or(|chain| chain.session.is_closed())
+    }
+
+    /// Log in again with exponential backoff, swapping the new session in behind the
+    /// open local ports. `false` once it gives up.
+    async fn reconnect(&self) -> bool {
+        let mut delay = Duration::from_secs(1);
+        let mut last_err = None;
+        for attempt in 1..=RECONNECT_ATTEMPTS {
+            self.health
+                .send_replace(TunnelHealth::Reconnecting { attempt });
+            match connect_chain(&self.ssh, &self.host_keys).await {
+                Ok(chain) => {
+                    let stale = self.chain.lock().await.replace(chain);
+                    drop(stale);
+                    self.health.send_replace(TunnelHealth::Connected);
+                    log::info!("SSH tunnel: reconnected to {}", self.ssh.host);
+                    return true;
+                }
+                Err(err) => {
+                    log::warn!("SSH tunnel: reconnect attempt {attempt} failed: {err:#}");
+                    last_err = Some(err);
                 }
             }
+            if attempt < RECONNECT_ATTEMPTS {
+                sleep(delay).await;
+                delay = (delay * 2).min(RECONNECT_BACKOFF_MAX);
+            }
+        }
+        let reason = last_err.map_or_else(String::new, |err| format!("{err:#}"));
+        self.health.send_replace(TunnelHealth::Failed(reason));
+        false
+    }
+
+    /// Wait out a reconnect; an error if the session failed.
+    async fn wait_connected(&self) -> Result<()> {
+        let mut health = self.health.subscribe();
+        let health = health
+            .wait_for(|h| !matches!(h, TunnelHealth::Reconnecting { .. }))
+            .await
+            .context("SSH tunnel: session closed")?;
+        if let TunnelHealth::Failed(reason) = &*health {
+            bail!("SSH tunnel: reconnect failed: {reason}");
+        }
+        Ok(())
+    }
+
+    /// A local port forwarding to `remote_host:remote_port`: the one already open for
+    /// that endpoint, or a new listener.
+    pub(crate) async fn forward(
+        self: &Arc<Self>,
+        remote_host: &str,
+        remote_port: u16,
+    ) -> Result<SshTunnel> {
+        let endpoint = (remote_host.to_string(), remote_port);
+        if let Some(forward) = self.forwards().get(&endpoint).and_then(Weak::upgrade) {
+            return Ok(SshTunnel { forward });
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("SSH tunnel: bind local port")?;
+        let local_addr = listener.local_addr().context("SSH tunnel: local address")?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        tokio::spawn(accept_loop(
+            listener,
+            Arc::downgrade(self),
+            endpoint.clone(),
+            shutdown_rx,
+        ));
+
+        let forward = Arc::new(LocalForward {
+            local_addr,
+            shutdown: Some(shutdown_tx),
+            session: self.clone(),
+        });
+        let mut forwards = self.forwards();
+        forwards.retain(|_, forward| forward.strong_count() > 0);
+        forwards.insert(endpoint, Arc::downgrade(&forward));
+        Ok(SshTunnel { forward })
+    }
+
+    fn forwards(&self) -> MutexGuard<'_, HashMap<(String, u16), Weak<LocalForward>>> {
+        self.forwards.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for SharedSession {
+    fn drop(&mut self) {
+        if let Some(SessionChain { session, jumps }) = self.chain.get_mut().take() {
+            tokio::spawn(async move {
+                drop(session);
+                drop(jumps);
+            });
+        }
+    }
+}
+
+/// A listen port shared by every [`SshTunnel`] to one endpoint. Holds the session open.
+struct LocalForward {
+    local_addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    session: Arc<SharedSession>,
+}
+
+impl Drop for LocalForward {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    session: Weak<SharedSession>,
+    (remote_host, remote_port): (String, u16),
+    mut shutdown: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => {
+                let Ok((socket, _)) = accepted else { break };
+                let Some(session) = session.upgrade() else { break };
+                let remote_host = remote_host.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = forward_one(&session, socket, &remote_host, remote_port).await {
+                        log::debug!("SSH tunnel forward ended: {err:#}");
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Poll `session` until it drops, then reconnect it. Ends with the session, or when
+/// reconnecting gives up.
+async fn supervise(session: Weak<SharedSession>) {
+    loop {
+        sleep(HEALTH_POLL).await;
+        let Some(session) = session.upgrade() else {
+            return;
+        };
+        if session.is_closed().await {
+            log::warn!("SSH tunnel: session to {} dropped", session.ssh.host);
+            if !session.reconnect().await {
+                return;
+            }
         }
+    }
+}
+
+/// One SSH server on the way to the database: the jump hosts, then the tunnel host.
+pub(crate) struct Hop<'a> {
+    pub(crate) host: &'a str,
+    pub(crate) port: u16,
+    pub(crate) user: &'a str,
+    pub(crate) key_path: Option<&'a str>,
+    key_passphrase: Option<&'a str>,
+}
+
+impl Hop<'_> {
+    fn name(&self) -> String {
+        format!("{}@{}:{}", self.user, self.host, self.port)
+    }
+}
+
+pub(crate) fn hops(ssh: &SshTunnelConfig) -> Vec<Hop<'_>> {
+    let mut hops: Vec<_> = ssh
+        .jump_hosts
+        .iter()
+        .map(|jump: &SshJumpHost| Hop {
+            host: &jump.host,
+            port: jump.port,
+            user: &jump.user,
+            key_path: jump.key_path.as_deref(),
+            key_passphrase: jump.key_passphrase.as_deref(),
+        })
+        .collect();
+    hops.push(Hop {
+        host: &ssh.host,
+        port: ssh.port,
+        user: &ssh.user,
+        key_path: ssh.key_path.as_deref(),
+        key_passphrase: ssh.key_passphrase.as_deref(),
     });
+    hops
+}
 
-    Ok(SshTunnel {
-        local_addr,
-        shutdown: Some(shutdown_tx),
-        handle: Some(session),
+/// Log in to each hop through a `direct-tcpip` channel on the previous one, as
+/// OpenSSH `ProxyJump` does. With jump hosts, errors name the hop that failed.
+async fn connect_chain(ssh: &SshTunnelConfig, host_keys: &HostKeyPolicy) -> Result<SessionChain> {
+    let hops = hops(ssh);
+    let config = Arc::new(client::Config {
+        keepalive_interval: ssh.keepalive(),
+        keepalive_max: ssh.keepalive_max() as usize,
+        ..client::Config::default()
+    });
+    let mut sessions: Vec<client::Handle<ClientHandler>> = Vec::with_capacity(hops.len());
+    for (i, hop) in hops.iter().enumerate() {
+        let session = connect_hop(hop, sessions.last(), config.clone(), host_keys).await;
+        let session = if hops.len() == 1 {
+            session?
+        } else {
+            session.with_context(|| {
+                format!(
+                    "SSH tunnel: hop {} of {} ({}) failed",
+                    i + 1,
+                    hops.len(),
+                    hop.name()
+                )
+            })?
+        };
+        sessions.push(session);
+    }
+    let session = sessions.pop().context("SSH tunnel: no hops")?;
+    Ok(SessionChain {
+        session,
+        jumps: sessions,
     })
 }
 
-async fn authenticate(
-    session: &mut client::Handle<ClientHandler>,
-    ssh: &SshTunnelConfig,
-) -> Result<()> {
-    if let Some(key_path) = ssh.key_path.as_deref().filter(|p| !p.trim().is_empty()) {
+/// Open an authenticated session to `hop`, directly or through `via`.
+async fn connect_hop(
+    hop: &Hop<'_>,
+    via: Option<&client::Handle<ClientHandler>>,
+    config: Arc<client::Config>,
+    host_keys: &HostKeyPolicy,
+) -> Result<client::Handle<ClientHandler>> {
+    let handler = ClientHandler {
+        host: hop.host.to_string(),
+        port: hop.port,
+        host_keys: host_keys.clone(),
+    };
+    let mut session = match via {
+        Some(via) => {
+            let channel = via
+                .channel_open_direct_tcpip(hop.host, u32::from(hop.port), "127.0.0.1", 0)
+                .await
+                .context("SSH tunnel: could not open direct-tcpip channel")?;
+            client::connect_stream(config, channel.into_stream(), handler)
+                .await
+                .map_err(|err| anyhow::anyhow!("SSH tunnel: {err}"))?
+        }
+        None => client::connect(config, (hop.host, hop.port), handler)
+            .await
+            .map_err(|err| anyhow::anyhow!("SSH tunnel: {err}"))?,
+    };
+    authenticate(&mut session, hop).await?;
+    Ok(session)
+}
+
+async fn authenticate(session: &mut client::Handle<ClientHandler>, hop: &Hop<'_>) -> Result<()> {
+    if let Some(key_path) = hop.key_path.filter(|p| !p.trim().is_empty()) {
         let path = expand_key_path(key_path);
-        let key = load_secret_key(&path, ssh.key_passphrase.as_deref())
+        let key = load_secret_key(&path, hop.key_passphrase)
             .with_context(|| format!("SSH tunnel: could not load key {}", path.display()))?;
         let hash = session
             .best_supported_rsa_hash()
@@ -159,7 +446,7 @@ async fn authenticate(
             .flatten();
         let key = PrivateKeyWithHashAlg::new(Arc::new(key), hash);
         let result = session
-            .authenticate_publickey(ssh.user.clone(), key)
+            .authenticate_publickey(hop.user, key)
             .await
             .context("SSH tunnel: public-key authentication failed")?;
         if !result.success() {
@@ -168,7 +455,7 @@ async fn authenticate(
         return Ok(());
     }
 
-    authenticate_with_agent(session, &ssh.user).await
+    authenticate_with_agent(session, hop.user).await
 }
 
 async fn authenticate_with_agent(
@@ -250,14 +537,19 @@ where
 }
 
 async fn forward_one(
-    handle: Arc<Mutex<client::Handle<ClientHandler>>>,
+    session: &SharedSession,
     mut socket: TcpStream,
     remote_host: &str,
     remote_port: u16,
 ) -> Result<()> {
-    let channel: Channel<client::Msg> = handle
+    session.wait_connected().await?;
+    let channel: Channel<client::Msg> = session
+        .chain
         .lock()
         .await
+        .as_ref()
+        .context("SSH tunnel: session closed")?
+        .session
         .channel_open_direct_tcpip(remote_host, u32::from(remote_port), "127.0.0.1", 0)
         .await
         .context("SSH tunnel: could not open direct-tcpip channel")?;
@@ -1,10 +1,14 @@
 use std::fs;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
-use based_core::SshTunnelConfig;
-use based_ssh::open_tunnel_with_known_hosts;
+use based_core::{SshJumpHost, SshTunnelConfig};
+use based_ssh::{
+    HostKeyCallback, HostKeyPolicy, SshTunnel, TunnelHealth, TunnelPool, UnknownHostKey,
+    fingerprint, open_tunnel_with, open_tunnel_with_known_hosts,
+};
 use rand_core::OsRng;
 use russh::keys::known_hosts::learn_known_hosts_path;
 use russh::keys::ssh_key::LineEnding;
@@ -13,6 +17,7 @@ use russh::server::{Auth, Config, Msg, Server as _, Session};
 use russh::{Channel, server};
 use tokio::io::{AsyncReadExt, AsyncWriteExt, copy_bidirectional};
 use tokio::net::{TcpListener, TcpStream};
+use tokio::time::sleep;
 
 #[derive(Clone)]
 struct ForwardServer {
@@ -114,6 +119,10 @@ async fn unknown_host_key_is_rejected() {
         user: "test".into(),
         key_path: Some(key_path.display().to_string()),
         key_passphrase: None,
+        alias: None,
+        keepalive_interval: None,
+        keepalive_count_max: None,
+        jump_hosts: vec![],
     };
     let err = match open_tunnel_with_known_hosts(&ssh, "127.0.0.1", 1, Some(known_hosts)).await {
         Ok(_) => panic!("expected unknown host key to fail"),
@@ -148,10 +157,15 @@ async fn forwards_bytes_through_local_port() {
         user: "test".into(),
         key_path: Some(key_path.display().to_string()),
         key_passphrase: None,
+        alias: None,
+        keepalive_interval: None,
+        keepalive_count_max: None,
+        jump_hosts: vec![],
     };
     let tunnel = open_tunnel_with_known_hosts(&ssh, "127.0.0.1", echo_port, Some(known_hosts))
         .await
         .expect("open tunnel");
+    assert_eq!(tunnel.health(), TunnelHealth::Connected);
 
     let mut client = TcpStream::connect(tunnel.local_addr()).await.unwrap();
     client.write_all(b"ping").await.unwrap();
@@ -159,3 +173,313 @@ async fn forwards_bytes_through_local_port() {
     client.read_exact(&mut buf).await.unwrap();
     assert_eq!(&buf, b"ping");
 }
+
+#[tokio::test]
+async fn forwards_through_jump_host_chain() {
+    let echo_port = start_echo().await;
+    let client_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let bastion_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let inner_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let bastion_pub = bastion_key.public_key().clone();
+    let inner_pub = inner_key.public_key().clone();
+    let bastion_port = start_ssh(bastion_key, client_key.public_key().clone()).await;
+    let inner_port = start_ssh(inner_key, client_key.public_key().clone()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let key_path = dir.path().join("id_ed25519");
+    client_key
+        .write_openssh_file(&key_path, LineEnding::LF)
+        .unwrap();
+    let known_hosts = dir.path().join("known_hosts");
+    learn_known_hosts_path("127.0.0.1", bastion_port, &bastion_pub, &known_hosts).unwrap();
+
+    let ssh = SshTunnelConfig {
+        host: "127.0.0.1".into(),
+        port: inner_port,
+        user: "test".into(),
+        key_path: Some(key_path.display().to_string()),
+        key_passphrase: None,
+        alias: None,
+        keepalive_interval: None,
+        keepalive_count_max: None,
+        jump_hosts: vec![SshJumpHost {
+            host: "127.0.0.1".into(),
+            port: bastion_port,
+            user: "jump".into(),
+            key_path: Some(key_path.display().to_string()),
+            key_passphrase: None,
+        }],
+    };
+
+    // The inner host key is not known yet: the error names the second hop.
+    let err =
+        match open_tunnel_with_known_hosts(&ssh, "127.0.0.1", echo_port, Some(known_hosts.clone()))
+            .await
+        {
+            Ok(_) => panic!("expected unknown inner host key to fail"),
+            Err(err) => err,
+        };
+    let msg = err.to_string();
+    assert!(
+        msg.contains(&format!("hop 2 of 2 (test@127.0.0.1:{inner_port})")),
+        "unexpected error: {msg}"
+    );
+
+    learn_known_hosts_path("127.0.0.1", inner_port, &inner_pub, &known_hosts).unwrap();
+    let tunnel = open_tunnel_with_known_hosts(&ssh, "127.0.0.1", echo_port, Some(known_hosts))
+        .await
+        .expect("open tunnel");
+
+    let mut client = TcpStream::connect(tunnel.local_addr()).await.unwrap();
+    client.write_all(b"ping").await.unwrap();
+    let mut buf = [0u8; 4];
+    client.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ping");
+}
+
+fn confirm_with(answer: bool, seen: Arc<AtomicUsize>) -> HostKeyCallback {
+    Arc::new(move |_: UnknownHostKey| {
+        seen.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move { answer })
+    })
+}
+
+#[tokio::test]
+async fn trusted_host_key_is_saved_to_managed_file() {
+    let echo_port = start_echo().await;
+    let client_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let server_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let server_fp = fingerprint(server_key.public_key());
+    let ssh_port = start_ssh(server_key, client_key.public_key().clone()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let key_path = dir.path().join("id_ed25519");
+    client_key
+        .write_openssh_file(&key_path, LineEnding::LF)
+        .unwrap();
+    let known_hosts = dir.path().join("known_hosts");
+    fs::write(&known_hosts, "").unwrap();
+    let managed = dir.path().join("based").join("known_hosts");
+
+    let ssh = SshTunnelConfig {
+        host: "127.0.0.1".into(),
+        port: ssh_port,
+        user: "test".into(),
+        key_path: Some(key_path.display().to_string()),
+        key_passphrase: None,
+        alias: None,
+        keepalive_interval: None,
+        keepalive_count_max: None,
+        jump_hosts: vec![],
+    };
+    let asked = Arc::new(AtomicUsize::new(0));
+    let prompted = asked.clone();
+    let policy = HostKeyPolicy {
+        known_hosts: Some(known_hosts.clone()),
+        managed_known_hosts: Some(managed.clone()),
+        confirm: Some(Arc::new(move |key: UnknownHostKey| {
+            prompted.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(key.port, ssh_port);
+            assert_eq!(key.algorithm, "ssh-ed25519");
+            assert_eq!(key.fingerprint, server_fp);
+            Box::pin(async { true })
+        })),
+    };
+
+    let tunnel = open_tunnel_with(&ssh, "127.0.0.1", echo_port, policy.clone())
+        .await
+        .expect("open tunnel");
+    let mut client = TcpStream::connect(tunnel.local_addr()).await.unwrap();
+    client.write_all(b"ping").await.unwrap();
+    let mut buf = [0u8; 4];
+    client.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ping");
+    assert_eq!(asked.load(Ordering::SeqCst), 1);
+    assert!(fs::read_to_string(&known_hosts).unwrap().is_empty());
+    assert!(
+        fs::read_to_string(&managed)
+            .unwrap()
+            .contains("ssh-ed25519")
+    );
+
+    // Once saved, the key is accepted without asking again.
+    drop(client);
+    drop(tunnel);
+    open_tunnel_with(&ssh, "127.0.0.1", echo_port, policy)
+        .await
+        .expect("reopen tunnel");
+    assert_eq!(asked.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn declined_host_key_fails_without_saving() {
+    let client_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let server_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let ssh_port = start_ssh(server_key, client_key.public_key().clone()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let key_path = dir.path().join("id_ed25519");
+    client_key
+        .write_openssh_file(&key_path, LineEnding::LF)
+        .unwrap();
+    let known_hosts = dir.path().join("known_hosts");
+    fs::write(&known_hosts, "").unwrap();
+    let managed = dir.path().join("managed_known_hosts");
+
+    let ssh = SshTunnelConfig {
+        host: "127.0.0.1".into(),
+        port: ssh_port,
+        user: "test".into(),
+        key_path: Some(key_path.display().to_string()),
+        key_passphrase: None,
+        alias: None,
+        keepalive_interval: None,
+        keepalive_count_max: None,
+        jump_hosts: vec![],
+    };
+    let asked = Arc::new(AtomicUsize::new(0));
+    let policy = HostKeyPolicy {
+        known_hosts: Some(known_hosts),
+        managed_known_hosts: Some(managed.clone()),
+        confirm: Some(confirm_with(false, asked.clone())),
+    };
+    let err = match open_tunnel_with(&ssh, "127.0.0.1", 1, policy).await {
+        Ok(_) => panic!("expected declined host key to fail"),
+        Err(err) => err,
+    };
+    assert!(
+        format!("{err:#}").contains("was not trusted"),
+        "unexpected error: {err:#}"
+    );
+    assert_eq!(asked.load(Ordering::SeqCst), 1);
+    assert!(!managed.exists());
+}
+
+#[tokio::test]
+async fn changed_host_key_fails_loudly_without_asking() {
+    let client_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let server_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let old_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let ssh_port = start_ssh(server_key, client_key.public_key().clone()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let key_path = dir.path().join("id_ed25519");
+    client_key
+        .write_openssh_file(&key_path, LineEnding::LF)
+        .unwrap();
+    let known_hosts = dir.path().join("known_hosts");
+    learn_known_hosts_path("127.0.0.1", ssh_port, old_key.public_key(), &known_hosts).unwrap();
+
+    let ssh = SshTunnelConfig {
+        host: "127.0.0.1".into(),
+        port: ssh_port,
+        user: "test".into(),
+        key_path: Some(key_path.display().to_string()),
+        key_passphrase: None,
+        alias: None,
+        keepalive_interval: None,
+        keepalive_count_max: None,
+        jump_hosts: vec![],
+    };
+    let asked = Arc::new(AtomicUsize::new(0));
+    let policy = HostKeyPolicy {
+        known_hosts: Some(known_hosts),
+        managed_known_hosts: None,
+        confirm: Some(confirm_with(true, asked.clone())),
+    };
+    let err = match open_tunnel_with(&ssh, "127.0.0.1", 1, policy).await {
+        Ok(_) => panic!("expected changed host key to fail"),
+        Err(err) => err,
+    };
+    let msg = format!("{err:#}");
+    assert!(
+        msg.contains("WARNING: the host key has changed") && msg.contains("line 1"),
+        "unexpected error: {msg}"
+    );
+    assert_eq!(asked.load(Ordering::SeqCst), 0);
+}
+
+async fn ping(tunnel: &SshTunnel) {
+    let mut client = TcpStream::connect(tunnel.local_addr()).await.unwrap();
+    client.write_all(b"ping").await.unwrap();
+    let mut buf = [0u8; 4];
+    client.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ping");
+}
+
+#[tokio::test]
+async fn pool_shares_one_session_across_endpoints() {
+    let echo_a = start_echo().await;
+    let echo_b = start_echo().await;
+    let client_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let server_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let server_pub = server_key.public_key().clone();
+    let ssh_port = start_ssh(server_key, client_key.public_key().clone()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let key_path = dir.path().join("id_ed25519");
+    client_key
+        .write_openssh_file(&key_path, LineEnding::LF)
+        .unwrap();
+    let known_hosts = dir.path().join("known_hosts");
+    learn_known_hosts_path("127.0.0.1", ssh_port, &server_pub, &known_hosts).unwrap();
+    let empty_known_hosts = dir.path().join("empty_known_hosts");
+    fs::write(&empty_known_hosts, "").unwrap();
+
+    let ssh = SshTunnelConfig {
+        host: "127.0.0.1".into(),
+        port: ssh_port,
+        user: "test".into(),
+        key_path: Some(key_path.display().to_string()),
+        key_passphrase: None,
+        alias: None,
+        keepalive_interval: None,
+        keepalive_count_max: None,
+        jump_hosts: vec![],
+    };
+    let pool = TunnelPool::default();
+    let trusting = HostKeyPolicy {
+        known_hosts: Some(known_hosts),
+        ..HostKeyPolicy::default()
+    };
+    // Later opens would fail to log in again: they must reuse the first session.
+    let reusing = HostKeyPolicy {
+        known_hosts: Some(empty_known_hosts),
+        ..HostKeyPolicy::default()
+    };
+    let a = pool
+        .open(&ssh, "127.0.0.1", echo_a, trusting)
+        .await
+        .expect("open tunnel");
+    let a_again = pool
+        .open(&ssh, "127.0.0.1", echo_a, reusing.clone())
+        .await
+        .expect("reuse tunnel");
+    let b = pool
+        .open(&ssh, "127.0.0.1", echo_b, reusing.clone())
+        .await
+        .expect("second endpoint");
+
+    assert_eq!(pool.session_count(), 1);
+    assert_eq!(a.local_port(), a_again.local_port());
+    assert_ne!(a.local_port(), b.local_port());
+    ping(&a).await;
+    ping(&b).await;
+
+    drop(a);
+    ping(&a_again).await;
+    drop(a_again);
+    drop(b);
+    // Forwarded connections and the health check let go of the session shortly after.
+    for _ in 0..50 {
+        if pool.session_count() == 0 {
+            break;
+        }
+        sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(pool.session_count(), 0);
+    assert!(
+        pool.open(&ssh, "127.0.0.1", echo_a, reusing).await.is_err(),
+        "the session should close with its last tunnel"
+    );
+}